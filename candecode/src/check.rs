@@ -0,0 +1,281 @@
+//
+// check.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Database consistency checks
+
+use crate::{
+    database::{Database, Message},
+    signal::Signal,
+};
+
+use embedded_can::Id;
+
+use std::{collections::HashMap, fmt};
+
+/// Valid payload lengths for CAN and CAN FD
+const DLCS: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Message or signal the issue applies to, e.g. `EngineData.Torque`
+    pub location: String,
+    pub description: String,
+}
+
+impl Issue {
+    fn error(location: impl Into<String>, description: impl Into<String>) -> Self {
+        Issue {
+            severity: Severity::Error,
+            location: location.into(),
+            description: description.into(),
+        }
+    }
+
+    fn warning(location: impl Into<String>, description: impl Into<String>) -> Self {
+        Issue {
+            severity: Severity::Warning,
+            location: location.into(),
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}: {}", self.severity, self.description)
+        } else {
+            write!(
+                f,
+                "{}: {}: {}",
+                self.severity, self.location, self.description
+            )
+        }
+    }
+}
+
+fn format_id(id: Id) -> String {
+    match id {
+        Id::Standard(id) => format!("0x{:03X}", id.as_raw()),
+        Id::Extended(id) => format!("0x{:08X}", id.as_raw()),
+    }
+}
+
+/// Check if two signals can be present in the same payload
+fn coexist(a: &Signal, b: &Signal) -> bool {
+    match (&a.multiplexed_by, &b.multiplexed_by) {
+        (Some(a), Some(b)) if a.switch == b.switch => a.values.iter().any(|a| {
+            b.values
+                .iter()
+                .any(|b| a.start() <= b.end() && b.start() <= a.end())
+        }),
+        // Selected by different multiplexors, assume the database knows what it is doing
+        (Some(_), Some(_)) => false,
+        _ => true,
+    }
+}
+
+fn check_message(message: &Message, db: &Database, issues: &mut Vec<Issue>) {
+    let dlc = message.dlc as usize;
+
+    if !DLCS.contains(&message.dlc) {
+        issues.push(Issue::error(
+            &message.name,
+            format!("invalid DLC {}", message.dlc),
+        ));
+    }
+
+    if let Some(transmitter) = &message.transmitter {
+        if !db.nodes().iter().any(|node| node.name == *transmitter) {
+            issues.push(Issue::warning(
+                &message.name,
+                format!("transmitter '{}' is not a defined node", transmitter),
+            ));
+        }
+    }
+
+    for (i, signal) in message.signals.iter().enumerate() {
+        let location = format!("{}.{}", message.name, signal.name);
+
+        if message.signals[..i].iter().any(|s| s.name == signal.name) {
+            issues.push(Issue::error(&location, "duplicate signal name"));
+        }
+
+        if signal.length == 0 || signal.length > 64 {
+            issues.push(Issue::error(
+                &location,
+                format!("invalid length {}", signal.length),
+            ));
+            continue;
+        }
+
+        if signal.start_bit as usize >= dlc * 8 {
+            issues.push(Issue::error(
+                &location,
+                format!(
+                    "start bit {} is outside of the {} byte payload",
+                    signal.start_bit, dlc
+                ),
+            ));
+            continue;
+        }
+
+        if !signal.fits(dlc) {
+            issues.push(Issue::error(
+                &location,
+                format!("extends past the DLC of {} bytes", dlc),
+            ));
+            continue;
+        }
+
+        for other in &message.signals[..i] {
+            if !other.fits(dlc) || !coexist(signal, other) {
+                continue;
+            }
+
+            if signal.bits().any(|bit| other.bits().any(|b| b == bit)) {
+                issues.push(Issue::error(
+                    &location,
+                    format!("overlaps signal {}", other.name),
+                ));
+            }
+        }
+
+        if let Some(switch) = signal
+            .multiplexed_by
+            .as_ref()
+            .and_then(|condition| condition.switch.as_ref())
+        {
+            if message.signal(switch).is_none() {
+                issues.push(Issue::error(
+                    &location,
+                    format!("multiplexor {} does not exist", switch),
+                ));
+            }
+        }
+
+        for receiver in &signal.receivers {
+            if !db.nodes().iter().any(|node| node.name == *receiver) {
+                issues.push(Issue::warning(
+                    &location,
+                    format!("receiver '{}' is not a defined node", receiver),
+                ));
+            }
+        }
+    }
+}
+
+/// Validate the layout and references of a database
+pub fn check(db: &Database) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    let mut ids: HashMap<Id, &str> = HashMap::new();
+    for message in db.messages() {
+        if let Some(first) = ids.insert(message.id, &message.name) {
+            issues.push(Issue::error(
+                &message.name,
+                format!("ID {} is also used by {}", format_id(message.id), first),
+            ));
+        }
+
+        check_message(message, db, &mut issues);
+    }
+
+    for reference in db.unresolved() {
+        issues.push(Issue::warning("", reference.clone()));
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptions(dbc: &str) -> Vec<String> {
+        let db = Database::from_dbc(dbc).unwrap();
+        check(&db).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn valid_database() {
+        let issues = descriptions(
+            r#"BU_: Engine Dashboard
+BO_ 100 EngineData: 8 Engine
+ SG_ EngineSpeed : 0|16@1+ (1,0) [0|0] "" Dashboard
+ SG_ Torque : 31|12@0- (1,0) [0|0] "" Dashboard
+ SG_ Mode M : 40|8@1+ (1,0) [0|0] "" Dashboard
+ SG_ A m0 : 48|16@1+ (1,0) [0|0] "" Dashboard
+ SG_ B m1 : 48|16@1+ (1,0) [0|0] "" Dashboard
+"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn layout_errors() {
+        let issues = descriptions(
+            r#"BU_: Engine
+BO_ 100 EngineData: 4 Engine
+ SG_ A : 0|16@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ B : 8|8@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ C : 24|16@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ D : 40|8@1+ (1,0) [0|0] "" Vector__XXX
+BO_ 100 Duplicate: 9 Gateway
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "error: EngineData.B: overlaps signal A",
+                "error: EngineData.C: extends past the DLC of 4 bytes",
+                "error: EngineData.D: start bit 40 is outside of the 4 byte payload",
+                "error: Duplicate: ID 0x064 is also used by EngineData",
+                "error: Duplicate: invalid DLC 9",
+                "warning: Duplicate: transmitter 'Gateway' is not a defined node",
+            ]
+        );
+    }
+
+    #[test]
+    fn unresolved_references() {
+        let issues = descriptions(
+            r#"BO_ 100 EngineData: 8 Vector__XXX
+ SG_ Gear : 0|8@1+ (1,0) [0|0] "" Vector__XXX
+VAL_ 100 Gear 0 "PARK" ;
+VAL_ 100 Mode 0 "OFF" ;
+VAL_ 200 Gear 0 "PARK" ;
+CM_ SG_ 100 Speed "Vehicle speed";
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "warning: value descriptions for unknown signal 100 Mode",
+                "warning: value descriptions for unknown signal 200 Gear",
+                "warning: comment for unknown signal 100 Speed",
+            ]
+        );
+    }
+}
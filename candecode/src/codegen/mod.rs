@@ -0,0 +1,357 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Rust code generation for message databases
+//!
+//! Generates one struct per message with typed getters and setters for each signal. The
+//! generated code has no dependencies and is intended to be written from a build script:
+//!
+//! ```no_run
+//! // build.rs
+//! let db = candecode::Database::load("vehicle.dbc").unwrap();
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("messages.rs");
+//! std::fs::write(out, candecode::codegen::generate(&db)).unwrap();
+//! ```
+//!
+//! and included with `include!(concat!(env!("OUT_DIR"), "/messages.rs"));`.
+
+mod runtime;
+
+use crate::{
+    database::{Database, Message},
+    signal::{ByteOrder, Signal, ValueType},
+};
+
+use embedded_can::Id;
+
+use std::{collections::HashSet, fmt::Write};
+
+const RUNTIME: &str = include_str!("runtime.rs");
+const RUNTIME_MARKER: &str = "// @generated-runtime\n";
+const RUNTIME_TESTS: &str = "#[cfg(test)]";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "new", "pack", "priv", "pub", "ref", "return", "self", "static", "struct", "super",
+    "trait", "true", "try", "type", "unpack", "unsafe", "use", "where", "while", "yield",
+];
+
+/// Generate Rust source for all messages in the database
+pub fn generate(db: &Database) -> String {
+    let mut out = String::new();
+
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "// Generated by candecode. Do not edit.");
+    if !db.version.is_empty() {
+        let _ = writeln!(out, "// Database version: {}", db.version);
+    }
+    out.push('\n');
+
+    let runtime = RUNTIME
+        .split_once(RUNTIME_MARKER)
+        .map(|(_, runtime)| runtime)
+        .unwrap_or(RUNTIME);
+    let runtime = runtime
+        .split_once(RUNTIME_TESTS)
+        .map(|(runtime, _)| runtime)
+        .unwrap_or(runtime);
+    out.push_str(runtime.trim());
+    out.push('\n');
+
+    for message in db.messages() {
+        out.push('\n');
+        message_struct(&mut out, message);
+    }
+
+    out
+}
+
+fn message_struct(out: &mut String, message: &Message) {
+    let name = upper_camel(&message.name);
+    let dlc = message.dlc as usize;
+
+    let (id, extended) = match message.id {
+        Id::Standard(id) => (id.as_raw() as u32, false),
+        Id::Extended(id) => (id.as_raw(), true),
+    };
+
+    let _ = writeln!(out, "/// {}, ID 0x{:X}", message.name, id);
+    if let Some(comment) = &message.comment {
+        doc_comment(out, "", comment);
+    }
+    let _ = writeln!(
+        out,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n\
+         pub struct {name} {{\n    data: [u8; {dlc}],\n}}\n\n\
+         impl Default for {name} {{\n    fn default() -> Self {{\n        Self::new()\n    }}\n}}\n\n\
+         #[allow(dead_code)]\n\
+         impl {name} {{\n    \
+             pub const ID: u32 = 0x{id:X};\n    \
+             pub const IS_EXTENDED: bool = {extended};\n    \
+             pub const DLC: usize = {dlc};\n"
+    );
+
+    let mut constants = HashSet::new();
+    for signal in &message.signals {
+        let raw_type = raw_type(signal);
+        for (value, label) in &signal.values {
+            let name = constant(&format!("{} {}", signal.name, label));
+            // Skip labels that would not compile, or are already defined
+            let negative = *value < 0 && signal.value_type == ValueType::Unsigned;
+            if negative || !constants.insert(name.clone()) {
+                continue;
+            }
+            let _ = writeln!(out, "    pub const {}: {} = {};", name, raw_type, value);
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "\n    /// Message with all signals set to zero\n    \
+         pub fn new() -> Self {{\n        Self {{ data: [0; {dlc}] }}\n    }}\n\n    \
+         /// Unpack a payload, `None` if it is shorter than the message\n    \
+         pub fn unpack(data: &[u8]) -> Option<Self> {{\n        \
+             let data = data.get(..Self::DLC)?.try_into().ok()?;\n        \
+             Some(Self {{ data }})\n    }}\n\n    \
+         pub fn pack(&self) -> [u8; {dlc}] {{\n        self.data\n    }}"
+    );
+
+    for signal in &message.signals {
+        accessors(out, message, signal);
+    }
+
+    let _ = writeln!(out, "}}");
+}
+
+fn accessors(out: &mut String, message: &Message, signal: &Signal) {
+    let name = snake(&signal.name);
+    // The other accessors have a prefix or suffix and never clash with keywords
+    let getter = if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name.clone()
+    };
+    let raw_type = raw_type(signal);
+    let start = signal.start_bit;
+    let len = signal.length;
+    let big_endian = signal.byte_order == ByteOrder::BigEndian;
+
+    if !signal.fits(message.dlc as usize) {
+        let _ = writeln!(
+            out,
+            "\n    // {} does not fit in the message and is skipped",
+            signal.name
+        );
+        return;
+    }
+
+    let extract = match signal.value_type {
+        ValueType::Unsigned => format!(
+            "dbc_extract(&self.data, {}, {}, {}) as {}",
+            start, len, big_endian, raw_type
+        ),
+        ValueType::Signed => format!(
+            "dbc_sign_extend(dbc_extract(&self.data, {}, {}, {}), {}) as {}",
+            start, len, big_endian, len, raw_type
+        ),
+    };
+
+    let (min, max) = match signal.value_type {
+        ValueType::Unsigned => (0.0, 2f64.powi(len as i32) - 1.0),
+        ValueType::Signed => (
+            -(2f64.powi(len as i32 - 1)),
+            2f64.powi(len as i32 - 1) - 1.0,
+        ),
+    };
+
+    out.push('\n');
+    let _ = write!(
+        out,
+        "    /// {} [{}, {}]",
+        signal.name, signal.min, signal.max
+    );
+    if !signal.unit.is_empty() {
+        let _ = write!(out, " {}", signal.unit);
+    }
+    out.push('\n');
+    if let Some(comment) = &signal.comment {
+        doc_comment(out, "    ", comment);
+    }
+    if signal.multiplexed_by.is_some() {
+        let _ = writeln!(
+            out,
+            "    ///\n    /// Multiplexed, only valid when selected by its multiplexor"
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "    pub fn {getter}(&self) -> f64 {{\n        \
+             self.{name}_raw() as f64 * {factor:?} + {offset:?}\n    }}\n\n    \
+         pub fn {name}_raw(&self) -> {raw_type} {{\n        {extract}\n    }}\n\n    \
+         /// Set the physical value, clamped to the range of the signal\n    \
+         pub fn set_{name}(&mut self, value: f64) {{\n        \
+             let raw = ((value - {offset:?}) / {factor:?}).round().clamp({min:?}, {max:?});\n        \
+             self.set_{name}_raw(raw as {raw_type});\n    }}\n\n    \
+         pub fn set_{name}_raw(&mut self, raw: {raw_type}) {{\n        \
+             dbc_insert(&mut self.data, {start}, {len}, {big_endian}, raw as u64);\n    }}",
+        factor = signal.factor,
+        offset = signal.offset,
+    );
+}
+
+fn doc_comment(out: &mut String, indent: &str, text: &str) {
+    let _ = writeln!(out, "{}///", indent);
+    for line in text.lines() {
+        let _ = writeln!(out, "{}/// {}", indent, line.trim_end());
+    }
+}
+
+/// Smallest integer type holding the raw value of a signal
+fn raw_type(signal: &Signal) -> &'static str {
+    let signed = signal.value_type == ValueType::Signed;
+    match (signal.length, signed) {
+        (0..=8, false) => "u8",
+        (0..=8, true) => "i8",
+        (9..=16, false) => "u16",
+        (9..=16, true) => "i16",
+        (17..=32, false) => "u32",
+        (17..=32, true) => "i32",
+        (_, false) => "u64",
+        (_, true) => "i64",
+    }
+}
+
+/// Split a DBC name into words at underscores and case changes
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_ascii_uppercase()
+            && (prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || (prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase())));
+
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Prefix names that are not valid identifiers
+fn identifier(name: String) -> String {
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+fn upper_camel(name: &str) -> String {
+    identifier(
+        words(name)
+            .iter()
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|c| {
+                        c.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect(),
+    )
+}
+
+fn snake(name: &str) -> String {
+    identifier(
+        words(name)
+            .iter()
+            .map(|word| word.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+    )
+}
+
+fn constant(name: &str) -> String {
+    identifier(
+        words(name)
+            .iter()
+            .map(|word| word.to_ascii_uppercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(upper_camel("ENGINE_DATA"), "EngineData");
+        assert_eq!(upper_camel("ABSStatus"), "AbsStatus");
+        assert_eq!(snake("EngineSpeed"), "engine_speed");
+        assert_eq!(snake("ABS_Active2"), "abs_active2");
+        assert_eq!(snake("Type"), "type");
+        assert_eq!(snake("2ndGear"), "_2nd_gear");
+        assert_eq!(constant("Gear 2nd"), "GEAR_2ND");
+    }
+
+    #[test]
+    fn generated_source() {
+        let db = Database::from_dbc(
+            r#"VERSION "2.1"
+BO_ 100 ENGINE_DATA: 8 Engine
+ SG_ EngineSpeed : 0|16@1+ (0.125,0) [0|8031.875] "rpm" Vector__XXX
+ SG_ Torque : 31|12@0- (0.5,-10) [-1024|1023.5] "Nm" Vector__XXX
+ SG_ Gear : 40|2@1+ (1,0) [0|3] "" Vector__XXX
+ SG_ Type : 48|16@1- (1,0) [0|0] "" Vector__XXX
+VAL_ 100 Gear 0 "PARK" 1 "REVERSE" ;
+"#,
+        )
+        .unwrap();
+
+        let code = generate(&db);
+
+        assert!(
+            code.starts_with("// Generated by candecode. Do not edit.\n// Database version: 2.1\n")
+        );
+        assert!(code.contains("fn dbc_extract("));
+        assert!(!code.contains("@author"));
+        assert!(code.contains("pub struct EngineData {\n    data: [u8; 8],\n}"));
+        assert!(code.contains("pub const ID: u32 = 0x64;"));
+        assert!(code.contains("pub const GEAR_REVERSE: u8 = 1;"));
+        assert!(code.contains("pub fn engine_speed_raw(&self) -> u16 {"));
+        assert!(code.contains("self.engine_speed_raw() as f64 * 0.125 + 0.0"));
+        assert!(code.contains("dbc_sign_extend(dbc_extract(&self.data, 31, 12, true), 12) as i16"));
+        assert!(code.contains("clamp(-2048.0, 2047.0)"));
+        assert!(code.contains("pub fn set_gear_raw(&mut self, raw: u8) {"));
+        assert!(code.contains("pub fn type_(&self) -> f64 {"));
+        assert!(code.contains("pub fn type_raw(&self) -> i16 {"));
+    }
+}
@@ -0,0 +1,66 @@
+//
+// runtime.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Bit access helpers copied into generated code
+//!
+//! Everything between the marker below and the tests is emitted verbatim, so it must not depend
+//! on this crate.
+
+// @generated-runtime
+
+/// Payload bit positions of a signal, most significant bit first
+#[allow(dead_code)]
+fn dbc_bits(start: usize, len: usize, big_endian: bool) -> impl Iterator<Item = usize> {
+    let mut motorola = start;
+    (0..len).map(move |i| {
+        if big_endian {
+            let bit = motorola;
+            motorola = if bit & 0x07 == 0 { bit + 15 } else { bit - 1 };
+            bit
+        } else {
+            start + len - 1 - i
+        }
+    })
+}
+
+#[allow(dead_code)]
+fn dbc_extract(data: &[u8], start: usize, len: usize, big_endian: bool) -> u64 {
+    dbc_bits(start, len, big_endian).fold(0u64, |raw, bit| {
+        (raw << 1) | ((data[bit / 8] >> (bit % 8)) & 0x01) as u64
+    })
+}
+
+#[allow(dead_code)]
+fn dbc_insert(data: &mut [u8], start: usize, len: usize, big_endian: bool, raw: u64) {
+    for (i, bit) in dbc_bits(start, len, big_endian).enumerate() {
+        let value = ((raw >> (len - 1 - i)) & 0x01) as u8;
+        data[bit / 8] = (data[bit / 8] & !(1 << (bit % 8))) | (value << (bit % 8));
+    }
+}
+
+#[allow(dead_code)]
+fn dbc_sign_extend(raw: u64, len: usize) -> i64 {
+    let shift = 64 - len as u32;
+    ((raw << shift) as i64) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_access() {
+        let mut data = [0u8; 4];
+
+        dbc_insert(&mut data, 11, 12, true, 0xABC);
+        assert_eq!(data, [0x00, 0x0A, 0xBC, 0x00]);
+        assert_eq!(dbc_extract(&data, 11, 12, true), 0xABC);
+
+        dbc_insert(&mut data, 24, 8, false, 0xFE);
+        assert_eq!(dbc_sign_extend(dbc_extract(&data, 24, 8, false), 8), -2);
+    }
+}
@@ -0,0 +1,189 @@
+//
+// database.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    dbc::{self, DbcError},
+    kcd::{self, KcdError},
+    signal::{Signal, SignalValue, ValueTable},
+};
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::{collections::HashMap, fs, path::Path};
+
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    #[error("Failed to read database: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid DBC file: {0}")]
+    Dbc(#[from] DbcError),
+    #[error("Invalid KCD file: {0}")]
+    Kcd(#[from] KcdError),
+    #[error("Unknown database format, expected .dbc or .kcd")]
+    UnknownFormat,
+}
+
+/// A node (ECU) on the bus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub comment: Option<String>,
+}
+
+/// A message and the signals it carries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: Id,
+    pub name: String,
+    pub dlc: u8,
+    pub transmitter: Option<String>,
+    pub signals: Vec<Signal>,
+    pub comment: Option<String>,
+}
+
+impl Message {
+    pub fn signal(&self, name: &str) -> Option<&Signal> {
+        self.signals.iter().find(|signal| signal.name == name)
+    }
+
+    /// Check if a signal is present in the payload given the value of its multiplexor(s)
+    pub fn is_active(&self, signal: &Signal, data: &[u8]) -> bool {
+        self.is_active_at(signal, data, 0)
+    }
+
+    fn is_active_at(&self, signal: &Signal, data: &[u8], depth: usize) -> bool {
+        let Some(condition) = &signal.multiplexed_by else {
+            return true;
+        };
+
+        // Guard against multiplexor cycles in malformed databases
+        if depth >= self.signals.len() {
+            return false;
+        }
+
+        let switch = match &condition.switch {
+            Some(name) => self.signal(name),
+            None => self
+                .signals
+                .iter()
+                .find(|s| s.multiplexor && s.multiplexed_by.is_none()),
+        };
+
+        switch.is_some_and(|switch| {
+            self.is_active_at(switch, data, depth + 1)
+                && switch
+                    .decode_raw(data)
+                    .is_some_and(|raw| condition.matches(raw))
+        })
+    }
+
+    /// Decode all signals of the message that fit in the payload. Multiplexed signals are only
+    /// decoded when their multiplexor selects them.
+    pub fn decode(&self, data: &[u8]) -> Vec<SignalValue<'_>> {
+        self.signals
+            .iter()
+            .filter(|signal| self.is_active(signal, data))
+            .filter_map(|signal| {
+                signal.decode_raw(data).map(|raw| SignalValue {
+                    signal,
+                    raw,
+                    value: signal.scale(raw),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Signal database
+#[derive(Debug, Clone, Default)]
+pub struct Database {
+    pub version: String,
+    pub comment: Option<String>,
+    /// Named value tables (VAL_TABLE_)
+    pub value_tables: HashMap<String, ValueTable>,
+    /// References to undefined messages, signals or nodes found while parsing
+    pub(crate) unresolved: Vec<String>,
+    nodes: Vec<Node>,
+    messages: Vec<Message>,
+    index: HashMap<Id, usize>,
+}
+
+impl Database {
+    /// Load a database, the format is selected from the file extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DatabaseError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("dbc") => Ok(Database::from_dbc(&text)?),
+            Some(ext) if ext.eq_ignore_ascii_case("kcd") => Ok(Database::from_kcd(&text)?),
+            _ => Err(DatabaseError::UnknownFormat),
+        }
+    }
+
+    pub fn from_dbc(text: &str) -> Result<Self, DbcError> {
+        dbc::parse(text)
+    }
+
+    pub fn from_kcd(text: &str) -> Result<Self, KcdError> {
+        kcd::parse(text)
+    }
+
+    pub(crate) fn new(version: String, nodes: Vec<Node>, messages: Vec<Message>) -> Self {
+        let index = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| (message.id, i))
+            .collect();
+
+        Database {
+            version,
+            comment: None,
+            value_tables: HashMap::new(),
+            unresolved: vec![],
+            nodes,
+            messages,
+            index,
+        }
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn message(&self, id: Id) -> Option<&Message> {
+        self.index.get(&id).map(|&i| &self.messages[i])
+    }
+
+    pub fn message_by_name(&self, name: &str) -> Option<&Message> {
+        self.messages.iter().find(|message| message.name == name)
+    }
+
+    pub fn unresolved(&self) -> &[String] {
+        &self.unresolved
+    }
+
+    pub(crate) fn node_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|node| node.name == name)
+    }
+
+    pub(crate) fn message_mut(&mut self, id: Id) -> Option<&mut Message> {
+        self.index.get(&id).map(|&i| &mut self.messages[i])
+    }
+
+    /// Decode the signals of a frame, empty if the message is not in the database
+    pub fn decode(&self, id: Id, data: &[u8]) -> Vec<SignalValue<'_>> {
+        self.message(id)
+            .map(|message| message.decode(data))
+            .unwrap_or_default()
+    }
+}
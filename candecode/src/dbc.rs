@@ -0,0 +1,969 @@
+//
+// dbc.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! DBC file parser and writer
+//!
+//! Only the sections needed for decoding are interpreted. Attribute definitions, environment
+//! variables and other sections are skipped.
+
+use crate::{
+    database::{Database, Message, Node},
+    signal::{ByteOrder, MuxCondition, Signal, ValueTable, ValueType},
+};
+
+use embedded_can::{ExtendedId, Id, StandardId};
+use thiserror::Error;
+
+use std::{collections::BTreeSet, fmt::Write as _, str::FromStr};
+
+/// Set in DBC message IDs for extended identifiers
+const EXTENDED_FLAG: u32 = 0x8000_0000;
+/// Placeholder used when a message has no transmitter or a signal has no receiver
+const NO_NODE: &str = "Vector__XXX";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct DbcError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Punct(char),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Lexer<'_> {
+    fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, DbcError> {
+        let mut lexer = Lexer {
+            chars: text.chars().peekable(),
+            line: 1,
+        };
+
+        let mut tokens = vec![];
+        while let Some(token) = lexer.next_token()? {
+            tokens.push(token);
+        }
+
+        Ok(tokens)
+    }
+
+    fn next_token(&mut self) -> Result<Option<(Token, usize)>, DbcError> {
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                self.line += 1;
+            }
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+
+        let line = self.line;
+        let Some(c) = self.chars.next() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(escaped) = self.chars.next() {
+                                s.push(escaped);
+                            }
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                self.line += 1;
+                            }
+                            s.push(c);
+                        }
+                        None => {
+                            return Err(DbcError {
+                                line,
+                                message: String::from("unterminated string"),
+                            })
+                        }
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut s = String::from(c);
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    s.push(c);
+                    self.chars.next();
+                }
+                Token::Ident(s)
+            }
+            c if c.is_ascii_digit() || ((c == '-' || c == '+') && self.peek_is_number()) => {
+                let mut s = String::from(c);
+                while let Some(&c) = self.chars.peek() {
+                    let exponent_sign = (c == '-' || c == '+') && s.ends_with(['e', 'E']);
+                    if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                        break;
+                    }
+                    s.push(c);
+                    self.chars.next();
+                }
+                Token::Number(s)
+            }
+            c => Token::Punct(c),
+        };
+
+        Ok(Some((token, line)))
+    }
+
+    fn peek_is_number(&mut self) -> bool {
+        self.chars
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+    }
+}
+
+/// Target of a CM_ comment
+enum CommentTarget {
+    Database,
+    Node(String),
+    Message(u32),
+    Signal(u32, String),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    /// Check if the next token is on the given line
+    fn on_line(&self, line: usize) -> bool {
+        self.tokens.get(self.pos).is_some_and(|(_, l)| *l == line)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, DbcError> {
+        Err(DbcError {
+            line: self.line(),
+            message: message.into(),
+        })
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), DbcError> {
+        match self.peek() {
+            Some(Token::Punct(p)) if *p == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => self.error(format!("expected '{}'", c)),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, DbcError> {
+        match self.peek() {
+            Some(Token::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.error("expected identifier"),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String, DbcError> {
+        match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.error("expected string"),
+        }
+    }
+
+    fn expect_number<T: FromStr>(&mut self) -> Result<T, DbcError> {
+        match self.peek() {
+            Some(Token::Number(s)) => match s.parse() {
+                Ok(value) => {
+                    self.pos += 1;
+                    Ok(value)
+                }
+                Err(_) => self.error(format!("invalid number '{}'", s)),
+            },
+            _ => self.error("expected number"),
+        }
+    }
+
+    fn skip_statement(&mut self) {
+        while let Some(token) = self.next() {
+            if token == Token::Punct(';') {
+                break;
+            }
+        }
+    }
+
+    fn skip_line(&mut self, line: usize) {
+        while self.on_line(line) {
+            self.pos += 1;
+        }
+    }
+
+    fn message(&mut self) -> Result<Option<Message>, DbcError> {
+        let raw: u32 = self.expect_number()?;
+        let name = self.expect_ident()?;
+        self.expect_punct(':')?;
+        let dlc = self.expect_number()?;
+        let transmitter = self.expect_ident()?;
+
+        let Some(id) = message_id(raw) else {
+            // Pseudo messages such as VECTOR__INDEPENDENT_SIG_MSG are not on the bus
+            return Ok(None);
+        };
+
+        Ok(Some(Message {
+            id,
+            name,
+            dlc,
+            transmitter: (transmitter != NO_NODE).then_some(transmitter),
+            signals: vec![],
+            comment: None,
+        }))
+    }
+
+    fn signal(&mut self) -> Result<Signal, DbcError> {
+        let line = self.line();
+        let name = self.expect_ident()?;
+
+        // Multiplexer indicator, `M` for the multiplexor, `m<N>` for signals present when the
+        // multiplexor is N and `m<N>M` for both with extended multiplexing
+        let (multiplexor, multiplexed_by) = match self.peek() {
+            Some(Token::Ident(indicator)) => {
+                let indicator = indicator.clone();
+                self.pos += 1;
+                self.mux_indicator(&indicator)?
+            }
+            _ => (false, None),
+        };
+
+        self.expect_punct(':')?;
+        let start_bit = self.expect_number()?;
+        self.expect_punct('|')?;
+        let length = self.expect_number()?;
+        self.expect_punct('@')?;
+
+        let byte_order = match self.expect_number::<u8>()? {
+            0 => ByteOrder::BigEndian,
+            1 => ByteOrder::LittleEndian,
+            _ => return self.error("invalid byte order"),
+        };
+
+        let value_type = match self.next() {
+            Some(Token::Punct('+')) => ValueType::Unsigned,
+            Some(Token::Punct('-')) => ValueType::Signed,
+            _ => return self.error("expected value type '+' or '-'"),
+        };
+
+        self.expect_punct('(')?;
+        let factor = self.expect_number()?;
+        self.expect_punct(',')?;
+        let offset = self.expect_number()?;
+        self.expect_punct(')')?;
+        self.expect_punct('[')?;
+        let min = self.expect_number()?;
+        self.expect_punct('|')?;
+        let max = self.expect_number()?;
+        self.expect_punct(']')?;
+        let unit = self.expect_string()?;
+
+        let mut receivers = vec![];
+        while self.on_line(line) {
+            match self.next() {
+                Some(Token::Ident(receiver)) if receiver != NO_NODE => receivers.push(receiver),
+                Some(Token::Ident(_)) | Some(Token::Punct(',')) => {}
+                _ => return self.error("expected receiver"),
+            }
+        }
+
+        Ok(Signal {
+            name,
+            start_bit,
+            length,
+            byte_order,
+            value_type,
+            factor,
+            offset,
+            min,
+            max,
+            unit,
+            receivers,
+            comment: None,
+            multiplexor,
+            multiplexed_by,
+            values: ValueTable::new(),
+        })
+    }
+
+    /// Value/label pairs up to the terminating ';'
+    fn value_descriptions(&mut self) -> Result<ValueTable, DbcError> {
+        let mut table = ValueTable::new();
+
+        loop {
+            match self.peek() {
+                Some(Token::Punct(';')) => {
+                    self.pos += 1;
+                    return Ok(table);
+                }
+                Some(Token::Number(value)) => {
+                    // Some tools write enumerated values as floats
+                    let value = match value.parse::<i64>() {
+                        Ok(value) => {
+                            self.pos += 1;
+                            value
+                        }
+                        Err(_) => self.expect_number::<f64>()? as i64,
+                    };
+                    let label = self.expect_string()?;
+                    table.insert(value, label);
+                }
+                _ => return self.error("expected value description"),
+            }
+        }
+    }
+
+    fn mux_indicator(&self, indicator: &str) -> Result<(bool, Option<MuxCondition>), DbcError> {
+        if indicator == "M" {
+            return Ok((true, None));
+        }
+
+        let Some(value) = indicator.strip_prefix('m') else {
+            return self.error(format!("invalid multiplexer indicator '{}'", indicator));
+        };
+
+        let (value, multiplexor) = match value.strip_suffix('M') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+
+        match value.parse::<u64>() {
+            Ok(value) => Ok((
+                multiplexor,
+                Some(MuxCondition {
+                    switch: None,
+                    values: vec![value..=value],
+                }),
+            )),
+            Err(_) => self.error(format!("invalid multiplexer indicator '{}'", indicator)),
+        }
+    }
+
+    /// SG_MUL_VAL_ <message id> <signal> <multiplexor> <from>-<to>, ... ;
+    fn extended_multiplexing(&mut self) -> Result<(u32, String, MuxCondition), DbcError> {
+        let id = self.expect_number()?;
+        let signal = self.expect_ident()?;
+        let switch = self.expect_ident()?;
+
+        let mut values = vec![];
+        loop {
+            let from: u64 = self.expect_number()?;
+            // The lexer reads `-<to>` as a negative number
+            let to: u64 = match self.next() {
+                Some(Token::Number(to)) if to.starts_with('-') => to[1..]
+                    .parse()
+                    .or_else(|_| self.error(format!("invalid number '{}'", to)))?,
+                Some(Token::Punct('-')) => self.expect_number()?,
+                _ => return self.error("expected multiplexor value range"),
+            };
+            values.push(from..=to);
+
+            match self.next() {
+                Some(Token::Punct(',')) => continue,
+                Some(Token::Punct(';')) => break,
+                _ => return self.error("expected ',' or ';'"),
+            }
+        }
+
+        Ok((
+            id,
+            signal,
+            MuxCondition {
+                switch: Some(switch),
+                values,
+            },
+        ))
+    }
+
+    fn comment(&mut self) -> Result<(CommentTarget, String), DbcError> {
+        let target = match self.peek() {
+            Some(Token::Str(_)) => CommentTarget::Database,
+            Some(Token::Ident(kind)) => {
+                let kind = kind.clone();
+                self.pos += 1;
+
+                match kind.as_str() {
+                    "BU_" | "EV_" => CommentTarget::Node(self.expect_ident()?),
+                    "BO_" => CommentTarget::Message(self.expect_number()?),
+                    "SG_" => {
+                        let id = self.expect_number()?;
+                        CommentTarget::Signal(id, self.expect_ident()?)
+                    }
+                    _ => return self.error(format!("unknown comment type '{}'", kind)),
+                }
+            }
+            _ => return self.error("expected comment"),
+        };
+
+        let text = self.expect_string()?;
+        self.expect_punct(';')?;
+
+        Ok((target, text))
+    }
+}
+
+fn message_id(raw: u32) -> Option<Id> {
+    if raw & EXTENDED_FLAG != 0 {
+        ExtendedId::new(raw & !EXTENDED_FLAG).map(Id::Extended)
+    } else {
+        u16::try_from(raw)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard)
+    }
+}
+
+/// Parse the contents of a DBC file
+fn signal_mut<'a>(db: &'a mut Database, raw: u32, name: &str) -> Option<&'a mut Signal> {
+    message_id(raw)
+        .and_then(|id| db.message_mut(id))
+        .and_then(|message| message.signals.iter_mut().find(|s| s.name == name))
+}
+
+/// Record a reference to a signal that is not in the database
+fn unresolved(db: &mut Database, what: &str, raw: u32, name: &str) {
+    // Signals of pseudo messages are dropped on purpose
+    if message_id(raw).is_some() {
+        db.unresolved
+            .push(format!("{} for unknown signal {} {}", what, raw, name));
+    }
+}
+
+pub fn parse(text: &str) -> Result<Database, DbcError> {
+    let mut parser = Parser {
+        tokens: Lexer::tokenize(text)?,
+        pos: 0,
+    };
+
+    let mut version = String::new();
+    let mut nodes = vec![];
+    let mut messages: Vec<Message> = vec![];
+    let mut comments = vec![];
+    let mut extended_mux = vec![];
+    let mut value_tables = vec![];
+    let mut signal_values = vec![];
+    // Signals of pseudo messages are dropped along with the message
+    let mut in_message = false;
+
+    while let Some(token) = parser.next() {
+        let keyword = match token {
+            Token::Ident(keyword) => keyword,
+            _ => return parser.error("expected keyword"),
+        };
+
+        match keyword.as_str() {
+            "VERSION" => version = parser.expect_string()?,
+            "NS_" => {
+                // List of new symbols, ends at the bit timing section
+                parser.expect_punct(':')?;
+                while let Some(Token::Ident(s)) = parser.peek() {
+                    if s == "BS_" || s == "BU_" || s == "BO_" {
+                        break;
+                    }
+                    parser.pos += 1;
+                }
+            }
+            "BS_" => {
+                let line = parser.line();
+                parser.skip_line(line);
+            }
+            "BU_" => {
+                let line = parser.line();
+                parser.expect_punct(':')?;
+                while parser.on_line(line) {
+                    nodes.push(Node {
+                        name: parser.expect_ident()?,
+                        comment: None,
+                    });
+                }
+            }
+            "BO_" => match parser.message()? {
+                Some(message) => {
+                    messages.push(message);
+                    in_message = true;
+                }
+                None => in_message = false,
+            },
+            "SG_" => {
+                let signal = parser.signal()?;
+                match messages.last_mut() {
+                    Some(message) if in_message => message.signals.push(signal),
+                    Some(_) => {}
+                    None => return parser.error("signal outside of a message"),
+                }
+            }
+            "CM_" => comments.push(parser.comment()?),
+            "SG_MUL_VAL_" => extended_mux.push(parser.extended_multiplexing()?),
+            "VAL_TABLE_" => {
+                let name = parser.expect_ident()?;
+                value_tables.push((name, parser.value_descriptions()?));
+            }
+            "VAL_" => match parser.peek() {
+                Some(Token::Number(_)) => {
+                    let id: u32 = parser.expect_number()?;
+                    let name = parser.expect_ident()?;
+                    signal_values.push((id, name, parser.value_descriptions()?));
+                }
+                // Value descriptions of environment variables
+                _ => parser.skip_statement(),
+            },
+            _ => parser.skip_statement(),
+        }
+    }
+
+    let mut db = Database::new(version, nodes, messages);
+
+    db.value_tables.extend(value_tables);
+
+    for (raw, name, values) in signal_values {
+        match signal_mut(&mut db, raw, &name) {
+            Some(signal) => signal.values = values,
+            None => unresolved(&mut db, "value descriptions", raw, &name),
+        }
+    }
+
+    // Extended multiplexing replaces the condition from the signal's multiplexer indicator
+    for (raw, name, condition) in extended_mux {
+        match signal_mut(&mut db, raw, &name) {
+            Some(signal) => signal.multiplexed_by = Some(condition),
+            None => unresolved(&mut db, "extended multiplexing", raw, &name),
+        }
+    }
+
+    for (target, text) in comments {
+        match target {
+            CommentTarget::Database => db.comment = Some(text),
+            CommentTarget::Node(name) => match db.node_mut(&name) {
+                Some(node) => node.comment = Some(text),
+                None => db
+                    .unresolved
+                    .push(format!("comment for unknown node {}", name)),
+            },
+            CommentTarget::Message(raw) => {
+                match message_id(raw).and_then(|id| db.message_mut(id)) {
+                    Some(message) => message.comment = Some(text),
+                    None if message_id(raw).is_some() => db
+                        .unresolved
+                        .push(format!("comment for unknown message {}", raw)),
+                    None => {}
+                }
+            }
+            CommentTarget::Signal(raw, name) => match signal_mut(&mut db, raw, &name) {
+                Some(signal) => signal.comment = Some(text),
+                None => unresolved(&mut db, "comment", raw, &name),
+            },
+        }
+    }
+
+    Ok(db)
+}
+
+/// Write messages as a DBC file
+///
+/// Nodes are taken from the transmitters and receivers. Comments, value descriptions and
+/// multiplexing are kept, so parsing the output gives back the same messages.
+pub fn write<'a>(messages: impl IntoIterator<Item = &'a Message>) -> String {
+    let messages = messages.into_iter().collect::<Vec<_>>();
+
+    let nodes = messages
+        .iter()
+        .flat_map(|message| {
+            message.transmitter.iter().chain(
+                message
+                    .signals
+                    .iter()
+                    .flat_map(|signal| signal.receivers.iter()),
+            )
+        })
+        .map(String::as_str)
+        .filter(|&node| node != NO_NODE)
+        .collect::<BTreeSet<_>>();
+
+    // Writing to a string cannot fail
+    let mut out = String::from("VERSION \"\"\n\nNS_ :\n\nBS_:\n\nBU_:");
+    for node in &nodes {
+        let _ = write!(out, " {}", node);
+    }
+    out.push('\n');
+
+    let mut trailer = String::new();
+    for message in &messages {
+        let raw = dbc_id(message.id);
+        let _ = write!(
+            out,
+            "\nBO_ {} {}: {} {}\n",
+            raw,
+            message.name,
+            message.dlc,
+            message.transmitter.as_deref().unwrap_or(NO_NODE)
+        );
+        if let Some(comment) = &message.comment {
+            let _ = writeln!(trailer, "CM_ BO_ {} {};", raw, quote(comment));
+        }
+
+        for signal in &message.signals {
+            let _ = writeln!(
+                out,
+                " SG_ {}{} : {}|{}@{}{} ({},{}) [{}|{}] {} {}",
+                signal.name,
+                multiplexer_indicator(signal),
+                signal.start_bit,
+                signal.length,
+                match signal.byte_order {
+                    ByteOrder::LittleEndian => '1',
+                    ByteOrder::BigEndian => '0',
+                },
+                match signal.value_type {
+                    ValueType::Unsigned => '+',
+                    ValueType::Signed => '-',
+                },
+                signal.factor,
+                signal.offset,
+                signal.min,
+                signal.max,
+                quote(&signal.unit),
+                match signal.receivers.is_empty() {
+                    true => NO_NODE.to_string(),
+                    false => signal.receivers.join(","),
+                }
+            );
+
+            if let Some(comment) = &signal.comment {
+                let _ = writeln!(
+                    trailer,
+                    "CM_ SG_ {} {} {};",
+                    raw,
+                    signal.name,
+                    quote(comment)
+                );
+            }
+            if !signal.values.is_empty() {
+                let _ = write!(trailer, "VAL_ {} {}", raw, signal.name);
+                for (value, label) in &signal.values {
+                    let _ = write!(trailer, " {} {}", value, quote(label));
+                }
+                trailer.push_str(" ;\n");
+            }
+            if let Some(condition) = extended_condition(message, signal) {
+                let _ = writeln!(
+                    trailer,
+                    "SG_MUL_VAL_ {} {} {};",
+                    raw, signal.name, condition
+                );
+            }
+        }
+    }
+
+    if !trailer.is_empty() {
+        out.push('\n');
+        out.push_str(&trailer);
+    }
+
+    out
+}
+
+fn dbc_id(id: Id) -> u32 {
+    match id {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | EXTENDED_FLAG,
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// ` M`, ` m<value>` or ` m<value>M` after the signal name
+fn multiplexer_indicator(signal: &Signal) -> String {
+    let mut indicator = String::new();
+    if let Some(value) = signal
+        .multiplexed_by
+        .as_ref()
+        .and_then(|condition| condition.values.first())
+    {
+        let _ = write!(indicator, " m{}", value.start());
+    }
+    if signal.multiplexor {
+        indicator.push_str(match indicator.is_empty() {
+            true => " M",
+            false => "M",
+        });
+    }
+    indicator
+}
+
+/// Multiplexor and value ranges of a condition the indicator cannot express
+fn extended_condition(message: &Message, signal: &Signal) -> Option<String> {
+    let condition = signal.multiplexed_by.as_ref()?;
+    let simple = condition.switch.is_none()
+        && matches!(condition.values.as_slice(), [value] if value.start() == value.end());
+    if simple {
+        return None;
+    }
+
+    let switch = match &condition.switch {
+        Some(switch) => switch.as_str(),
+        None => message
+            .signals
+            .iter()
+            .find(|s| s.multiplexor && s.multiplexed_by.is_none())
+            .map(|s| s.name.as_str())?,
+    };
+    let ranges = condition
+        .values
+        .iter()
+        .map(|range| format!("{}-{}", range.start(), range.end()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(format!("{} {}", switch, ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBC: &str = r#"VERSION "1.0"
+
+NS_ :
+    NS_DESC_
+    CM_
+    BA_DEF_
+    VAL_
+
+BS_:
+
+BU_: Engine Gateway Dashboard
+
+BO_ 100 EngineData: 8 Engine
+ SG_ EngineSpeed : 0|16@1+ (0.125,0) [0|8031.875] "rpm" Gateway,Dashboard
+ SG_ CoolantTemp : 16|8@1+ (1,-40) [-40|215] "degC" Dashboard
+ SG_ Torque : 31|12@0- (0.5,0) [-1024|1023.5] "Nm" Vector__XXX
+
+BO_ 2566844926 EEC1: 8 Vector__XXX
+ SG_ Speed : 24|16@1+ (1E-1,0) [0|6553.5] "km/h" Vector__XXX
+
+BO_ 3221225472 VECTOR__INDEPENDENT_SIG_MSG: 0 Vector__XXX
+ SG_ Unused : 0|8@1+ (1,0) [0|0] "" Vector__XXX
+
+CM_ "Test database";
+CM_ BU_ Engine "Engine control unit";
+CM_ BO_ 100 "Engine status";
+CM_ SG_ 100 EngineSpeed "Crankshaft
+speed";
+VAL_TABLE_ GearTable 0 "PARK" 1 "REVERSE" 2 "NEUTRAL" 3 "DRIVE" ;
+VAL_ 100 CoolantTemp 255 "Not available" ;
+BA_DEF_ BO_ "GenMsgCycleTime" INT 0 10000;
+BA_ "GenMsgCycleTime" BO_ 100 10;
+"#;
+
+    fn standard(id: u16) -> Id {
+        Id::Standard(StandardId::new(id).unwrap())
+    }
+
+    #[test]
+    fn parse_database() {
+        let db = parse(DBC).unwrap();
+
+        assert_eq!(db.version, "1.0");
+        assert_eq!(db.comment.as_deref(), Some("Test database"));
+        assert_eq!(db.nodes().len(), 3);
+        assert_eq!(
+            db.nodes()[0].comment.as_deref(),
+            Some("Engine control unit")
+        );
+        assert_eq!(db.messages().len(), 2);
+
+        let msg = db.message(standard(100)).unwrap();
+        assert_eq!(msg.name, "EngineData");
+        assert_eq!(msg.dlc, 8);
+        assert_eq!(msg.transmitter.as_deref(), Some("Engine"));
+        assert_eq!(msg.comment.as_deref(), Some("Engine status"));
+
+        let speed = msg.signal("EngineSpeed").unwrap();
+        assert_eq!(speed.byte_order, ByteOrder::LittleEndian);
+        assert_eq!(speed.value_type, ValueType::Unsigned);
+        assert_eq!(speed.factor, 0.125);
+        assert_eq!(speed.max, 8031.875);
+        assert_eq!(speed.receivers, vec!["Gateway", "Dashboard"]);
+        assert_eq!(speed.comment.as_deref(), Some("Crankshaft\nspeed"));
+
+        let coolant = msg.signal("CoolantTemp").unwrap();
+        assert_eq!(coolant.offset, -40.0);
+        assert_eq!(coolant.min, -40.0);
+        assert_eq!(coolant.label(255), Some("Not available"));
+
+        assert_eq!(
+            db.value_tables["GearTable"].get(&1).map(String::as_str),
+            Some("REVERSE")
+        );
+
+        let torque = msg.signal("Torque").unwrap();
+        assert_eq!(torque.byte_order, ByteOrder::BigEndian);
+        assert_eq!(torque.value_type, ValueType::Signed);
+        assert!(torque.receivers.is_empty());
+
+        let eec1 = db.message_by_name("EEC1").unwrap();
+        assert_eq!(eec1.id, Id::Extended(ExtendedId::new(0x18FEF1FE).unwrap()));
+        assert_eq!(eec1.transmitter, None);
+        assert_eq!(eec1.signals[0].factor, 0.1);
+    }
+
+    #[test]
+    fn decode_message() {
+        let db = parse(DBC).unwrap();
+
+        let values = db.decode(standard(100), &[0x60, 0x2F, 0x82, 0xFF, 0xF0, 0, 0, 0]);
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            values,
+            vec![
+                "EngineSpeed = 1516.000 rpm",
+                "CoolantTemp = 90 degC",
+                "Torque = -0.5 Nm"
+            ]
+        );
+
+        let values = db.decode(standard(100), &[0, 0, 0xFF, 0, 0, 0, 0, 0]);
+        assert_eq!(values[1].to_string(), "CoolantTemp = Not available");
+        assert_eq!(
+            format!("{:#}", values[1]),
+            "CoolantTemp = Not available (255)"
+        );
+    }
+
+    const MUX_DBC: &str = r#"
+BO_ 200 Mux: 8 Vector__XXX
+ SG_ Selector M : 0|8@1+ (1,0) [0|255] "" Vector__XXX
+ SG_ Voltage m0 : 8|16@1+ (0.01,0) [0|655.35] "V" Vector__XXX
+ SG_ Current m1 : 8|16@1+ (0.1,0) [0|6553.5] "A" Vector__XXX
+ SG_ Sub m2M : 8|8@1+ (1,0) [0|255] "" Vector__XXX
+ SG_ Cell m2 : 16|8@1+ (1,0) [0|255] "" Vector__XXX
+
+SG_MUL_VAL_ 200 Sub Selector 2-2;
+SG_MUL_VAL_ 200 Cell Sub 0-3, 10-10;
+"#;
+
+    fn decode_names(db: &Database, data: &[u8]) -> Vec<String> {
+        db.decode(standard(200), data)
+            .iter()
+            .map(|v| v.signal.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn multiplexed_signals() {
+        let db = parse(MUX_DBC).unwrap();
+
+        let values = db.decode(standard(200), &[0, 0x10, 0x27, 0, 0, 0, 0, 0]);
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        assert_eq!(values, vec!["Selector = 0", "Voltage = 100.00 V"]);
+
+        assert_eq!(
+            decode_names(&db, &[1, 0, 0, 0, 0, 0, 0, 0]),
+            vec!["Selector", "Current"]
+        );
+        // Nothing is multiplexed on selector 3
+        assert_eq!(
+            decode_names(&db, &[3, 0, 0, 0, 0, 0, 0, 0]),
+            vec!["Selector"]
+        );
+    }
+
+    #[test]
+    fn extended_multiplexing() {
+        let db = parse(MUX_DBC).unwrap();
+
+        let cell = db.message(standard(200)).unwrap().signal("Cell").unwrap();
+        let condition = cell.multiplexed_by.as_ref().unwrap();
+        assert_eq!(condition.switch.as_deref(), Some("Sub"));
+        assert_eq!(condition.values, vec![0..=3, 10..=10]);
+
+        assert_eq!(
+            decode_names(&db, &[2, 10, 0, 0, 0, 0, 0, 0]),
+            vec!["Selector", "Sub", "Cell"]
+        );
+        assert_eq!(
+            decode_names(&db, &[2, 5, 0, 0, 0, 0, 0, 0]),
+            vec!["Selector", "Sub"]
+        );
+        // Cell is inactive when its multiplexor is inactive
+        assert_eq!(
+            decode_names(&db, &[1, 10, 0, 0, 0, 0, 0, 0]),
+            vec!["Selector", "Current"]
+        );
+    }
+
+    #[test]
+    fn write_round_trip() {
+        for text in [DBC, MUX_DBC] {
+            let db = parse(text).unwrap();
+            let written = parse(&write(db.messages())).unwrap();
+            assert_eq!(written.messages(), db.messages());
+        }
+
+        let db = parse(DBC).unwrap();
+        let text = write(db.messages());
+        assert!(text.contains("BU_: Dashboard Engine Gateway\n"));
+        assert!(text.contains("CM_ SG_ 100 EngineSpeed \"Crankshaft\nspeed\";"));
+    }
+
+    #[test]
+    fn syntax_error() {
+        let err =
+            parse("VERSION \"\"\n\nBO_ 100 Msg: 8 Node\n SG_ Sig : 0|8@2+ (1,0) [0|0] \"\" X\n")
+                .unwrap_err();
+        assert_eq!(err.line, 4);
+    }
+}
@@ -0,0 +1,457 @@
+//
+// filter.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Filter expressions over decoded signals
+//!
+//! ```text
+//! EngineSpeed > 3000 && (Gear == REVERSE || !(CoolantTemp < 90))
+//! EngineData.EngineSpeed >= 800 and Gear != "Not available"
+//! ```
+//!
+//! Signals may be qualified with their message name. Enumerated signals can be compared
+//! against their labels. A comparison is false when the frame does not carry the signal.
+
+use crate::{database::Database, signal::SignalValue};
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FilterError {
+    #[error("Unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("Unterminated string")]
+    UnterminatedString,
+    #[error("Expected {0}")]
+    Expected(&'static str),
+    #[error("Unexpected '{0}' after expression")]
+    Trailing(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn apply<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Ge => lhs >= rhs,
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Right hand side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    Label(String),
+}
+
+/// A signal, optionally qualified with its message name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalRef {
+    pub message: Option<String>,
+    pub signal: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Compare {
+        signal: SignalRef,
+        op: CompareOp,
+        value: Operand,
+    },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluate the expression against a frame
+    pub fn matches(&self, db: &Database, id: Id, data: &[u8]) -> bool {
+        let Some(message) = db.message(id) else {
+            return false;
+        };
+
+        let values = message.decode(data);
+        self.evaluate(&message.name, &values)
+    }
+
+    /// Evaluate the expression against the decoded signals of a message
+    pub fn evaluate(&self, message: &str, values: &[SignalValue]) -> bool {
+        match self {
+            Expr::Compare { signal, op, value } => {
+                if signal.message.as_deref().is_some_and(|m| m != message) {
+                    return false;
+                }
+
+                let Some(current) = values.iter().find(|v| v.signal.name == signal.signal) else {
+                    return false;
+                };
+
+                match value {
+                    Operand::Number(n) => op.apply(current.value, *n),
+                    Operand::Label(label) => {
+                        // Labels are compared through their raw value so ordering works too
+                        match current.signal.values.iter().find(|(_, l)| *l == label) {
+                            Some((raw, _)) => op.apply(current.raw, *raw as i128),
+                            None => *op == CompareOp::Ne,
+                        }
+                    }
+                }
+            }
+            Expr::Not(expr) => !expr.evaluate(message, values),
+            Expr::And(lhs, rhs) => lhs.evaluate(message, values) && rhs.evaluate(message, values),
+            Expr::Or(lhs, rhs) => lhs.evaluate(message, values) || rhs.evaluate(message, values),
+        }
+    }
+
+    /// Signals referenced by the expression
+    pub fn signals(&self) -> Vec<&SignalRef> {
+        match self {
+            Expr::Compare { signal, .. } => vec![signal],
+            Expr::Not(expr) => expr.signals(),
+            Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+                let mut signals = lhs.signals();
+                signals.extend(rhs.signals());
+                signals
+            }
+        }
+    }
+}
+
+impl fmt::Display for SignalRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}.{}", message, self.signal),
+            None => write!(f, "{}", self.signal),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Number(n) => write!(f, "{}", n),
+            Operand::Label(label) if label.contains(|c: char| !c.is_ascii_alphanumeric()) => {
+                write!(f, "\"{}\"", label)
+            }
+            Operand::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Compare { signal, op, value } => write!(f, "{} {} {}", signal, op, value),
+            Expr::Not(expr) => write!(f, "!({})", expr),
+            Expr::And(lhs, rhs) => {
+                // Or binds weaker than and
+                let wrap = |e: &Expr| match e {
+                    Expr::Or(..) => format!("({})", e),
+                    _ => e.to_string(),
+                };
+                write!(f, "{} && {}", wrap(lhs), wrap(rhs))
+            }
+            Expr::Or(lhs, rhs) => write!(f, "{} || {}", lhs, rhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Ne),
+            '!' => Token::Not,
+            '=' => {
+                // Accept both `=` and `==`
+                chars.next_if_eq(&'=');
+                Token::Op(CompareOp::Eq)
+            }
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Le),
+            '<' => Token::Op(CompareOp::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Ge),
+            '>' => Token::Op(CompareOp::Gt),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => s.push(c),
+                        None => return Err(FilterError::UnterminatedString),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut s = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    s.push(c);
+                }
+
+                let number = match s.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).map(|n| n as f64).ok(),
+                    None => s.parse().ok(),
+                };
+                Token::Number(number.ok_or(FilterError::Expected("number"))?)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut s = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    s.push(c);
+                }
+
+                match s.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(s),
+                }
+            }
+            c => return Err(FilterError::UnexpectedChar(c)),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(FilterError::Expected("')'")),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                let signal = match name.split_once('.') {
+                    Some((message, signal)) => SignalRef {
+                        message: Some(message.to_string()),
+                        signal: signal.to_string(),
+                    },
+                    None => SignalRef {
+                        message: None,
+                        signal: name,
+                    },
+                };
+
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    _ => return Err(FilterError::Expected("comparison operator")),
+                };
+
+                let value = match self.next() {
+                    Some(Token::Number(n)) => Operand::Number(n),
+                    Some(Token::Ident(label)) | Some(Token::Str(label)) => Operand::Label(label),
+                    _ => return Err(FilterError::Expected("value")),
+                };
+
+                Ok(Expr::Compare { signal, op, value })
+            }
+            _ => Err(FilterError::Expected("signal name")),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+
+        let expr = parser.or()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(FilterError::Trailing(format!("{:?}", token))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    const DBC: &str = r#"
+BO_ 100 EngineData: 8 Engine
+ SG_ EngineSpeed : 0|16@1+ (0.125,0) [0|8031.875] "rpm" Vector__XXX
+ SG_ Gear : 16|8@1+ (1,0) [0|3] "" Vector__XXX
+
+BO_ 101 Other: 8 Engine
+ SG_ EngineSpeed : 0|16@1+ (1,0) [0|65535] "rpm" Vector__XXX
+
+VAL_ 100 Gear 0 "PARK" 1 "REVERSE" 2 "NEUTRAL" 3 "DRIVE" ;
+"#;
+
+    fn id(id: u16) -> Id {
+        Id::Standard(StandardId::new(id).unwrap())
+    }
+
+    fn matches(expr: &str, message: u16, data: &[u8]) -> bool {
+        let db = Database::from_dbc(DBC).unwrap();
+        expr.parse::<Expr>()
+            .unwrap()
+            .matches(&db, id(message), data)
+    }
+
+    // EngineSpeed = 3200 rpm, Gear = REVERSE
+    const DATA: [u8; 3] = [0x00, 0x64, 0x01];
+
+    #[test]
+    fn comparisons() {
+        assert!(matches("EngineSpeed > 3000", 100, &DATA));
+        assert!(!matches("EngineSpeed <= 3000", 100, &DATA));
+        assert!(matches("EngineSpeed == 3200", 100, &DATA));
+        assert!(matches("Gear = REVERSE", 100, &DATA));
+        assert!(matches("Gear < NEUTRAL", 100, &DATA));
+        assert!(!matches("Gear != \"REVERSE\"", 100, &DATA));
+    }
+
+    #[test]
+    fn boolean_operators() {
+        assert!(matches("EngineSpeed > 3000 && Gear == REVERSE", 100, &DATA));
+        assert!(matches("EngineSpeed > 5000 || Gear == REVERSE", 100, &DATA));
+        assert!(!matches(
+            "!(EngineSpeed > 3000) and Gear == REVERSE",
+            100,
+            &DATA
+        ));
+        assert!(matches("not Gear == PARK", 100, &DATA));
+    }
+
+    #[test]
+    fn message_qualified() {
+        assert!(matches("EngineData.EngineSpeed > 3000", 100, &DATA));
+        assert!(!matches("Other.EngineSpeed > 3000", 100, &DATA));
+        // Signals missing from the frame never match
+        assert!(!matches("Gear == REVERSE", 101, &DATA));
+        assert!(!matches("EngineSpeed > 0", 102, &DATA));
+    }
+
+    #[test]
+    fn display() {
+        let expr: Expr = "(EngineSpeed>3000||Gear=REVERSE)and !Data.Gear == \"Not available\""
+            .parse()
+            .unwrap();
+        let text = expr.to_string();
+        assert_eq!(
+            text,
+            "(EngineSpeed > 3000 || Gear == REVERSE) && !(Data.Gear == \"Not available\")"
+        );
+        assert_eq!(text.parse::<Expr>(), Ok(expr));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "EngineSpeed 3000".parse::<Expr>(),
+            Err(FilterError::Expected("comparison operator"))
+        );
+        assert_eq!(
+            "(EngineSpeed > 3000".parse::<Expr>(),
+            Err(FilterError::Expected("')'"))
+        );
+        assert_eq!(
+            "Gear == $".parse::<Expr>(),
+            Err(FilterError::UnexpectedChar('$'))
+        );
+    }
+}
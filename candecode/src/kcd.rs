@@ -0,0 +1,375 @@
+//
+// kcd.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! KCD (Kayak) bus description parser
+//!
+//! Messages of all buses in the file are merged into one database.
+
+use crate::{
+    database::{Database, Message, Node},
+    signal::{ByteOrder, MuxCondition, Signal, ValueTable, ValueType},
+};
+
+use embedded_can::{ExtendedId, Id, StandardId};
+use roxmltree::{Document, Node as XmlNode};
+use thiserror::Error;
+
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Error)]
+pub enum KcdError {
+    #[error("{0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("{element} '{name}': {message}")]
+    Invalid {
+        element: &'static str,
+        name: String,
+        message: String,
+    },
+}
+
+fn invalid(element: &'static str, node: XmlNode, message: impl Into<String>) -> KcdError {
+    KcdError::Invalid {
+        element,
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        message: message.into(),
+    }
+}
+
+fn children<'a, 'input: 'a>(
+    node: XmlNode<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = XmlNode<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.tag_name().name() == name)
+}
+
+fn child<'a, 'input: 'a>(
+    node: XmlNode<'a, 'input>,
+    name: &'static str,
+) -> Option<XmlNode<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// Parse an optional attribute, falling back to `default` when it is missing
+fn attribute<T: FromStr>(
+    element: &'static str,
+    node: XmlNode,
+    name: &str,
+    default: T,
+) -> Result<T, KcdError> {
+    match node.attribute(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| invalid(element, node, format!("invalid {} '{}'", name, value))),
+        None => Ok(default),
+    }
+}
+
+/// Node references of a producer or consumer element
+fn node_refs(node: Option<XmlNode>, nodes: &HashMap<&str, &str>) -> Vec<String> {
+    node.into_iter()
+        .flat_map(|node| children(node, "NodeRef"))
+        .filter_map(|node_ref| node_ref.attribute("id"))
+        .filter_map(|id| nodes.get(id))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn signal(node: XmlNode, nodes: &HashMap<&str, &str>) -> Result<Signal, KcdError> {
+    let name = node
+        .attribute("name")
+        .ok_or_else(|| invalid("Signal", node, "missing name"))?
+        .to_string();
+
+    let offset: u16 = attribute("Signal", node, "offset", 0)?;
+    let length = attribute("Signal", node, "length", 1)?;
+
+    let byte_order = match node.attribute("endianess").unwrap_or("little") {
+        "little" => ByteOrder::LittleEndian,
+        "big" => ByteOrder::BigEndian,
+        other => {
+            return Err(invalid(
+                "Signal",
+                node,
+                format!("invalid endianess '{}'", other),
+            ))
+        }
+    };
+
+    // KCD numbers the bits of big endian signals from the most significant bit of each byte
+    let start_bit = match byte_order {
+        ByteOrder::LittleEndian => offset,
+        ByteOrder::BigEndian => 8 * (offset / 8) + (7 - offset % 8),
+    };
+
+    let value = child(node, "Value");
+
+    let value_type = match value
+        .and_then(|v| v.attribute("type"))
+        .unwrap_or("unsigned")
+    {
+        "signed" => ValueType::Signed,
+        // Floating point signals are not supported and decode as raw integers
+        _ => ValueType::Unsigned,
+    };
+
+    let (factor, offset, min, max, unit) = match value {
+        Some(value) => (
+            attribute("Value", value, "slope", 1.0)?,
+            attribute("Value", value, "intercept", 0.0)?,
+            attribute("Value", value, "min", 0.0)?,
+            attribute("Value", value, "max", 0.0)?,
+            value.attribute("unit").unwrap_or_default().to_string(),
+        ),
+        None => (1.0, 0.0, 0.0, 0.0, String::new()),
+    };
+
+    let mut values = ValueTable::new();
+    for label in child(node, "LabelSet")
+        .into_iter()
+        .flat_map(|set| children(set, "Label"))
+    {
+        let value = attribute("Label", label, "value", 0)?;
+        values.insert(
+            value,
+            label.attribute("name").unwrap_or_default().to_string(),
+        );
+    }
+
+    Ok(Signal {
+        name,
+        start_bit,
+        length,
+        byte_order,
+        value_type,
+        factor,
+        offset,
+        min,
+        max,
+        unit,
+        receivers: node_refs(child(node, "Consumer"), nodes),
+        comment: child(node, "Notes")
+            .and_then(|n| n.text())
+            .map(str::to_string),
+        multiplexor: false,
+        multiplexed_by: None,
+        values,
+    })
+}
+
+fn message(node: XmlNode, nodes: &HashMap<&str, &str>) -> Result<Message, KcdError> {
+    let name = node.attribute("name").unwrap_or_default().to_string();
+
+    let raw = node
+        .attribute("id")
+        .ok_or_else(|| invalid("Message", node, "missing id"))?;
+    let raw = match raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => raw.parse(),
+    }
+    .map_err(|_| invalid("Message", node, format!("invalid id '{}'", raw)))?;
+
+    let id = match node.attribute("format").unwrap_or("standard") {
+        "extended" => ExtendedId::new(raw).map(Id::Extended),
+        _ => u16::try_from(raw)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard),
+    }
+    .ok_or_else(|| invalid("Message", node, format!("id 0x{:X} out of range", raw)))?;
+
+    let mut signals = vec![];
+
+    for element in node.children().filter(|n| n.is_element()) {
+        match element.tag_name().name() {
+            "Signal" => signals.push(signal(element, nodes)?),
+            "Multiplex" => {
+                let mut mux = signal(element, nodes)?;
+                mux.multiplexor = true;
+                let switch = mux.name.clone();
+                signals.push(mux);
+
+                for group in children(element, "MuxGroup") {
+                    let count: u64 = attribute("MuxGroup", group, "count", 0)?;
+
+                    for element in children(group, "Signal") {
+                        let mut signal = signal(element, nodes)?;
+                        signal.multiplexed_by = Some(MuxCondition {
+                            switch: Some(switch.clone()),
+                            values: vec![count..=count],
+                        });
+                        signals.push(signal);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The message length defaults to the number of bytes covered by its signals
+    let dlc = match node.attribute("length").unwrap_or("auto") {
+        "auto" => signals
+            .iter()
+            .filter_map(|s| (0..=64).find(|&len| s.fits(len)))
+            .max()
+            .unwrap_or(0) as u8,
+        length => length
+            .parse()
+            .map_err(|_| invalid("Message", node, format!("invalid length '{}'", length)))?,
+    };
+
+    Ok(Message {
+        id,
+        name,
+        dlc,
+        transmitter: node_refs(child(node, "Producer"), nodes).into_iter().next(),
+        signals,
+        comment: child(node, "Notes")
+            .and_then(|n| n.text())
+            .map(str::to_string),
+    })
+}
+
+/// Parse the contents of a KCD file
+pub fn parse(text: &str) -> Result<Database, KcdError> {
+    let doc = Document::parse(text)?;
+    let root = doc.root_element();
+
+    let version = child(root, "Document")
+        .and_then(|d| d.attribute("version"))
+        .unwrap_or_default()
+        .to_string();
+
+    let node_names: HashMap<&str, &str> = children(root, "Node")
+        .filter_map(|node| Some((node.attribute("id")?, node.attribute("name")?)))
+        .collect();
+
+    let nodes = children(root, "Node")
+        .filter_map(|node| node.attribute("name"))
+        .map(|name| Node {
+            name: name.to_string(),
+            comment: None,
+        })
+        .collect();
+
+    let mut messages: Vec<Message> = vec![];
+    for bus in children(root, "Bus") {
+        for node in children(bus, "Message") {
+            let message = message(node, &node_names)?;
+            // The same message may be described on several buses
+            if !messages.iter().any(|m| m.id == message.id) {
+                messages.push(message);
+            }
+        }
+    }
+
+    let mut db = Database::new(version, nodes, messages);
+    db.comment = child(root, "Document")
+        .and_then(|d| d.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+
+    Ok(db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<NetworkDefinition xmlns="http://kayak.2codeornot2code.org/1.0">
+  <Document name="Test" version="1.2">Test network</Document>
+  <Node id="1" name="Engine"/>
+  <Node id="2" name="Dashboard"/>
+  <Bus name="Main">
+    <Message id="0x064" name="EngineData" length="8">
+      <Notes>Engine status</Notes>
+      <Producer><NodeRef id="1"/></Producer>
+      <Signal name="EngineSpeed" offset="0" length="16">
+        <Value slope="0.125" unit="rpm" max="8031.875"/>
+        <Consumer><NodeRef id="2"/></Consumer>
+      </Signal>
+      <Signal name="Gear" offset="16" length="8">
+        <LabelSet>
+          <Label name="PARK" value="0"/>
+          <Label name="REVERSE" value="1"/>
+        </LabelSet>
+      </Signal>
+      <Signal name="Torque" offset="24" length="16" endianess="big">
+        <Value type="signed" slope="0.5"/>
+      </Signal>
+    </Message>
+    <Message id="0x18FEF1FE" name="Mux" format="extended">
+      <Multiplex name="Selector" offset="0" length="8">
+        <MuxGroup count="0">
+          <Signal name="Voltage" offset="8" length="16"><Value slope="0.01" unit="V"/></Signal>
+        </MuxGroup>
+        <MuxGroup count="1">
+          <Signal name="Current" offset="8" length="16"><Value slope="0.1" unit="A"/></Signal>
+        </MuxGroup>
+      </Multiplex>
+    </Message>
+  </Bus>
+</NetworkDefinition>
+"#;
+
+    #[test]
+    fn parse_database() {
+        let db = parse(KCD).unwrap();
+
+        assert_eq!(db.version, "1.2");
+        assert_eq!(db.comment.as_deref(), Some("Test network"));
+        assert_eq!(db.nodes().len(), 2);
+
+        let msg = db.message_by_name("EngineData").unwrap();
+        assert_eq!(msg.id, Id::Standard(StandardId::new(0x64).unwrap()));
+        assert_eq!(msg.transmitter.as_deref(), Some("Engine"));
+        assert_eq!(msg.comment.as_deref(), Some("Engine status"));
+
+        let speed = msg.signal("EngineSpeed").unwrap();
+        assert_eq!(speed.factor, 0.125);
+        assert_eq!(speed.receivers, vec!["Dashboard"]);
+
+        let torque = msg.signal("Torque").unwrap();
+        assert_eq!(torque.byte_order, ByteOrder::BigEndian);
+        assert_eq!(torque.start_bit, 31);
+
+        let values = msg.decode(&[0x60, 0x2F, 0x01, 0xFF, 0xFF, 0, 0, 0]);
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            values,
+            vec![
+                "EngineSpeed = 1516.000 rpm",
+                "Gear = REVERSE",
+                "Torque = -0.5"
+            ]
+        );
+    }
+
+    #[test]
+    fn multiplexed_message() {
+        let db = parse(KCD).unwrap();
+        let msg = db.message_by_name("Mux").unwrap();
+
+        assert_eq!(msg.dlc, 3);
+
+        let values = msg.decode(&[1, 0x64, 0x00]);
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        assert_eq!(values, vec!["Selector = 1", "Current = 10.0 A"]);
+    }
+
+    #[test]
+    fn invalid_message() {
+        let err = parse(
+            r#"<NetworkDefinition><Bus><Message id="zz" name="Bad"/></Bus></NetworkDefinition>"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Message 'Bad': invalid id 'zz'");
+    }
+}
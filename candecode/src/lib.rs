@@ -0,0 +1,22 @@
+//
+// lib.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! CAN database (DBC, KCD) parsing and signal decoding
+pub mod check;
+pub mod codegen;
+pub mod database;
+pub mod dbc;
+pub mod filter;
+pub mod kcd;
+pub mod signal;
+
+pub use check::{Issue, Severity};
+pub use database::{Database, DatabaseError, Message, Node};
+pub use dbc::DbcError;
+pub use filter::{Expr, FilterError};
+pub use kcd::KcdError;
+pub use signal::{ByteOrder, MuxCondition, Signal, SignalValue, ValueTable, ValueType};
@@ -0,0 +1,297 @@
+//
+// signal.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use std::{collections::BTreeMap, fmt, ops::RangeInclusive};
+
+/// Labels of enumerated raw values
+pub type ValueTable = BTreeMap<i64, String>;
+
+/// Bit layout of a signal in the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Intel (`@1`), the start bit is the least significant bit
+    LittleEndian,
+    /// Motorola (`@0`), the start bit is the most significant bit
+    BigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Unsigned,
+    Signed,
+}
+
+/// Multiplexor values for which a multiplexed signal is present
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuxCondition {
+    /// Multiplexor signal, `None` for the message's top level multiplexor
+    pub switch: Option<String>,
+    pub values: Vec<RangeInclusive<u64>>,
+}
+
+impl MuxCondition {
+    pub fn matches(&self, raw: i128) -> bool {
+        u64::try_from(raw).is_ok_and(|raw| self.values.iter().any(|range| range.contains(&raw)))
+    }
+}
+
+/// A signal within a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub name: String,
+    pub start_bit: u16,
+    pub length: u16,
+    pub byte_order: ByteOrder,
+    pub value_type: ValueType,
+    pub factor: f64,
+    pub offset: f64,
+    pub min: f64,
+    pub max: f64,
+    pub unit: String,
+    pub receivers: Vec<String>,
+    pub comment: Option<String>,
+    /// The signal selects which multiplexed signals are present
+    pub multiplexor: bool,
+    /// The signal is only present for certain multiplexor values
+    pub multiplexed_by: Option<MuxCondition>,
+    /// Labels for enumerated values
+    pub values: ValueTable,
+}
+
+impl Signal {
+    /// Positions of the signal's bits in the payload, most significant bit first
+    pub fn bits(&self) -> impl Iterator<Item = usize> + '_ {
+        let start = self.start_bit as usize;
+        let length = self.length as usize;
+
+        let mut motorola = start;
+
+        (0..length).map(move |i| match self.byte_order {
+            ByteOrder::LittleEndian => start + length - 1 - i,
+            ByteOrder::BigEndian => {
+                // Big endian signals count down within a byte and continue at the most
+                // significant bit of the next byte
+                let bit = motorola;
+                motorola = if bit.is_multiple_of(8) {
+                    bit + 15
+                } else {
+                    bit - 1
+                };
+                bit
+            }
+        })
+    }
+
+    /// Check if the signal fits in a payload of `len` bytes
+    pub fn fits(&self, len: usize) -> bool {
+        self.length > 0 && self.length <= 64 && self.bits().all(|bit| bit < len * 8)
+    }
+
+    /// Extract the raw value of the signal, sign extended for signed signals
+    pub fn decode_raw(&self, data: &[u8]) -> Option<i128> {
+        if !self.fits(data.len()) {
+            return None;
+        }
+
+        let raw = self.bits().fold(0u64, |raw, bit| {
+            (raw << 1) | ((data[bit / 8] >> (bit % 8)) & 0x01) as u64
+        });
+
+        let length = self.length as u32;
+        let value = match self.value_type {
+            ValueType::Signed if length < 64 && raw >> (length - 1) & 0x01 != 0 => {
+                raw as i128 - (1i128 << length)
+            }
+            ValueType::Signed => raw as i64 as i128,
+            ValueType::Unsigned => raw as i128,
+        };
+
+        Some(value)
+    }
+
+    /// Extract and scale the value of the signal
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        self.decode_raw(data).map(|raw| self.scale(raw))
+    }
+
+    /// Physical value of a raw value
+    pub fn scale(&self, raw: i128) -> f64 {
+        raw as f64 * self.factor + self.offset
+    }
+
+    /// Label of a raw value, if the signal is enumerated
+    pub fn label(&self, raw: i128) -> Option<&str> {
+        i64::try_from(raw)
+            .ok()
+            .and_then(|raw| self.values.get(&raw))
+            .map(String::as_str)
+    }
+
+    /// Write a physical value into `data`. The value is clamped to the range of the signal.
+    pub fn encode(&self, value: f64, data: &mut [u8]) -> bool {
+        if !self.fits(data.len()) || self.factor == 0.0 {
+            return false;
+        }
+
+        let length = self.length as u32;
+        let (lo, hi) = match self.value_type {
+            ValueType::Unsigned => (0i128, (1i128 << length) - 1),
+            ValueType::Signed => (-(1i128 << (length - 1)), (1i128 << (length - 1)) - 1),
+        };
+
+        let raw = (((value - self.offset) / self.factor).round() as i128).clamp(lo, hi) as u64;
+
+        for (i, bit) in self.bits().enumerate() {
+            let value = (raw >> (length as usize - 1 - i)) & 0x01;
+            data[bit / 8] = (data[bit / 8] & !(1 << (bit % 8))) | ((value as u8) << (bit % 8));
+        }
+
+        true
+    }
+}
+
+/// A decoded signal value
+///
+/// Enumerated values are displayed as their label, the alternate format (`{:#}`) adds the raw
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalValue<'a> {
+    pub signal: &'a Signal,
+    pub raw: i128,
+    pub value: f64,
+}
+
+impl SignalValue<'_> {
+    pub fn label(&self) -> Option<&str> {
+        self.signal.label(self.raw)
+    }
+}
+
+impl fmt::Display for SignalValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = self.label() {
+            write!(f, "{} = {}", self.signal.name, label)?;
+
+            if f.alternate() {
+                write!(f, " ({})", self.raw)?;
+            }
+
+            return Ok(());
+        }
+
+        // Show as many decimals as the factor of the signal requires
+        let factor = self.signal.factor.abs();
+        let decimals = (0..6)
+            .find(|&d| {
+                let scaled = factor * 10f64.powi(d);
+                (scaled - scaled.round()).abs() < 1e-9
+            })
+            .unwrap_or(6) as usize;
+
+        write!(f, "{} = {:.*}", self.signal.name, decimals, self.value)?;
+
+        if !self.signal.unit.is_empty() {
+            write!(f, " {}", self.signal.unit)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(start_bit: u16, length: u16, byte_order: ByteOrder, value_type: ValueType) -> Signal {
+        Signal {
+            name: String::from("Test"),
+            start_bit,
+            length,
+            byte_order,
+            value_type,
+            factor: 1.0,
+            offset: 0.0,
+            min: 0.0,
+            max: 0.0,
+            unit: String::new(),
+            receivers: vec![],
+            comment: None,
+            multiplexor: false,
+            multiplexed_by: None,
+            values: ValueTable::new(),
+        }
+    }
+
+    #[test]
+    fn little_endian() {
+        let sig = signal(12, 12, ByteOrder::LittleEndian, ValueType::Unsigned);
+        assert_eq!(sig.decode_raw(&[0x00, 0x40, 0x23, 0x01]), Some(0x234));
+
+        let sig = signal(0, 8, ByteOrder::LittleEndian, ValueType::Signed);
+        assert_eq!(sig.decode_raw(&[0xFE]), Some(-2));
+    }
+
+    #[test]
+    fn big_endian() {
+        // 16 bit value starting at the MSB of byte 0
+        let sig = signal(7, 16, ByteOrder::BigEndian, ValueType::Unsigned);
+        assert_eq!(sig.decode_raw(&[0x12, 0x34]), Some(0x1234));
+
+        // 12 bit value starting in the middle of byte 1
+        let sig = signal(11, 12, ByteOrder::BigEndian, ValueType::Unsigned);
+        assert_eq!(sig.decode_raw(&[0x00, 0x0A, 0xBC]), Some(0xABC));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let sig = signal(60, 8, ByteOrder::LittleEndian, ValueType::Unsigned);
+        assert_eq!(sig.decode_raw(&[0; 8]), None);
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let mut sig = signal(11, 12, ByteOrder::BigEndian, ValueType::Signed);
+        sig.factor = 0.5;
+        sig.offset = 10.0;
+
+        let mut data = [0xFF; 4];
+        assert!(sig.encode(-20.5, &mut data));
+        assert_eq!(sig.decode(&data), Some(-20.5));
+        // Bits outside the signal are untouched
+        assert_eq!(data[0], 0xFF);
+        assert_eq!(data[3], 0xFF);
+    }
+
+    #[test]
+    fn display() {
+        let mut sig = signal(0, 16, ByteOrder::LittleEndian, ValueType::Unsigned);
+        sig.factor = 0.125;
+        sig.unit = String::from("rpm");
+
+        let value = SignalValue {
+            signal: &sig,
+            raw: 12128,
+            value: 1516.0,
+        };
+        assert_eq!(value.to_string(), "Test = 1516.000 rpm");
+    }
+
+    #[test]
+    fn display_label() {
+        let mut sig = signal(0, 8, ByteOrder::LittleEndian, ValueType::Unsigned);
+        sig.name = String::from("Gear");
+        sig.values.insert(2, String::from("REVERSE"));
+
+        let value = SignalValue {
+            signal: &sig,
+            raw: 2,
+            value: 2.0,
+        };
+        assert_eq!(value.to_string(), "Gear = REVERSE");
+        assert_eq!(format!("{:#}", value), "Gear = REVERSE (2)");
+    }
+}
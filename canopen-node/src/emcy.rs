@@ -0,0 +1,281 @@
+//
+// emcy.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::od::{AbortCode, ObjectDictionary, ERROR_REGISTER, PREDEFINED_ERROR_FIELD};
+
+use core::time::Duration;
+use heapless::{Deque, Vec};
+
+/// Base COB-ID of EMCY frames
+pub const EMCY_COB_ID: u16 = 0x080;
+
+/// Number of entries kept in the pre-defined error field (0x1003)
+pub const HISTORY_SIZE: usize = 8;
+/// Maximum number of simultaneously active errors
+pub const MAX_ACTIVE_ERRORS: usize = 8;
+/// Maximum number of EMCYs waiting to be sent
+pub const QUEUE_SIZE: usize = 8;
+
+/// Error code signalling that an error condition has been reset
+pub const ERROR_RESET: u16 = 0x0000;
+
+/// Error register (0x1001) bits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorRegister(pub u8);
+
+impl ErrorRegister {
+    pub const GENERIC: ErrorRegister = ErrorRegister(0x01);
+    pub const CURRENT: ErrorRegister = ErrorRegister(0x02);
+    pub const VOLTAGE: ErrorRegister = ErrorRegister(0x04);
+    pub const TEMPERATURE: ErrorRegister = ErrorRegister(0x08);
+    pub const COMMUNICATION: ErrorRegister = ErrorRegister(0x10);
+    pub const DEVICE_PROFILE: ErrorRegister = ErrorRegister(0x20);
+    pub const MANUFACTURER: ErrorRegister = ErrorRegister(0x80);
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl core::ops::BitOr for ErrorRegister {
+    type Output = ErrorRegister;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        ErrorRegister(self.0 | rhs.0)
+    }
+}
+
+/// A single EMCY message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Emcy {
+    pub error_code: u16,
+    pub error_register: ErrorRegister,
+    pub data: [u8; 5],
+}
+
+impl Emcy {
+    /// EMCY frame payload
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut buf = [0u8; 8];
+        buf[..2].copy_from_slice(&self.error_code.to_le_bytes());
+        buf[2] = self.error_register.bits();
+        buf[3..].copy_from_slice(&self.data);
+        buf
+    }
+}
+
+/// EMCY producer, error register and error history bookkeeping
+#[derive(Debug, Default)]
+pub struct EmcyProducer {
+    active: Vec<(u16, ErrorRegister), MAX_ACTIVE_ERRORS>,
+    history: Deque<u32, HISTORY_SIZE>,
+    queue: Deque<Emcy, QUEUE_SIZE>,
+    last_sent: Option<Duration>,
+}
+
+impl EmcyProducer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raise an error. The generic error bit is always set while an error is active.
+    pub fn raise(&mut self, error_code: u16, register: ErrorRegister, data: [u8; 5]) {
+        if !self.active.iter().any(|(code, _)| *code == error_code) {
+            if self.active.is_full() {
+                self.active.remove(0);
+            }
+            // Space was made above
+            let _ = self.active.push((error_code, register));
+        }
+
+        if self.history.is_full() {
+            self.history.pop_back();
+        }
+        let info = u16::from_le_bytes([data[0], data[1]]) as u32;
+        let _ = self.history.push_front(error_code as u32 | (info << 16));
+
+        self.enqueue(Emcy {
+            error_code,
+            error_register: self.error_register(),
+            data,
+        });
+    }
+
+    /// Clear an active error, producing an error reset EMCY
+    pub fn clear(&mut self, error_code: u16) {
+        let Some(pos) = self.active.iter().position(|(code, _)| *code == error_code) else {
+            return;
+        };
+
+        self.active.remove(pos);
+
+        self.enqueue(Emcy {
+            error_code: ERROR_RESET,
+            error_register: self.error_register(),
+            data: [0; 5],
+        });
+    }
+
+    /// Current value of the error register
+    pub fn error_register(&self) -> ErrorRegister {
+        self.active
+            .iter()
+            .fold(ErrorRegister::default(), |reg, (_, bits)| {
+                reg | *bits | ErrorRegister::GENERIC
+            })
+    }
+
+    /// Pre-defined error field entries, most recent first
+    pub fn history(&self) -> impl Iterator<Item = &u32> {
+        self.history.iter()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Get the next EMCY to send, respecting the inhibit time
+    pub fn poll(&mut self, inhibit_time: Duration, now: Duration) -> Option<Emcy> {
+        if self
+            .last_sent
+            .is_some_and(|last| now.saturating_sub(last) < inhibit_time)
+        {
+            return None;
+        }
+
+        let emcy = self.queue.pop_front()?;
+        self.last_sent = Some(now);
+
+        Some(emcy)
+    }
+
+    /// Drop queued messages, active errors are kept
+    pub fn reset(&mut self) {
+        self.queue.clear();
+        self.last_sent = None;
+    }
+
+    fn enqueue(&mut self, emcy: Emcy) {
+        if self.queue.is_full() {
+            self.queue.pop_front();
+        }
+        let _ = self.queue.push_back(emcy);
+    }
+}
+
+/// Object dictionary view serving 0x1001 and 0x1003 from the EMCY producer
+pub(crate) struct ErrorObjects<'a, OD> {
+    pub od: &'a mut OD,
+    pub emcy: &'a mut EmcyProducer,
+}
+
+impl<OD: ObjectDictionary> ObjectDictionary for ErrorObjects<'_, OD> {
+    fn read(&self, index: u16, subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode> {
+        let value = match (index, subindex) {
+            (ERROR_REGISTER, 0) => self.emcy.error_register().bits() as u32,
+            (PREDEFINED_ERROR_FIELD, 0) => self.emcy.history.len() as u32,
+            (PREDEFINED_ERROR_FIELD, sub) => *self
+                .emcy
+                .history
+                .iter()
+                .nth(sub as usize - 1)
+                .ok_or(AbortCode::SubIndexDoesNotExist)?,
+            _ => return self.od.read(index, subindex, buf),
+        };
+
+        let size = if index == ERROR_REGISTER || subindex == 0 {
+            1
+        } else {
+            4
+        };
+
+        if buf.len() < size {
+            return Err(AbortCode::OutOfMemory);
+        }
+
+        buf[..size].copy_from_slice(&value.to_le_bytes()[..size]);
+        Ok(size)
+    }
+
+    fn write(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode> {
+        match (index, subindex) {
+            (ERROR_REGISTER, _) => Err(AbortCode::ReadOnly),
+            // Writing zero to sub 0 deletes the error history
+            (PREDEFINED_ERROR_FIELD, 0) => match data {
+                [0] => {
+                    self.emcy.clear_history();
+                    Ok(())
+                }
+                [_] => Err(AbortCode::InvalidValue),
+                _ => Err(AbortCode::LengthMismatch),
+            },
+            (PREDEFINED_ERROR_FIELD, _) => Err(AbortCode::ReadOnly),
+            _ => self.od.write(index, subindex, data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestOd;
+
+    #[test]
+    fn register_and_reset() {
+        let mut emcy = EmcyProducer::new();
+
+        emcy.raise(0x4210, ErrorRegister::TEMPERATURE, [1, 2, 3, 4, 5]);
+        assert_eq!(emcy.error_register(), ErrorRegister(0x09));
+
+        let msg = emcy.poll(Duration::ZERO, Duration::ZERO).unwrap();
+        assert_eq!(msg.to_bytes(), [0x10, 0x42, 0x09, 1, 2, 3, 4, 5]);
+
+        emcy.clear(0x4210);
+        assert_eq!(emcy.error_register(), ErrorRegister(0x00));
+
+        let msg = emcy.poll(Duration::ZERO, Duration::ZERO).unwrap();
+        assert_eq!(msg.to_bytes(), [0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn inhibit_time() {
+        let mut emcy = EmcyProducer::new();
+        let inhibit = Duration::from_millis(10);
+
+        emcy.raise(0x1000, ErrorRegister::GENERIC, [0; 5]);
+        emcy.raise(0x2000, ErrorRegister::CURRENT, [0; 5]);
+
+        assert!(emcy.poll(inhibit, Duration::from_millis(0)).is_some());
+        assert!(emcy.poll(inhibit, Duration::from_millis(5)).is_none());
+        assert!(emcy.poll(inhibit, Duration::from_millis(10)).is_some());
+    }
+
+    #[test]
+    fn error_objects() {
+        let mut od = TestOd::default();
+        let mut emcy = EmcyProducer::new();
+        emcy.raise(0x1000, ErrorRegister::GENERIC, [0; 5]);
+        emcy.raise(0x3100, ErrorRegister::VOLTAGE, [0xCD, 0xAB, 0, 0, 0]);
+
+        let mut objects = ErrorObjects {
+            od: &mut od,
+            emcy: &mut emcy,
+        };
+
+        assert_eq!(objects.read_u8(ERROR_REGISTER, 0), Ok(0x05));
+        assert_eq!(objects.read_u8(PREDEFINED_ERROR_FIELD, 0), Ok(2));
+        assert_eq!(objects.read_u32(PREDEFINED_ERROR_FIELD, 1), Ok(0xABCD_3100));
+        assert_eq!(objects.read_u32(PREDEFINED_ERROR_FIELD, 2), Ok(0x1000));
+
+        assert_eq!(
+            objects.write(PREDEFINED_ERROR_FIELD, 0, &[1]),
+            Err(AbortCode::InvalidValue)
+        );
+        objects.write(PREDEFINED_ERROR_FIELD, 0, &[0]).unwrap();
+        assert_eq!(objects.read_u8(PREDEFINED_ERROR_FIELD, 0), Ok(0));
+    }
+}
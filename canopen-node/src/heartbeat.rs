@@ -0,0 +1,45 @@
+//
+// heartbeat.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use core::time::Duration;
+
+/// Base COB-ID of heartbeat and bootup frames
+pub const HEARTBEAT_COB_ID: u16 = 0x700;
+
+/// Heartbeat producer timing
+#[derive(Debug, Default)]
+pub struct HeartbeatProducer {
+    last_sent: Option<Duration>,
+}
+
+impl HeartbeatProducer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a heartbeat is due given the producer time (0x1017) in milliseconds
+    pub fn poll(&mut self, period_ms: u16, now: Duration) -> bool {
+        if period_ms == 0 {
+            return false;
+        }
+
+        match self.last_sent {
+            Some(last) if now.saturating_sub(last) < Duration::from_millis(period_ms as u64) => {
+                false
+            }
+            _ => {
+                self.last_sent = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Restart timing, the next poll will produce a heartbeat
+    pub fn reset(&mut self) {
+        self.last_sent = None;
+    }
+}
@@ -0,0 +1,137 @@
+//
+// lib.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! A `no_std` CANopen (CiA 301) slave stack built on the `embedded-can` traits.
+//!
+//! The stack does not own a CAN peripheral. Received frames are passed to
+//! [`Node::handle_frame`] and [`Node::process`] is called periodically; both return the
+//! frames the application must transmit.
+#![cfg_attr(not(test), no_std)]
+
+pub mod emcy;
+pub mod heartbeat;
+pub mod nmt;
+pub mod od;
+pub mod pdo;
+pub mod sdo;
+pub mod storage;
+
+mod node;
+
+pub use node::{Node, Outbox};
+
+use embedded_can::{Frame, StandardId};
+
+/// Build a standard ID frame from a COB-ID
+pub(crate) fn make_frame<F: Frame>(cob_id: u16, data: &[u8]) -> Option<F> {
+    StandardId::new(cob_id).and_then(|id| F::new(id, data))
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::od::{AbortCode, ObjectDictionary};
+    use embedded_can::{Frame, Id};
+
+    /// Minimal frame implementation for tests
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TestFrame {
+        pub id: Id,
+        pub data: Vec<u8>,
+    }
+
+    impl Frame for TestFrame {
+        fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+            Some(TestFrame {
+                id: id.into(),
+                data: data.to_vec(),
+            })
+        }
+
+        fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+            None
+        }
+
+        fn is_extended(&self) -> bool {
+            matches!(self.id, Id::Extended(_))
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            false
+        }
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn dlc(&self) -> usize {
+            self.data.len()
+        }
+
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+    }
+
+    impl TestFrame {
+        pub fn raw_id(&self) -> u16 {
+            match self.id {
+                Id::Standard(id) => id.as_raw(),
+                Id::Extended(_) => unreachable!(),
+            }
+        }
+    }
+
+    pub fn frame(cob_id: u16, data: &[u8]) -> TestFrame {
+        crate::make_frame(cob_id, data).unwrap()
+    }
+
+    /// Object dictionary backed by a list of entries. Index 0x2000 is read-only.
+    #[derive(Default)]
+    pub struct TestOd {
+        pub entries: Vec<(u16, u8, Vec<u8>)>,
+    }
+
+    impl TestOd {
+        pub fn with(mut self, index: u16, subindex: u8, value: &[u8]) -> Self {
+            self.entries.push((index, subindex, value.to_vec()));
+            self
+        }
+
+        pub fn get(&self, index: u16, subindex: u8) -> Option<&[u8]> {
+            self.entries
+                .iter()
+                .find(|(i, s, _)| *i == index && *s == subindex)
+                .map(|(_, _, v)| v.as_slice())
+        }
+    }
+
+    impl ObjectDictionary for TestOd {
+        fn read(&self, index: u16, subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode> {
+            let value = self
+                .get(index, subindex)
+                .ok_or(AbortCode::ObjectDoesNotExist)?;
+            if value.len() > buf.len() {
+                return Err(AbortCode::OutOfMemory);
+            }
+            buf[..value.len()].copy_from_slice(value);
+            Ok(value.len())
+        }
+
+        fn write(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode> {
+            if index == 0x2000 {
+                return Err(AbortCode::ReadOnly);
+            }
+            let entry = self
+                .entries
+                .iter_mut()
+                .find(|(i, s, _)| *i == index && *s == subindex)
+                .ok_or(AbortCode::ObjectDoesNotExist)?;
+            entry.2 = data.to_vec();
+            Ok(())
+        }
+    }
+}
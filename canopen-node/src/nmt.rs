@@ -0,0 +1,162 @@
+//
+// nmt.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+/// COB-ID of NMT module control frames
+pub const NMT_COB_ID: u16 = 0x000;
+
+/// NMT slave states
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NmtState {
+    Initializing,
+    PreOperational,
+    Operational,
+    Stopped,
+}
+
+impl NmtState {
+    /// State as encoded in heartbeat frames
+    pub fn heartbeat_byte(self) -> u8 {
+        match self {
+            NmtState::Initializing => 0x00,
+            NmtState::Stopped => 0x04,
+            NmtState::Operational => 0x05,
+            NmtState::PreOperational => 0x7F,
+        }
+    }
+}
+
+impl TryFrom<u8> for NmtState {
+    type Error = ();
+
+    /// State of a heartbeat frame, ignoring the node guarding toggle bit
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value & 0x7F {
+            0x00 => Ok(NmtState::Initializing),
+            0x04 => Ok(NmtState::Stopped),
+            0x05 => Ok(NmtState::Operational),
+            0x7F => Ok(NmtState::PreOperational),
+            _ => Err(()),
+        }
+    }
+}
+
+/// NMT module control commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NmtCommand {
+    Start,
+    Stop,
+    EnterPreOperational,
+    ResetNode,
+    ResetCommunication,
+}
+
+impl TryFrom<u8> for NmtCommand {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(NmtCommand::Start),
+            0x02 => Ok(NmtCommand::Stop),
+            0x80 => Ok(NmtCommand::EnterPreOperational),
+            0x81 => Ok(NmtCommand::ResetNode),
+            0x82 => Ok(NmtCommand::ResetCommunication),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parse an NMT frame payload, returning the command if it is addressed to `node_id`
+pub fn parse_command(data: &[u8], node_id: u8) -> Option<NmtCommand> {
+    match data {
+        [cs, target, ..] if *target == 0 || *target == node_id => NmtCommand::try_from(*cs).ok(),
+        _ => None,
+    }
+}
+
+/// NMT slave state machine
+#[derive(Debug)]
+pub struct NmtSlave {
+    state: NmtState,
+}
+
+impl Default for NmtSlave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NmtSlave {
+    pub fn new() -> Self {
+        NmtSlave {
+            state: NmtState::Initializing,
+        }
+    }
+
+    pub fn state(&self) -> NmtState {
+        self.state
+    }
+
+    /// Finish initialization and enter pre-operational
+    pub fn boot(&mut self) {
+        self.state = NmtState::PreOperational;
+    }
+
+    /// Apply an NMT command and return the new state
+    pub fn handle(&mut self, cmd: NmtCommand) -> NmtState {
+        self.state = match cmd {
+            NmtCommand::Start => NmtState::Operational,
+            NmtCommand::Stop => NmtState::Stopped,
+            NmtCommand::EnterPreOperational => NmtState::PreOperational,
+            NmtCommand::ResetNode | NmtCommand::ResetCommunication => NmtState::Initializing,
+        };
+
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_addressing() {
+        assert_eq!(parse_command(&[0x01, 0x05], 5), Some(NmtCommand::Start));
+        assert_eq!(parse_command(&[0x02, 0x00], 5), Some(NmtCommand::Stop));
+        assert_eq!(parse_command(&[0x01, 0x06], 5), None);
+        assert_eq!(parse_command(&[0x01], 5), None);
+    }
+
+    #[test]
+    fn heartbeat_states() {
+        for state in [
+            NmtState::Initializing,
+            NmtState::PreOperational,
+            NmtState::Operational,
+            NmtState::Stopped,
+        ] {
+            assert_eq!(NmtState::try_from(state.heartbeat_byte()), Ok(state));
+        }
+        assert_eq!(NmtState::try_from(0x85), Ok(NmtState::Operational));
+        assert_eq!(NmtState::try_from(0x01), Err(()));
+    }
+
+    #[test]
+    fn state_transitions() {
+        let mut nmt = NmtSlave::new();
+        assert_eq!(nmt.state(), NmtState::Initializing);
+
+        nmt.boot();
+        assert_eq!(nmt.state(), NmtState::PreOperational);
+
+        assert_eq!(nmt.handle(NmtCommand::Start), NmtState::Operational);
+        assert_eq!(nmt.handle(NmtCommand::Stop), NmtState::Stopped);
+        assert_eq!(
+            nmt.handle(NmtCommand::ResetCommunication),
+            NmtState::Initializing
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//
+// node.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    emcy::{EmcyProducer, ErrorObjects, ErrorRegister, EMCY_COB_ID},
+    heartbeat::{HeartbeatProducer, HEARTBEAT_COB_ID},
+    make_frame,
+    nmt::{self, NmtSlave, NmtState, NMT_COB_ID},
+    od::{ObjectDictionary, COB_ID_EMCY, COB_ID_SYNC, INHIBIT_TIME_EMCY, PRODUCER_HEARTBEAT_TIME},
+    pdo::{self, Direction, Tpdo, TpdoParameters, DEFAULT_SYNC_COB_ID, NUM_PDOS},
+    sdo::{SdoServer, SDO_RX_COB_ID, SDO_TX_COB_ID},
+};
+
+use core::time::Duration;
+use embedded_can::{Frame, Id};
+
+/// Frames produced by the node that need to be transmitted
+pub type Outbox<F> = heapless::Vec<F, 8>;
+
+/// CANopen slave node
+pub struct Node<OD> {
+    node_id: u8,
+    od: OD,
+    nmt: NmtSlave,
+    sdo: SdoServer,
+    heartbeat: HeartbeatProducer,
+    emcy: EmcyProducer,
+    tpdos: [Tpdo; NUM_PDOS],
+}
+
+impl<OD: ObjectDictionary> Node<OD> {
+    /// Create a node. The node boots on the first call to [`Node::process`].
+    pub fn new(node_id: u8, od: OD) -> Self {
+        Node {
+            node_id,
+            od,
+            nmt: NmtSlave::new(),
+            sdo: SdoServer::new(),
+            heartbeat: HeartbeatProducer::new(),
+            emcy: EmcyProducer::new(),
+            tpdos: [Tpdo::default(); NUM_PDOS],
+        }
+    }
+
+    pub fn node_id(&self) -> u8 {
+        self.node_id
+    }
+
+    pub fn state(&self) -> NmtState {
+        self.nmt.state()
+    }
+
+    pub fn od(&self) -> &OD {
+        &self.od
+    }
+
+    pub fn od_mut(&mut self) -> &mut OD {
+        &mut self.od
+    }
+
+    /// Signal an application event for TPDO `n` (0 based). Event driven PDOs are sent on the
+    /// next call to [`Node::process`], synchronous acyclic PDOs on the next SYNC.
+    pub fn trigger_tpdo(&mut self, n: usize) {
+        if let Some(tpdo) = self.tpdos.get_mut(n) {
+            tpdo.trigger();
+        }
+    }
+
+    /// Raise an application error. An EMCY is sent and the error is recorded in the error
+    /// register (0x1001) and pre-defined error field (0x1003) until cleared.
+    pub fn raise_error(&mut self, error_code: u16, register: ErrorRegister, data: [u8; 5]) {
+        self.emcy.raise(error_code, register, data);
+    }
+
+    /// Clear a previously raised error, sending an error reset EMCY
+    pub fn clear_error(&mut self, error_code: u16) {
+        self.emcy.clear(error_code);
+    }
+
+    pub fn error_register(&self) -> ErrorRegister {
+        self.emcy.error_register()
+    }
+
+    /// Handle a received CAN frame
+    pub fn handle_frame<F: Frame>(&mut self, frame: &F, now: Duration) -> Outbox<F> {
+        let mut outbox = Outbox::new();
+
+        let cob_id = match frame.id() {
+            Id::Standard(id) if !frame.is_remote_frame() => id.as_raw(),
+            _ => return outbox,
+        };
+
+        let state = self.nmt.state();
+
+        if cob_id == NMT_COB_ID {
+            if let Some(cmd) = nmt::parse_command(frame.data(), self.node_id) {
+                if self.nmt.handle(cmd) == NmtState::Initializing {
+                    self.reset_communication();
+                }
+            }
+        } else if cob_id == SDO_RX_COB_ID + self.node_id as u16 {
+            if matches!(state, NmtState::PreOperational | NmtState::Operational) {
+                let mut objects = ErrorObjects {
+                    od: &mut self.od,
+                    emcy: &mut self.emcy,
+                };
+
+                if let Some(res) = self.sdo.handle(&mut objects, frame.data(), now) {
+                    self.push(&mut outbox, SDO_TX_COB_ID + self.node_id as u16, &res);
+                }
+            }
+        } else if cob_id == self.sync_cob_id() {
+            if state == NmtState::Operational {
+                self.process_sync(&mut outbox, now);
+            }
+        } else if state == NmtState::Operational {
+            let rpdo =
+                (0..NUM_PDOS).find(|&n| pdo::cob_id(&self.od, Direction::Rx, n) == Some(cob_id));
+
+            if let Some(n) = rpdo {
+                // Errors in received PDOs are not reported back to the sender
+                let _ = pdo::unpack(&mut self.od, n, frame.data());
+            }
+        }
+
+        outbox
+    }
+
+    /// Run time based services. Call this periodically.
+    pub fn process<F: Frame>(&mut self, now: Duration) -> Outbox<F> {
+        let mut outbox = Outbox::new();
+
+        if self.nmt.state() == NmtState::Initializing {
+            self.nmt.boot();
+            self.push(&mut outbox, HEARTBEAT_COB_ID + self.node_id as u16, &[0x00]);
+        }
+
+        if let Some(res) = self.sdo.poll(now) {
+            self.push(&mut outbox, SDO_TX_COB_ID + self.node_id as u16, &res);
+        }
+
+        let period = self.od.read_u16(PRODUCER_HEARTBEAT_TIME, 0).unwrap_or(0);
+        if self.heartbeat.poll(period, now) {
+            let state = self.nmt.state().heartbeat_byte();
+            self.push(
+                &mut outbox,
+                HEARTBEAT_COB_ID + self.node_id as u16,
+                &[state],
+            );
+        }
+
+        if matches!(
+            self.nmt.state(),
+            NmtState::PreOperational | NmtState::Operational
+        ) {
+            self.process_emcy(&mut outbox, now);
+        }
+
+        if self.nmt.state() == NmtState::Operational {
+            self.process_tpdos(&mut outbox, now);
+        }
+
+        outbox
+    }
+
+    fn process_tpdos<F: Frame>(&mut self, outbox: &mut Outbox<F>, now: Duration) {
+        for n in 0..NUM_PDOS {
+            let params = TpdoParameters::read(&self.od, n);
+            if self.tpdos[n].poll(&params, now) {
+                self.send_tpdo(outbox, n);
+            }
+        }
+    }
+
+    fn process_sync<F: Frame>(&mut self, outbox: &mut Outbox<F>, now: Duration) {
+        for n in 0..NUM_PDOS {
+            let params = TpdoParameters::read(&self.od, n);
+            if self.tpdos[n].on_sync(&params, now) {
+                self.send_tpdo(outbox, n);
+            }
+        }
+    }
+
+    fn process_emcy<F: Frame>(&mut self, outbox: &mut Outbox<F>, now: Duration) {
+        let inhibit = self.od.read_u16(INHIBIT_TIME_EMCY, 0).unwrap_or(0);
+        let inhibit = Duration::from_micros(inhibit as u64 * 100);
+
+        let cob_id = match self.od.read_u32(COB_ID_EMCY, 0) {
+            // EMCY disabled
+            Ok(raw) if raw & (1 << 31) != 0 => return,
+            Ok(raw) => (raw & 0x7FF) as u16,
+            Err(_) => EMCY_COB_ID + self.node_id as u16,
+        };
+
+        if let Some(emcy) = self.emcy.poll(inhibit, now) {
+            self.push(outbox, cob_id, &emcy.to_bytes());
+        }
+    }
+
+    fn send_tpdo<F: Frame>(&mut self, outbox: &mut Outbox<F>, n: usize) {
+        let Some(cob_id) = pdo::cob_id(&self.od, Direction::Tx, n) else {
+            return;
+        };
+
+        // The error register is commonly mapped into PDOs
+        let objects = ErrorObjects {
+            od: &mut self.od,
+            emcy: &mut self.emcy,
+        };
+
+        let mut buf = [0u8; 8];
+        if let Ok(len) = pdo::pack(&objects, n, &mut buf) {
+            self.push(outbox, cob_id, &buf[..len]);
+        }
+    }
+
+    fn sync_cob_id(&self) -> u16 {
+        self.od
+            .read_u32(COB_ID_SYNC, 0)
+            .map_or(DEFAULT_SYNC_COB_ID, |raw| (raw & 0x7FF) as u16)
+    }
+
+    fn reset_communication(&mut self) {
+        self.sdo.reset();
+        self.heartbeat.reset();
+        self.emcy.reset();
+        self.tpdos.iter_mut().for_each(Tpdo::reset);
+    }
+
+    fn push<F: Frame>(&self, outbox: &mut Outbox<F>, cob_id: u16, data: &[u8]) {
+        if let Some(frame) = make_frame(cob_id, data) {
+            // The outbox is sized for the maximum number of frames produced per call
+            let _ = outbox.push(frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{frame, TestFrame, TestOd};
+
+    fn node() -> Node<TestOd> {
+        let od = TestOd::default()
+            .with(0x1017, 0, &100u16.to_le_bytes())
+            .with(0x1800, 1, &0x185u32.to_le_bytes())
+            .with(0x1A00, 0, &[1])
+            .with(0x1A00, 1, &0x6000_0008u32.to_le_bytes())
+            .with(0x6000, 0, &[0x42]);
+
+        Node::new(5, od)
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn bootup_and_heartbeat() {
+        let mut node = node();
+
+        let out: Outbox<TestFrame> = node.process(ms(0));
+        assert_eq!(out[0], frame(0x705, &[0x00]));
+        assert_eq!(out[1], frame(0x705, &[0x7F]));
+        assert_eq!(node.state(), NmtState::PreOperational);
+
+        let out: Outbox<TestFrame> = node.process(ms(50));
+        assert!(out.is_empty());
+
+        let out: Outbox<TestFrame> = node.process(ms(100));
+        assert_eq!(out[0], frame(0x705, &[0x7F]));
+    }
+
+    #[test]
+    fn nmt_start_and_tpdo_event() {
+        let mut node = node();
+        let _: Outbox<TestFrame> = node.process(ms(0));
+
+        node.handle_frame(&frame(0x000, &[0x01, 0x05]), ms(10));
+        assert_eq!(node.state(), NmtState::Operational);
+
+        node.trigger_tpdo(0);
+        let out: Outbox<TestFrame> = node.process(ms(20));
+        assert_eq!(
+            out.iter().find(|f| f.raw_id() == 0x185),
+            Some(&frame(0x185, &[0x42]))
+        );
+    }
+
+    #[test]
+    fn synchronous_tpdo() {
+        let mut node = node();
+        node.od_mut().entries.push((0x1800, 2, vec![2]));
+
+        let _: Outbox<TestFrame> = node.process(ms(0));
+        node.handle_frame(&frame(0x000, &[0x01, 0x00]), ms(1));
+
+        let out = node.handle_frame(&frame(0x080, &[]), ms(2));
+        assert!(out.is_empty());
+        let out = node.handle_frame(&frame(0x080, &[]), ms(3));
+        assert_eq!(out[0], frame(0x185, &[0x42]));
+
+        // Synchronous PDOs ignore application events outside of SYNC
+        node.trigger_tpdo(0);
+        let out: Outbox<TestFrame> = node.process(ms(4));
+        assert!(out.iter().all(|f| f.raw_id() != 0x185));
+    }
+
+    #[test]
+    fn emcy_and_error_register() {
+        let mut node = node();
+        let _: Outbox<TestFrame> = node.process(ms(0));
+
+        node.raise_error(0x3210, ErrorRegister::VOLTAGE, [0; 5]);
+        let out: Outbox<TestFrame> = node.process(ms(10));
+        assert_eq!(out[0], frame(0x085, &[0x10, 0x32, 0x05, 0, 0, 0, 0, 0]));
+
+        let out = node.handle_frame(&frame(0x605, &[0x40, 0x01, 0x10, 0x00, 0, 0, 0, 0]), ms(11));
+        assert_eq!(
+            out[0],
+            frame(0x585, &[0x4F, 0x01, 0x10, 0x00, 0x05, 0, 0, 0])
+        );
+
+        node.clear_error(0x3210);
+        let out: Outbox<TestFrame> = node.process(ms(20));
+        assert_eq!(out[0], frame(0x085, &[0; 8]));
+    }
+
+    #[test]
+    fn sdo_request_response() {
+        let mut node = node();
+        let _: Outbox<TestFrame> = node.process(ms(0));
+
+        let out = node.handle_frame(&frame(0x605, &[0x40, 0x00, 0x60, 0x00, 0, 0, 0, 0]), ms(1));
+        assert_eq!(
+            out[0],
+            frame(0x585, &[0x4F, 0x00, 0x60, 0x00, 0x42, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn reset_sends_bootup() {
+        let mut node = node();
+        let _: Outbox<TestFrame> = node.process(ms(0));
+
+        node.handle_frame(&frame(0x000, &[0x82, 0x00]), ms(10));
+        let out: Outbox<TestFrame> = node.process(ms(20));
+        assert_eq!(out[0], frame(0x705, &[0x00]));
+    }
+}
@@ -0,0 +1,99 @@
+//
+// od.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+/// Device type
+pub const DEVICE_TYPE: u16 = 0x1000;
+/// Error register
+pub const ERROR_REGISTER: u16 = 0x1001;
+/// Pre-defined error field
+pub const PREDEFINED_ERROR_FIELD: u16 = 0x1003;
+/// COB-ID of the SYNC message
+pub const COB_ID_SYNC: u16 = 0x1005;
+/// COB-ID of the EMCY message
+pub const COB_ID_EMCY: u16 = 0x1014;
+/// EMCY inhibit time in multiples of 100us
+pub const INHIBIT_TIME_EMCY: u16 = 0x1015;
+/// Producer heartbeat time in milliseconds
+pub const PRODUCER_HEARTBEAT_TIME: u16 = 0x1017;
+/// Identity object
+pub const IDENTITY: u16 = 0x1018;
+/// First RPDO communication parameter
+pub const RPDO_COMMUNICATION: u16 = 0x1400;
+/// First RPDO mapping parameter
+pub const RPDO_MAPPING: u16 = 0x1600;
+/// First TPDO communication parameter
+pub const TPDO_COMMUNICATION: u16 = 0x1800;
+/// First TPDO mapping parameter
+pub const TPDO_MAPPING: u16 = 0x1A00;
+
+/// SDO abort codes (CiA 301 Table 22)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AbortCode {
+    ToggleBitNotAlternated = 0x0503_0000,
+    SdoProtocolTimedOut = 0x0504_0000,
+    InvalidCommandSpecifier = 0x0504_0001,
+    OutOfMemory = 0x0504_0005,
+    UnsupportedAccess = 0x0601_0000,
+    WriteOnly = 0x0601_0001,
+    ReadOnly = 0x0601_0002,
+    ObjectDoesNotExist = 0x0602_0000,
+    CannotMapToPdo = 0x0604_0041,
+    PdoLengthExceeded = 0x0604_0042,
+    GeneralIncompatibility = 0x0604_0043,
+    HardwareError = 0x0606_0000,
+    LengthMismatch = 0x0607_0010,
+    LengthTooHigh = 0x0607_0012,
+    LengthTooLow = 0x0607_0013,
+    SubIndexDoesNotExist = 0x0609_0011,
+    InvalidValue = 0x0609_0030,
+    ValueTooHigh = 0x0609_0031,
+    ValueTooLow = 0x0609_0032,
+    GeneralError = 0x0800_0000,
+    CannotStore = 0x0800_0020,
+    CannotStoreLocalControl = 0x0800_0021,
+    CannotStoreDeviceState = 0x0800_0022,
+}
+
+impl AbortCode {
+    /// Raw abort code as sent on the bus
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Storage backing the node's object dictionary
+///
+/// Values are exchanged as little-endian byte strings, exactly as they appear in SDO and PDO
+/// payloads.
+pub trait ObjectDictionary {
+    /// Read the entry at `index`/`subindex` into `buf`, returning the number of bytes written
+    fn read(&self, index: u16, subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode>;
+    /// Write `data` to the entry at `index`/`subindex`
+    fn write(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode>;
+
+    /// Read an entry as an unsigned 8-bit value
+    fn read_u8(&self, index: u16, subindex: u8) -> Result<u8, AbortCode> {
+        let mut buf = [0u8; 1];
+        self.read(index, subindex, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Read an entry as an unsigned 16-bit value
+    fn read_u16(&self, index: u16, subindex: u8) -> Result<u16, AbortCode> {
+        let mut buf = [0u8; 2];
+        self.read(index, subindex, &mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    /// Read an entry as an unsigned 32-bit value
+    fn read_u32(&self, index: u16, subindex: u8) -> Result<u32, AbortCode> {
+        let mut buf = [0u8; 4];
+        self.read(index, subindex, &mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+}
@@ -0,0 +1,376 @@
+//
+// pdo.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::od::{
+    AbortCode, ObjectDictionary, RPDO_COMMUNICATION, RPDO_MAPPING, TPDO_COMMUNICATION, TPDO_MAPPING,
+};
+
+use core::time::Duration;
+
+/// Default COB-ID of the SYNC message
+pub const DEFAULT_SYNC_COB_ID: u16 = 0x080;
+
+/// Number of RPDOs and TPDOs supported by the stack
+pub const NUM_PDOS: usize = 4;
+
+/// COB-ID bit marking a PDO as not valid
+const COB_ID_INVALID: u32 = 1 << 31;
+
+/// Highest index used for dummy mapping entries (CiA 301 7.4.7.1)
+pub(crate) const DUMMY_MAPPING_MAX: u16 = 0x0007;
+
+/// A single PDO mapping entry (sub 1..=8 of a mapping parameter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappingEntry {
+    pub index: u16,
+    pub subindex: u8,
+    pub bits: u8,
+}
+
+impl From<u32> for MappingEntry {
+    fn from(value: u32) -> Self {
+        MappingEntry {
+            index: (value >> 16) as u16,
+            subindex: (value >> 8) as u8,
+            bits: value as u8,
+        }
+    }
+}
+
+impl From<MappingEntry> for u32 {
+    fn from(value: MappingEntry) -> Self {
+        ((value.index as u32) << 16) | ((value.subindex as u32) << 8) | value.bits as u32
+    }
+}
+
+/// PDO direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Rx,
+    Tx,
+}
+
+impl Direction {
+    fn communication_index(self, n: usize) -> u16 {
+        match self {
+            Direction::Rx => RPDO_COMMUNICATION + n as u16,
+            Direction::Tx => TPDO_COMMUNICATION + n as u16,
+        }
+    }
+
+    fn mapping_index(self, n: usize) -> u16 {
+        match self {
+            Direction::Rx => RPDO_MAPPING + n as u16,
+            Direction::Tx => TPDO_MAPPING + n as u16,
+        }
+    }
+}
+
+/// Get the COB-ID of PDO `n`, or `None` if it does not exist or is not valid
+pub fn cob_id<OD: ObjectDictionary>(od: &OD, dir: Direction, n: usize) -> Option<u16> {
+    od.read_u32(dir.communication_index(n), 1)
+        .ok()
+        .filter(|raw| raw & COB_ID_INVALID == 0)
+        .map(|raw| (raw & 0x7FF) as u16)
+}
+
+/// Read the mapping entries of PDO `n`
+pub fn mapping<OD: ObjectDictionary>(
+    od: &OD,
+    dir: Direction,
+    n: usize,
+) -> Result<heapless::Vec<MappingEntry, 8>, AbortCode> {
+    let index = dir.mapping_index(n);
+    let count = od.read_u8(index, 0)?;
+
+    if count > 8 {
+        return Err(AbortCode::PdoLengthExceeded);
+    }
+
+    let mut entries = heapless::Vec::new();
+    for sub in 1..=count {
+        let entry = MappingEntry::from(od.read_u32(index, sub)?);
+
+        // Only byte aligned mappings are supported
+        if entry.bits % 8 != 0 {
+            return Err(AbortCode::GeneralIncompatibility);
+        }
+
+        // Capacity is 8 and count is at most 8
+        let _ = entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Assemble the payload of TPDO `n` from the object dictionary
+pub fn pack<OD: ObjectDictionary>(
+    od: &OD,
+    n: usize,
+    buf: &mut [u8; 8],
+) -> Result<usize, AbortCode> {
+    let mut len = 0;
+
+    for entry in mapping(od, Direction::Tx, n)? {
+        let size = (entry.bits / 8) as usize;
+        if len + size > 8 {
+            return Err(AbortCode::PdoLengthExceeded);
+        }
+
+        let mut value = [0u8; 8];
+        od.read(entry.index, entry.subindex, &mut value)?;
+        buf[len..len + size].copy_from_slice(&value[..size]);
+
+        len += size;
+    }
+
+    Ok(len)
+}
+
+/// Write the payload of RPDO `n` into the object dictionary
+pub fn unpack<OD: ObjectDictionary>(od: &mut OD, n: usize, data: &[u8]) -> Result<(), AbortCode> {
+    let mut offset = 0;
+
+    for entry in mapping(od, Direction::Rx, n)? {
+        let size = (entry.bits / 8) as usize;
+        if offset + size > data.len() {
+            return Err(AbortCode::LengthTooLow);
+        }
+
+        if entry.index > DUMMY_MAPPING_MAX {
+            od.write(entry.index, entry.subindex, &data[offset..offset + size])?;
+        }
+
+        offset += size;
+    }
+
+    Ok(())
+}
+
+/// TPDO transmission type (communication parameter sub 2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransmissionType {
+    /// Sent on the SYNC following an application event (type 0)
+    SyncAcyclic,
+    /// Sent every N SYNCs (types 1 to 240)
+    SyncCyclic(u8),
+    /// Sent on application events and the event timer (types 254 and 255)
+    Event,
+    /// RTR only and reserved types, not sent by the stack
+    Unsupported,
+}
+
+impl From<u8> for TransmissionType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => TransmissionType::SyncAcyclic,
+            1..=240 => TransmissionType::SyncCyclic(value),
+            254 | 255 => TransmissionType::Event,
+            _ => TransmissionType::Unsupported,
+        }
+    }
+}
+
+/// Transmission settings of a TPDO
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TpdoParameters {
+    pub transmission_type: TransmissionType,
+    /// Minimum time between two transmissions
+    pub inhibit_time: Duration,
+    /// Period of timer driven transmissions, zero when disabled
+    pub event_timer: Duration,
+}
+
+impl TpdoParameters {
+    /// Read the communication parameters of TPDO `n`. Missing entries take their defaults.
+    pub fn read<OD: ObjectDictionary>(od: &OD, n: usize) -> Self {
+        let index = TPDO_COMMUNICATION + n as u16;
+
+        // Inhibit time is in multiples of 100us, the event timer in milliseconds
+        let inhibit = od.read_u16(index, 3).unwrap_or(0);
+        let event_timer = od.read_u16(index, 5).unwrap_or(0);
+
+        TpdoParameters {
+            transmission_type: od.read_u8(index, 2).unwrap_or(255).into(),
+            inhibit_time: Duration::from_micros(inhibit as u64 * 100),
+            event_timer: Duration::from_millis(event_timer as u64),
+        }
+    }
+}
+
+/// Transmit state of a single TPDO
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tpdo {
+    event_pending: bool,
+    last_sent: Option<Duration>,
+    sync_count: u8,
+}
+
+impl Tpdo {
+    /// Signal an application event for this PDO
+    pub fn trigger(&mut self) {
+        self.event_pending = true;
+    }
+
+    /// Check if an event driven PDO should be sent now
+    pub fn poll(&mut self, params: &TpdoParameters, now: Duration) -> bool {
+        if params.transmission_type != TransmissionType::Event {
+            return false;
+        }
+
+        let elapsed = self.last_sent.map(|last| now.saturating_sub(last));
+
+        let timer_expired =
+            !params.event_timer.is_zero() && elapsed.is_none_or(|e| e >= params.event_timer);
+        let inhibited = elapsed.is_some_and(|e| e < params.inhibit_time);
+
+        // Events occurring during the inhibit time stay pending until it has passed
+        if (self.event_pending || timer_expired) && !inhibited {
+            self.event_pending = false;
+            self.last_sent = Some(now);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check if a synchronous PDO should be sent in response to a SYNC
+    pub fn on_sync(&mut self, params: &TpdoParameters, now: Duration) -> bool {
+        let send = match params.transmission_type {
+            TransmissionType::SyncAcyclic => core::mem::take(&mut self.event_pending),
+            TransmissionType::SyncCyclic(n) => {
+                self.sync_count += 1;
+                if self.sync_count >= n {
+                    self.sync_count = 0;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        };
+
+        if send {
+            self.last_sent = Some(now);
+        }
+
+        send
+    }
+
+    pub fn reset(&mut self) {
+        *self = Tpdo::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestOd;
+
+    fn od() -> TestOd {
+        TestOd::default()
+            .with(0x1800, 1, &0x185u32.to_le_bytes())
+            .with(0x1A00, 0, &[2])
+            .with(0x1A00, 1, &0x6000_0108u32.to_le_bytes())
+            .with(0x1A00, 2, &0x6001_0010u32.to_le_bytes())
+            .with(0x1400, 1, &(0x205u32 | COB_ID_INVALID).to_le_bytes())
+            .with(0x1600, 0, &[1])
+            .with(0x1600, 1, &0x6001_0010u32.to_le_bytes())
+            .with(0x6000, 1, &[0xAA])
+            .with(0x6001, 0, &[0x34, 0x12])
+    }
+
+    #[test]
+    fn mapping_entry_conversion() {
+        let entry = MappingEntry::from(0x6040_0010);
+        assert_eq!(
+            entry,
+            MappingEntry {
+                index: 0x6040,
+                subindex: 0,
+                bits: 16
+            }
+        );
+        assert_eq!(u32::from(entry), 0x6040_0010);
+    }
+
+    #[test]
+    fn cob_id_validity() {
+        let od = od();
+        assert_eq!(cob_id(&od, Direction::Tx, 0), Some(0x185));
+        assert_eq!(cob_id(&od, Direction::Rx, 0), None);
+        assert_eq!(cob_id(&od, Direction::Tx, 1), None);
+    }
+
+    #[test]
+    fn pack_and_unpack() {
+        let mut od = od();
+
+        let mut buf = [0u8; 8];
+        assert_eq!(pack(&od, 0, &mut buf), Ok(3));
+        assert_eq!(&buf[..3], &[0xAA, 0x34, 0x12]);
+
+        unpack(&mut od, 0, &[0x78, 0x56]).unwrap();
+        assert_eq!(od.get(0x6001, 0), Some(&[0x78, 0x56][..]));
+
+        assert_eq!(unpack(&mut od, 0, &[0x01]), Err(AbortCode::LengthTooLow));
+    }
+
+    fn params(transmission_type: u8, inhibit_ms: u64, event_timer_ms: u64) -> TpdoParameters {
+        TpdoParameters {
+            transmission_type: transmission_type.into(),
+            inhibit_time: Duration::from_millis(inhibit_ms),
+            event_timer: Duration::from_millis(event_timer_ms),
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn event_with_inhibit_time() {
+        let params = params(255, 10, 0);
+        let mut tpdo = Tpdo::default();
+
+        tpdo.trigger();
+        assert!(tpdo.poll(&params, ms(0)));
+        assert!(!tpdo.poll(&params, ms(1)));
+
+        // Held back until the inhibit time has passed
+        tpdo.trigger();
+        assert!(!tpdo.poll(&params, ms(5)));
+        assert!(tpdo.poll(&params, ms(10)));
+    }
+
+    #[test]
+    fn event_timer() {
+        let params = params(254, 0, 100);
+        let mut tpdo = Tpdo::default();
+
+        assert!(tpdo.poll(&params, ms(0)));
+        assert!(!tpdo.poll(&params, ms(50)));
+        assert!(tpdo.poll(&params, ms(100)));
+    }
+
+    #[test]
+    fn synchronous() {
+        let cyclic = params(3, 0, 0);
+        let mut tpdo = Tpdo::default();
+
+        let sent = (0..6).filter(|_| tpdo.on_sync(&cyclic, ms(0))).count();
+        assert_eq!(sent, 2);
+        assert!(!tpdo.poll(&cyclic, ms(0)));
+
+        let acyclic = params(0, 0, 0);
+        let mut tpdo = Tpdo::default();
+        assert!(!tpdo.on_sync(&acyclic, ms(0)));
+        tpdo.trigger();
+        assert!(tpdo.on_sync(&acyclic, ms(0)));
+        assert!(!tpdo.on_sync(&acyclic, ms(0)));
+    }
+}
@@ -0,0 +1,417 @@
+//
+// sdo.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::od::{AbortCode, ObjectDictionary};
+
+use core::time::Duration;
+
+/// Base COB-ID of SDO requests (client to server)
+pub const SDO_RX_COB_ID: u16 = 0x600;
+/// Base COB-ID of SDO responses (server to client)
+pub const SDO_TX_COB_ID: u16 = 0x580;
+
+/// Largest value that can be transferred with a segmented transfer
+pub const BUFFER_SIZE: usize = 256;
+/// Time without a client request before an active transfer is aborted
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+// Client command specifiers
+const CCS_DOWNLOAD_SEGMENT: u8 = 0;
+const CCS_INITIATE_DOWNLOAD: u8 = 1;
+const CCS_INITIATE_UPLOAD: u8 = 2;
+const CCS_UPLOAD_SEGMENT: u8 = 3;
+const CCS_ABORT: u8 = 4;
+
+/// SDO response payload
+pub type Response = [u8; 8];
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    Download {
+        index: u16,
+        subindex: u8,
+        toggle: bool,
+        len: usize,
+        size: Option<usize>,
+    },
+    Upload {
+        index: u16,
+        subindex: u8,
+        toggle: bool,
+        offset: usize,
+        len: usize,
+    },
+}
+
+/// Expedited and segmented SDO server
+pub struct SdoServer {
+    state: State,
+    buffer: [u8; BUFFER_SIZE],
+    last_request: Duration,
+    timeout: Duration,
+}
+
+impl Default for SdoServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SdoServer {
+    pub fn new() -> Self {
+        SdoServer {
+            state: State::Idle,
+            buffer: [0u8; BUFFER_SIZE],
+            last_request: Duration::ZERO,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set the SDO protocol timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Check if a segmented transfer is in progress
+    pub fn is_busy(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+
+    /// Drop any in-progress transfer
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// Handle an SDO request payload and produce the response
+    pub fn handle<OD: ObjectDictionary>(
+        &mut self,
+        od: &mut OD,
+        request: &[u8],
+        now: Duration,
+    ) -> Option<Response> {
+        // SDO frames are always 8 bytes. Be lenient and zero fill short frames.
+        let mut req = [0u8; 8];
+        let n = request.len().min(8);
+        req[..n].copy_from_slice(&request[..n]);
+
+        self.last_request = now;
+
+        let ccs = req[0] >> 5;
+
+        match (ccs, self.state) {
+            (CCS_ABORT, _) => {
+                self.state = State::Idle;
+                None
+            }
+            (CCS_INITIATE_DOWNLOAD, _) => Some(self.initiate_download(od, &req)),
+            (CCS_INITIATE_UPLOAD, _) => Some(self.initiate_upload(od, &req)),
+            (CCS_DOWNLOAD_SEGMENT, State::Download { .. }) => Some(self.download_segment(od, &req)),
+            (CCS_UPLOAD_SEGMENT, State::Upload { .. }) => Some(self.upload_segment(&req)),
+            _ => {
+                let (index, subindex) = self.transfer_address().unwrap_or(multiplexer(&req));
+                self.state = State::Idle;
+                Some(abort(index, subindex, AbortCode::InvalidCommandSpecifier))
+            }
+        }
+    }
+
+    /// Check the active transfer for a timeout, returning an abort if one occurred
+    pub fn poll(&mut self, now: Duration) -> Option<Response> {
+        let (index, subindex) = self.transfer_address()?;
+
+        if now.saturating_sub(self.last_request) > self.timeout {
+            self.state = State::Idle;
+            Some(abort(index, subindex, AbortCode::SdoProtocolTimedOut))
+        } else {
+            None
+        }
+    }
+
+    fn transfer_address(&self) -> Option<(u16, u8)> {
+        match self.state {
+            State::Idle => None,
+            State::Download {
+                index, subindex, ..
+            }
+            | State::Upload {
+                index, subindex, ..
+            } => Some((index, subindex)),
+        }
+    }
+
+    fn initiate_download<OD: ObjectDictionary>(&mut self, od: &mut OD, req: &[u8; 8]) -> Response {
+        let (index, subindex) = multiplexer(req);
+        let expedited = req[0] & 0x02 != 0;
+        let size_indicated = req[0] & 0x01 != 0;
+
+        if expedited {
+            self.state = State::Idle;
+
+            let len = if size_indicated {
+                4 - ((req[0] >> 2) & 0x03) as usize
+            } else {
+                4
+            };
+
+            match od.write(index, subindex, &req[4..4 + len]) {
+                Ok(_) => response(0x60, index, subindex, &[]),
+                Err(code) => abort(index, subindex, code),
+            }
+        } else {
+            let size = if size_indicated {
+                Some(u32::from_le_bytes([req[4], req[5], req[6], req[7]]) as usize)
+            } else {
+                None
+            };
+
+            if size.is_some_and(|size| size > BUFFER_SIZE) {
+                self.state = State::Idle;
+                return abort(index, subindex, AbortCode::OutOfMemory);
+            }
+
+            self.state = State::Download {
+                index,
+                subindex,
+                toggle: false,
+                len: 0,
+                size,
+            };
+
+            response(0x60, index, subindex, &[])
+        }
+    }
+
+    fn download_segment<OD: ObjectDictionary>(&mut self, od: &mut OD, req: &[u8; 8]) -> Response {
+        let State::Download {
+            index,
+            subindex,
+            toggle,
+            len,
+            size,
+        } = self.state
+        else {
+            unreachable!()
+        };
+
+        let t = req[0] & 0x10 != 0;
+        if t != toggle {
+            self.state = State::Idle;
+            return abort(index, subindex, AbortCode::ToggleBitNotAlternated);
+        }
+
+        let count = 7 - ((req[0] >> 1) & 0x07) as usize;
+        let last = req[0] & 0x01 != 0;
+
+        if len + count > BUFFER_SIZE {
+            self.state = State::Idle;
+            return abort(index, subindex, AbortCode::OutOfMemory);
+        }
+
+        self.buffer[len..len + count].copy_from_slice(&req[1..1 + count]);
+        let len = len + count;
+
+        if last {
+            self.state = State::Idle;
+
+            if size.is_some_and(|size| size != len) {
+                return abort(index, subindex, AbortCode::LengthMismatch);
+            }
+
+            if let Err(code) = od.write(index, subindex, &self.buffer[..len]) {
+                return abort(index, subindex, code);
+            }
+        } else {
+            self.state = State::Download {
+                index,
+                subindex,
+                toggle: !toggle,
+                len,
+                size,
+            };
+        }
+
+        let mut res = [0u8; 8];
+        res[0] = 0x20 | ((t as u8) << 4);
+        res
+    }
+
+    fn initiate_upload<OD: ObjectDictionary>(&mut self, od: &mut OD, req: &[u8; 8]) -> Response {
+        let (index, subindex) = multiplexer(req);
+        self.state = State::Idle;
+
+        let len = match od.read(index, subindex, &mut self.buffer) {
+            Ok(len) => len,
+            Err(code) => return abort(index, subindex, code),
+        };
+
+        if (1..=4).contains(&len) {
+            let cmd = 0x43 | (((4 - len) as u8) << 2);
+            response(cmd, index, subindex, &self.buffer[..len])
+        } else {
+            self.state = State::Upload {
+                index,
+                subindex,
+                toggle: false,
+                offset: 0,
+                len,
+            };
+
+            response(0x41, index, subindex, &(len as u32).to_le_bytes())
+        }
+    }
+
+    fn upload_segment(&mut self, req: &[u8; 8]) -> Response {
+        let State::Upload {
+            index,
+            subindex,
+            toggle,
+            offset,
+            len,
+        } = self.state
+        else {
+            unreachable!()
+        };
+
+        let t = req[0] & 0x10 != 0;
+        if t != toggle {
+            self.state = State::Idle;
+            return abort(index, subindex, AbortCode::ToggleBitNotAlternated);
+        }
+
+        let count = (len - offset).min(7);
+        let last = offset + count == len;
+
+        let mut res = [0u8; 8];
+        res[0] = ((t as u8) << 4) | (((7 - count) as u8) << 1) | last as u8;
+        res[1..1 + count].copy_from_slice(&self.buffer[offset..offset + count]);
+
+        self.state = if last {
+            State::Idle
+        } else {
+            State::Upload {
+                index,
+                subindex,
+                toggle: !toggle,
+                offset: offset + count,
+                len,
+            }
+        };
+
+        res
+    }
+}
+
+/// Build an SDO abort transfer payload
+pub fn abort(index: u16, subindex: u8, code: AbortCode) -> Response {
+    response(0x80, index, subindex, &code.code().to_le_bytes())
+}
+
+fn multiplexer(req: &[u8; 8]) -> (u16, u8) {
+    (u16::from_le_bytes([req[1], req[2]]), req[3])
+}
+
+fn response(cmd: u8, index: u16, subindex: u8, data: &[u8]) -> Response {
+    let mut res = [0u8; 8];
+    res[0] = cmd;
+    res[1..3].copy_from_slice(&index.to_le_bytes());
+    res[3] = subindex;
+    res[4..4 + data.len()].copy_from_slice(data);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestOd;
+
+    fn now() -> Duration {
+        Duration::ZERO
+    }
+
+    #[test]
+    fn expedited_download_and_upload() {
+        let mut od = TestOd::default().with(0x1017, 0, &[0, 0]);
+        let mut sdo = SdoServer::new();
+
+        let res = sdo.handle(&mut od, &[0x2B, 0x17, 0x10, 0x00, 0xE8, 0x03, 0, 0], now());
+        assert_eq!(res, Some([0x60, 0x17, 0x10, 0x00, 0, 0, 0, 0]));
+        assert_eq!(od.get(0x1017, 0), Some(&[0xE8, 0x03][..]));
+
+        let res = sdo.handle(&mut od, &[0x40, 0x17, 0x10, 0x00, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x4B, 0x17, 0x10, 0x00, 0xE8, 0x03, 0, 0]));
+    }
+
+    #[test]
+    fn abort_on_missing_object() {
+        let mut od = TestOd::default();
+        let mut sdo = SdoServer::new();
+
+        let res = sdo.handle(&mut od, &[0x40, 0x00, 0x60, 0x01, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x80, 0x00, 0x60, 0x01, 0x00, 0x00, 0x02, 0x06]));
+    }
+
+    #[test]
+    fn segmented_upload() {
+        let mut od = TestOd::default().with(0x1008, 0, b"ican node");
+        let mut sdo = SdoServer::new();
+
+        let res = sdo.handle(&mut od, &[0x40, 0x08, 0x10, 0x00, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x41, 0x08, 0x10, 0x00, 9, 0, 0, 0]));
+
+        let res = sdo.handle(&mut od, &[0x60, 0, 0, 0, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x00, b'i', b'c', b'a', b'n', b' ', b'n', b'o']));
+
+        let res = sdo.handle(&mut od, &[0x70, 0, 0, 0, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x1B, b'd', b'e', 0, 0, 0, 0, 0]));
+        assert!(!sdo.is_busy());
+    }
+
+    #[test]
+    fn segmented_download() {
+        let mut od = TestOd::default().with(0x2001, 0, &[]);
+        let mut sdo = SdoServer::new();
+
+        let res = sdo.handle(&mut od, &[0x21, 0x01, 0x20, 0x00, 9, 0, 0, 0], now());
+        assert_eq!(res, Some([0x60, 0x01, 0x20, 0x00, 0, 0, 0, 0]));
+
+        let res = sdo.handle(&mut od, &[0x00, 1, 2, 3, 4, 5, 6, 7], now());
+        assert_eq!(res, Some([0x20, 0, 0, 0, 0, 0, 0, 0]));
+
+        let res = sdo.handle(&mut od, &[0x1B, 8, 9, 0, 0, 0, 0, 0], now());
+        assert_eq!(res, Some([0x30, 0, 0, 0, 0, 0, 0, 0]));
+
+        assert_eq!(od.get(0x2001, 0), Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9][..]));
+    }
+
+    #[test]
+    fn toggle_error_aborts() {
+        let mut od = TestOd::default().with(0x2001, 0, &[]);
+        let mut sdo = SdoServer::new();
+
+        sdo.handle(&mut od, &[0x21, 0x01, 0x20, 0x00, 9, 0, 0, 0], now());
+        let res = sdo.handle(&mut od, &[0x10, 1, 2, 3, 4, 5, 6, 7], now());
+        assert_eq!(
+            res,
+            Some(abort(0x2001, 0, AbortCode::ToggleBitNotAlternated))
+        );
+        assert!(!sdo.is_busy());
+    }
+
+    #[test]
+    fn transfer_timeout() {
+        let mut od = TestOd::default().with(0x2001, 0, &[]);
+        let mut sdo = SdoServer::new();
+
+        sdo.handle(&mut od, &[0x21, 0x01, 0x20, 0x00, 9, 0, 0, 0], now());
+        assert_eq!(sdo.poll(Duration::from_millis(500)), None);
+        assert_eq!(
+            sdo.poll(Duration::from_millis(1500)),
+            Some(abort(0x2001, 0, AbortCode::SdoProtocolTimedOut))
+        );
+    }
+}
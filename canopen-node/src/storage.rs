@@ -0,0 +1,494 @@
+//
+// storage.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Table based object dictionary storage.
+//!
+//! An [`ObjectTable`] is built from a static list of [`Entry`] values, typically generated from
+//! the device's EDS. Entries either hold their value in the table or defer to the application's
+//! [`Callbacks`] for volatile data such as sensor readings.
+
+use crate::{
+    od::{AbortCode, ObjectDictionary, RPDO_MAPPING, TPDO_MAPPING},
+    pdo::{MappingEntry, DUMMY_MAPPING_MAX, NUM_PDOS},
+};
+
+/// Entry access rights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    Const,
+}
+
+impl Access {
+    pub fn is_readable(self) -> bool {
+        !matches!(self, Access::WriteOnly)
+    }
+
+    pub fn is_writable(self) -> bool {
+        matches!(self, Access::WriteOnly | Access::ReadWrite)
+    }
+}
+
+/// Entry data types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Boolean,
+    Integer8,
+    Integer16,
+    Integer32,
+    Unsigned8,
+    Unsigned16,
+    Unsigned32,
+    Real32,
+    VisibleString,
+    OctetString,
+    Domain,
+}
+
+impl DataType {
+    /// Size in bytes of fixed size types
+    pub fn size(self) -> Option<usize> {
+        match self {
+            DataType::Boolean | DataType::Integer8 | DataType::Unsigned8 => Some(1),
+            DataType::Integer16 | DataType::Unsigned16 => Some(2),
+            DataType::Integer32 | DataType::Unsigned32 | DataType::Real32 => Some(4),
+            DataType::VisibleString | DataType::OctetString | DataType::Domain => None,
+        }
+    }
+
+    /// Decode an integer value for limit checks
+    fn to_integer(self, data: &[u8]) -> Option<i64> {
+        let mut buf = [0u8; 8];
+        buf[..data.len().min(8)].copy_from_slice(&data[..data.len().min(8)]);
+
+        match self {
+            DataType::Integer8 => Some(buf[0] as i8 as i64),
+            DataType::Integer16 => Some(i16::from_le_bytes([buf[0], buf[1]]) as i64),
+            DataType::Integer32 => {
+                Some(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as i64)
+            }
+            DataType::Boolean | DataType::Unsigned8 => Some(buf[0] as i64),
+            DataType::Unsigned16 => Some(u16::from_le_bytes([buf[0], buf[1]]) as i64),
+            DataType::Unsigned32 => {
+                Some(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as i64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive value limits of an integer entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub low: i64,
+    pub high: i64,
+}
+
+/// Where the value of an entry is kept
+#[derive(Debug)]
+pub enum Storage<'a> {
+    /// Constant value
+    Const(&'a [u8]),
+    /// Value held in the table. `len` is the current length of variable sized values.
+    Ram { data: &'a mut [u8], len: usize },
+    /// Value provided by the application callbacks
+    Callback,
+}
+
+impl<'a> Storage<'a> {
+    /// RAM storage initialized with the full buffer
+    pub fn ram(data: &'a mut [u8]) -> Self {
+        let len = data.len();
+        Storage::Ram { data, len }
+    }
+}
+
+/// A single object dictionary entry
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pub index: u16,
+    pub subindex: u8,
+    pub data_type: DataType,
+    pub access: Access,
+    pub pdo_mappable: bool,
+    pub limits: Option<Limits>,
+    pub storage: Storage<'a>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn new(
+        index: u16,
+        subindex: u8,
+        data_type: DataType,
+        access: Access,
+        storage: Storage<'a>,
+    ) -> Self {
+        Entry {
+            index,
+            subindex,
+            data_type,
+            access,
+            pdo_mappable: false,
+            limits: None,
+            storage,
+        }
+    }
+
+    /// Allow the entry to be mapped into PDOs
+    pub fn mappable(mut self) -> Self {
+        self.pdo_mappable = true;
+        self
+    }
+
+    /// Restrict writes to the inclusive range `low..=high`
+    pub fn limits(mut self, low: i64, high: i64) -> Self {
+        self.limits = Some(Limits { low, high });
+        self
+    }
+
+    fn key(&self) -> (u16, u8) {
+        (self.index, self.subindex)
+    }
+}
+
+/// Application hooks for entries using [`Storage::Callback`]
+pub trait Callbacks {
+    /// Read a volatile entry
+    fn read(&self, index: u16, subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode>;
+    /// Write a volatile entry
+    fn write(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode>;
+    /// Notification that an entry stored in the table was written
+    fn on_write(&mut self, _index: u16, _subindex: u8) {}
+}
+
+/// No application callbacks
+impl Callbacks for () {
+    fn read(&self, _index: u16, _subindex: u8, _buf: &mut [u8]) -> Result<usize, AbortCode> {
+        Err(AbortCode::HardwareError)
+    }
+
+    fn write(&mut self, _index: u16, _subindex: u8, _data: &[u8]) -> Result<(), AbortCode> {
+        Err(AbortCode::HardwareError)
+    }
+}
+
+/// Object dictionary backed by a table of entries
+pub struct ObjectTable<'a, C = ()> {
+    entries: &'a mut [Entry<'a>],
+    callbacks: C,
+}
+
+impl<'a> ObjectTable<'a, ()> {
+    pub fn new(entries: &'a mut [Entry<'a>]) -> Self {
+        Self::with_callbacks(entries, ())
+    }
+}
+
+impl<'a, C: Callbacks> ObjectTable<'a, C> {
+    pub fn with_callbacks(entries: &'a mut [Entry<'a>], callbacks: C) -> Self {
+        entries.sort_unstable_by_key(Entry::key);
+        ObjectTable { entries, callbacks }
+    }
+
+    pub fn callbacks(&self) -> &C {
+        &self.callbacks
+    }
+
+    pub fn callbacks_mut(&mut self) -> &mut C {
+        &mut self.callbacks
+    }
+
+    /// Look up an entry
+    pub fn entry(&self, index: u16, subindex: u8) -> Result<&Entry<'a>, AbortCode> {
+        self.position(index, subindex).map(|i| &self.entries[i])
+    }
+
+    fn position(&self, index: u16, subindex: u8) -> Result<usize, AbortCode> {
+        self.entries
+            .binary_search_by_key(&(index, subindex), Entry::key)
+            .map_err(|_| {
+                if self.entries.iter().any(|e| e.index == index) {
+                    AbortCode::SubIndexDoesNotExist
+                } else {
+                    AbortCode::ObjectDoesNotExist
+                }
+            })
+    }
+
+    /// Validate writes to PDO mapping parameters
+    fn validate_mapping(&self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode> {
+        let is_rpdo = (RPDO_MAPPING..RPDO_MAPPING + NUM_PDOS as u16).contains(&index);
+        let is_tpdo = (TPDO_MAPPING..TPDO_MAPPING + NUM_PDOS as u16).contains(&index);
+
+        if !is_rpdo && !is_tpdo {
+            return Ok(());
+        }
+
+        if subindex == 0 {
+            // Enabling the mapping, the referenced entries must fit in a single frame
+            let count = data.first().copied().unwrap_or(0);
+            if count > 8 {
+                return Err(AbortCode::PdoLengthExceeded);
+            }
+
+            let mut bits = 0u32;
+            for sub in 1..=count {
+                bits += MappingEntry::from(self.read_u32(index, sub)?).bits as u32;
+            }
+
+            if bits > 64 {
+                return Err(AbortCode::PdoLengthExceeded);
+            }
+        } else {
+            // Entries only change while the mapping is disabled
+            if self.read_u8(index, 0)? != 0 {
+                return Err(AbortCode::GeneralIncompatibility);
+            }
+
+            let mut raw = [0u8; 4];
+            raw[..data.len().min(4)].copy_from_slice(&data[..data.len().min(4)]);
+            let raw = u32::from_le_bytes(raw);
+            // Clearing the entry
+            if raw == 0 {
+                return Ok(());
+            }
+            let mapping = MappingEntry::from(raw);
+
+            // Dummy entries are only valid for receive PDOs
+            if is_rpdo && (1..=DUMMY_MAPPING_MAX).contains(&mapping.index) {
+                return Ok(());
+            }
+
+            let entry = self.entry(mapping.index, mapping.subindex)?;
+            let access_ok = if is_rpdo {
+                entry.access.is_writable()
+            } else {
+                entry.access.is_readable()
+            };
+            let size_ok = entry
+                .data_type
+                .size()
+                .is_some_and(|size| size * 8 == mapping.bits as usize);
+
+            if !entry.pdo_mappable || !access_ok || !size_ok {
+                return Err(AbortCode::CannotMapToPdo);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: Callbacks> ObjectDictionary for ObjectTable<'_, C> {
+    fn read(&self, index: u16, subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode> {
+        let entry = self.entry(index, subindex)?;
+
+        if !entry.access.is_readable() {
+            return Err(AbortCode::WriteOnly);
+        }
+
+        let value = match &entry.storage {
+            Storage::Const(data) => &data[..],
+            Storage::Ram { data, len } => &data[..*len],
+            Storage::Callback => return self.callbacks.read(index, subindex, buf),
+        };
+
+        if value.len() > buf.len() {
+            return Err(AbortCode::OutOfMemory);
+        }
+
+        buf[..value.len()].copy_from_slice(value);
+        Ok(value.len())
+    }
+
+    fn write(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), AbortCode> {
+        let pos = self.position(index, subindex)?;
+        let entry = &self.entries[pos];
+
+        if !entry.access.is_writable() {
+            return Err(AbortCode::ReadOnly);
+        }
+
+        if let Some(size) = entry.data_type.size() {
+            if data.len() > size {
+                return Err(AbortCode::LengthTooHigh);
+            }
+            if data.len() < size {
+                return Err(AbortCode::LengthTooLow);
+            }
+        }
+
+        if let (Some(limits), Some(value)) = (entry.limits, entry.data_type.to_integer(data)) {
+            if value > limits.high {
+                return Err(AbortCode::ValueTooHigh);
+            }
+            if value < limits.low {
+                return Err(AbortCode::ValueTooLow);
+            }
+        }
+
+        self.validate_mapping(index, subindex, data)?;
+
+        match &mut self.entries[pos].storage {
+            Storage::Const(_) => Err(AbortCode::ReadOnly),
+            Storage::Ram { data: value, len } => {
+                if data.len() > value.len() {
+                    return Err(AbortCode::LengthTooHigh);
+                }
+
+                value[..data.len()].copy_from_slice(data);
+                *len = data.len();
+
+                self.callbacks.on_write(index, subindex);
+                Ok(())
+            }
+            Storage::Callback => self.callbacks.write(index, subindex, data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sensor {
+        value: u16,
+    }
+
+    impl Callbacks for Sensor {
+        fn read(&self, _index: u16, _subindex: u8, buf: &mut [u8]) -> Result<usize, AbortCode> {
+            buf[..2].copy_from_slice(&self.value.to_le_bytes());
+            Ok(2)
+        }
+
+        fn write(&mut self, _index: u16, _subindex: u8, _data: &[u8]) -> Result<(), AbortCode> {
+            Err(AbortCode::ReadOnly)
+        }
+    }
+
+    #[test]
+    fn read_write_and_access() {
+        let mut heartbeat = [0u8; 2];
+        let mut entries = [
+            Entry::new(
+                0x1017,
+                0,
+                DataType::Unsigned16,
+                Access::ReadWrite,
+                Storage::ram(&mut heartbeat),
+            )
+            .limits(0, 10000),
+            Entry::new(
+                0x1000,
+                0,
+                DataType::Unsigned32,
+                Access::Const,
+                Storage::Const(&[0x91, 0x01, 0, 0]),
+            ),
+            Entry::new(
+                0x6000,
+                0,
+                DataType::Unsigned16,
+                Access::ReadOnly,
+                Storage::Callback,
+            ),
+        ];
+        let mut od = ObjectTable::with_callbacks(&mut entries, Sensor { value: 0x1234 });
+
+        od.write(0x1017, 0, &1000u16.to_le_bytes()).unwrap();
+        assert_eq!(od.read_u16(0x1017, 0), Ok(1000));
+        assert_eq!(od.read_u32(0x1000, 0), Ok(0x191));
+        assert_eq!(od.read_u16(0x6000, 0), Ok(0x1234));
+
+        assert_eq!(od.write(0x1000, 0, &[0; 4]), Err(AbortCode::ReadOnly));
+        assert_eq!(od.write(0x1017, 0, &[0]), Err(AbortCode::LengthTooLow));
+        assert_eq!(
+            od.write(0x1017, 0, &20000u16.to_le_bytes()),
+            Err(AbortCode::ValueTooHigh)
+        );
+        assert_eq!(od.read_u8(0x1017, 1), Err(AbortCode::SubIndexDoesNotExist));
+        assert_eq!(od.read_u8(0x2000, 0), Err(AbortCode::ObjectDoesNotExist));
+    }
+
+    #[test]
+    fn pdo_mapping_validation() {
+        let mut count = [0u8; 1];
+        let mut map1 = [0u8; 4];
+        let mut map2 = [0u8; 4];
+        let mut entries = [
+            Entry::new(
+                0x1A00,
+                0,
+                DataType::Unsigned8,
+                Access::ReadWrite,
+                Storage::ram(&mut count),
+            ),
+            Entry::new(
+                0x1A00,
+                1,
+                DataType::Unsigned32,
+                Access::ReadWrite,
+                Storage::ram(&mut map1),
+            ),
+            Entry::new(
+                0x1A00,
+                2,
+                DataType::Unsigned32,
+                Access::ReadWrite,
+                Storage::ram(&mut map2),
+            ),
+            Entry::new(
+                0x6000,
+                0,
+                DataType::Unsigned16,
+                Access::ReadOnly,
+                Storage::Const(&[0, 0]),
+            )
+            .mappable(),
+            Entry::new(
+                0x6001,
+                0,
+                DataType::Unsigned16,
+                Access::ReadOnly,
+                Storage::Const(&[0, 0]),
+            ),
+        ];
+        let mut od = ObjectTable::new(&mut entries);
+
+        od.write(0x1A00, 1, &0x6000_0010u32.to_le_bytes()).unwrap();
+        od.write(0x1A00, 0, &[1]).unwrap();
+
+        // Entries are fixed while the mapping is enabled
+        assert_eq!(
+            od.write(0x1A00, 2, &0x6000_0010u32.to_le_bytes()),
+            Err(AbortCode::GeneralIncompatibility)
+        );
+        assert_eq!(
+            od.write(0x1A00, 1, &[0; 4]),
+            Err(AbortCode::GeneralIncompatibility)
+        );
+        // Too many entries
+        assert_eq!(od.write(0x1A00, 0, &[9]), Err(AbortCode::PdoLengthExceeded));
+
+        od.write(0x1A00, 0, &[0]).unwrap();
+        // Not mappable
+        assert_eq!(
+            od.write(0x1A00, 2, &0x6001_0010u32.to_le_bytes()),
+            Err(AbortCode::CannotMapToPdo)
+        );
+        // Wrong size
+        assert_eq!(
+            od.write(0x1A00, 2, &0x6000_0020u32.to_le_bytes()),
+            Err(AbortCode::CannotMapToPdo)
+        );
+        // A zero entry clears it
+        od.write(0x1A00, 1, &[0; 4]).unwrap();
+        assert_eq!(od.read_u32(0x1A00, 1), Ok(0));
+    }
+}
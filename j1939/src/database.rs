@@ -0,0 +1,327 @@
+//
+// database.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Loadable PGN/SPN definitions
+//!
+//! Definitions can be loaded from JSON:
+//!
+//! ```json
+//! { "pgns": [ { "pgn": 61444, "name": "Electronic Engine Controller 1", "acronym": "EEC1",
+//!     "spns": [ { "spn": 190, "name": "Engine Speed", "start_bit": 24, "length": 16,
+//!                 "scale": 0.125, "offset": 0, "unit": "rpm" } ] } ] }
+//! ```
+//!
+//! or from CSV with one SPN per row:
+//!
+//! ```text
+//! pgn,pgn_name,acronym,spn,spn_name,start_bit,length,scale,offset,unit
+//! 61444,Electronic Engine Controller 1,EEC1,190,Engine Speed,24,16,0.125,0,rpm
+//! ```
+//!
+//! `start_bit` is the zero based little-endian bit position in the payload.
+
+use crate::id::Pgn;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    #[error("Failed to read database: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON database: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid CSV database on line {line}: {message}")]
+    Csv { line: usize, message: String },
+    #[error("Unknown database format, expected .json or .csv")]
+    UnknownFormat,
+}
+
+/// Suspect parameter definition
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpnDefinition {
+    pub spn: u32,
+    pub name: String,
+    pub start_bit: usize,
+    pub length: usize,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default)]
+    pub unit: String,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// Parameter group definition
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PgnDefinition {
+    pub pgn: u32,
+    pub name: String,
+    #[serde(default)]
+    pub acronym: String,
+    #[serde(default)]
+    pub spns: Vec<SpnDefinition>,
+}
+
+impl PgnDefinition {
+    /// Short name of the PGN, the acronym if available
+    pub fn label(&self) -> &str {
+        if self.acronym.is_empty() {
+            &self.name
+        } else {
+            &self.acronym
+        }
+    }
+}
+
+/// A decoded SPN value
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpnValue<'a> {
+    pub definition: &'a SpnDefinition,
+    /// Scaled value, `None` if the sender reported it as not available or in error
+    pub value: Option<f64>,
+}
+
+impl fmt::Display for SpnValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(value) => {
+                // Show as many decimals as the resolution of the SPN requires
+                let scale = self.definition.scale.abs();
+                let decimals = (0..6)
+                    .find(|&d| {
+                        let scaled = scale * 10f64.powi(d);
+                        (scaled - scaled.round()).abs() < 1e-9
+                    })
+                    .unwrap_or(6) as usize;
+
+                write!(f, "{} = {:.*}", self.definition.name, decimals, value)?;
+
+                if !self.definition.unit.is_empty() {
+                    write!(f, " {}", self.definition.unit)?;
+                }
+
+                Ok(())
+            }
+            None => write!(f, "{} = n/a", self.definition.name),
+        }
+    }
+}
+
+impl SpnDefinition {
+    /// Extract and scale the value of this SPN from a PGN payload
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        if self.length == 0 || self.length > 32 || self.start_bit + self.length > data.len() * 8 {
+            return None;
+        }
+
+        let raw = (0..self.length).fold(0u64, |raw, i| {
+            let bit = self.start_bit + i;
+            let value = (data[bit / 8] >> (bit % 8)) & 0x01;
+            raw | ((value as u64) << i)
+        });
+
+        // The top of the range is reserved for error and not available indicators
+        // (J1939-71 5.1.4). Byte sized values are valid up to 0xFA, 0xFAFF, etc. and discrete
+        // values use all ones for not available.
+        let not_valid = if self.length >= 8 {
+            raw >= 0xFB << (self.length - 8)
+        } else {
+            self.length > 1 && raw == (1 << self.length) - 1
+        };
+        if not_valid {
+            return None;
+        }
+
+        Some(raw as f64 * self.scale + self.offset)
+    }
+}
+
+/// PGN/SPN database
+#[derive(Debug, Clone, Default)]
+pub struct Database {
+    pgns: HashMap<Pgn, PgnDefinition>,
+}
+
+#[derive(Deserialize)]
+struct JsonDatabase {
+    pgns: Vec<PgnDefinition>,
+}
+
+impl Database {
+    /// Load a database, the format is selected from the file extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DatabaseError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Database::from_json(&text),
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Database::from_csv(&text),
+            _ => Err(DatabaseError::UnknownFormat),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, DatabaseError> {
+        let db: JsonDatabase = serde_json::from_str(text)?;
+        Ok(Database::from_definitions(db.pgns))
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, DatabaseError> {
+        let mut pgns: HashMap<Pgn, PgnDefinition> = HashMap::new();
+
+        // Skip the header line
+        for (n, line) in text.lines().enumerate().skip(1) {
+            let line_no = n + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 7 {
+                return Err(DatabaseError::Csv {
+                    line: line_no,
+                    message: format!("expected at least 7 fields, found {}", fields.len()),
+                });
+            }
+
+            let field = |idx: usize| fields.get(idx).copied().unwrap_or("");
+
+            let optional = |idx: usize| -> Result<Option<f64>, DatabaseError> {
+                match field(idx) {
+                    "" => Ok(None),
+                    f => f.parse().map(Some).map_err(|_| DatabaseError::Csv {
+                        line: line_no,
+                        message: format!("invalid number '{}'", f),
+                    }),
+                }
+            };
+
+            let required = |idx: usize| -> Result<f64, DatabaseError> {
+                optional(idx)?.ok_or_else(|| DatabaseError::Csv {
+                    line: line_no,
+                    message: format!("missing field {}", idx + 1),
+                })
+            };
+
+            let pgn = required(0)? as u32;
+            let spn = SpnDefinition {
+                spn: required(3)? as u32,
+                name: field(4).to_string(),
+                start_bit: required(5)? as usize,
+                length: required(6)? as usize,
+                scale: optional(7)?.unwrap_or(1.0),
+                offset: optional(8)?.unwrap_or(0.0),
+                unit: field(9).to_string(),
+            };
+
+            pgns.entry(Pgn::from(pgn))
+                .or_insert_with(|| PgnDefinition {
+                    pgn,
+                    name: field(1).to_string(),
+                    acronym: field(2).to_string(),
+                    spns: vec![],
+                })
+                .spns
+                .push(spn);
+        }
+
+        Ok(Database { pgns })
+    }
+
+    pub fn from_definitions(definitions: Vec<PgnDefinition>) -> Self {
+        let pgns = definitions
+            .into_iter()
+            .map(|def| (Pgn::from(def.pgn), def))
+            .collect();
+
+        Database { pgns }
+    }
+
+    pub fn get(&self, pgn: Pgn) -> Option<&PgnDefinition> {
+        self.pgns.get(&pgn)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pgns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pgns.is_empty()
+    }
+
+    /// Decode all known SPNs of a PGN payload
+    pub fn decode(&self, pgn: Pgn, data: &[u8]) -> Vec<SpnValue<'_>> {
+        self.get(pgn)
+            .map(|def| {
+                def.spns
+                    .iter()
+                    .map(|spn| SpnValue {
+                        definition: spn,
+                        value: spn.decode(data),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EEC1: [u8; 8] = [0xFF, 0xFF, 0xFF, 0x60, 0x2F, 0xFF, 0xFF, 0xFF];
+
+    #[test]
+    fn csv_database() {
+        let db = Database::from_csv(
+            "pgn,pgn_name,acronym,spn,spn_name,start_bit,length,scale,offset,unit\n\
+             61444,Electronic Engine Controller 1,EEC1,190,Engine Speed,24,16,0.125,0,rpm\n\
+             61444,Electronic Engine Controller 1,EEC1,899,Engine Torque Mode,0,4,1,0,\n",
+        )
+        .unwrap();
+
+        let def = db.get(Pgn(61444)).unwrap();
+        assert_eq!(def.label(), "EEC1");
+        assert_eq!(def.spns.len(), 2);
+
+        let values = db.decode(Pgn(61444), &EEC1);
+        assert_eq!(values[0].value, Some(1516.0));
+        assert_eq!(values[0].to_string(), "Engine Speed = 1516.000 rpm");
+        // Discrete value with all bits set is not available
+        assert_eq!(values[1].value, None);
+    }
+
+    #[test]
+    fn json_database() {
+        let db = Database::from_json(
+            r#"{ "pgns": [ { "pgn": 65262, "name": "Engine Temperature 1", "acronym": "ET1",
+                 "spns": [ { "spn": 110, "name": "Engine Coolant Temperature", "start_bit": 0,
+                             "length": 8, "scale": 1, "offset": -40, "unit": "C" } ] } ] }"#,
+        )
+        .unwrap();
+
+        let values = db.decode(Pgn(65262), &[0x82, 0xFF]);
+        assert_eq!(values[0].value, Some(90.0));
+        assert_eq!(values[0].to_string(), "Engine Coolant Temperature = 90 C");
+
+        // Not available
+        let values = db.decode(Pgn(65262), &[0xFF]);
+        assert_eq!(values[0].value, None);
+    }
+
+    #[test]
+    fn csv_errors() {
+        let err = Database::from_csv("header\n61444,EEC1,EEC1,abc,Speed,24,16\n").unwrap_err();
+        assert!(matches!(err, DatabaseError::Csv { line: 2, .. }));
+    }
+}
@@ -0,0 +1,186 @@
+//
+// dm.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Diagnostic messages DM1 (active DTCs) and DM2 (previously active DTCs)
+
+use crate::{frame::J1939Error, id::Pgn};
+
+use std::fmt;
+
+/// State of a warning lamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LampStatus {
+    Off,
+    On,
+    Error,
+    NotAvailable,
+}
+
+impl From<u8> for LampStatus {
+    fn from(value: u8) -> Self {
+        match value & 0x03 {
+            0 => LampStatus::Off,
+            1 => LampStatus::On,
+            2 => LampStatus::Error,
+            _ => LampStatus::NotAvailable,
+        }
+    }
+}
+
+impl fmt::Display for LampStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LampStatus::Off => "off",
+            LampStatus::On => "on",
+            LampStatus::Error => "error",
+            LampStatus::NotAvailable => "n/a",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Lamp status of the sending ECU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lamps {
+    pub malfunction_indicator: LampStatus,
+    pub red_stop: LampStatus,
+    pub amber_warning: LampStatus,
+    pub protect: LampStatus,
+}
+
+impl fmt::Display for Lamps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MIL {} RSL {} AWL {} PL {}",
+            self.malfunction_indicator, self.red_stop, self.amber_warning, self.protect
+        )
+    }
+}
+
+/// Diagnostic trouble code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dtc {
+    /// Suspect parameter number
+    pub spn: u32,
+    /// Failure mode identifier
+    pub fmi: u8,
+    /// Occurrence count
+    pub oc: u8,
+}
+
+impl Dtc {
+    /// Decode a 4 byte DTC (conversion method version 4)
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        Dtc {
+            spn: bytes[0] as u32 | (bytes[1] as u32) << 8 | ((bytes[2] as u32) >> 5) << 16,
+            fmi: bytes[2] & 0x1F,
+            oc: bytes[3] & 0x7F,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 4] {
+        [
+            self.spn as u8,
+            (self.spn >> 8) as u8,
+            (((self.spn >> 16) as u8) << 5) | (self.fmi & 0x1F),
+            self.oc & 0x7F,
+        ]
+    }
+}
+
+impl fmt::Display for Dtc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SPN {} FMI {} OC {}", self.spn, self.fmi, self.oc)
+    }
+}
+
+/// Decoded DM1 or DM2 message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticMessage {
+    pub lamps: Lamps,
+    pub dtcs: Vec<Dtc>,
+}
+
+impl DiagnosticMessage {
+    /// Parse a DM1/DM2 payload, single frame or reassembled from the transport protocol
+    pub fn parse(pgn: Pgn, data: &[u8]) -> Result<Self, J1939Error> {
+        if data.len() < 2 {
+            return Err(J1939Error::TooShort(pgn));
+        }
+
+        let lamps = Lamps {
+            malfunction_indicator: LampStatus::from(data[0] >> 6),
+            red_stop: LampStatus::from(data[0] >> 4),
+            amber_warning: LampStatus::from(data[0] >> 2),
+            protect: LampStatus::from(data[0]),
+        };
+
+        // An ECU without trouble codes sends a single all zero DTC, padding may follow
+        let dtcs = data[2..]
+            .chunks_exact(4)
+            .map(|chunk| Dtc::from_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .filter(|dtc| dtc.spn != 0 && dtc.spn != 0x7FFFF)
+            .collect();
+
+        Ok(DiagnosticMessage { lamps, dtcs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dtc_conversion() {
+        // SPN 520192 (0x7F000) FMI 31 OC 127
+        let dtc = Dtc::from_bytes([0x00, 0xF0, 0xFF, 0x7F]);
+        assert_eq!(
+            dtc,
+            Dtc {
+                spn: 0x7F000,
+                fmi: 31,
+                oc: 127
+            }
+        );
+        assert_eq!(dtc.to_bytes(), [0x00, 0xF0, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn single_dtc() {
+        // MIL on, SPN 110 FMI 0 OC 3
+        let dm1 =
+            DiagnosticMessage::parse(Pgn::DM1, &[0x40, 0xFF, 0x6E, 0x00, 0x00, 0x03, 0xFF, 0xFF])
+                .unwrap();
+        assert_eq!(dm1.lamps.malfunction_indicator, LampStatus::On);
+        assert_eq!(dm1.lamps.amber_warning, LampStatus::Off);
+        assert_eq!(
+            dm1.dtcs,
+            vec![Dtc {
+                spn: 110,
+                fmi: 0,
+                oc: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn no_active_dtcs() {
+        let dm1 =
+            DiagnosticMessage::parse(Pgn::DM1, &[0x00, 0xFF, 0, 0, 0, 0, 0xFF, 0xFF]).unwrap();
+        assert!(dm1.dtcs.is_empty());
+    }
+
+    #[test]
+    fn multi_packet() {
+        let data = [0x04, 0xFF, 0x6E, 0x00, 0x00, 0x03, 0x64, 0x00, 0x01, 0x01];
+        let dm1 = DiagnosticMessage::parse(Pgn::DM1, &data).unwrap();
+        assert_eq!(dm1.lamps.amber_warning, LampStatus::On);
+        assert_eq!(dm1.dtcs.len(), 2);
+        assert_eq!(dm1.dtcs[1].spn, 100);
+    }
+}
@@ -0,0 +1,221 @@
+//
+// frame.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    id::{J1939Id, Pgn},
+    transport::TpCm,
+};
+
+use embedded_can::{ExtendedId, Frame, Id};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum J1939Error {
+    #[error("J1939 requires extended identifiers")]
+    StandardId,
+    #[error("J1939 does not use remote frames")]
+    RemoteFrame,
+    #[error("Payload too short for PGN {0}")]
+    TooShort(Pgn),
+    #[error("Unknown transport protocol control byte {0}")]
+    UnknownControl(u8),
+}
+
+/// A J1939 frame with its identifier split into fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct J1939Frame {
+    pub id: J1939Id,
+    pub data: Vec<u8>,
+}
+
+/// Acknowledgement control byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckControl {
+    Ack,
+    Nack,
+    AccessDenied,
+    CannotRespond,
+    Other(u8),
+}
+
+impl From<u8> for AckControl {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => AckControl::Ack,
+            1 => AckControl::Nack,
+            2 => AckControl::AccessDenied,
+            3 => AckControl::CannotRespond,
+            other => AckControl::Other(other),
+        }
+    }
+}
+
+/// Decoded J1939 network management and transport messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// Request for a PGN
+    Request { pgn: Pgn },
+    /// Positive or negative acknowledgement of a PGN
+    Acknowledgement { control: AckControl, pgn: Pgn },
+    /// Address claimed with the sender's NAME
+    AddressClaimed { name: u64 },
+    /// Transport protocol connection management
+    TpCm(TpCm),
+    /// Transport protocol data packet
+    TpDt { sequence: u8, data: &'a [u8] },
+    /// Any other parameter group
+    Data(&'a [u8]),
+}
+
+impl J1939Frame {
+    pub fn new(id: J1939Id, data: &[u8]) -> Self {
+        J1939Frame {
+            id,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Interpret a CAN frame as a J1939 frame
+    pub fn from_frame<F: Frame>(frame: &F) -> Result<Self, J1939Error> {
+        if frame.is_remote_frame() {
+            return Err(J1939Error::RemoteFrame);
+        }
+
+        match frame.id() {
+            Id::Extended(id) => Ok(J1939Frame::new(
+                J1939Id::from_raw(id.as_raw()),
+                frame.data(),
+            )),
+            Id::Standard(_) => Err(J1939Error::StandardId),
+        }
+    }
+
+    /// Build a request for `pgn`. Requests are sent with the default priority of 6.
+    pub fn request(pgn: Pgn, destination: u8, source: u8) -> Self {
+        let id = J1939Id::new(6, Pgn::REQUEST, destination, source);
+        J1939Frame::new(id, &pgn.as_raw().to_le_bytes()[..3])
+    }
+
+    /// Convert to a CAN frame
+    pub fn to_frame<F: Frame>(&self) -> Option<F> {
+        ExtendedId::new(self.id.to_raw()).and_then(|id| F::new(id, &self.data))
+    }
+
+    pub fn pgn(&self) -> Pgn {
+        self.id.pgn()
+    }
+
+    pub fn source_address(&self) -> u8 {
+        self.id.sa
+    }
+
+    pub fn destination(&self) -> Option<u8> {
+        self.id.destination()
+    }
+
+    /// Decode the frame payload
+    pub fn message(&self) -> Result<Message<'_>, J1939Error> {
+        let pgn = self.pgn();
+
+        match pgn {
+            Pgn::REQUEST => match self.data[..] {
+                [a, b, c, ..] => Ok(Message::Request {
+                    pgn: Pgn::from(u32::from_le_bytes([a, b, c, 0])),
+                }),
+                _ => Err(J1939Error::TooShort(pgn)),
+            },
+            Pgn::ACKNOWLEDGEMENT => match self.data[..] {
+                [control, _, _, _, _, a, b, c] => Ok(Message::Acknowledgement {
+                    control: control.into(),
+                    pgn: Pgn::from(u32::from_le_bytes([a, b, c, 0])),
+                }),
+                _ => Err(J1939Error::TooShort(pgn)),
+            },
+            Pgn::ADDRESS_CLAIMED => {
+                let name: [u8; 8] = self.data[..]
+                    .try_into()
+                    .map_err(|_| J1939Error::TooShort(pgn))?;
+                Ok(Message::AddressClaimed {
+                    name: u64::from_le_bytes(name),
+                })
+            }
+            Pgn::TP_CM => TpCm::parse(&self.data).map(Message::TpCm),
+            Pgn::TP_DT => match &self.data[..] {
+                [sequence, data @ ..] => Ok(Message::TpDt {
+                    sequence: *sequence,
+                    data,
+                }),
+                [] => Err(J1939Error::TooShort(pgn)),
+            },
+            _ => Ok(Message::Data(&self.data)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(raw_id: u32, data: &[u8]) -> J1939Frame {
+        J1939Frame::new(J1939Id::from_raw(raw_id), data)
+    }
+
+    #[test]
+    fn request_message() {
+        let frame = frame(0x18EA00F9, &[0xEE, 0xFE, 0x00]);
+        assert_eq!(frame.destination(), Some(0x00));
+        assert_eq!(frame.message(), Ok(Message::Request { pgn: Pgn(65262) }));
+    }
+
+    #[test]
+    fn build_request() {
+        let frame = J1939Frame::request(Pgn(65262), 0x00, 0xF9);
+        assert_eq!(frame.id.to_raw(), 0x18EA00F9);
+        assert_eq!(frame.data, vec![0xEE, 0xFE, 0x00]);
+    }
+
+    #[test]
+    fn nack_message() {
+        let frame = frame(
+            0x18E8FF00,
+            &[0x01, 0xFF, 0xFF, 0xFF, 0xF9, 0xEE, 0xFE, 0x00],
+        );
+        assert_eq!(
+            frame.message(),
+            Ok(Message::Acknowledgement {
+                control: AckControl::Nack,
+                pgn: Pgn(65262)
+            })
+        );
+    }
+
+    #[test]
+    fn bam_message() {
+        let frame = frame(
+            0x18ECFF00,
+            &[0x20, 0x0E, 0x00, 0x02, 0xFF, 0xCA, 0xFE, 0x00],
+        );
+        assert_eq!(
+            frame.message(),
+            Ok(Message::TpCm(TpCm::Bam {
+                size: 14,
+                packets: 2,
+                pgn: Pgn::DM1
+            }))
+        );
+
+        if let Ok(Message::TpCm(cm)) = frame.message() {
+            assert_eq!(&cm.to_bytes()[..], &frame.data[..]);
+        }
+    }
+
+    #[test]
+    fn short_payload() {
+        let frame = frame(0x18EA00F9, &[0xEE]);
+        assert_eq!(frame.message(), Err(J1939Error::TooShort(Pgn::REQUEST)));
+    }
+}
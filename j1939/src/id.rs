@@ -0,0 +1,199 @@
+//
+// id.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use std::fmt;
+
+/// Global destination address
+pub const GLOBAL_ADDRESS: u8 = 0xFF;
+/// Null source address, used by nodes that have not claimed an address
+pub const NULL_ADDRESS: u8 = 0xFE;
+
+/// Parameter group number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pgn(pub u32);
+
+impl Pgn {
+    /// Acknowledgement
+    pub const ACKNOWLEDGEMENT: Pgn = Pgn(59392);
+    /// Request
+    pub const REQUEST: Pgn = Pgn(59904);
+    /// Transport protocol data transfer (TP.DT)
+    pub const TP_DT: Pgn = Pgn(60160);
+    /// Transport protocol connection management (TP.CM)
+    pub const TP_CM: Pgn = Pgn(60416);
+    /// Address claimed
+    pub const ADDRESS_CLAIMED: Pgn = Pgn(60928);
+    /// Active diagnostic trouble codes
+    pub const DM1: Pgn = Pgn(65226);
+    /// Previously active diagnostic trouble codes
+    pub const DM2: Pgn = Pgn(65227);
+
+    pub fn as_raw(self) -> u32 {
+        self.0
+    }
+
+    /// Short name of the network management and diagnostic PGNs known to this crate
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Pgn::ACKNOWLEDGEMENT => Some("ACKM"),
+            Pgn::REQUEST => Some("RQST"),
+            Pgn::TP_DT => Some("TP.DT"),
+            Pgn::TP_CM => Some("TP.CM"),
+            Pgn::ADDRESS_CLAIMED => Some("AC"),
+            Pgn::DM1 => Some("DM1"),
+            Pgn::DM2 => Some("DM2"),
+            _ => None,
+        }
+    }
+
+    /// PDU format field of the PGN
+    pub fn pdu_format(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Check if the PGN is destination specific (PDU1)
+    pub fn is_pdu1(self) -> bool {
+        self.pdu_format() < 240
+    }
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for Pgn {
+    fn from(value: u32) -> Self {
+        Pgn(value & 0x3FFFF)
+    }
+}
+
+/// Interpretation of the PDU specific field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PduFormat {
+    /// Destination specific, PS is the destination address
+    Pdu1 { destination: u8 },
+    /// Broadcast, PS is the group extension
+    Pdu2 { group_extension: u8 },
+}
+
+/// A 29-bit J1939 identifier split into its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct J1939Id {
+    pub priority: u8,
+    pub edp: bool,
+    pub dp: bool,
+    pub pf: u8,
+    pub ps: u8,
+    pub sa: u8,
+}
+
+impl J1939Id {
+    /// Split a raw 29-bit identifier
+    pub fn from_raw(raw: u32) -> Self {
+        J1939Id {
+            priority: ((raw >> 26) & 0x07) as u8,
+            edp: (raw >> 25) & 0x01 != 0,
+            dp: (raw >> 24) & 0x01 != 0,
+            pf: (raw >> 16) as u8,
+            ps: (raw >> 8) as u8,
+            sa: raw as u8,
+        }
+    }
+
+    /// Build an identifier for `pgn`. `destination` is ignored for PDU2 PGNs.
+    pub fn new(priority: u8, pgn: Pgn, destination: u8, sa: u8) -> Self {
+        let mut id = J1939Id::from_raw((pgn.as_raw() << 8) | sa as u32);
+        id.priority = priority & 0x07;
+
+        if pgn.is_pdu1() {
+            id.ps = destination;
+        }
+
+        id
+    }
+
+    pub fn to_raw(&self) -> u32 {
+        ((self.priority as u32 & 0x07) << 26)
+            | ((self.edp as u32) << 25)
+            | ((self.dp as u32) << 24)
+            | ((self.pf as u32) << 16)
+            | ((self.ps as u32) << 8)
+            | self.sa as u32
+    }
+
+    pub fn pdu_format(&self) -> PduFormat {
+        if self.pf < 240 {
+            PduFormat::Pdu1 {
+                destination: self.ps,
+            }
+        } else {
+            PduFormat::Pdu2 {
+                group_extension: self.ps,
+            }
+        }
+    }
+
+    /// Parameter group number. The destination address of PDU1 messages is not part of the PGN.
+    pub fn pgn(&self) -> Pgn {
+        let base = ((self.edp as u32) << 17) | ((self.dp as u32) << 16) | ((self.pf as u32) << 8);
+
+        match self.pdu_format() {
+            PduFormat::Pdu1 { .. } => Pgn(base),
+            PduFormat::Pdu2 { group_extension } => Pgn(base | group_extension as u32),
+        }
+    }
+
+    /// Destination address, `None` for broadcast (PDU2) messages
+    pub fn destination(&self) -> Option<u8> {
+        match self.pdu_format() {
+            PduFormat::Pdu1 { destination } => Some(destination),
+            PduFormat::Pdu2 { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pdu2_fields() {
+        // EEC1 from the engine
+        let id = J1939Id::from_raw(0x0CF00400);
+        assert_eq!(id.priority, 3);
+        assert!(!id.edp);
+        assert!(!id.dp);
+        assert_eq!(id.pf, 0xF0);
+        assert_eq!(id.ps, 0x04);
+        assert_eq!(id.sa, 0x00);
+        assert_eq!(id.pgn(), Pgn(61444));
+        assert_eq!(id.destination(), None);
+        assert_eq!(id.to_raw(), 0x0CF00400);
+    }
+
+    #[test]
+    fn pdu1_fields() {
+        // Request from 0xF9 to the engine
+        let id = J1939Id::from_raw(0x18EA00F9);
+        assert_eq!(id.priority, 6);
+        assert_eq!(id.pgn(), Pgn::REQUEST);
+        assert_eq!(id.pdu_format(), PduFormat::Pdu1 { destination: 0x00 });
+        assert_eq!(id.sa, 0xF9);
+        assert_eq!(id.pgn().name(), Some("RQST"));
+    }
+
+    #[test]
+    fn build_id() {
+        let id = J1939Id::new(6, Pgn::REQUEST, 0x17, 0xF9);
+        assert_eq!(id.to_raw(), 0x18EA17F9);
+
+        let id = J1939Id::new(6, Pgn::DM1, 0x17, 0x00);
+        assert_eq!(id.to_raw(), 0x18FECA00);
+    }
+}
@@ -0,0 +1,19 @@
+//
+// lib.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! SAE J1939 identifier and message parsing
+pub mod database;
+pub mod dm;
+pub mod frame;
+pub mod id;
+pub mod transport;
+
+pub use database::{Database, DatabaseError, PgnDefinition, SpnDefinition, SpnValue};
+pub use dm::{DiagnosticMessage, Dtc, LampStatus, Lamps};
+pub use frame::{AckControl, J1939Error, J1939Frame, Message};
+pub use id::{J1939Id, PduFormat, Pgn};
+pub use transport::{Assembled, Reassembler};
@@ -0,0 +1,321 @@
+//
+// transport.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    frame::{J1939Error, J1939Frame, Message},
+    id::{Pgn, GLOBAL_ADDRESS},
+};
+
+use std::collections::HashMap;
+
+// TP.CM control bytes
+const RTS: u8 = 16;
+const CTS: u8 = 17;
+const END_OF_MSG_ACK: u8 = 19;
+const BAM: u8 = 32;
+const ABORT: u8 = 255;
+
+/// Transport protocol connection management message (TP.CM)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TpCm {
+    /// Request to send
+    Rts {
+        size: u16,
+        packets: u8,
+        max_packets: u8,
+        pgn: Pgn,
+    },
+    /// Clear to send
+    Cts {
+        packets: u8,
+        next_packet: u8,
+        pgn: Pgn,
+    },
+    /// End of message acknowledgement
+    EndOfMsgAck { size: u16, packets: u8, pgn: Pgn },
+    /// Broadcast announce message
+    Bam { size: u16, packets: u8, pgn: Pgn },
+    /// Connection abort
+    Abort { reason: u8, pgn: Pgn },
+}
+
+impl TpCm {
+    pub fn parse(data: &[u8]) -> Result<Self, J1939Error> {
+        if data.len() < 8 {
+            return Err(J1939Error::TooShort(Pgn::TP_CM));
+        }
+
+        let size = u16::from_le_bytes([data[1], data[2]]);
+        let pgn = Pgn::from(u32::from_le_bytes([data[5], data[6], data[7], 0]));
+
+        match data[0] {
+            RTS => Ok(TpCm::Rts {
+                size,
+                packets: data[3],
+                max_packets: data[4],
+                pgn,
+            }),
+            CTS => Ok(TpCm::Cts {
+                packets: data[1],
+                next_packet: data[2],
+                pgn,
+            }),
+            END_OF_MSG_ACK => Ok(TpCm::EndOfMsgAck {
+                size,
+                packets: data[3],
+                pgn,
+            }),
+            BAM => Ok(TpCm::Bam {
+                size,
+                packets: data[3],
+                pgn,
+            }),
+            ABORT => Ok(TpCm::Abort {
+                reason: data[1],
+                pgn,
+            }),
+            control => Err(J1939Error::UnknownControl(control)),
+        }
+    }
+
+    /// PGN of the message being transferred
+    pub fn pgn(&self) -> Pgn {
+        match *self {
+            TpCm::Rts { pgn, .. }
+            | TpCm::Cts { pgn, .. }
+            | TpCm::EndOfMsgAck { pgn, .. }
+            | TpCm::Bam { pgn, .. }
+            | TpCm::Abort { pgn, .. } => pgn,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut buf = [0xFFu8; 8];
+
+        match *self {
+            TpCm::Rts {
+                size,
+                packets,
+                max_packets,
+                ..
+            } => {
+                buf[0] = RTS;
+                buf[1..3].copy_from_slice(&size.to_le_bytes());
+                buf[3] = packets;
+                buf[4] = max_packets;
+            }
+            TpCm::Cts {
+                packets,
+                next_packet,
+                ..
+            } => {
+                buf[0] = CTS;
+                buf[1] = packets;
+                buf[2] = next_packet;
+            }
+            TpCm::EndOfMsgAck { size, packets, .. } => {
+                buf[0] = END_OF_MSG_ACK;
+                buf[1..3].copy_from_slice(&size.to_le_bytes());
+                buf[3] = packets;
+            }
+            TpCm::Bam { size, packets, .. } => {
+                buf[0] = BAM;
+                buf[1..3].copy_from_slice(&size.to_le_bytes());
+                buf[3] = packets;
+            }
+            TpCm::Abort { reason, .. } => {
+                buf[0] = ABORT;
+                buf[1] = reason;
+            }
+        }
+
+        buf[5..8].copy_from_slice(&self.pgn().as_raw().to_le_bytes()[..3]);
+        buf
+    }
+}
+
+/// A complete parameter group, either from a single frame or reassembled from a transport
+/// session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembled {
+    pub pgn: Pgn,
+    pub source: u8,
+    pub destination: Option<u8>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Session {
+    pgn: Pgn,
+    size: usize,
+    packets: u8,
+    next_sequence: u8,
+    data: Vec<u8>,
+}
+
+/// Passive reassembler for BAM and RTS/CTS transport sessions
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    sessions: HashMap<(u8, Option<u8>), Session>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a frame, returning a parameter group once it is complete
+    ///
+    /// Frames that are not part of the transport protocol are returned as is.
+    pub fn process(&mut self, frame: &J1939Frame) -> Option<Assembled> {
+        let source = frame.source_address();
+        let destination = frame.destination().filter(|&da| da != GLOBAL_ADDRESS);
+
+        match frame.message() {
+            Ok(Message::TpCm(cm)) => {
+                self.connection_management(cm, source, destination);
+                None
+            }
+            Ok(Message::TpDt { sequence, data }) => {
+                self.data_transfer(source, destination, sequence, data)
+            }
+            Ok(_) => Some(Assembled {
+                pgn: frame.pgn(),
+                source,
+                destination,
+                data: frame.data.clone(),
+            }),
+            Err(_) => None,
+        }
+    }
+
+    fn connection_management(&mut self, cm: TpCm, source: u8, destination: Option<u8>) {
+        match cm {
+            TpCm::Bam { size, packets, pgn }
+            | TpCm::Rts {
+                size, packets, pgn, ..
+            } => {
+                // A new announcement replaces any unfinished session
+                self.sessions.insert(
+                    (source, destination),
+                    Session {
+                        pgn,
+                        size: size as usize,
+                        packets,
+                        next_sequence: 1,
+                        data: Vec::with_capacity(size as usize),
+                    },
+                );
+            }
+            TpCm::Abort { .. } => {
+                // Either side of a connection can abort
+                self.sessions.remove(&(source, destination));
+                if let Some(destination) = destination {
+                    self.sessions.remove(&(destination, Some(source)));
+                }
+            }
+            TpCm::Cts { .. } | TpCm::EndOfMsgAck { .. } => {}
+        }
+    }
+
+    fn data_transfer(
+        &mut self,
+        source: u8,
+        destination: Option<u8>,
+        sequence: u8,
+        data: &[u8],
+    ) -> Option<Assembled> {
+        let key = (source, destination);
+        let session = self.sessions.get_mut(&key)?;
+
+        if sequence != session.next_sequence {
+            // Lost or out of order packet, the message cannot be recovered
+            self.sessions.remove(&key);
+            return None;
+        }
+
+        session.data.extend_from_slice(data);
+        session.next_sequence = session.next_sequence.wrapping_add(1);
+
+        if sequence < session.packets {
+            return None;
+        }
+
+        let mut session = self.sessions.remove(&key)?;
+        if session.data.len() < session.size {
+            return None;
+        }
+        session.data.truncate(session.size);
+
+        Some(Assembled {
+            pgn: session.pgn,
+            source,
+            destination,
+            data: session.data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::J1939Id;
+
+    fn frame(raw_id: u32, data: &[u8]) -> J1939Frame {
+        J1939Frame::new(J1939Id::from_raw(raw_id), data)
+    }
+
+    #[test]
+    fn bam_reassembly() {
+        let mut tp = Reassembler::new();
+
+        let bam = frame(
+            0x18ECFF00,
+            &[0x20, 0x0A, 0x00, 0x02, 0xFF, 0xCA, 0xFE, 0x00],
+        );
+        assert_eq!(tp.process(&bam), None);
+
+        let dt1 = frame(0x18EBFF00, &[1, 0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(tp.process(&dt1), None);
+
+        let dt2 = frame(0x18EBFF00, &[2, 7, 8, 9, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(
+            tp.process(&dt2),
+            Some(Assembled {
+                pgn: Pgn::DM1,
+                source: 0x00,
+                destination: None,
+                data: vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+            })
+        );
+    }
+
+    #[test]
+    fn out_of_sequence_drops_session() {
+        let mut tp = Reassembler::new();
+
+        tp.process(&frame(
+            0x18ECFF00,
+            &[0x20, 0x0A, 0x00, 0x02, 0xFF, 0xCA, 0xFE, 0x00],
+        ));
+        assert_eq!(
+            tp.process(&frame(0x18EBFF00, &[2, 0, 0, 0, 0, 0, 0, 0])),
+            None
+        );
+        assert_eq!(
+            tp.process(&frame(0x18EBFF00, &[1, 0, 0, 0, 0, 0, 0, 0])),
+            None
+        );
+    }
+
+    #[test]
+    fn single_frame_passthrough() {
+        let mut tp = Reassembler::new();
+        let assembled = tp.process(&frame(0x0CF00400, &[1, 2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(assembled.map(|a| a.pgn), Some(Pgn(61444)));
+    }
+}
@@ -0,0 +1,104 @@
+//
+// alert.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    busstate::{BusState, BusStateTracker, Transition},
+    frame::CanFrame,
+};
+
+use clap::Args;
+
+use std::process::{Command, Stdio};
+
+/// Bus state alerting options shared by the receiving commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct AlertArgs {
+    /// Run a shell command when the controller error state changes, e.g. to bus-off. The command
+    /// gets ICAN_INTERFACE, ICAN_BUS_STATE and ICAN_PREVIOUS_STATE in its environment.
+    #[arg(long = "alert-cmd")]
+    pub alert_cmd: Option<String>,
+    /// Show a desktop notification when the controller error state changes, using notify-send
+    #[arg(long = "notify")]
+    pub notify: bool,
+}
+
+impl AlertArgs {
+    pub fn enabled(&self) -> bool {
+        self.alert_cmd.is_some() || self.notify
+    }
+
+    /// Run the hooks for a transition, without waiting for them
+    ///
+    /// The hooks' output is discarded, so they cannot garble a terminal UI.
+    pub fn alert(&self, interface: &str, transition: Transition) {
+        if let Some(cmd) = &self.alert_cmd {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(cmd)
+                .env("ICAN_INTERFACE", interface)
+                .env("ICAN_BUS_STATE", transition.to.to_string())
+                .env("ICAN_PREVIOUS_STATE", transition.from.to_string());
+            spawn(command);
+        }
+
+        if self.notify {
+            let urgency = match transition.to {
+                BusState::BusOff | BusState::ErrorPassive => "critical",
+                _ => "normal",
+            };
+            let mut command = Command::new("notify-send");
+            command
+                .arg("--urgency")
+                .arg(urgency)
+                .arg(format!("ican: {} is {}", interface, transition.to))
+                .arg(format!("Controller state changed {}", transition));
+            spawn(command);
+        }
+    }
+}
+
+/// Follows the bus state through error frames, running the alert hooks on every transition
+pub struct BusAlerts {
+    args: AlertArgs,
+    interface: String,
+    tracker: BusStateTracker,
+}
+
+impl BusAlerts {
+    pub fn new(args: AlertArgs, interface: String) -> Self {
+        BusAlerts {
+            args,
+            interface,
+            tracker: BusStateTracker::default(),
+        }
+    }
+
+    pub fn tracker(&self) -> &BusStateTracker {
+        &self.tracker
+    }
+
+    pub fn update(&mut self, frame: &CanFrame) -> Option<Transition> {
+        let transition = self.tracker.update(frame)?;
+        self.args.alert(&self.interface, transition);
+        Some(transition)
+    }
+}
+
+/// Start a hook, reaping it from a thread once it exits
+pub(crate) fn spawn(mut command: Command) {
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    // A missing notify-send or failing hook must not stop receiving
+    if let Ok(mut child) = child {
+        std::thread::spawn(move || child.wait());
+    }
+}
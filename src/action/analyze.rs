@@ -0,0 +1,150 @@
+//
+// analyze.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    action::replay::InputArgs,
+    analysis::{ByteKind, PayloadAnalyzer},
+    logfile, utils, CommandContext,
+};
+
+use clap::Parser;
+use embedded_can::Frame;
+use tokio::time::Duration;
+
+use std::{fmt::Write as _, path::PathBuf};
+
+/// Arguments for the analyze command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Log file to analyze (BLF, TRC, candump or CSV), instead of receiving from the interface
+    pub log: Option<PathBuf>,
+    /// Seconds to receive for before reporting, until Ctrl-C if not set
+    #[arg(long = "duration")]
+    pub duration: Option<f64>,
+    /// Write a starter DBC of the analyzed IDs to this file
+    #[arg(long = "emit-dbc")]
+    pub emit_dbc: Option<PathBuf>,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+}
+
+/// Analyze a log file, no interface required
+pub fn run_log(args: Args) -> anyhow::Result<()> {
+    let Some(path) = &args.log else {
+        anyhow::bail!("A log file is required without an interface");
+    };
+
+    let mut analyzer = PayloadAnalyzer::new();
+    for frame in logfile::open_with(path, &args.input_args.options())? {
+        let frame = frame?;
+        if !frame.error && !frame.remote {
+            analyzer.update(frame.id, &frame.data);
+        }
+    }
+
+    finish(&analyzer, &args)
+}
+
+/// Analyze the frames received on the interface
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let mut driver = ctx.driver;
+    let mut analyzer = PayloadAnalyzer::new();
+
+    let receive = async {
+        let mut frames = vec![];
+        while driver.recv_many(&mut frames).await > 0 {
+            for frame in frames.drain(..) {
+                if !frame.is_error_frame() && !frame.is_remote_frame() {
+                    analyzer.update(frame.id(), frame.data());
+                }
+            }
+        }
+    };
+    let stop = async {
+        match args.duration {
+            Some(secs) => tokio::time::sleep(Duration::from_secs_f64(secs)).await,
+            None => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    };
+    tokio::select! {
+        _ = receive => {},
+        _ = stop => {},
+    }
+
+    finish(&analyzer, &args)
+}
+
+/// Print the report and write the DBC skeleton if requested
+fn finish(analyzer: &PayloadAnalyzer, args: &Args) -> anyhow::Result<()> {
+    print!("{}", report(analyzer));
+    if let Some(path) = &args.emit_dbc {
+        std::fs::write(path, analyzer.dbc_skeleton())?;
+        println!("DBC skeleton written to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Byte classifications and signal candidates of every ID
+fn report(analyzer: &PayloadAnalyzer) -> String {
+    let mut out = String::new();
+
+    for (id, stats) in analyzer.ids() {
+        let _ = writeln!(out, "{}  {} frames", utils::format_id(&id), stats.frames());
+
+        let Some(kinds) = stats.classify() else {
+            let _ = writeln!(out, "  too few frames to classify");
+            continue;
+        };
+        for (i, kind) in kinds.iter().enumerate() {
+            let _ = writeln!(out, "  byte {}  {}", i, kind);
+        }
+
+        let candidates = stats.signal_candidates();
+        if !candidates.is_empty() {
+            let candidates = candidates
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = writeln!(out, "  candidate signals: {}", candidates);
+        }
+        if kinds
+            .iter()
+            .all(|kind| matches!(kind, ByteKind::Constant(_)))
+        {
+            let _ = writeln!(out, "  payload never changed");
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{Id, StandardId};
+
+    #[test]
+    fn report_format() {
+        let mut analyzer = PayloadAnalyzer::new();
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        for i in 0..16u8 {
+            analyzer.update(id, &[i, 0x00]);
+        }
+        let id = Id::Standard(StandardId::new(0x200).unwrap());
+        analyzer.update(id, &[1]);
+
+        assert_eq!(
+            report(&analyzer),
+            "123  16 frames\n  byte 0  counter\n  byte 1  constant 00\n\
+             200  1 frames\n  too few frames to classify\n"
+        );
+    }
+}
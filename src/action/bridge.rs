@@ -5,9 +5,27 @@
 // @date Oct 01 2023
 //
 
-use crate::{drivers::AsyncCanDriverPtr, CommandContext, DriverOpts};
+use crate::{
+    action::filter::FilterArgs, config::IdFilter, dedup::Deduplicator, drivers::CanDriver,
+    frame::CanFrame, supervisor::Supervisor, utils, CommandContext, DriverOpts,
+};
+
+#[cfg(feature = "script")]
+use crate::action::script::FrameScript;
 
 use clap::Parser;
+use embedded_can::Frame;
+use tokio::{sync::Notify, time::Duration};
+
+use std::{
+    collections::VecDeque,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
 /// Arguments for the bridge command
 #[derive(Debug, Parser)]
@@ -15,26 +33,295 @@ pub struct Args {
     /// The CAN interface to bridge to
     #[arg(value_enum, value_parser = clap::value_parser!(DriverOpts))]
     pub interface: DriverOpts,
+    /// Number of frames buffered for a slow destination
+    #[arg(long = "queue-size", default_value = "1024")]
+    pub queue_size: usize,
+    /// What to do when the queue is full: block receiving, drop-oldest or drop-newest frames
+    #[arg(long = "policy", default_value = "block")]
+    pub policy: QueuePolicy,
+    /// Seconds between reports of forwarded and dropped frames, 0 to only report on exit
+    #[arg(long = "report", default_value = "10")]
+    pub report: u64,
+    #[command(flatten)]
+    pub ids: FilterArgs,
+    /// Do not forward frames identical to one received within this window, e.g. 5ms, so frames
+    /// echoed back by a gateway are not bridged again. Keep it below the period of the fastest ID.
+    #[arg(long = "dedup", value_parser = utils::parse_duration)]
+    pub dedup: Option<Duration>,
+    /// Rhai script whose on_frame(frame) rewrites or drops every forwarded frame
+    #[cfg(feature = "script")]
+    #[arg(long = "script")]
+    pub script: Option<std::path::PathBuf>,
+}
+
+/// Handling of frames received while the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Wait for the destination, leaving frames in the source's receive buffer
+    Block,
+    /// Drop the oldest queued frame
+    DropOldest,
+    /// Drop the received frame
+    DropNewest,
+}
+
+impl FromStr for QueuePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(QueuePolicy::Block),
+            "drop-oldest" => Ok(QueuePolicy::DropOldest),
+            "drop-newest" => Ok(QueuePolicy::DropNewest),
+            _ => Err(format!("unknown queue policy '{}'", s)),
+        }
+    }
+}
+
+/// Bounded queue between the source and the destination
+struct FrameQueue {
+    frames: Mutex<VecDeque<CanFrame>>,
+    capacity: usize,
+    policy: QueuePolicy,
+    readable: Notify,
+    writable: Notify,
+    closed: AtomicBool,
+    forwarded: AtomicU64,
+    dropped: AtomicU64,
+    duplicates: AtomicU64,
+}
+
+impl FrameQueue {
+    fn new(capacity: usize, policy: QueuePolicy) -> Self {
+        FrameQueue {
+            frames: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: capacity.max(1),
+            policy,
+            readable: Notify::new(),
+            writable: Notify::new(),
+            closed: AtomicBool::new(false),
+            forwarded: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            duplicates: AtomicU64::new(0),
+        }
+    }
+
+    /// Queue a frame, applying the policy when full
+    async fn push(&self, frame: CanFrame) {
+        loop {
+            {
+                let mut frames = self.frames.lock().unwrap();
+                if frames.len() >= self.capacity {
+                    match self.policy {
+                        QueuePolicy::Block => {}
+                        QueuePolicy::DropOldest => {
+                            frames.pop_front();
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        QueuePolicy::DropNewest => {
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                }
+
+                if frames.len() < self.capacity {
+                    frames.push_back(frame);
+                    self.readable.notify_one();
+                    return;
+                }
+            }
+
+            self.writable.notified().await;
+        }
+    }
+
+    /// Take the queued frames, waiting for at least one. Returns `false` once closed and empty.
+    async fn pop_many(&self, buf: &mut Vec<CanFrame>) -> bool {
+        loop {
+            {
+                let mut frames = self.frames.lock().unwrap();
+                if !frames.is_empty() {
+                    buf.extend(frames.drain(..));
+                    self.writable.notify_one();
+                    return true;
+                }
+                if self.closed.load(Ordering::Relaxed) {
+                    return false;
+                }
+            }
+
+            self.readable.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.readable.notify_one();
+    }
+
+    fn report(&self) -> String {
+        let mut report = format!(
+            "Forwarded {} frames, dropped {}, {} queued",
+            self.forwarded.load(Ordering::Relaxed),
+            self.dropped.load(Ordering::Relaxed),
+            self.frames.lock().unwrap().len()
+        );
+        let duplicates = self.duplicates.load(Ordering::Relaxed);
+        if duplicates > 0 {
+            report += &format!(", {} duplicates suppressed", duplicates);
+        }
+        report
+    }
 }
 
 pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
-    let from_driver: AsyncCanDriverPtr = ctx.driver;
-    let to_driver: AsyncCanDriverPtr = args.interface.try_into()?;
+    let from_driver = ctx.driver;
+    let to_interface = args.interface.to_string();
+    let to_driver = ctx.tx.wrap(args.interface.try_into()?, &to_interface);
+
+    #[cfg(feature = "script")]
+    let script = args.script.as_deref().map(FrameScript::load).transpose()?;
+
+    let ids = args.ids.resolve(&ctx.config)?;
+    let dedup = args.dedup.map(Deduplicator::new);
+    let queue = Arc::new(FrameQueue::new(args.queue_size, args.policy));
 
-    tokio::spawn(bridge_task(from_driver, to_driver));
+    let mut supervisor = Supervisor::new();
+    supervisor.spawn(receive_task(from_driver, ids, dedup, queue.clone()));
+    // Sending ends once the source closes and the queue is drained
+    supervisor.spawn_main(transmit_task(
+        to_driver,
+        queue.clone(),
+        #[cfg(feature = "script")]
+        script,
+    ));
+    if args.report > 0 {
+        supervisor.spawn(report_task(queue.clone(), args.report));
+    }
+    let res = supervisor.run().await;
+
+    eprintln!("{}", queue.report());
 
-    tokio::signal::ctrl_c().await?;
+    res
+}
+
+async fn receive_task(
+    mut from_driver: CanDriver,
+    ids: Option<IdFilter>,
+    mut dedup: Option<Deduplicator>,
+    queue: Arc<FrameQueue>,
+) -> anyhow::Result<()> {
+    let mut frames = vec![];
+    while from_driver.recv_many(&mut frames).await > 0 {
+        let now = Instant::now();
+        for frame in frames.drain(..) {
+            if ids.as_ref().is_some_and(|ids| !ids.matches(frame.id())) {
+                continue;
+            }
+            if dedup
+                .as_mut()
+                .is_some_and(|dedup| dedup.is_duplicate(&frame, now))
+            {
+                queue.duplicates.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            queue.push(frame).await;
+        }
+    }
+    queue.close();
 
     Ok(())
 }
 
-async fn bridge_task(
-    mut from_driver: AsyncCanDriverPtr,
-    mut to_driver: AsyncCanDriverPtr,
+async fn transmit_task(
+    mut to_driver: CanDriver,
+    queue: Arc<FrameQueue>,
+    #[cfg(feature = "script")] mut script: Option<FrameScript>,
 ) -> anyhow::Result<()> {
-    while let Some(frame) = from_driver.recv().await {
-        to_driver.send(frame).await;
+    let mut frames = vec![];
+    while queue.pop_many(&mut frames).await {
+        for frame in frames.drain(..) {
+            #[cfg(feature = "script")]
+            let frame = match script.as_mut() {
+                Some(script) => match script.apply(&frame)? {
+                    Some(frame) => frame,
+                    None => {
+                        queue.dropped.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                },
+                None => frame,
+            };
+
+            to_driver.send(frame).await;
+            queue.forwarded.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     Ok(())
 }
+
+async fn report_task(queue: Arc<FrameQueue>, period: u64) -> anyhow::Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(period));
+    // The first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        eprintln!("{}", queue.report());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{Frame, StandardId};
+
+    fn frame(data: u8) -> CanFrame {
+        CanFrame::new(StandardId::new(0x100).unwrap(), &[data]).unwrap()
+    }
+
+    async fn queued(policy: QueuePolicy) -> (Vec<u8>, u64) {
+        let queue = FrameQueue::new(2, policy);
+        for i in 0..4 {
+            if policy == QueuePolicy::Block && i >= 2 {
+                break;
+            }
+            queue.push(frame(i)).await;
+        }
+        queue.close();
+
+        let mut frames = vec![];
+        while queue.pop_many(&mut frames).await {}
+
+        let data = frames.iter().map(|f| f.data()[0]).collect();
+        (data, queue.dropped.load(Ordering::Relaxed))
+    }
+
+    #[tokio::test]
+    async fn policies() {
+        assert_eq!(queued(QueuePolicy::DropOldest).await, (vec![2, 3], 2));
+        assert_eq!(queued(QueuePolicy::DropNewest).await, (vec![0, 1], 2));
+        assert_eq!(queued(QueuePolicy::Block).await, (vec![0, 1], 0));
+    }
+
+    #[tokio::test]
+    async fn block_until_sent() {
+        let queue = Arc::new(FrameQueue::new(1, QueuePolicy::Block));
+        queue.push(frame(0)).await;
+
+        // The second push waits for the queue to be emptied
+        let pusher = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push(frame(1)).await }
+        });
+
+        let mut frames = vec![];
+        assert!(queue.pop_many(&mut frames).await);
+        pusher.await.unwrap();
+        assert!(queue.pop_many(&mut frames).await);
+        assert_eq!(frames.len(), 2);
+    }
+}
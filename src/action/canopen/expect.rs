@@ -0,0 +1,139 @@
+//
+// expect.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Wait for a node to report a state in its heartbeat, for gating hardware in the loop stages
+//!
+//! ```text
+//! ican can0 canopen expect --node 5 --state operational --within 10s && ./run-tests
+//! ```
+
+use crate::{frame::CanFrame, utils::parse_duration, CommandContext};
+
+use canopen_node::{heartbeat::HEARTBEAT_COB_ID, nmt::NmtState};
+use clap::Parser;
+use embedded_can::{Frame, Id};
+use tokio::time::{timeout_at, Instant};
+
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Node ID to watch, 1 to 127
+    #[arg(long = "node", value_parser = clap::value_parser!(u8).range(1..=127))]
+    node: u8,
+    /// State expected in the heartbeat: operational, pre-operational, stopped or initializing
+    #[arg(long = "state", value_parser = parse_state)]
+    state: NmtState,
+    /// Time allowed for the state to be reported, e.g. 10s or 500ms
+    #[arg(long = "within", value_parser = parse_duration, default_value = "10s")]
+    within: Duration,
+}
+
+/// Exit successfully once the node's heartbeat reports the state, fail at the deadline
+pub async fn run(args: Args, ctx: CommandContext) -> anyhow::Result<()> {
+    let mut driver = ctx.driver;
+    let start = Instant::now();
+    let mut last = None;
+
+    let wait = async {
+        let mut frames = vec![];
+        while driver.recv_many(&mut frames).await > 0 {
+            for frame in frames.drain(..) {
+                let Some(state) = heartbeat(&frame, args.node) else {
+                    continue;
+                };
+                if state == args.state {
+                    return true;
+                }
+                last = Some(state);
+            }
+        }
+        false
+    };
+
+    if let Ok(true) = timeout_at(start + args.within, wait).await {
+        println!(
+            "Node {} is {} after {:.3}s",
+            args.node,
+            state_name(args.state),
+            start.elapsed().as_secs_f64()
+        );
+        return Ok(());
+    }
+
+    match last {
+        Some(state) => anyhow::bail!(
+            "Node {} not {} within {:?}, last heartbeat reported {}",
+            args.node,
+            state_name(args.state),
+            args.within,
+            state_name(state)
+        ),
+        None => anyhow::bail!(
+            "Node {} not {} within {:?}, no heartbeat received",
+            args.node,
+            state_name(args.state),
+            args.within
+        ),
+    }
+}
+
+/// State reported by a heartbeat or boot-up frame of the node
+fn heartbeat(frame: &CanFrame, node: u8) -> Option<NmtState> {
+    let cob_id = HEARTBEAT_COB_ID + node as u16;
+    match (frame.id(), frame.data()) {
+        (Id::Standard(id), [state, ..]) if id.as_raw() == cob_id && !frame.is_remote_frame() => {
+            NmtState::try_from(*state).ok()
+        }
+        _ => None,
+    }
+}
+
+fn state_name(state: NmtState) -> &'static str {
+    match state {
+        NmtState::Initializing => "initializing",
+        NmtState::PreOperational => "pre-operational",
+        NmtState::Operational => "operational",
+        NmtState::Stopped => "stopped",
+    }
+}
+
+fn parse_state(s: &str) -> Result<NmtState, String> {
+    match s {
+        "initializing" | "boot-up" => Ok(NmtState::Initializing),
+        "pre-operational" => Ok(NmtState::PreOperational),
+        "operational" => Ok(NmtState::Operational),
+        "stopped" => Ok(NmtState::Stopped),
+        _ => Err(format!("unknown NMT state '{}'", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn frame(id: u16, data: &[u8]) -> CanFrame {
+        CanFrame::new(StandardId::new(id).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn node_heartbeats() {
+        assert_eq!(
+            heartbeat(&frame(0x705, &[0x05]), 5),
+            Some(NmtState::Operational)
+        );
+        assert_eq!(
+            heartbeat(&frame(0x705, &[0x00]), 5),
+            Some(NmtState::Initializing)
+        );
+        assert_eq!(heartbeat(&frame(0x706, &[0x05]), 5), None);
+        assert_eq!(heartbeat(&frame(0x705, &[]), 5), None);
+        assert_eq!(heartbeat(&frame(0x705, &[0x42]), 5), None);
+    }
+}
@@ -5,7 +5,9 @@
 // @date Aug 17 2022
 //
 
-pub mod monitor;
+pub mod expect;
+// The monitor needs canopen-eds and canopen-client
+// pub mod monitor;
 
 use clap::Subcommand;
 
@@ -14,12 +16,24 @@ use crate::CommandContext;
 /// CANopen subcommands
 #[derive(Debug, Subcommand)]
 pub enum CanOpenCommands {
-    /// Monitor CAN traffic and decode CANopen data
-    Monitor(monitor::Args),
+    /// Wait for a node's heartbeat to report a state, failing if it does not in time
+    Expect(expect::Args),
+    // /// Monitor CAN traffic and decode CANopen data
+    // Monitor(monitor::Args),
+}
+
+impl CanOpenCommands {
+    /// Whether the subcommand sends frames on the bus
+    pub fn transmits(&self) -> bool {
+        match self {
+            CanOpenCommands::Expect(_) => false,
+        }
+    }
 }
 
 pub async fn run(cmd: CanOpenCommands, ctx: CommandContext) -> anyhow::Result<()> {
     match cmd {
-        CanOpenCommands::Monitor(args) => Ok(monitor::run(args, ctx).await?),
+        CanOpenCommands::Expect(args) => Ok(expect::run(args, ctx).await?),
+        // CanOpenCommands::Monitor(args) => Ok(monitor::run(args, ctx).await?),
     }
 }
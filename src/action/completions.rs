@@ -0,0 +1,134 @@
+//
+// completions.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::cli::Args as CliArgs;
+
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Network device type of CAN interfaces, ARPHRD_CAN
+const ARPHRD_CAN: &str = "280";
+
+/// Arguments for the completions command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let mut out = io::stdout().lock();
+    write_completions(args.shell, &mut out)?;
+
+    Ok(())
+}
+
+/// Print the CAN interfaces, one per line, for the completion scripts
+pub fn list_interfaces() -> anyhow::Result<()> {
+    for interface in can_interfaces(Path::new("/sys/class/net")) {
+        println!("{}", interface);
+    }
+    #[cfg(feature = "gsusb")]
+    for adapter in crate::drivers::gsusb::list() {
+        println!("gsusb://{}", adapter);
+    }
+
+    Ok(())
+}
+
+/// Write the generated script, hooked to complete interface names as the first argument where
+/// the shell allows
+fn write_completions(shell: Shell, out: &mut impl Write) -> io::Result<()> {
+    clap_complete::generate(shell, &mut CliArgs::command(), "ican", out);
+
+    let hook = match shell {
+        Shell::Bash => BASH_INTERFACES,
+        Shell::Zsh => ZSH_INTERFACES,
+        Shell::Fish => FISH_INTERFACES,
+        _ => return Ok(()),
+    };
+    writeln!(out)?;
+    out.write_all(hook.as_bytes())
+}
+
+const BASH_INTERFACES: &str = r#"_ican_interfaces() {
+    _ican "$@"
+    if [[ ${COMP_CWORD} -eq 1 ]]; then
+        COMPREPLY+=( $(compgen -W "$(ican list-interfaces 2>/dev/null)" -- "${COMP_WORDS[COMP_CWORD]}") )
+    fi
+}
+complete -F _ican_interfaces -o nosort -o bashdefault -o default ican
+"#;
+
+const ZSH_INTERFACES: &str = r#"_ican_interfaces() {
+    if (( CURRENT == 2 )); then
+        local -a interfaces
+        interfaces=(${(f)"$(ican list-interfaces 2>/dev/null)"})
+        _describe -t interfaces 'CAN interface' interfaces
+    fi
+    _ican "$@"
+}
+compdef _ican_interfaces ican
+"#;
+
+const FISH_INTERFACES: &str = r#"complete -c ican -n "__fish_use_subcommand" -f -a "(ican list-interfaces 2>/dev/null)" -d "CAN interface"
+"#;
+
+/// Names of the CAN interfaces under a sysfs network class directory
+fn can_interfaces(net: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(net) else {
+        return vec![];
+    };
+
+    let mut interfaces = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            fs::read_to_string(entry.path().join("type"))
+                .is_ok_and(|kind| kind.trim() == ARPHRD_CAN)
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    interfaces.sort();
+
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interfaces() {
+        let net = std::env::temp_dir().join(format!("ican-net-{}", std::process::id()));
+        for (name, kind) in [("vcan1", "280"), ("eth0", "1"), ("can0", "280\n")] {
+            fs::create_dir_all(net.join(name)).unwrap();
+            fs::write(net.join(name).join("type"), kind).unwrap();
+        }
+
+        let interfaces = can_interfaces(&net);
+        fs::remove_dir_all(&net).unwrap();
+
+        assert_eq!(interfaces, vec!["can0", "vcan1"]);
+        assert!(can_interfaces(&net).is_empty());
+    }
+
+    #[test]
+    fn hooked_scripts() {
+        let mut script = vec![];
+        write_completions(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("convert"));
+        assert!(script.contains("ican list-interfaces"));
+    }
+}
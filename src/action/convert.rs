@@ -0,0 +1,66 @@
+//
+// convert.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    action::replay::InputArgs,
+    logfile::{self, trc::TrcVersion, WriteOptions, WriteReport},
+};
+
+use candecode::Database;
+use clap::Parser;
+
+use std::path::PathBuf;
+
+/// Arguments for the convert command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Log file to read (BLF, TRC, candump or CSV)
+    input: PathBuf,
+    /// Log file to write (TRC, MF4, candump or Parquet)
+    output: PathBuf,
+    /// File version of written TRC files, 1.0 to 2.1
+    #[arg(long = "trc-version", default_value = "2.1")]
+    trc_version: TrcVersion,
+    /// Signal database (DBC or KCD) used to add decoded signal channels to MF4 files, or to export
+    /// decoded signals instead of frames to Parquet files
+    #[arg(long = "dbc")]
+    dbc: Option<PathBuf>,
+    #[command(flatten)]
+    input_args: InputArgs,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    let mut reader = logfile::open_with(&args.input, &args.input_args.options())?;
+
+    // Some formats only know their start time once the first frame has been read
+    let first = reader.next().transpose()?;
+    let mut options = WriteOptions {
+        trc_version: args.trc_version,
+        database: args.dbc.as_ref().map(Database::load).transpose()?,
+        ..Default::default()
+    };
+    if let Some(start_time) = reader.start_time() {
+        options.start_time = start_time;
+    }
+    let mut writer = logfile::create(&args.output, &options)?;
+
+    let mut report = WriteReport::default();
+    for frame in first.into_iter().map(Ok).chain(reader) {
+        report.write(writer.as_mut(), &frame?)?;
+    }
+    writer.finish()?;
+
+    println!(
+        "Converted {} frames from {} to {}",
+        report.written,
+        args.input.display(),
+        args.output.display()
+    );
+    eprint!("{}", report);
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//
+// dbc.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use candecode::{ByteOrder, Database, Severity, ValueType};
+
+use clap::{Parser, Subcommand};
+use thiserror::Error;
+
+use std::path::PathBuf;
+
+#[derive(Debug, Error)]
+pub enum DbcCheckError {
+    #[error("Database check failed with {0} error(s)")]
+    Failed(usize),
+}
+
+/// Signal database subcommands
+#[derive(Debug, Subcommand)]
+pub enum DbcCommands {
+    /// Validate a database for layout errors and broken references
+    Check(CheckArgs),
+    /// Summarize the nodes and messages of a database
+    Info(InfoArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct CheckArgs {
+    /// Signal database (DBC or KCD)
+    file: PathBuf,
+    /// Treat warnings as errors
+    #[arg(long = "strict")]
+    strict: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct InfoArgs {
+    /// Signal database (DBC or KCD)
+    file: PathBuf,
+    /// List the signals of each message
+    #[arg(short = 's', long = "signals")]
+    signals: bool,
+}
+
+pub fn run(cmd: DbcCommands) -> anyhow::Result<()> {
+    match cmd {
+        DbcCommands::Check(args) => check(args),
+        DbcCommands::Info(args) => info(args),
+    }
+}
+
+fn check(args: CheckArgs) -> anyhow::Result<()> {
+    let db = Database::load(&args.file)?;
+    let issues = candecode::check::check(&db);
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    println!(
+        "{}: {} message(s), {} error(s), {} warning(s)",
+        args.file.display(),
+        db.messages().len(),
+        errors,
+        warnings
+    );
+
+    let failures = if args.strict { issues.len() } else { errors };
+    if failures > 0 {
+        return Err(DbcCheckError::Failed(failures).into());
+    }
+
+    Ok(())
+}
+
+fn info(args: InfoArgs) -> anyhow::Result<()> {
+    let db = Database::load(&args.file)?;
+
+    if !db.version.is_empty() {
+        println!("Version:      {}", db.version);
+    }
+    if let Some(comment) = &db.comment {
+        println!("Comment:      {}", comment);
+    }
+
+    let nodes = db
+        .nodes()
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    println!("Nodes:        {} ({})", nodes.len(), nodes.join(", "));
+    println!("Messages:     {}", db.messages().len());
+    println!(
+        "Signals:      {}",
+        db.messages().iter().map(|m| m.signals.len()).sum::<usize>()
+    );
+    println!("Value tables: {}", db.value_tables.len());
+
+    if db.messages().is_empty() {
+        return Ok(());
+    }
+
+    let name_width = db
+        .messages()
+        .iter()
+        .map(|m| m.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    println!();
+    println!(
+        "{:<10}  {:<name_width$}  {:>3}  {:<12}  Signals",
+        "ID", "Name", "DLC", "Transmitter"
+    );
+
+    for message in db.messages() {
+        let id = match message.id {
+            embedded_can::Id::Standard(id) => format!("0x{:03X}", id.as_raw()),
+            embedded_can::Id::Extended(id) => format!("0x{:08X}", id.as_raw()),
+        };
+
+        println!(
+            "{:<10}  {:<name_width$}  {:>3}  {:<12}  {}",
+            id,
+            message.name,
+            message.dlc,
+            message.transmitter.as_deref().unwrap_or("-"),
+            message.signals.len()
+        );
+
+        if args.signals {
+            for signal in &message.signals {
+                let byte_order = match signal.byte_order {
+                    ByteOrder::LittleEndian => 1,
+                    ByteOrder::BigEndian => 0,
+                };
+                let sign = match signal.value_type {
+                    ValueType::Unsigned => '+',
+                    ValueType::Signed => '-',
+                };
+
+                println!(
+                    "    {} : {}|{}@{}{} ({},{}) [{}|{}] \"{}\"",
+                    signal.name,
+                    signal.start_bit,
+                    signal.length,
+                    byte_order,
+                    sign,
+                    signal.factor,
+                    signal.offset,
+                    signal.min,
+                    signal.max,
+                    signal.unit
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -5,24 +5,364 @@
 // @date Jul 31 2022
 //
 use crate::{
-    drivers::AsyncCanDriverPtr,
-    format::{CanFrameFormatter, DataFormatMode},
+    action::{
+        alert::{AlertArgs, BusAlerts},
+        filter::FilterArgs,
+        j1939::{annotate::Annotator, decode_spns},
+        replay::FileSourceArgs,
+        trigger::{TriggerArgs, Triggers},
+        uds::annotate::{self as isotp, parse_pair, IsoTpPair},
+    },
+    config::IdFilter,
+    drivers::CanDriver,
+    format::{write_signals, CanFrameFormatter, DataFormatMode},
+    logfile::{self, LogFrame, LogWriter, WriteOptions},
+    names::IdNames,
+    timesync::SyncHandle,
     CommandContext,
 };
 
-pub async fn run(ctx: CommandContext) -> anyhow::Result<()> {
-    tokio::spawn(dump_task(ctx.driver));
+use candecode::{Database as SignalDatabase, Expr};
+use clap::Parser;
+use embedded_can::Frame;
+use j1939::Database;
+use tokio::{
+    sync::mpsc::{channel, error::TrySendError, Receiver, Sender},
+    task::JoinHandle,
+};
+
+use std::{
+    fmt::{self, Write as _},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// Number of line batches queued for the output thread
+const OUTPUT_QUEUE_SIZE: usize = 256;
+/// Size of the stdout buffer
+const OUTPUT_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Arguments for the dump command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Signal database (DBC or KCD) used to decode frames
+    #[arg(long = "dbc")]
+    dbc: Option<PathBuf>,
+    /// Show the raw value of enumerated signals next to their label
+    #[arg(long = "raw-values")]
+    raw_values: bool,
+    /// ID names and descriptions (TOML or CSV), shown for frames the `--dbc` database does not
+    /// decode
+    #[arg(long = "names")]
+    names: Option<PathBuf>,
+    /// Only show frames whose signals match the expression, e.g. "EngineSpeed > 3000"
+    #[arg(long = "where", requires = "dbc")]
+    filter: Option<Expr>,
+    #[command(flatten)]
+    ids: FilterArgs,
+    /// Annotate extended frames with their J1939 PGN and addresses, printing transport sessions
+    /// as one assembled message
+    #[arg(long = "j1939")]
+    j1939: bool,
+    /// J1939 PGN/SPN database (JSON or CSV) used to decode extended frames
+    #[arg(long = "j1939-db")]
+    j1939_db: Option<PathBuf>,
+    /// Reassemble ISO-TP messages on a request:response ID pair, e.g. 7E0:7E8, and annotate them
+    /// with their UDS service. Can be repeated.
+    #[arg(long = "isotp", value_parser = parse_pair)]
+    isotp: Vec<IsoTpPair>,
+    /// Also record received frames to a log file (TRC, MF4, candump or Parquet). MF4 and Parquet
+    /// files include the decoded signals of the `--dbc` database.
+    #[arg(long = "log")]
+    log: Option<PathBuf>,
+    /// Drop output lines when the terminal cannot keep up, instead of slowing down receiving.
+    /// The number of dropped lines is printed on exit.
+    #[arg(long = "quiet-drop")]
+    quiet_drop: bool,
+    // Alerting receives error frames and prints the controller state changes
+    #[command(flatten)]
+    alert: AlertArgs,
+    #[command(flatten)]
+    trigger: TriggerArgs,
+    #[command(flatten)]
+    pub source: FileSourceArgs,
+}
+
+/// Prints lines from a dedicated thread, so writing to a slow terminal does not stall receiving
+///
+/// Lines are collected into a batch and queued with `flush`.
+struct Output {
+    tx: Sender<String>,
+    thread: JoinHandle<io::Result<()>>,
+    batch: String,
+    lines: usize,
+    quiet_drop: bool,
+    dropped: usize,
+}
+
+impl Output {
+    fn new(quiet_drop: bool) -> Self {
+        let (tx, rx) = channel(OUTPUT_QUEUE_SIZE);
+        let thread = tokio::task::spawn_blocking(move || write_lines(rx, io::stdout().lock()));
+
+        Output {
+            tx,
+            thread,
+            batch: String::new(),
+            lines: 0,
+            quiet_drop,
+            dropped: 0,
+        }
+    }
 
-    tokio::signal::ctrl_c().await?;
+    fn line(&mut self, line: impl fmt::Display) {
+        self.write(|out| writeln!(out, "{}", line));
+    }
+
+    /// Write a line, terminated by the caller, into the batch
+    fn write(&mut self, line: impl FnOnce(&mut String) -> fmt::Result) {
+        // Writing to a string cannot fail
+        let _ = line(&mut self.batch);
+        self.lines += 1;
+    }
+
+    /// Queue the batch, returning `false` if the output has been closed
+    async fn flush(&mut self) -> bool {
+        if self.batch.is_empty() {
+            return true;
+        }
+        let batch = std::mem::take(&mut self.batch);
+        let lines = std::mem::take(&mut self.lines);
+
+        if !self.quiet_drop {
+            return self.tx.send(batch).await.is_ok();
+        }
+
+        match self.tx.try_send(batch) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped += lines;
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Wait for the queued lines to be written, returning the number of lines dropped
+    async fn close(self) -> anyhow::Result<usize> {
+        drop(self.tx);
+        match self.thread.await? {
+            // The reader went away, e.g. piped into `head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(self.dropped),
+            res => res.map(|_| self.dropped).map_err(Into::into),
+        }
+    }
+}
+
+/// Write batches of lines, flushing whenever the queue is caught up
+fn write_lines(mut rx: Receiver<String>, out: impl Write) -> io::Result<()> {
+    let mut out = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, out);
+
+    while let Some(batch) = rx.blocking_recv() {
+        out.write_all(batch.as_bytes())?;
+        while let Ok(batch) = rx.try_recv() {
+            out.write_all(batch.as_bytes())?;
+        }
+        out.flush()?;
+    }
 
     Ok(())
 }
 
-async fn dump_task(mut driver: AsyncCanDriverPtr) -> anyhow::Result<()> {
-    while let Some(frame) = driver.recv().await {
-        let fmt: CanFrameFormatter = (frame, DataFormatMode::Hex).into();
-        println!("{}", fmt);
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let j1939_db = args.j1939_db.map(Database::load).transpose()?;
+    let dbc = args.dbc.map(SignalDatabase::load).transpose()?;
+    let names = args.names.as_deref().map(IdNames::load).transpose()?;
+    let ids = args.ids.resolve(&ctx.config)?;
+
+    let annotator = args.j1939.then(Annotator::new);
+    let isotp = isotp::Annotator::new(&args.isotp);
+
+    let mut log = args
+        .log
+        .map(|path| {
+            let options = WriteOptions {
+                database: dbc.clone(),
+                ..Default::default()
+            };
+            logfile::create(path, &options)
+        })
+        .transpose()?;
+
+    let mut driver = ctx.driver;
+    // Error frames are only received for alerting
+    if args.alert.enabled() {
+        driver.receive_error_frames(true)?;
+    }
+    let sync = driver.clock_sync();
+    let errors = driver.errors();
+    let triggers = Triggers::new(args.trigger, ctx.interface.clone())?;
+    let alerts = BusAlerts::new(args.alert, ctx.interface.clone());
+
+    let mut output = Output::new(args.quiet_drop);
+
+    // Run in place, rather than as a task, so the log can be completed on exit
+    let task = dump_task(
+        driver,
+        j1939_db,
+        dbc,
+        names,
+        args.raw_values,
+        ids,
+        args.filter,
+        annotator,
+        isotp,
+        alerts,
+        triggers,
+        log.as_mut(),
+        &mut output,
+    );
+    let res = tokio::select! {
+        res = task => res,
+        res = tokio::signal::ctrl_c() => res.map_err(Into::into),
+    };
+
+    if let Some(log) = log.as_mut() {
+        log.finish()?;
+    }
+
+    let dropped = output.close().await?;
+    if dropped > 0 {
+        eprintln!(
+            "Dropped {} lines the terminal could not keep up with",
+            dropped
+        );
+    }
+    if let Some(quality) = sync.as_ref().and_then(SyncHandle::quality) {
+        eprintln!("Clock sync with {}: {}", ctx.interface, quality);
+    }
+    if let Some(errors) = errors.filter(|errors| errors.count() > 0) {
+        eprintln!("{} on {}", errors, ctx.interface);
+    }
+
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn dump_task(
+    mut driver: CanDriver,
+    j1939_db: Option<Database>,
+    dbc: Option<SignalDatabase>,
+    names: Option<IdNames>,
+    raw_values: bool,
+    ids: Option<IdFilter>,
+    filter: Option<Expr>,
+    mut annotator: Option<Annotator>,
+    mut isotp: isotp::Annotator,
+    mut alerts: BusAlerts,
+    mut triggers: Triggers,
+    mut log: Option<&mut Box<dyn LogWriter>>,
+    output: &mut Output,
+) -> anyhow::Result<()> {
+    let start = SystemTime::now();
+
+    let mut frames = vec![];
+    while driver.recv_many(&mut frames).await > 0 {
+        for frame in frames.drain(..) {
+            // Frames outside the ID filter are neither shown nor logged
+            let excluded = ids.as_ref().is_some_and(|ids| !ids.matches(frame.id()));
+            if excluded && !frame.is_error_frame() {
+                continue;
+            }
+
+            if let Some(log) = log.as_mut() {
+                // The driver's receive time, so the log is not skewed by processing delays
+                let timestamp = frame
+                    .timestamp()
+                    .unwrap_or_else(SystemTime::now)
+                    .duration_since(start)
+                    .unwrap_or_default();
+                log.write(&LogFrame::from_frame(timestamp, &frame))?;
+            }
+
+            if frame.is_error_frame() {
+                if let Some(transition) = alerts.update(&frame) {
+                    output.line(format_args!("Bus state {}", transition));
+                }
+                continue;
+            }
+
+            if let Some(db) = dbc.as_ref().filter(|_| !triggers.is_empty()) {
+                triggers.update(db, &frame);
+            }
+
+            if let (Some(filter), Some(db)) = (&filter, &dbc) {
+                if !filter.matches(db, frame.id(), frame.data()) {
+                    continue;
+                }
+            }
+
+            if isotp.matches(frame.id()) {
+                if let Some(line) = isotp.annotate(&frame) {
+                    output.line(line);
+                }
+                continue;
+            }
+
+            if let Some(annotator) = annotator.as_mut() {
+                if let Some(line) = annotator.annotate(&frame, j1939_db.as_ref()) {
+                    output.line(line);
+                }
+                continue;
+            }
+
+            let spns = j1939_db
+                .as_ref()
+                .map(|db| decode_spns(db, &frame))
+                .unwrap_or_default();
+
+            // Format straight into the output batch
+            let fmt: CanFrameFormatter = (&frame, DataFormatMode::Hex).into();
+            output.write(|out| {
+                write!(out, "{}", fmt)?;
+                if let Some(db) = &dbc {
+                    write_signals(out, db, &frame, "\n    ", raw_values)?;
+                }
+                let decoded = dbc
+                    .as_ref()
+                    .is_some_and(|db| db.message(frame.id()).is_some());
+                if let Some(names) = names.as_ref().filter(|_| !decoded) {
+                    names.write(out, frame.id())?;
+                }
+                for spn in &spns {
+                    write!(out, "\n    {}", spn)?;
+                }
+                writeln!(out)
+            });
+        }
+
+        if !output.flush().await {
+            break;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_batches() {
+        let (tx, rx) = channel(4);
+        tx.try_send("a\nb\n".to_string()).unwrap();
+        tx.try_send("c\n".to_string()).unwrap();
+        drop(tx);
+
+        let mut out = vec![];
+        write_lines(rx, &mut out).unwrap();
+        assert_eq!(out, b"a\nb\nc\n");
+    }
+}
@@ -0,0 +1,47 @@
+//
+// filter.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::config::{Config, IdFilter};
+
+use clap::Args;
+
+/// ID filter options shared by the receiving commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct FilterArgs {
+    /// Only take frames with IDs in a named filter of the config file, or in a list of IDs and
+    /// ranges such as "0x100-0x1FF,0x7E8". Can be repeated, frames matching any are taken.
+    #[arg(long = "filter")]
+    pub filters: Vec<String>,
+}
+
+impl FilterArgs {
+    /// Combine the filters, `None` if none were given
+    pub fn resolve(&self, config: &Config) -> anyhow::Result<Option<IdFilter>> {
+        if self.filters.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ids = IdFilter::default();
+        for name in &self.filters {
+            match config.filter(name) {
+                Some(filter) => ids.extend(filter),
+                None => {
+                    let filter = name.parse::<IdFilter>().map_err(|e| {
+                        anyhow::anyhow!(
+                            "'{}' is not a filter of the config file or a list of IDs: {}",
+                            name,
+                            e
+                        )
+                    })?;
+                    ids.extend(&filter);
+                }
+            }
+        }
+
+        Ok(Some(ids))
+    }
+}
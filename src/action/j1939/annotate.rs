@@ -0,0 +1,156 @@
+//
+// annotate.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    format::{CanFrameFormatter, DataFormatMode},
+    frame::CanFrame,
+};
+
+use j1939::{id::GLOBAL_ADDRESS, transport::TpCm, Database, J1939Frame, Message, Pgn, Reassembler};
+
+/// Annotates frames with their J1939 PGN and addresses
+///
+/// Transport protocol frames are collected and printed once as the assembled message.
+#[derive(Debug, Default)]
+pub struct Annotator {
+    reassembler: Reassembler,
+}
+
+impl Annotator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the annotated line(s) of a frame, `None` if the frame is part of an unfinished
+    /// transport session
+    pub fn annotate(&mut self, frame: &CanFrame, db: Option<&Database>) -> Option<String> {
+        let fmt: CanFrameFormatter = (frame, DataFormatMode::Hex).into();
+
+        let Ok(j1939) = J1939Frame::from_frame(frame) else {
+            return Some(fmt.to_string());
+        };
+
+        let source = j1939.source_address();
+        let destination = j1939.destination();
+
+        let assembled = self.reassembler.process(&j1939);
+
+        match j1939.message() {
+            // Aborted sessions are still shown so missing messages can be explained
+            Ok(Message::TpCm(TpCm::Abort { reason, pgn })) => Some(format!(
+                "{}  {} TP abort PGN {} reason {}",
+                fmt,
+                addresses(source, destination),
+                pgn_label(pgn, db),
+                reason
+            )),
+            Ok(Message::TpCm(_)) | Ok(Message::TpDt { .. }) => {
+                let assembled = assembled?;
+                let data = assembled
+                    .data
+                    .iter()
+                    .map(|b| format!(" {:02X}", b))
+                    .collect::<String>();
+
+                Some(format!(
+                    "TP       [{}] {}  PGN {} {}{}",
+                    assembled.data.len(),
+                    data,
+                    pgn_label(assembled.pgn, db),
+                    addresses(assembled.source, assembled.destination),
+                    spns(db, assembled.pgn, &assembled.data)
+                ))
+            }
+            _ => Some(format!(
+                "{}  PGN {} {}{}",
+                fmt,
+                pgn_label(j1939.pgn(), db),
+                addresses(source, destination),
+                spns(db, j1939.pgn(), &j1939.data)
+            )),
+        }
+    }
+}
+
+fn pgn_label(pgn: Pgn, db: Option<&Database>) -> String {
+    let name = db
+        .and_then(|db| db.get(pgn))
+        .map(|def| def.label())
+        .or_else(|| pgn.name());
+
+    match name {
+        Some(name) => format!("{} ({})", pgn, name),
+        None => pgn.to_string(),
+    }
+}
+
+fn addresses(source: u8, destination: Option<u8>) -> String {
+    match destination.filter(|&da| da != GLOBAL_ADDRESS) {
+        Some(da) => format!("SA 0x{:02X} DA 0x{:02X}", source, da),
+        None => format!("SA 0x{:02X}", source),
+    }
+}
+
+fn spns(db: Option<&Database>, pgn: Pgn, data: &[u8]) -> String {
+    db.map(|db| db.decode(pgn, data))
+        .unwrap_or_default()
+        .iter()
+        .map(|spn| format!("\n    {}", spn))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, Frame, StandardId};
+
+    fn frame(raw_id: u32, data: &[u8]) -> CanFrame {
+        CanFrame::new(ExtendedId::new(raw_id).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn single_frame() {
+        let mut annotator = Annotator::new();
+
+        let line = annotator.annotate(&frame(0x18EA00F9, &[0xEE, 0xFE, 0x00]), None);
+        assert_eq!(
+            line.as_deref(),
+            Some("18EA00F9 [3]  EE FE 00  PGN 59904 (RQST) SA 0xF9 DA 0x00")
+        );
+
+        let standard = CanFrame::new(StandardId::new(0x123).unwrap(), &[1]).unwrap();
+        assert_eq!(
+            annotator.annotate(&standard, None).as_deref(),
+            Some("123 [1]  01")
+        );
+    }
+
+    #[test]
+    fn transport_session() {
+        let mut annotator = Annotator::new();
+
+        let bam = frame(
+            0x18ECFF00,
+            &[0x20, 0x0A, 0x00, 0x02, 0xFF, 0xCA, 0xFE, 0x00],
+        );
+        assert_eq!(annotator.annotate(&bam, None), None);
+        assert_eq!(
+            annotator.annotate(&frame(0x18EBFF00, &[1, 0, 1, 2, 3, 4, 5, 6]), None),
+            None
+        );
+
+        let line = annotator.annotate(
+            &frame(0x18EBFF00, &[2, 7, 8, 9, 0xFF, 0xFF, 0xFF, 0xFF]),
+            None,
+        );
+        assert_eq!(
+            line.as_deref(),
+            Some("TP       [10]  00 01 02 03 04 05 06 07 08 09  PGN 65226 (DM1) SA 0x00")
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//
+// dtc.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{drivers::AsyncCanDriverPtr, frame::CanFrame, supervisor::Supervisor, CommandContext};
+
+use super::parse_address;
+
+use j1939::{DiagnosticMessage, J1939Frame, Pgn, Reassembler};
+
+use clap::Parser;
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Show previously active trouble codes (DM2) instead of active ones (DM1)
+    #[arg(short = 'p', long = "previous")]
+    previous: bool,
+    /// Source address used to request DM2 (defaults to the off-board diagnostic tool address)
+    #[arg(long = "sa", value_parser = parse_address, default_value = "0xF9")]
+    source_address: u8,
+}
+
+impl Args {
+    /// DM1 is only listened for, DM2 is requested
+    pub fn transmits(&self) -> bool {
+        self.previous
+    }
+}
+
+pub async fn run(args: Args, ctx: CommandContext) -> anyhow::Result<()> {
+    let pgn = if args.previous { Pgn::DM2 } else { Pgn::DM1 };
+
+    let mut supervisor = Supervisor::new();
+    supervisor.spawn(dtc_task(ctx.driver.into(), pgn, args.source_address));
+    supervisor.run().await
+}
+
+async fn dtc_task(mut driver: AsyncCanDriverPtr, pgn: Pgn, sa: u8) -> anyhow::Result<()> {
+    // DM1 is broadcast periodically, DM2 is only sent on request
+    if pgn == Pgn::DM2 {
+        let request = J1939Frame::request(pgn, j1939::id::GLOBAL_ADDRESS, sa);
+
+        if let Some(frame) = request.to_frame::<CanFrame>() {
+            driver.send(frame).await;
+        }
+    }
+
+    let mut reassembler = Reassembler::new();
+    let mut messages: BTreeMap<u8, DiagnosticMessage> = BTreeMap::new();
+
+    while let Some(frame) = driver.recv().await {
+        let Ok(frame) = J1939Frame::from_frame(&frame) else {
+            continue;
+        };
+
+        let Some(assembled) = reassembler.process(&frame).filter(|a| a.pgn == pgn) else {
+            continue;
+        };
+
+        if let Ok(dm) = DiagnosticMessage::parse(pgn, &assembled.data) {
+            // Only print when the codes reported by an ECU change
+            if messages.get(&assembled.source) != Some(&dm) {
+                print_message(assembled.source, &dm);
+                messages.insert(assembled.source, dm);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_message(source: u8, dm: &DiagnosticMessage) {
+    println!("SA 0x{:02X} {}", source, dm.lamps);
+
+    if dm.dtcs.is_empty() {
+        println!("    no trouble codes");
+    }
+
+    for dtc in &dm.dtcs {
+        println!("    {}", dtc);
+    }
+}
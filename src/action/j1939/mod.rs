@@ -0,0 +1,57 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+pub mod annotate;
+pub mod dtc;
+pub mod request;
+
+use clap::Subcommand;
+
+use crate::{frame::CanFrame, CommandContext};
+
+use j1939::{Database, J1939Frame, SpnValue};
+
+/// J1939 subcommands
+#[derive(Debug, Subcommand)]
+pub enum J1939Commands {
+    /// List diagnostic trouble codes per source address
+    Dtc(dtc::Args),
+    /// Request a PGN and print the response(s)
+    Request(request::Args),
+}
+
+impl J1939Commands {
+    /// Whether the subcommand sends frames on the bus
+    pub fn transmits(&self) -> bool {
+        match self {
+            J1939Commands::Dtc(args) => args.transmits(),
+            J1939Commands::Request(_) => true,
+        }
+    }
+}
+
+pub async fn run(cmd: J1939Commands, ctx: CommandContext) -> anyhow::Result<()> {
+    match cmd {
+        J1939Commands::Dtc(args) => Ok(dtc::run(args, ctx).await?),
+        J1939Commands::Request(args) => Ok(request::run(args, ctx).await?),
+    }
+}
+
+/// Parse an address given in decimal or hex (0x prefixed)
+pub fn parse_address(s: &str) -> Result<u8, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// Decode the SPNs of a frame using the given database
+pub fn decode_spns<'a>(db: &'a Database, frame: &CanFrame) -> Vec<SpnValue<'a>> {
+    J1939Frame::from_frame(frame)
+        .map(|frame| db.decode(frame.pgn(), &frame.data))
+        .unwrap_or_default()
+}
@@ -0,0 +1,257 @@
+//
+// request.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{drivers::AsyncCanDriverPtr, frame::CanFrame, CommandContext};
+
+use super::parse_address;
+
+use j1939::{
+    id::GLOBAL_ADDRESS, transport::TpCm, AckControl, Assembled, Database, J1939Frame, J1939Id,
+    Message, Pgn, Reassembler,
+};
+
+use clap::Parser;
+
+use std::{path::PathBuf, time::Duration};
+
+use tokio::time::Instant;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// PGN to request
+    #[arg(long = "pgn")]
+    pgn: u32,
+    /// Destination address, requests are sent to all nodes by default
+    #[arg(long = "da", value_parser = parse_address, default_value = "0xFF")]
+    destination: u8,
+    /// Source address of the request (defaults to the off-board diagnostic tool address)
+    #[arg(long = "sa", value_parser = parse_address, default_value = "0xF9")]
+    source_address: u8,
+    /// Time to wait for responses in milliseconds
+    #[arg(short = 't', long = "timeout", default_value = "1250")]
+    timeout: u64,
+    /// PGN/SPN database used to decode the responses
+    #[arg(long = "j1939-db")]
+    j1939_db: Option<PathBuf>,
+}
+
+pub async fn run(args: Args, ctx: CommandContext) -> anyhow::Result<()> {
+    let db = args.j1939_db.as_ref().map(Database::load).transpose()?;
+    let pgn = Pgn::from(args.pgn);
+
+    let responses = request_task(ctx.driver.into(), &args, pgn, db.as_ref()).await;
+
+    if responses == 0 {
+        anyhow::bail!("No response to request for PGN {}", pgn);
+    }
+
+    Ok(())
+}
+
+/// Transport session addressed to us that needs flow control
+struct Connection {
+    source: u8,
+    size: u16,
+    packets: u8,
+    window: u8,
+    end_of_window: u8,
+    pgn: Pgn,
+}
+
+async fn request_task(
+    mut driver: AsyncCanDriverPtr,
+    args: &Args,
+    pgn: Pgn,
+    db: Option<&Database>,
+) -> usize {
+    let sa = args.source_address;
+
+    let request = J1939Frame::request(pgn, args.destination, sa);
+    send(&mut driver, &request).await;
+
+    let mut reassembler = Reassembler::new();
+    let mut connection: Option<Connection> = None;
+    let mut responses = 0;
+
+    // Collect responses until the timeout expires
+    let deadline = Instant::now() + Duration::from_millis(args.timeout);
+
+    while let Ok(Some(frame)) = tokio::time::timeout_at(deadline, driver.recv()).await {
+        let Ok(frame) = J1939Frame::from_frame(&frame) else {
+            continue;
+        };
+
+        // Responders other than the requested node are not of interest
+        if args.destination != GLOBAL_ADDRESS && frame.source_address() != args.destination {
+            continue;
+        }
+
+        if frame.destination() == Some(sa) {
+            if let Some(reply) = flow_control(&frame, sa, &mut connection) {
+                send(&mut driver, &reply).await;
+            }
+        }
+
+        if let Ok(Message::Acknowledgement {
+            control,
+            pgn: acked,
+        }) = frame.message()
+        {
+            if acked == pgn {
+                println!("SA 0x{:02X} {}", frame.source_address(), ack_label(control));
+                responses += 1;
+            }
+            continue;
+        }
+
+        if let Some(assembled) = reassembler.process(&frame).filter(|a| a.pgn == pgn) {
+            print_response(&assembled, db);
+            responses += 1;
+        }
+    }
+
+    responses
+}
+
+/// Answer RTS and data packets of a connection mode transfer addressed to `sa`
+fn flow_control(
+    frame: &J1939Frame,
+    sa: u8,
+    connection: &mut Option<Connection>,
+) -> Option<J1939Frame> {
+    let source = frame.source_address();
+
+    let cm = match frame.message().ok()? {
+        Message::TpCm(TpCm::Rts {
+            size,
+            packets,
+            max_packets,
+            pgn,
+        }) => {
+            // 0xFF means the sender does not limit the number of packets per CTS
+            let window = packets.min(max_packets).max(1);
+            *connection = Some(Connection {
+                source,
+                size,
+                packets,
+                window,
+                end_of_window: window,
+                pgn,
+            });
+
+            TpCm::Cts {
+                packets: window,
+                next_packet: 1,
+                pgn,
+            }
+        }
+        Message::TpDt { sequence, .. } => {
+            let conn = connection.as_mut().filter(|c| c.source == source)?;
+
+            if sequence == conn.packets {
+                let cm = TpCm::EndOfMsgAck {
+                    size: conn.size,
+                    packets: conn.packets,
+                    pgn: conn.pgn,
+                };
+                *connection = None;
+                cm
+            } else if sequence == conn.end_of_window {
+                let packets = conn.window.min(conn.packets - sequence);
+                conn.end_of_window = sequence + packets;
+
+                TpCm::Cts {
+                    packets,
+                    next_packet: sequence + 1,
+                    pgn: conn.pgn,
+                }
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+
+    let id = J1939Id::new(7, Pgn::TP_CM, source, sa);
+    Some(J1939Frame::new(id, &cm.to_bytes()))
+}
+
+async fn send(driver: &mut AsyncCanDriverPtr, frame: &J1939Frame) {
+    if let Some(frame) = frame.to_frame::<CanFrame>() {
+        driver.send(frame).await;
+    }
+}
+
+fn ack_label(control: AckControl) -> String {
+    match control {
+        AckControl::Ack => "ACK".to_string(),
+        AckControl::Nack => "NACK".to_string(),
+        AckControl::AccessDenied => "NACK (access denied)".to_string(),
+        AckControl::CannotRespond => "NACK (cannot respond)".to_string(),
+        AckControl::Other(control) => format!("ACK control 0x{:02X}", control),
+    }
+}
+
+fn print_response(assembled: &Assembled, db: Option<&Database>) {
+    let data = assembled
+        .data
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let name = db
+        .and_then(|db| db.get(assembled.pgn))
+        .map(|def| format!(" ({})", def.label()))
+        .unwrap_or_default();
+
+    println!(
+        "SA 0x{:02X} PGN {}{} [{}] {}",
+        assembled.source,
+        assembled.pgn,
+        name,
+        assembled.data.len(),
+        data
+    );
+
+    for spn in db
+        .map(|db| db.decode(assembled.pgn, &assembled.data))
+        .unwrap_or_default()
+    {
+        println!("    {}", spn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(raw_id: u32, data: &[u8]) -> J1939Frame {
+        J1939Frame::new(J1939Id::from_raw(raw_id), data)
+    }
+
+    #[test]
+    fn connection_flow_control() {
+        let mut connection = None;
+
+        // 20 bytes in 3 packets, at most 2 packets per CTS
+        let rts = frame(0x1CECF900, &[0x10, 20, 0, 3, 2, 0xEE, 0xFE, 0x00]);
+        let cts = flow_control(&rts, 0xF9, &mut connection).unwrap();
+        assert_eq!(cts.id.to_raw(), 0x1CEC00F9);
+        assert_eq!(&cts.data[..3], &[0x11, 2, 1]);
+
+        let dt = |seq| frame(0x1CEBF900, &[seq, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(flow_control(&dt(1), 0xF9, &mut connection).is_none());
+
+        let cts = flow_control(&dt(2), 0xF9, &mut connection).unwrap();
+        assert_eq!(&cts.data[..3], &[0x11, 1, 3]);
+
+        let ack = flow_control(&dt(3), 0xF9, &mut connection).unwrap();
+        assert_eq!(ack.data, vec![0x13, 20, 0, 3, 0xFF, 0xEE, 0xFE, 0x00]);
+        assert!(connection.is_none());
+    }
+}
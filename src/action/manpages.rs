@@ -0,0 +1,29 @@
+//
+// manpages.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::cli::Args as CliArgs;
+
+use clap::{CommandFactory, Parser};
+
+use std::{fs, path::PathBuf};
+
+/// Arguments for the manpages command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Directory to write the man pages to, created if missing
+    dir: PathBuf,
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    fs::create_dir_all(&args.dir)?;
+    // One page for ican and one for every subcommand, e.g. ican-dump.1
+    clap_mangen::generate_to(CliArgs::command(), &args.dir)?;
+
+    println!("Wrote man pages to {}", args.dir.display());
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//
+// merge.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::logfile::{
+    self, LogFrame, LogReader, LogWriter, ReadOptions, WriteOptions, WriteReport,
+};
+
+use clap::Parser;
+use thiserror::Error;
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+#[derive(Debug, Error)]
+pub enum MergeError {
+    #[error("'{0}' does not name an input log")]
+    UnknownInput(String),
+}
+
+/// Arguments for the merge command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Log files to merge (BLF, TRC, candump or CSV)
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Log file to write (TRC, MF4, candump or Parquet)
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+    /// Shift the timestamps of an input, named by its file name or stem, e.g. b=+1.25s or
+    /// a=-250ms. Can be repeated.
+    #[arg(long = "offset", value_parser = parse_offset)]
+    offsets: Vec<(String, i128)>,
+    /// Put the frames of an input on a zero-based channel, e.g. b=2. Inputs are put on channels 0,
+    /// 1, ... in order by default. Can be repeated.
+    #[arg(long = "channel", value_parser = parse_channel)]
+    channels: Vec<(String, u8)>,
+    /// Keep the logged channels instead of tagging frames with the channel of their input
+    #[arg(long = "keep-channels", conflicts_with = "channels")]
+    keep_channels: bool,
+    /// Skip malformed lines of candump logs and CSV files instead of stopping
+    #[arg(long = "tolerant")]
+    tolerant: bool,
+}
+
+/// Split a `NAME=VALUE` argument
+fn split_assignment(s: &str) -> Result<(String, &str), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value))
+        .ok_or(format!("expected NAME=VALUE, got '{}'", s))
+}
+
+/// Parse a signed offset in s, ms or us into nanoseconds, seconds without a unit
+fn parse_offset(s: &str) -> Result<(String, i128), String> {
+    let (name, value) = split_assignment(s)?;
+
+    let (number, scale) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 1e6)
+    } else if let Some(us) = value.strip_suffix("us") {
+        (us, 1e3)
+    } else {
+        (value.strip_suffix('s').unwrap_or(value), 1e9)
+    };
+
+    let number = number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or(format!("invalid offset '{}'", value))?;
+
+    Ok((name, (number * scale).round() as i128))
+}
+
+fn parse_channel(s: &str) -> Result<(String, u8), String> {
+    let (name, value) = split_assignment(s)?;
+    let channel = value
+        .parse()
+        .map_err(|_| format!("invalid channel '{}'", value))?;
+
+    Ok((name, channel))
+}
+
+/// An input log and the next frame to merge from it
+struct Input {
+    reader: LogReader,
+    /// Offset of the log's timestamps on the merged timeline, in nanoseconds
+    offset: i128,
+    /// Channel the frames are tagged with, `None` to keep the logged channel
+    channel: Option<u8>,
+    head: Option<LogFrame>,
+}
+
+impl Input {
+    fn new(mut reader: LogReader, offset: i128, channel: Option<u8>) -> anyhow::Result<Self> {
+        let head = reader.next().transpose()?;
+        Ok(Input {
+            reader,
+            offset,
+            channel,
+            head,
+        })
+    }
+
+    /// Time of the next frame on the merged timeline
+    fn next_time(&self) -> Option<i128> {
+        self.head
+            .as_ref()
+            .map(|frame| self.offset + frame.timestamp.as_nanos() as i128)
+    }
+
+    fn advance(&mut self) -> anyhow::Result<Option<LogFrame>> {
+        let next = self.reader.next().transpose()?;
+        Ok(std::mem::replace(&mut self.head, next))
+    }
+}
+
+fn names(path: &Path) -> [Option<&str>; 2] {
+    [
+        path.file_name().and_then(|name| name.to_str()),
+        path.file_stem().and_then(|stem| stem.to_str()),
+    ]
+}
+
+pub fn run(args: Args) -> anyhow::Result<()> {
+    for name in args
+        .offsets
+        .iter()
+        .map(|(name, _)| name)
+        .chain(args.channels.iter().map(|(name, _)| name))
+    {
+        if !args
+            .inputs
+            .iter()
+            .any(|path| names(path).contains(&Some(name)))
+        {
+            return Err(MergeError::UnknownInput(name.clone()).into());
+        }
+    }
+
+    let options = ReadOptions {
+        tolerant: args.tolerant,
+        ..Default::default()
+    };
+
+    let mut inputs = vec![];
+    for (i, path) in args.inputs.iter().enumerate() {
+        let find = |name: &String| names(path).contains(&Some(name.as_str()));
+        let offset = args
+            .offsets
+            .iter()
+            .filter(|(name, _)| find(name))
+            .map(|(_, offset)| offset)
+            .sum();
+        let channel = match args.keep_channels {
+            true => None,
+            false => Some(
+                args.channels
+                    .iter()
+                    .rfind(|(name, _)| find(name))
+                    .map(|&(_, channel)| channel)
+                    .unwrap_or(i as u8),
+            ),
+        };
+
+        let reader = logfile::open_with(path, &options)?;
+        inputs.push(Input::new(reader, offset, channel)?);
+    }
+
+    let aligned = align(&mut inputs);
+    if !aligned && inputs.len() > 1 {
+        eprintln!("Not every log records its start time, aligning the logs at their start");
+    }
+
+    // The merged log starts at the earliest frame
+    let mut options = WriteOptions::default();
+    if let Some(start) = inputs
+        .iter()
+        .filter_map(Input::next_time)
+        .min()
+        .filter(|_| aligned)
+        .and_then(|start| u64::try_from(start).ok())
+    {
+        options.start_time = UNIX_EPOCH + Duration::from_nanos(start);
+    }
+    let mut writer = logfile::create(&args.output, &options)?;
+    let report = merge(inputs, writer.as_mut())?;
+    writer.finish()?;
+
+    println!(
+        "Merged {} frames from {} logs into {}",
+        report.written,
+        args.inputs.len(),
+        args.output.display()
+    );
+    eprint!("{}", report);
+
+    Ok(())
+}
+
+/// Offset the inputs by their absolute start times, if every input records one
+///
+/// Returns `false` if the inputs are left aligned at their timestamp zero.
+fn align(inputs: &mut [Input]) -> bool {
+    let starts = inputs
+        .iter()
+        .map(|input| input.reader.start_time())
+        .collect::<Option<Vec<_>>>();
+
+    let Some(starts) = starts else {
+        return false;
+    };
+
+    for (input, start) in inputs.iter_mut().zip(starts) {
+        input.offset += start
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as i128;
+    }
+
+    true
+}
+
+/// Interleave the inputs by time, reporting what was written
+///
+/// Timestamps are written relative to the earliest frame.
+fn merge(mut inputs: Vec<Input>, writer: &mut dyn LogWriter) -> anyhow::Result<WriteReport> {
+    let origin = inputs
+        .iter()
+        .filter_map(Input::next_time)
+        .min()
+        .unwrap_or_default();
+
+    let mut report = WriteReport::default();
+
+    while let Some(input) = inputs
+        .iter_mut()
+        .filter(|input| input.head.is_some())
+        .min_by_key(|input| input.next_time())
+    {
+        let time = input.next_time().unwrap_or_default() - origin;
+        let channel = input.channel;
+
+        if let Some(mut frame) = input.advance()? {
+            frame.timestamp = Duration::from_nanos(time.max(0) as u64);
+            if channel.is_some() {
+                frame.channel = channel;
+            }
+
+            report.write(writer, &frame)?;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::logfile::{candump::CandumpReader, LogError};
+
+    #[derive(Default)]
+    struct Collect(Vec<LogFrame>);
+
+    impl LogWriter for Collect {
+        fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError> {
+            self.0.push(frame.clone());
+            Ok(true)
+        }
+
+        fn finish(&mut self) -> Result<(), LogError> {
+            Ok(())
+        }
+    }
+
+    fn input(log: &'static str, offset: i128, channel: u8) -> Input {
+        Input::new(
+            Box::new(CandumpReader::new(log.as_bytes())),
+            offset,
+            Some(channel),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(
+            parse_offset("b=+1.25s").unwrap(),
+            ("b".into(), 1_250_000_000)
+        );
+        assert_eq!(parse_offset("a.log=-250ms").unwrap().1, -250_000_000);
+        assert_eq!(parse_offset("a=2").unwrap().1, 2_000_000_000);
+        assert_eq!(parse_offset("a=10us").unwrap().1, 10_000);
+        assert!(parse_offset("a").is_err());
+        assert!(parse_offset("a=fast").is_err());
+    }
+
+    #[test]
+    fn merge_logs() {
+        let a = "(100.000000) can0 100#01\n(100.500000) can0 100#02\n(101.000000) can0 100#03\n";
+        let b = "(100.200000) vcan1 200#01\n(100.700000) vcan1 200#02\n";
+
+        let mut inputs = vec![input(a, 0, 0), input(b, 500_000_000, 3)];
+        assert!(align(&mut inputs));
+
+        let mut writer = Collect::default();
+        let report = merge(inputs, &mut writer).unwrap();
+        assert_eq!(report.written, 5);
+        assert!(report.is_lossless());
+
+        let frames = writer
+            .0
+            .iter()
+            .map(|f| (f.timestamp.as_millis(), f.channel.unwrap(), f.data[0]))
+            .collect::<Vec<_>>();
+
+        // The second log is shifted by 500ms
+        assert_eq!(
+            frames,
+            vec![
+                (0, 0, 1),
+                (500, 0, 2),
+                (700, 3, 1),
+                (1000, 0, 3),
+                (1200, 3, 2)
+            ]
+        );
+    }
+}
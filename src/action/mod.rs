@@ -1,5 +1,24 @@
+pub mod alert;
+pub mod analyze;
 pub mod bridge;
+pub mod canopen;
+pub mod completions;
+pub mod convert;
+pub mod dbc;
 pub mod dump;
+pub mod filter;
+pub mod j1939;
+pub mod manpages;
+pub mod merge;
 pub mod monitor;
+pub mod replay;
+pub mod rewrite;
+pub mod scenario;
+#[cfg(feature = "script")]
+pub mod script;
 pub mod send;
-//pub mod canopen;
+pub mod signals;
+pub mod trigger;
+pub mod uds;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+pub mod vcan;
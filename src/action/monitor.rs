@@ -6,35 +6,131 @@
 //
 
 use crate::{
-    drivers::AsyncCanDriverPtr,
-    format::{CanFrameFormatter, DataFormatMode},
+    action::{
+        alert::{AlertArgs, BusAlerts},
+        filter::FilterArgs,
+        j1939::decode_spns,
+        replay::FileSourceArgs,
+        trigger::{TriggerArgs, Triggers},
+        uds::annotate::{self as isotp, parse_pair, IsoTpPair},
+    },
+    activity::ByteActivity,
+    busstate::BusState,
+    config::IdFilter,
+    dedup::Deduplicator,
+    drivers::{CanDriver, DriverErrors},
+    format::{format_signals, write_signals, CanFrameFormatter, DataFormatMode},
     frame::CanFrame,
+    names::IdNames,
+    period::{Deviation, PeriodTracker},
+    ringbuf::RingBuffer,
+    supervisor::Supervisor,
+    timesync::SyncHandle,
     utils, CommandContext,
 };
 
+use candecode::{
+    ByteOrder, Database as SignalDatabase, Expr, Message, Signal, ValueTable, ValueType,
+};
+use clap::Parser;
 use embedded_can::Frame;
+use j1939::Database;
 
 use std::{
     collections::BTreeMap,
+    fmt::Write as _,
     io,
-    sync::{Arc, Mutex},
-    time::{Duration, Instant},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::sync::mpsc::{
+    channel,
+    error::{TryRecvError, TrySendError},
+    Receiver, Sender,
 };
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::Show,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
-    style::Style,
-    text::Span,
-    widgets::{Block, Borders, List, ListItem},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame as UiFrame, Terminal,
 };
 
+/// Number of received frames buffered for the UI, frames beyond it are dropped
+const UPDATE_QUEUE_SIZE: usize = 8192;
+/// Longest time between redraws while the view changes without frames
+const IDLE_REDRAW: Duration = Duration::from_millis(500);
+/// Blank columns between the columns of the ID view
+const COLUMN_GAP: usize = 3;
+
+/// Arguments for the monitor command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Signal database (DBC or KCD) used to decode frames
+    #[arg(long = "dbc")]
+    dbc: Option<PathBuf>,
+    /// Show the raw value of enumerated signals next to their label
+    #[arg(long = "raw-values")]
+    raw_values: bool,
+    /// ID names and descriptions (TOML or CSV), shown for frames the `--dbc` database does not
+    /// decode
+    #[arg(long = "names")]
+    names: Option<PathBuf>,
+    /// Only show frames whose signals match the expression, e.g. "EngineSpeed > 3000"
+    #[arg(long = "where", requires = "dbc")]
+    filter: Option<Expr>,
+    #[command(flatten)]
+    ids: FilterArgs,
+    /// J1939 PGN/SPN database (JSON or CSV) used to decode extended frames
+    #[arg(long = "j1939-db")]
+    j1939_db: Option<PathBuf>,
+    /// Reassemble ISO-TP messages on a request:response ID pair, e.g. 7E0:7E8, and show the last
+    /// message of each ID annotated with its UDS service. Can be repeated.
+    #[arg(long = "isotp", value_parser = parse_pair)]
+    isotp: Vec<IsoTpPair>,
+    /// Number of frames kept per ID
+    #[arg(long = "history", default_value = "64")]
+    history: usize,
+    /// Number of frames kept for the trace view
+    #[arg(long = "trace-size", default_value = "10000")]
+    trace_size: usize,
+    /// Drop frames identical to one received within this window, e.g. 5ms, as the copies of
+    /// aggregated interfaces or gateway echoes. Keep it below the period of the fastest ID.
+    #[arg(long = "dedup", value_parser = utils::parse_duration)]
+    dedup: Option<Duration>,
+    /// Percent an interval may deviate from the learned period of an ID before the frame is
+    /// flagged as early or late
+    #[arg(long = "period-tolerance", default_value = "25")]
+    period_tolerance: f64,
+    /// Seconds of frames the heatmap view counts byte changes over
+    #[arg(long = "heatmap-window", default_value = "5")]
+    heatmap_window: f64,
+    /// File the signals defined in the monitor are exported to, as a DBC
+    #[arg(long = "export-dbc", default_value = "signals.dbc")]
+    export_dbc: PathBuf,
+    #[command(flatten)]
+    alert: AlertArgs,
+    #[command(flatten)]
+    trigger: TriggerArgs,
+    #[command(flatten)]
+    pub source: FileSourceArgs,
+}
+
 /// Track information on received CAN frames
 struct TrackedFrame {
     // The CAN frame
@@ -43,52 +139,413 @@ struct TrackedFrame {
     pub recv_time: Instant,
     // Delta since the last frame
     pub delta: f32,
+    // Last frames of this ID, oldest first
+    pub history: RingBuffer<ReceivedFrame>,
+    // Learned period and the frames outside of it
+    pub period: PeriodTracker,
+    // Byte changes for the heatmap
+    pub activity: ByteActivity,
+    // Formatted row, rebuilt when dirty
+    pub line: String,
+    pub dirty: bool,
 }
 
 impl TrackedFrame {
-    pub fn new(frame: CanFrame, recv_time: Instant, delta: f32) -> Self {
+    pub fn new(
+        received: ReceivedFrame,
+        history_size: usize,
+        period: PeriodTracker,
+        activity: ByteActivity,
+    ) -> Self {
+        let mut history = RingBuffer::new(history_size);
+        let (frame, recv_time) = (received.frame.clone(), received.recv_time);
+        history.push(received);
+
         Self {
             frame,
             recv_time,
-            delta,
+            delta: 0.0,
+            history,
+            period,
+            activity,
+            line: String::new(),
+            dirty: true,
+        }
+    }
+
+    pub fn update(&mut self, received: ReceivedFrame) {
+        self.delta = (received.recv_time - self.recv_time).as_secs_f32();
+        self.frame = received.frame.clone();
+        self.recv_time = received.recv_time;
+        self.history.push(received);
+        self.dirty = true;
+    }
+
+    /// Rebuild the row if the frame changed
+    pub fn refresh(
+        &mut self,
+        format_mode: DataFormatMode,
+        j1939_db: Option<&Database>,
+        dbc: Option<&SignalDatabase>,
+        adhoc: Option<&Message>,
+        names: Option<&IdNames>,
+        raw_values: bool,
+    ) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let frame = &self.frame;
+        let frame_fmt: CanFrameFormatter = (frame, format_mode).into();
+
+        self.line.clear();
+        // Writing to a string cannot fail
+        let _ = write!(self.line, "{}", frame_fmt);
+        if let Some(db) = dbc {
+            let _ = write_signals(&mut self.line, db, frame, "  ", raw_values);
+        }
+        // Named IDs fill in for messages missing from the database
+        if let Some(names) = names.filter(|_| !decoded(dbc, frame)) {
+            let _ = names.write(&mut self.line, frame.id());
+        }
+        for value in adhoc.map(|m| m.decode(frame.data())).unwrap_or_default() {
+            let _ = write!(self.line, "  {}", value);
+        }
+        for spn in j1939_db
+            .map(|db| decode_spns(db, frame))
+            .unwrap_or_default()
+        {
+            let _ = write!(self.line, "  {}", spn);
         }
     }
 }
 
+/// Track the last reassembled ISO-TP message of an ID
+struct TrackedMessage {
+    // Annotated message
+    pub line: String,
+    pub recv_time: Instant,
+    pub delta: f32,
+}
+
 // #[derive(Debug, Clone, Copy)]
 // enum DataFormatMode {
 //     Hex,
 //     Binary,
 // }
 
+/// A received frame, sent from the frame task to the UI
+#[derive(Clone)]
+struct ReceivedFrame {
+    frame: CanFrame,
+    recv_time: Instant,
+}
+
+/// Frames the frame task did not pass to the UI
+#[derive(Default)]
+struct Overflow {
+    /// Frames that did not fit in the queue
+    dropped: AtomicU64,
+    /// Frames suppressed as duplicates
+    duplicates: AtomicU64,
+}
+
+/// Bits selected in the frame detail popup, and the signal being defined on them
+struct SignalDraft {
+    /// ID of the frame
+    id: u32,
+    /// Start bit as in a DBC, the least significant bit for little endian and the most
+    /// significant for big endian
+    start_bit: u16,
+    length: u16,
+    byte_order: ByteOrder,
+    /// Name, scale and offset being entered, `None` while selecting bits
+    form: Option<SignalForm>,
+}
+
+impl SignalDraft {
+    fn signal(&self, name: String, factor: f64, offset: f64) -> Signal {
+        let max_raw = ((1u128 << self.length) - 1) as f64;
+        let (min, max) = (offset, max_raw * factor + offset);
+        Signal {
+            name,
+            start_bit: self.start_bit,
+            length: self.length,
+            byte_order: self.byte_order,
+            value_type: ValueType::Unsigned,
+            factor,
+            offset,
+            min: min.min(max),
+            max: min.max(max),
+            unit: String::new(),
+            receivers: vec![],
+            comment: None,
+            multiplexor: false,
+            multiplexed_by: None,
+            values: ValueTable::new(),
+        }
+    }
+
+    /// Signal on the selected bits, to show the bits and raw value
+    fn preview(&self) -> Signal {
+        self.signal(String::new(), 1.0, 0.0)
+    }
+}
+
+/// Fields of a signal being defined
+struct SignalForm {
+    /// Name, scale and offset
+    fields: [String; 3],
+    /// Field being edited
+    field: usize,
+}
+
+impl SignalForm {
+    const LABELS: [&'static str; 3] = ["Name", "Scale", "Offset"];
+
+    fn new() -> Self {
+        SignalForm {
+            fields: [String::new(), String::from("1"), String::from("0")],
+            field: 0,
+        }
+    }
+}
+
+/// Monitor views
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    /// The last frame of each ID
+    Ids,
+    /// Received frames in order
+    Trace,
+    /// How often each byte of each ID changes
+    Heatmap,
+}
+
 struct App {
     pub frames: BTreeMap<u32, TrackedFrame>,
+    pub trace: RingBuffer<ReceivedFrame>,
+    pub history_size: usize,
+    /// Period tolerance in percent for new IDs
+    pub period_tolerance: f64,
+    /// Window of the byte activity heatmap
+    pub heatmap_window: Duration,
+    pub view: View,
+    pub start_time: Instant,
+    /// Wall clock time of `start_time`, for driver timestamps
+    pub start_system_time: SystemTime,
     pub device_name: String,
     pub format_mode: DataFormatMode,
+    pub j1939_db: Option<Database>,
+    pub dbc: Option<SignalDatabase>,
+    /// ID names for frames the database does not decode
+    pub names: Option<IdNames>,
+    pub raw_values: bool,
+    pub filter: Option<Expr>,
+    pub isotp: isotp::Annotator,
+    pub messages: BTreeMap<u32, TrackedMessage>,
+    pub received: u64,
+    pub dropped: u64,
+    pub duplicates: u64,
+    /// Controller state, from error frames
+    pub bus: BusAlerts,
+    /// Time of the last controller state change
+    pub bus_changed: Option<Instant>,
+    pub triggers: Option<Triggers>,
+    /// The `--filter` arguments, for the status bar
+    pub id_filters: Vec<String>,
+    pub dedup: Option<Duration>,
+    /// The driver stopped delivering frames, as at the end of a log file
+    pub closed: bool,
+    /// Clock sync of a driver receiving sender timestamps
+    pub sync: Option<SyncHandle>,
+    /// Failures the driver carried on after
+    pub driver_errors: Option<DriverErrors>,
+    /// Row selected in the ID view
+    pub selected: usize,
+    /// Frame detail popup of the selected ID
+    pub detail: Option<SignalDraft>,
+    /// Signals defined in the monitor, by ID
+    pub adhoc: BTreeMap<u32, Message>,
+    pub export_path: PathBuf,
+    /// Outcome of the last signal definition or export
+    pub status: Option<String>,
+    /// Something shown changed since the last draw
+    pub changed: bool,
 }
 
 impl App {
-    pub fn new(device_name: String) -> Self {
+    pub fn new(
+        device_name: String,
+        j1939_db: Option<Database>,
+        dbc: Option<SignalDatabase>,
+    ) -> Self {
         Self {
             frames: BTreeMap::default(),
+            trace: RingBuffer::new(0),
+            history_size: 0,
+            period_tolerance: 25.0,
+            heatmap_window: Duration::from_secs(5),
+            view: View::Ids,
+            start_time: Instant::now(),
+            start_system_time: SystemTime::now(),
             device_name,
             format_mode: DataFormatMode::Hex,
+            j1939_db,
+            dbc,
+            names: None,
+            raw_values: false,
+            filter: None,
+            isotp: isotp::Annotator::default(),
+            messages: BTreeMap::default(),
+            received: 0,
+            dropped: 0,
+            duplicates: 0,
+            bus: BusAlerts::new(AlertArgs::default(), String::new()),
+            bus_changed: None,
+            triggers: None,
+            id_filters: vec![],
+            dedup: None,
+            closed: false,
+            sync: None,
+            driver_errors: None,
+            selected: 0,
+            detail: None,
+            adhoc: BTreeMap::default(),
+            export_path: PathBuf::from("signals.dbc"),
+            status: None,
+            changed: true,
         }
     }
 }
 
 impl App {
-    pub fn update(&mut self, frame: CanFrame) {
-        let now = Instant::now();
+    pub fn update(&mut self, received: ReceivedFrame) {
+        self.received += 1;
+        self.changed = true;
+
+        let frame = &received.frame;
+        if frame.is_error_frame() {
+            if self.bus.update(frame).is_some() {
+                self.bus_changed = Some(received.recv_time);
+            }
+            return;
+        }
+        if let (Some(triggers), Some(db)) = (&mut self.triggers, &self.dbc) {
+            triggers.update(db, frame);
+        }
+        if let (Some(filter), Some(db)) = (&self.filter, &self.dbc) {
+            if !filter.matches(db, frame.id(), frame.data()) {
+                return;
+            }
+        }
+        self.trace.push(received.clone());
+
+        let now = received.recv_time;
         let id = utils::id_to_raw(&frame.id());
 
-        // Get delta with the last received frame of this ID
-        let delta = self
-            .frames
-            .get(&id)
-            .map_or(0.0, |f| (now - f.recv_time).as_secs_f32());
+        if self.isotp.matches(frame.id()) {
+            if let Some(line) = self.isotp.annotate(frame) {
+                let delta = self
+                    .messages
+                    .get(&id)
+                    .map_or(0.0, |m| (now - m.recv_time).as_secs_f32());
+                self.messages.insert(
+                    id,
+                    TrackedMessage {
+                        line,
+                        recv_time: now,
+                        delta,
+                    },
+                );
+            }
+            return;
+        }
+
+        let time = self.frame_time(&received);
+        match self.frames.get_mut(&id) {
+            Some(tracked) => {
+                tracked.period.update(time);
+                tracked.activity.update(time, received.frame.data());
+                tracked.update(received);
+            }
+            None => {
+                let mut period = PeriodTracker::new(self.period_tolerance);
+                period.update(time);
+                let mut activity = ByteActivity::new(self.heatmap_window);
+                activity.update(time, received.frame.data());
+                let tracked = TrackedFrame::new(received, self.history_size, period, activity);
+                self.frames.insert(id, tracked);
+            }
+        }
+    }
+
+    /// Time since the start, from the driver's receive time where it records one
+    ///
+    /// Frames read in one batch share their queue time, which would skew their intervals.
+    fn frame_time(&self, received: &ReceivedFrame) -> Duration {
+        received
+            .frame
+            .timestamp()
+            .and_then(|time| time.duration_since(self.start_system_time).ok())
+            .unwrap_or_else(|| {
+                received
+                    .recv_time
+                    .saturating_duration_since(self.start_time)
+            })
+    }
+
+    /// Early and late frames of every ID
+    pub fn period_deviations(&self) -> u64 {
+        self.frames
+            .values()
+            .map(|f| f.period.early() + f.period.late())
+            .sum()
+    }
+
+    pub fn cycle_view(&mut self) {
+        self.view = match self.view {
+            View::Ids => View::Trace,
+            View::Trace => View::Heatmap,
+            View::Heatmap => View::Ids,
+        };
+        self.changed = true;
+    }
+
+    /// Rebuild the rows of frames that changed
+    pub fn refresh(&mut self) {
+        let now = self.start_time.elapsed();
+        let App {
+            frames,
+            format_mode,
+            j1939_db,
+            dbc,
+            names,
+            adhoc,
+            raw_values,
+            view,
+            ..
+        } = self;
+
+        for (id, frame) in frames.iter_mut() {
+            frame.refresh(
+                *format_mode,
+                j1939_db.as_ref(),
+                dbc.as_ref(),
+                adhoc.get(id),
+                names.as_ref(),
+                *raw_values,
+            );
+            if *view == View::Heatmap {
+                frame.activity.expire(now);
+            }
+        }
+    }
 
-        self.frames.insert(id, TrackedFrame::new(frame, now, delta));
+    /// Frames overwritten in the per-ID histories
+    pub fn history_overwritten(&self) -> u64 {
+        self.frames.values().map(|f| f.history.overwritten()).sum()
     }
 
     pub fn cycle_display_format(&mut self) {
@@ -98,114 +555,1176 @@ impl App {
             DataFormatMode::Hex => DataFormatMode::Binary,
             DataFormatMode::Binary => DataFormatMode::Hex,
         };
+
+        for frame in self.frames.values_mut() {
+            frame.dirty = true;
+        }
+        self.changed = true;
+    }
+
+    /// Handle a key press, returns true to quit
+    pub fn on_key(&mut self, key: KeyEvent) -> bool {
+        // Raw mode delivers Ctrl-C as a key rather than a signal
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return true;
+        }
+        self.changed = true;
+
+        match &self.detail {
+            Some(draft) if draft.form.is_some() => self.on_form_key(key),
+            Some(_) => self.on_detail_key(key),
+            None => match key.code {
+                KeyCode::Char('q') => return true,
+                KeyCode::Char('t') => self.cycle_display_format(),
+                KeyCode::Char('v') => self.cycle_view(),
+                KeyCode::Char('x') => self.export_signals(),
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down => {
+                    self.selected = (self.selected + 1).min(self.frames.len().saturating_sub(1))
+                }
+                KeyCode::Enter if self.view == View::Ids => self.open_detail(),
+                _ => {}
+            },
+        }
+
+        false
+    }
+
+    /// Open the detail popup of the selected ID, with its first byte selected
+    fn open_detail(&mut self) {
+        let Some((&id, tracked)) = self.frames.iter().nth(self.selected) else {
+            return;
+        };
+        if tracked.frame.data().is_empty() {
+            return;
+        }
+        self.detail = Some(SignalDraft {
+            id,
+            start_bit: 0,
+            length: 8,
+            byte_order: ByteOrder::LittleEndian,
+            form: None,
+        });
+    }
+
+    /// Move the start bit with the arrows, bytes are shown as rows with bit 7 on the left
+    fn on_detail_key(&mut self, key: KeyEvent) {
+        let Some(draft) = &mut self.detail else {
+            return;
+        };
+        let len = self
+            .frames
+            .get(&draft.id)
+            .map_or(0, |tracked| tracked.frame.data().len());
+
+        let (start, length) = (draft.start_bit, draft.length);
+        let (byte, bit) = (start / 8, start % 8);
+        let mut byte_order = draft.byte_order;
+        let (start, length) = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.detail = None;
+                return;
+            }
+            KeyCode::Char('n') => {
+                draft.form = Some(SignalForm::new());
+                return;
+            }
+            KeyCode::Left if bit < 7 => (start + 1, length),
+            KeyCode::Right if bit > 0 => (start - 1, length),
+            KeyCode::Up if byte > 0 => (start - 8, length),
+            KeyCode::Down => (start + 8, length),
+            KeyCode::Char('+') => (start, length + 1),
+            KeyCode::Char('-') if length > 1 => (start, length - 1),
+            KeyCode::Char('e') => {
+                byte_order = match byte_order {
+                    ByteOrder::LittleEndian => ByteOrder::BigEndian,
+                    ByteOrder::BigEndian => ByteOrder::LittleEndian,
+                };
+                (start, length)
+            }
+            _ => return,
+        };
+
+        // Selections running past the payload are ignored
+        let moved = SignalDraft {
+            id: draft.id,
+            start_bit: start,
+            length,
+            byte_order,
+            form: None,
+        };
+        if moved.preview().fits(len) {
+            *draft = moved;
+        }
+    }
+
+    fn on_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.detail.as_mut().and_then(|draft| draft.form.as_mut()) else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                if let Some(draft) = &mut self.detail {
+                    draft.form = None;
+                }
+            }
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % form.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                form.field = (form.field + form.fields.len() - 1) % form.fields.len()
+            }
+            KeyCode::Backspace => {
+                form.fields[form.field].pop();
+            }
+            KeyCode::Char(c) => form.fields[form.field].push(c),
+            KeyCode::Enter => {
+                self.status = Some(match self.define_signal() {
+                    Ok(name) => format!("Defined signal {}", name),
+                    Err(e) => e,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Add the signal of the form to its ID, replacing one of the same name
+    fn define_signal(&mut self) -> Result<String, String> {
+        let Some(draft) = &mut self.detail else {
+            return Err(String::from("No frame selected"));
+        };
+        let Some(form) = &draft.form else {
+            return Err(String::from("No signal being defined"));
+        };
+
+        let [name, scale, offset] = &form.fields;
+        let name = name.trim().to_string();
+        let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("Invalid signal name '{}'", name));
+        }
+        let factor = scale
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|factor| factor.is_finite() && *factor != 0.0)
+            .ok_or_else(|| format!("Invalid scale '{}'", scale))?;
+        let offset = offset
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|offset| offset.is_finite())
+            .ok_or_else(|| format!("Invalid offset '{}'", offset))?;
+
+        let signal = draft.signal(name.clone(), factor, offset);
+        draft.form = None;
+
+        let Some(tracked) = self.frames.get_mut(&draft.id) else {
+            return Err(String::from("Frame no longer tracked"));
+        };
+        let id = tracked.frame.id();
+        let message = self.adhoc.entry(draft.id).or_insert_with(|| Message {
+            id,
+            name: format!("MSG_{}", utils::format_id(&id)),
+            dlc: tracked.frame.data().len() as u8,
+            transmitter: None,
+            signals: vec![],
+            comment: None,
+        });
+        message.signals.retain(|s| s.name != signal.name);
+        message.signals.push(signal);
+        tracked.dirty = true;
+
+        Ok(name)
+    }
+
+    /// Write the signals defined so far to the export file
+    fn export_signals(&mut self) {
+        let signals = self.adhoc.values().map(|m| m.signals.len()).sum::<usize>();
+        self.status = Some(match signals {
+            0 => String::from("No signals defined, press Enter on an ID to define one"),
+            _ => match std::fs::write(
+                &self.export_path,
+                candecode::dbc::write(self.adhoc.values()),
+            ) {
+                Ok(()) => format!(
+                    "Exported {} signals to {}",
+                    signals,
+                    self.export_path.display()
+                ),
+                Err(e) => format!("Failed to export signals: {}", e),
+            },
+        });
     }
 }
 
-pub async fn run(ctx: CommandContext) -> anyhow::Result<()> {
-    let driver = ctx.driver;
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let mut driver = ctx.driver;
     let device = ctx.interface;
+
+    // The bus state banner is best effort, not every driver reports controller errors
+    let _ = driver.receive_error_frames(true);
     let tick_rate = ctx.tick_rate;
 
     // let tick_rate = Duration::from_millis(250);
 
-    let app = Arc::new(Mutex::new(App::new(device)));
+    let j1939_db = args.j1939_db.map(Database::load).transpose()?;
+    let dbc = args.dbc.map(SignalDatabase::load).transpose()?;
+    let names = args.names.as_deref().map(IdNames::load).transpose()?;
 
-    let ui_task = tokio::spawn(ui_task(app.clone(), tick_rate));
-    tokio::spawn(frame_processor_task(driver, app));
+    let triggers = Triggers::new(args.trigger, device.clone())?;
+    let ids = args.ids.resolve(&ctx.config)?;
 
-    // TODO: Use the nested results...
-    tokio::join!(ui_task).0??;
+    let mut app = App::new(device.clone(), j1939_db, dbc);
+    app.triggers = (!triggers.is_empty()).then_some(triggers);
+    app.id_filters = args.ids.filters.clone();
+    app.dedup = args.dedup;
+    app.bus = BusAlerts::new(args.alert, device);
+    app.names = names;
+    app.raw_values = args.raw_values;
+    app.filter = args.filter;
+    app.isotp = isotp::Annotator::new(&args.isotp);
+    app.history_size = args.history.max(1);
+    app.period_tolerance = args.period_tolerance;
+    app.heatmap_window = Duration::from_secs_f64(args.heatmap_window.max(0.1));
+    app.trace = RingBuffer::new(args.trace_size);
+    app.export_path = args.export_dbc;
+    app.sync = driver.clock_sync();
+    app.driver_errors = driver.errors();
 
-    Ok(())
+    let (tx, rx) = channel(UPDATE_QUEUE_SIZE);
+    let overflow = Arc::new(Overflow::default());
+    let dedup = args.dedup.map(Deduplicator::new);
+
+    let mut supervisor = Supervisor::new();
+    supervisor.spawn_main(ui_task(app, rx, overflow.clone(), tick_rate));
+    supervisor.spawn(frame_processor_task(driver, ids, dedup, tx, overflow));
+    supervisor.run().await
 }
 
+/// Puts the terminal in raw mode on an alternate screen, restoring it when dropped
+///
+/// Dropping restores the terminal however the UI ends: quitting, an error, a panic or the task
+/// being cancelled.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            Show
+        );
+    }
+}
+
+/// Receive frames and queue them for the UI, never waiting on it
+///
+/// Frames that do not fit in the queue are dropped and counted, so a slow UI cannot hold up
+/// receiving from the driver.
 async fn frame_processor_task(
-    mut driver: AsyncCanDriverPtr,
-    app: Arc<Mutex<App>>,
+    mut driver: CanDriver,
+    ids: Option<IdFilter>,
+    mut dedup: Option<Deduplicator>,
+    tx: Sender<ReceivedFrame>,
+    overflow: Arc<Overflow>,
 ) -> anyhow::Result<()> {
-    while let Some(frame) = driver.recv().await {
-        let mut app = app.lock().unwrap();
-        app.update(frame);
+    let mut frames = vec![];
+    while driver.recv_many(&mut frames).await > 0 {
+        let recv_time = Instant::now();
+
+        for frame in frames.drain(..) {
+            let excluded = ids.as_ref().is_some_and(|ids| !ids.matches(frame.id()));
+            if excluded && !frame.is_error_frame() {
+                continue;
+            }
+            if dedup
+                .as_mut()
+                .is_some_and(|dedup| dedup.is_duplicate(&frame, recv_time))
+            {
+                overflow.duplicates.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            match tx.try_send(ReceivedFrame { frame, recv_time }) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    overflow.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                // The UI has exited
+                Err(TrySendError::Closed(_)) => return Ok(()),
+            }
+        }
     }
 
     Ok(())
 }
 
-async fn ui_task(app: Arc<Mutex<App>>, tick_rate: u64) -> anyhow::Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
+/// Apply the queued updates to the app state
+fn drain(app: &mut App, rx: &mut Receiver<ReceivedFrame>, overflow: &Overflow) {
+    loop {
+        match rx.try_recv() {
+            Ok(received) => app.update(received),
+            // The frame task ended with the driver
+            Err(TryRecvError::Disconnected) if !app.closed => {
+                app.closed = true;
+                app.changed = true;
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+
+    let dropped = overflow.dropped.load(Ordering::Relaxed);
+    let duplicates = overflow.duplicates.load(Ordering::Relaxed);
+    if dropped != app.dropped || duplicates != app.duplicates {
+        app.dropped = dropped;
+        app.duplicates = duplicates;
+        app.changed = true;
+    }
+}
 
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+async fn ui_task(
+    mut app: App,
+    mut rx: Receiver<ReceivedFrame>,
+    overflow: Arc<Overflow>,
+    tick_rate: u64,
+) -> anyhow::Result<()> {
+    let _guard = TerminalGuard::enter()?;
 
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
+    let mut last_draw = Instant::now();
 
     loop {
-        // The below is in a new scope block so app is out of scope before `await` is called.
+        drain(&mut app, &mut rx, &overflow);
+
+        // IDs with a learned period may become overdue and the heatmap cool down without
+        // receiving anything
+        if last_draw.elapsed() >= IDLE_REDRAW
+            && (app.view == View::Heatmap
+                || app.frames.values().any(|f| f.period.nominal().is_some()))
         {
-            let mut app = app.lock().unwrap();
-            terminal.draw(|f| ui(f, &*app))?;
-
-            if crossterm::event::poll(Duration::from_millis(10))? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('q') => break,
-                        KeyCode::Char('t') => app.cycle_display_format(),
-                        _ => {}
-                    }
-                }
+            app.changed = true;
+        }
+
+        // Only redraw when something changed, an idle bus costs nothing
+        if app.changed {
+            app.refresh();
+            terminal.draw(|f| ui(f, &app))?;
+            app.changed = false;
+            last_draw = Instant::now();
+        }
+
+        if crossterm::event::poll(Duration::from_millis(10))? {
+            match event::read()? {
+                Event::Key(key) if app.on_key(key) => break,
+                Event::Resize(..) => app.changed = true,
+                _ => {}
             }
         }
 
         tokio::time::sleep(Duration::from_millis(tick_rate)).await;
     }
 
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
     Ok(())
 }
 
 fn ui<B: Backend>(f: &mut UiFrame<B>, app: &App) {
+    let banner = bus_banner(app);
+    let constraints = match banner {
+        Some(_) => vec![
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ],
+        None => vec![Constraint::Min(0), Constraint::Length(1)],
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)].as_ref())
+        .constraints(constraints)
         .split(f.size());
 
-    let format_mode = app.format_mode;
+    let (status, chunks) = chunks.split_last().expect("status bar chunk");
+    let bar = Paragraph::new(status_bar(app)).style(Style::default().bg(Color::DarkGray));
+    f.render_widget(bar, *status);
 
-    let items: Vec<ListItem> = app
+    let chunks = match banner {
+        Some(banner) => {
+            f.render_widget(Paragraph::new(banner), chunks[0]);
+            &chunks[1..]
+        }
+        None => chunks,
+    };
+
+    match app.view {
+        View::Trace => return trace_ui(f, app, chunks[0]),
+        View::Heatmap => return heatmap_ui(f, app, chunks[0]),
+        View::Ids => {}
+    }
+
+    let now = app.start_time.elapsed();
+    let rows: Vec<Spans> = app
         .frames
-        .iter()
-        .map(|(_, frame)| {
-            let TrackedFrame {
-                frame,
-                recv_time: _,
-                delta,
-            } = frame;
+        .values()
+        .map(|frame| frame_row(frame, now))
+        .chain(app.messages.values().map(|message| {
+            Spans::from(Span::styled(
+                format!("{:.3} {}", message.delta, message.line),
+                Style::default(),
+            ))
+        }))
+        .collect();
+
+    let mut title = format!(
+        "IDs ({} overwritten in ID history",
+        app.history_overwritten()
+    );
+    match app.period_deviations() {
+        0 => title.push(')'),
+        deviations => {
+            let _ = write!(title, ", {} period deviations)", deviations);
+        }
+    }
+    if let Some(status) = &app.status {
+        let _ = write!(title, " {}", status);
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let selected = (!app.frames.is_empty()).then_some(app.selected);
+    id_columns(f, rows, selected, block, chunks[0]);
+
+    if let Some(draft) = &app.detail {
+        detail_ui(f, app, draft, chunks[0]);
+    }
+}
+
+/// Bits of the selected ID, the signals defined on it and the signal being defined
+fn detail_ui<B: Backend>(f: &mut UiFrame<B>, app: &App, draft: &SignalDraft, area: Rect) {
+    let Some(tracked) = app.frames.get(&draft.id) else {
+        return;
+    };
+    let data = tracked.frame.data();
+    let preview = draft.preview();
+    let selected = preview.bits().collect::<Vec<_>>();
+
+    let mut lines = vec![
+        Spans::from(format!(
+            "{}  {} bytes",
+            utils::format_id(&tracked.frame.id()),
+            data.len()
+        )),
+        Spans::from(""),
+        Spans::from("         7 6 5 4 3 2 1 0"),
+    ];
+    for (i, byte) in data.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("byte {}   ", i))];
+        for bit in (0..8).rev() {
+            let position = i * 8 + bit;
+            let mut style = Style::default();
+            if selected.contains(&position) {
+                style = style.fg(Color::Black).bg(Color::Cyan);
+            }
+            if position == draft.start_bit as usize {
+                style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            }
+            spans.push(Span::styled(format!("{}", (byte >> bit) & 0x01), style));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(format!(
+        "start bit {}  length {}  {}  raw {}",
+        draft.start_bit,
+        draft.length,
+        match draft.byte_order {
+            ByteOrder::LittleEndian => "little endian",
+            ByteOrder::BigEndian => "big endian",
+        },
+        preview.decode_raw(data).unwrap_or_default()
+    )));
+    if let Some(message) = app.adhoc.get(&draft.id) {
+        for value in message.decode(data) {
+            lines.push(Spans::from(format!("  {}", value)));
+        }
+    }
+
+    lines.push(Spans::from(""));
+    match &draft.form {
+        Some(form) => {
+            for (i, (label, value)) in SignalForm::LABELS.iter().zip(&form.fields).enumerate() {
+                let style = match i == form.field {
+                    true => Style::default().add_modifier(Modifier::REVERSED),
+                    false => Style::default(),
+                };
+                lines.push(Spans::from(vec![
+                    Span::raw(format!("{:<8}", label)),
+                    Span::styled(format!("{:<24}", value), style),
+                ]));
+            }
+            lines.push(Spans::from("Tab next field  Enter define  Esc cancel"));
+        }
+        None => lines.push(Spans::from(
+            "Arrows start bit  +/- length  e byte order  n define signal  Esc close",
+        )),
+    }
+
+    let width = 76.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Define signal");
+
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// Rows of the ID view, in as many columns as the area fits when they do not fit in one
+///
+/// Rows fill each column top to bottom. When even the columns overflow, the page of rows holding
+/// the selection is shown.
+fn id_columns<B: Backend>(
+    f: &mut UiFrame<B>,
+    rows: Vec<Spans>,
+    selected: Option<usize>,
+    block: Block,
+    area: Rect,
+) {
+    let inner = block.inner(area);
+    let widest = rows.iter().map(Spans::width).max().unwrap_or(0);
+    let columns = column_count(rows.len(), widest, inner.width, inner.height);
+    let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+    if columns == 1 {
+        let items = rows.into_iter().map(ListItem::new).collect::<Vec<_>>();
+        let list = List::new(items).block(block).highlight_style(highlight);
+        let mut state = ListState::default();
+        state.select(selected);
+        return f.render_stateful_widget(list, area, &mut state);
+    }
+    f.render_widget(block, area);
+
+    let height = inner.height as usize;
+    let page = columns * height;
+    let first = selected.map_or(0, |selected| selected / page * page);
+    let width = inner.width / columns as u16;
+
+    let mut rows = rows.into_iter().skip(first);
+    for column in 0..columns {
+        let start = first + column * height;
+        let items = rows
+            .by_ref()
+            .take(height)
+            .map(ListItem::new)
+            .collect::<Vec<_>>();
+        let area = Rect {
+            x: inner.x + column as u16 * width,
+            width,
+            ..inner
+        };
+
+        let mut state = ListState::default();
+        state.select(selected.and_then(|selected| {
+            (start..start + items.len())
+                .contains(&selected)
+                .then(|| selected - start)
+        }));
+        f.render_stateful_widget(
+            List::new(items).highlight_style(highlight),
+            area,
+            &mut state,
+        );
+    }
+}
+
+/// Columns of the ID view, more than one when the rows overflow the height and are narrow enough
+fn column_count(rows: usize, widest: usize, width: u16, height: u16) -> usize {
+    let height = height.max(1) as usize;
+    if rows <= height {
+        return 1;
+    }
+    let fit = width as usize / (widest + COLUMN_GAP);
+    fit.clamp(1, rows.div_ceil(height))
+}
+
+/// Whether the database has the message of a frame
+fn decoded(dbc: Option<&SignalDatabase>, frame: &CanFrame) -> bool {
+    dbc.is_some_and(|db| db.message(frame.id()).is_some())
+}
+
+/// Row of an ID, its interval colored when it deviates from the learned period
+fn frame_row(frame: &TrackedFrame, now: Duration) -> Spans<'_> {
+    let period = &frame.period;
+    let style = match period.deviation() {
+        // The sender may have stopped
+        _ if period.overdue(now) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        Some(Deviation::Late) => Style::default().fg(Color::Red),
+        Some(Deviation::Early) => Style::default().fg(Color::Yellow),
+        None => Style::default(),
+    };
+
+    let mut spans = vec![
+        Span::styled(format!("{:.3}", frame.delta), style),
+        Span::raw(" "),
+        Span::raw(frame.line.as_str()),
+    ];
+    if period.early() + period.late() > 0 {
+        spans.push(Span::styled(
+            format!("  early {} late {}", period.early(), period.late()),
+            style,
+        ));
+    }
+
+    Spans::from(spans)
+}
 
-            let frame_fmt: CanFrameFormatter = (frame.clone(), format_mode).into();
+/// Received frames in order, the newest at the bottom
+fn trace_ui<B: Backend>(f: &mut UiFrame<B>, app: &App, area: Rect) {
+    let rows = area.height.saturating_sub(2) as usize;
 
-            let line = Span::from(Span::styled(
-                format!("{:.3} {}", delta, frame_fmt),
+    let items: Vec<ListItem> = app
+        .trace
+        .iter()
+        .skip(app.trace.len().saturating_sub(rows))
+        .map(|received| {
+            let time = received
+                .recv_time
+                .saturating_duration_since(app.start_time)
+                .as_secs_f64();
+            let mut signals = app
+                .dbc
+                .as_ref()
+                .map(|db| format_signals(db, &received.frame, "  ", app.raw_values))
+                .unwrap_or_default();
+            if let Some(names) = app.names.as_ref() {
+                if !decoded(app.dbc.as_ref(), &received.frame) {
+                    let _ = names.write(&mut signals, received.frame.id());
+                }
+            }
+            let id = utils::id_to_raw(&received.frame.id());
+            if let Some(message) = app.adhoc.get(&id) {
+                for value in message.decode(received.frame.data()) {
+                    let _ = write!(signals, "  {}", value);
+                }
+            }
+            let frame_fmt: CanFrameFormatter = (&received.frame, app.format_mode).into();
+
+            ListItem::new(Span::styled(
+                format!("{:>12.6} {}{}", time, frame_fmt, signals),
                 Style::default(),
-            ));
-            ListItem::new(line)
+            ))
+        })
+        .collect();
+
+    let title = format!(
+        "Trace (last {} kept, {} overwritten)",
+        app.trace.capacity(),
+        app.trace.overwritten()
+    );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(list, area);
+}
+
+/// Each byte of each ID, colored by how often it changed within the window
+fn heatmap_ui<B: Backend>(f: &mut UiFrame<B>, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .frames
+        .values()
+        .map(|tracked| {
+            let id = utils::format_id(&tracked.frame.id());
+            let mut spans = vec![Span::raw(format!("{:>8} ", id))];
+
+            let rates = tracked.activity.change_rates();
+            for (byte, rate) in tracked.activity.last().iter().zip(rates) {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(format!("{:02X}", byte), heat_style(rate)));
+            }
+
+            ListItem::new(Spans::from(spans))
         })
         .collect();
 
-    let list = List::new(items).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(app.device_name.as_str()),
+    let title = format!(
+        "Byte changes over {:.1}s (blue: rare, green, yellow, red: every frame)",
+        app.heatmap_window.as_secs_f64()
+    );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(list, area);
+}
+
+/// Cell color for the fraction of frames that changed a byte
+fn heat_style(rate: f64) -> Style {
+    let color = match rate {
+        r if r <= 0.0 => return Style::default().fg(Color::DarkGray),
+        r if r <= 0.1 => Color::Blue,
+        r if r <= 0.4 => Color::Green,
+        r if r <= 0.8 => Color::Yellow,
+        _ => Color::Red,
+    };
+    Style::default().fg(Color::Black).bg(color)
+}
+
+/// Banner shown while the controller is not error-active, and for good once it went bus-off
+fn bus_banner(app: &App) -> Option<Spans<'static>> {
+    let tracker = app.bus.tracker();
+    let state = tracker.state();
+    if state == BusState::ErrorActive && tracker.bus_off_count() == 0 {
+        return None;
+    }
+
+    let color = match state {
+        BusState::BusOff | BusState::ErrorPassive => Color::Red,
+        _ if tracker.bus_off_count() > 0 => Color::Red,
+        _ => Color::Yellow,
+    };
+    let since = app
+        .bus_changed
+        .map(|time| time.saturating_duration_since(app.start_time).as_secs_f64())
+        .unwrap_or_default();
+
+    let mut text = format!(" Bus state {} since {:.3}s", state, since);
+    if tracker.bus_off_count() > 0 {
+        let _ = write!(text, ", bus-off {} times", tracker.bus_off_count());
+    }
+
+    Some(Spans::from(Span::styled(
+        text,
+        Style::default()
+            .fg(Color::White)
+            .bg(color)
+            .add_modifier(Modifier::BOLD),
+    )))
+}
+
+/// Bar at the bottom of every view: the interface, its state, the frame counts and the modes
+fn status_bar(app: &App) -> Spans<'_> {
+    let (state, color) = driver_state(app);
+    let bar = Style::default().bg(Color::DarkGray).fg(Color::White);
+
+    let mut modes = format!(
+        " | {} | {} | {}",
+        counters(app),
+        match app.format_mode {
+            DataFormatMode::Hex => "hex",
+            DataFormatMode::Binary => "binary",
+        },
+        match app.view {
+            View::Ids => "ids",
+            View::Trace => "trace",
+            View::Heatmap => "heatmap",
+        }
     );
+    if !app.id_filters.is_empty() {
+        let _ = write!(modes, " | filter {}", app.id_filters.join(","));
+    }
+    if let Some(filter) = &app.filter {
+        let _ = write!(modes, " | where {}", filter);
+    }
+    if let Some(window) = app.dedup {
+        let _ = write!(modes, " | dedup {:?}", window);
+    }
+    if let Some(sync) = &app.sync {
+        let _ = match sync.quality() {
+            Some(quality) => write!(
+                modes,
+                " | sync {:+.3} ms, jitter {:.3} ms, {} samples",
+                quality.offset * 1e3,
+                quality.jitter * 1e3,
+                quality.samples
+            ),
+            None => write!(modes, " | sync waiting"),
+        };
+    }
+
+    Spans::from(vec![
+        Span::styled(
+            format!(" {} ", app.device_name),
+            bar.add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(state, bar.fg(color)),
+        Span::styled(modes, bar),
+    ])
+}
+
+/// Connected, the controller state when not error-active, or closed once the driver ended
+fn driver_state(app: &App) -> (String, Color) {
+    if app.closed {
+        return (String::from("closed"), Color::Red);
+    }
+    match app.bus.tracker().state() {
+        BusState::ErrorActive => (String::from("connected"), Color::Green),
+        BusState::ErrorWarning => (BusState::ErrorWarning.to_string(), Color::Yellow),
+        state => (state.to_string(), Color::Red),
+    }
+}
+
+/// Received, dropped and duplicate frame counts, and driver errors
+fn counters(app: &App) -> String {
+    let mut counters = format!("{} frames", app.received);
+    if app.dropped > 0 {
+        counters += &format!(", {} dropped", app.dropped);
+    }
+    if app.duplicates > 0 {
+        counters += &format!(", {} duplicates", app.duplicates);
+    }
+    if let Some(errors) = app.driver_errors.as_ref().map(DriverErrors::count) {
+        if errors > 0 {
+            counters += &format!(", {} driver errors", errors);
+        }
+    }
+    counters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::drivers::{
+        loopback::LoopbackDriver,
+        udp::{UdpDriver, UdpMode, UdpOpts},
+    };
+
+    use embedded_can::{Id, StandardId};
+
+    #[tokio::test]
+    async fn overflow() {
+        let (mut bus, driver) = LoopbackDriver::pair();
+        let id = Id::Standard(StandardId::new(0x100).unwrap());
+        for i in 0..5 {
+            bus.send(CanFrame::new(id, &[i]).unwrap()).await;
+        }
+        drop(bus);
+
+        let (tx, mut rx) = channel(2);
+        let overflow = Arc::new(Overflow::default());
+        frame_processor_task(driver.into(), None, None, tx, overflow.clone())
+            .await
+            .unwrap();
+
+        let mut app = App::new("vcan0".into(), None, None);
+        drain(&mut app, &mut rx, &overflow);
+
+        assert_eq!(app.received, 2);
+        assert_eq!(app.dropped, 3);
+        assert_eq!(app.frames[&0x100].frame.data(), &[1]);
+    }
+
+    #[tokio::test]
+    async fn duplicates() {
+        let (mut bus, driver) = LoopbackDriver::pair();
+        let id = Id::Standard(StandardId::new(0x100).unwrap());
+        // A frame and its echo, then another frame
+        for data in [1, 1, 2] {
+            bus.send(CanFrame::new(id, &[data]).unwrap()).await;
+        }
+        drop(bus);
+
+        let (tx, mut rx) = channel(8);
+        let overflow = Arc::new(Overflow::default());
+        let dedup = Deduplicator::new(Duration::from_secs(1));
+        frame_processor_task(driver.into(), None, Some(dedup), tx, overflow.clone())
+            .await
+            .unwrap();
+
+        let mut app = App::new("vcan0".into(), None, None);
+        drain(&mut app, &mut rx, &overflow);
+
+        assert_eq!(app.received, 2);
+        assert_eq!(counters(&app), "2 frames, 1 duplicates");
+
+        let errors = DriverErrors::default();
+        app.driver_errors = Some(errors.clone());
+        assert_eq!(counters(&app), "2 frames, 1 duplicates");
+        errors.record("send to 10.0.0.2:8000: Network is unreachable");
+        assert_eq!(counters(&app), "2 frames, 1 duplicates, 1 driver errors");
+    }
+
+    #[test]
+    fn bounded_history() {
+        let mut app = App::new("vcan0".into(), None, None);
+        app.history_size = 2;
+        app.trace = RingBuffer::new(3);
+
+        for (raw, data) in [(0x100, 1), (0x200, 2), (0x100, 3), (0x100, 4), (0x200, 5)] {
+            let id = Id::Standard(StandardId::new(raw).unwrap());
+            app.update(ReceivedFrame {
+                frame: CanFrame::new(id, &[data]).unwrap(),
+                recv_time: Instant::now(),
+            });
+        }
+
+        let history = &app.frames[&0x100].history;
+        let data = history
+            .iter()
+            .map(|r| r.frame.data()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(data, vec![3, 4]);
+        assert_eq!(app.history_overwritten(), 1);
+
+        let trace = app
+            .trace
+            .iter()
+            .map(|r| r.frame.data()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(trace, vec![3, 4, 5]);
+        assert_eq!(app.trace.overwritten(), 2);
+    }
+
+    #[test]
+    fn dirty_rows() {
+        let mut app = App::new("vcan0".into(), None, None);
+        app.history_size = 1;
+
+        let received = |raw, data| ReceivedFrame {
+            frame: CanFrame::new(StandardId::new(raw).unwrap(), &[data]).unwrap(),
+            recv_time: Instant::now(),
+        };
+        app.update(received(0x100, 1));
+        app.update(received(0x200, 2));
+        app.refresh();
+        app.changed = false;
+        assert!(app.frames[&0x100].line.ends_with("100 [1]  01"));
+
+        app.update(received(0x100, 3));
+        assert!(app.changed);
+        assert!(app.frames[&0x100].dirty && !app.frames[&0x200].dirty);
+        app.refresh();
+        assert!(app.frames[&0x100].line.ends_with("100 [1]  03"));
+
+        app.cycle_display_format();
+        assert!(app.frames.values().all(|frame| frame.dirty));
+    }
+
+    #[test]
+    fn period_deviations() {
+        let mut app = App::new("vcan0".into(), None, None);
+        app.history_size = 1;
 
-    f.render_widget(list, chunks[0]);
+        let id = StandardId::new(0x100).unwrap();
+        let start = app.start_system_time;
+        let mut received = |ms: u64| {
+            let frame = CanFrame::new(id, &[])
+                .unwrap()
+                .with_timestamp(start + Duration::from_millis(ms));
+            app.update(ReceivedFrame {
+                frame,
+                recv_time: Instant::now(),
+            });
+        };
+        // Received in one batch, timed by the driver
+        for i in 0..10 {
+            received(i * 10);
+        }
+        received(140);
+
+        let period = &app.frames[&0x100].period;
+        assert_eq!(period.nominal(), Some(Duration::from_millis(10)));
+        assert_eq!(period.deviation(), Some(Deviation::Late));
+        assert_eq!(app.period_deviations(), 1);
+    }
+
+    #[test]
+    fn columns() {
+        // Everything fits in one column
+        assert_eq!(column_count(20, 30, 200, 40), 1);
+        // 150 IDs of 30 characters on a 200 column, 40 row terminal
+        assert_eq!(column_count(150, 30, 200, 40), 4);
+        // As many as fit
+        assert_eq!(column_count(500, 30, 200, 40), 6);
+        // Decoded rows too wide to share the width
+        assert_eq!(column_count(150, 120, 200, 40), 1);
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let (bus, driver) = LoopbackDriver::pair();
+        let (tx, mut rx) = channel(8);
+        let overflow = Arc::new(Overflow::default());
+
+        let mut app = App::new("vcan0".into(), None, None);
+        app.id_filters = vec![String::from("0x100-0x1FF")];
+        app.dedup = Some(Duration::from_millis(5));
+        let text = |app: &App| {
+            let spans = status_bar(app);
+            spans
+                .0
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        };
+        drain(&mut app, &mut rx, &overflow);
+        assert_eq!(
+            text(&app),
+            " vcan0 connected | 0 frames | hex | ids | filter 0x100-0x1FF | dedup 5ms"
+        );
+
+        // The driver ends, as a log file does
+        drop(bus);
+        frame_processor_task(driver.into(), None, None, tx, overflow.clone())
+            .await
+            .unwrap();
+        drain(&mut app, &mut rx, &overflow);
+        app.cycle_view();
+        assert_eq!(
+            text(&app),
+            " vcan0 closed | 0 frames | hex | trace | filter 0x100-0x1FF | dedup 5ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn clock_sync_status() {
+        let opts = |bind: &str, peer| UdpOpts {
+            bind: bind.parse().unwrap(),
+            peer,
+            mode: UdpMode::Unicast,
+            ttl: None,
+        };
+        let mut driver = CanDriver::from(UdpDriver::open(&opts("127.0.0.1:0", None)).unwrap());
+        let CanDriver::Udp(udp) = &driver else {
+            unreachable!()
+        };
+        let peer = udp.local_addr().unwrap();
+        let mut sender = UdpDriver::open(&opts("127.0.0.1:0", Some(peer))).unwrap();
+
+        let mut app = App::new("udp://127.0.0.1".into(), None, None);
+        app.sync = driver.clock_sync();
+        let text = |app: &App| {
+            status_bar(app)
+                .0
+                .iter()
+                .map(|span| span.content.to_string())
+                .collect::<String>()
+        };
+        assert!(text(&app).ends_with(" | ids | sync waiting"));
+
+        sender
+            .send(CanFrame::new(StandardId::new(0x100).unwrap(), &[1]).unwrap())
+            .await;
+        driver.recv().await.unwrap();
+        let text = text(&app);
+        assert!(text.contains(" | sync +"), "{}", text);
+        assert!(
+            text.ends_with(" ms, jitter 0.000 ms, 1 samples"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn heatmap_view() {
+        let mut app = App::new("vcan0".into(), None, None);
+        app.history_size = 1;
+        for view in [View::Trace, View::Heatmap, View::Ids] {
+            app.cycle_view();
+            assert_eq!(app.view, view);
+        }
+
+        for i in 0..4 {
+            app.update(ReceivedFrame {
+                frame: CanFrame::new(StandardId::new(0x100).unwrap(), &[i, 0]).unwrap(),
+                recv_time: Instant::now(),
+            });
+        }
+        let rates = app.frames[&0x100].activity.change_rates();
+        assert_eq!(rates[..2], [0.75, 0.0]);
+        assert_eq!(heat_style(rates[1]), Style::default().fg(Color::DarkGray));
+    }
+
+    #[test]
+    fn define_and_export_signal() {
+        let mut app = App::new("vcan0".into(), None, None);
+        app.history_size = 1;
+        app.export_path =
+            std::env::temp_dir().join(format!("ican-monitor-{}.dbc", std::process::id()));
+        for raw in [0x100, 0x200] {
+            app.update(ReceivedFrame {
+                frame: CanFrame::new(StandardId::new(raw).unwrap(), &[0x00, 0x34, 0x12]).unwrap(),
+                recv_time: Instant::now(),
+            });
+        }
+
+        let press = |app: &mut App, code| app.on_key(KeyEvent::new(code, KeyModifiers::NONE));
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                press(app, KeyCode::Char(c));
+            }
+        };
+
+        // Select bits 8-23 of the second ID, little endian
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Down);
+        type_text(&mut app, "++++++++");
+        // A selection past the payload is ignored
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Left);
+        let draft = app.detail.as_ref().unwrap();
+        assert_eq!((draft.id, draft.start_bit, draft.length), (0x200, 8, 16));
+
+        // Letters go to the form while it is open
+        type_text(&mut app, "nSpeed");
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Backspace);
+        type_text(&mut app, "0.5");
+        assert!(!press(&mut app, KeyCode::Enter));
+        assert_eq!(app.status.as_deref(), Some("Defined signal Speed"));
+
+        assert!(!press(&mut app, KeyCode::Esc));
+        assert!(app.detail.is_none());
+        app.refresh();
+        assert!(app.frames[&0x200].line.ends_with("Speed = 2330.0"));
+
+        press(&mut app, KeyCode::Char('x'));
+        let text = std::fs::read_to_string(&app.export_path).unwrap();
+        std::fs::remove_file(&app.export_path).unwrap();
+        let db = SignalDatabase::from_dbc(&text).unwrap();
+        let signal = db
+            .message_by_name("MSG_200")
+            .unwrap()
+            .signal("Speed")
+            .unwrap();
+        assert_eq!(
+            (signal.start_bit, signal.length, signal.factor),
+            (8, 16, 0.5)
+        );
+
+        assert!(press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn bus_off_banner() {
+        let mut app = App::new("vcan0".into(), None, None);
+        assert!(bus_banner(&app).is_none());
+
+        // A bus-off error frame, then the controller restarting
+        for class in [0x40, 0x100] {
+            app.update(ReceivedFrame {
+                frame: CanFrame::new_error(class, &[0; 8]).unwrap(),
+                recv_time: Instant::now(),
+            });
+        }
+
+        // Error frames are not shown as IDs, the banner stays after recovery
+        assert!(app.frames.is_empty());
+        let banner = bus_banner(&app).unwrap();
+        assert!(banner.0[0].content.contains("bus-off 1 times"));
+    }
 }
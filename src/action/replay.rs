@@ -0,0 +1,142 @@
+//
+// replay.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    action::rewrite::RewriteArgs,
+    drivers::{file::FileDriver, AsyncCanDriverPtr, CanDriver},
+    logfile::{
+        self,
+        csv::{CsvColumns, CsvOptions, TimeUnit},
+        LogError, LogFormat, ReadOptions,
+    },
+    CommandContext,
+};
+
+use clap::{Args as ClapArgs, Parser};
+use tokio::time::{self, Instant};
+
+use std::path::PathBuf;
+
+/// Arguments for the replay command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Log file to replay
+    file: PathBuf,
+    /// Send frames as fast as possible instead of with their logged timing
+    #[arg(long = "no-timing")]
+    no_timing: bool,
+    /// Only replay frames logged on this zero-based channel
+    #[arg(long = "channel")]
+    channel: Option<u8>,
+    #[command(flatten)]
+    rewrite: RewriteArgs,
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+/// Options for reading log files
+#[derive(Debug, ClapArgs)]
+pub struct InputArgs {
+    /// Skip malformed lines of candump logs and CSV files instead of stopping
+    #[arg(long = "tolerant")]
+    tolerant: bool,
+    /// Format of the log file (blf, trc, candump, csv), detected from its content if not set
+    #[arg(long = "input-format")]
+    input_format: Option<LogFormat>,
+    /// Columns of CSV files, e.g. timestamp=Time,id=ID,dlc=DLC,data=Data or data=B0+B1+B2, by
+    /// header name or zero-based index. Unmapped fields are found by common header names.
+    #[arg(long = "csv-columns")]
+    csv_columns: Option<CsvColumns>,
+    /// Unit of CSV timestamps (s, ms, us, ns)
+    #[arg(long = "csv-time-unit", default_value = "s")]
+    csv_time_unit: TimeUnit,
+}
+
+impl InputArgs {
+    pub fn options(&self) -> ReadOptions {
+        ReadOptions {
+            format: self.input_format,
+            tolerant: self.tolerant,
+            csv: CsvOptions {
+                columns: self.csv_columns.clone().unwrap_or_default(),
+                time_unit: self.csv_time_unit,
+            },
+        }
+    }
+}
+
+/// Options for receiving from a log file instead of an interface
+#[derive(Debug, ClapArgs)]
+pub struct FileSourceArgs {
+    /// Read frames from a log file instead of the CAN interface
+    #[arg(long = "from-file")]
+    pub from_file: Option<PathBuf>,
+    /// Keep reading the frames appended to the `--from-file` log, as another process writes it
+    #[arg(long = "follow", requires = "from_file")]
+    pub follow: bool,
+    #[command(flatten)]
+    pub input: InputArgs,
+}
+
+impl FileSourceArgs {
+    /// Name of the log file and the driver receiving its frames, if one is set
+    pub fn open(&self) -> Result<Option<(String, CanDriver)>, LogError> {
+        let Some(path) = &self.from_file else {
+            return Ok(None);
+        };
+        let driver = FileDriver::open(path, &self.input.options(), self.follow)?;
+        let driver = CanDriver::from(Box::new(driver) as AsyncCanDriverPtr);
+        Ok(Some((path.display().to_string(), driver)))
+    }
+}
+
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let mut driver = ctx.driver;
+    let log = logfile::open_with(&args.file, &args.input.options())?;
+
+    let start = Instant::now();
+    let mut first = None;
+    let mut sent = 0usize;
+    let mut skipped = 0usize;
+    let mut rewritten = 0usize;
+
+    for entry in log {
+        let mut entry = entry?;
+
+        if args.channel.is_some() && entry.channel != args.channel {
+            continue;
+        }
+
+        if !args.rewrite.is_empty() && args.rewrite.apply(&mut entry) {
+            rewritten += 1;
+        }
+
+        // Only classic frames can be sent by the drivers
+        let Some(frame) = entry.to_frame() else {
+            skipped += 1;
+            continue;
+        };
+
+        if !args.no_timing {
+            let first = *first.get_or_insert(entry.timestamp);
+            time::sleep_until(start + entry.timestamp.saturating_sub(first)).await;
+        }
+
+        driver.send(frame).await;
+        sent += 1;
+    }
+
+    println!("Replayed {} frames", sent);
+    if rewritten > 0 {
+        println!("Rewrote {} frames", rewritten);
+    }
+    if skipped > 0 {
+        eprintln!("Skipped {} CAN FD or error frames", skipped);
+    }
+
+    Ok(())
+}
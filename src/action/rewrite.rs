@@ -0,0 +1,307 @@
+//
+// rewrite.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! ID and payload rewriting of replayed frames
+//!
+//! ```text
+//! --map 0x100=0x200
+//! --set "id==0x18F00400 => data[3]=0xFF"
+//! --set "sa==0x00 && data[0]==0x01 => sa=0x21; data[1]=0"
+//! ```
+//!
+//! Maps are applied first, then the rules in the order given, each seeing the frame as left by
+//! the ones before it.
+
+use crate::{logfile::LogFrame, utils};
+
+use clap::Args;
+use embedded_can::{ExtendedId, Id, StandardId};
+
+use std::str::FromStr;
+
+/// Largest extended ID
+const MAX_ID: u32 = 0x1FFF_FFFF;
+
+/// Rewriting options of the replay command
+#[derive(Debug, Clone, Default, Args)]
+pub struct RewriteArgs {
+    /// Replace an ID, as <from>=<to>, e.g. 0x100=0x200. Can be repeated.
+    #[arg(long = "map")]
+    pub map: Vec<IdMap>,
+    /// Rewrite the frames matching a condition, e.g. "id==0x18F00400 => data[3]=0xFF".
+    /// Conditions and assignments are on id, sa (the J1939 source address) and data[N], joined
+    /// with && and ;. Can be repeated, rules are applied in order after the maps.
+    #[arg(long = "set")]
+    pub set: Vec<RewriteRule>,
+}
+
+impl RewriteArgs {
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty() && self.set.is_empty()
+    }
+
+    /// Rewrite a frame, returning whether anything changed
+    pub fn apply(&self, frame: &mut LogFrame) -> bool {
+        let (id, data) = (frame.id, frame.data.clone());
+
+        if let Some(map) = self
+            .map
+            .iter()
+            .find(|map| utils::id_to_raw(&frame.id) == map.from)
+        {
+            frame.id = make_id(frame.id, map.to);
+        }
+        for rule in &self.set {
+            rule.apply(frame);
+        }
+
+        frame.id != id || frame.data != data
+    }
+}
+
+/// Replace one ID with another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdMap {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl FromStr for IdMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected <from>=<to>, got '{}'", s))?;
+        Ok(IdMap {
+            from: parse_value(from, MAX_ID)?,
+            to: parse_value(to, MAX_ID)?,
+        })
+    }
+}
+
+/// Part of a frame a rule tests or sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    /// Source address, the low byte of an extended ID
+    Sa,
+    Data(usize),
+}
+
+impl Field {
+    fn max(self) -> u32 {
+        match self {
+            Field::Id => MAX_ID,
+            Field::Sa | Field::Data(_) => 0xFF,
+        }
+    }
+
+    fn get(self, frame: &LogFrame) -> Option<u32> {
+        match (self, frame.id) {
+            (Field::Id, id) => Some(utils::id_to_raw(&id)),
+            (Field::Sa, Id::Extended(id)) => Some(id.as_raw() & 0xFF),
+            (Field::Sa, Id::Standard(_)) => None,
+            (Field::Data(i), _) => frame.data.get(i).map(|&byte| byte as u32),
+        }
+    }
+
+    /// Set the field, bytes past the end of the data are left alone
+    fn set(self, frame: &mut LogFrame, value: u32) {
+        match (self, frame.id) {
+            (Field::Id, id) => frame.id = make_id(id, value),
+            (Field::Sa, Id::Extended(id)) => {
+                frame.id = make_id(frame.id, id.as_raw() & !0xFF | value);
+            }
+            (Field::Sa, Id::Standard(_)) => {}
+            (Field::Data(i), _) => {
+                if let Some(byte) = frame.data.get_mut(i) {
+                    *byte = value as u8;
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "id" => Ok(Field::Id),
+            "sa" => Ok(Field::Sa),
+            _ => s
+                .strip_prefix("data[")
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|i| i.trim().parse::<usize>().ok())
+                .filter(|&i| i < 64)
+                .map(Field::Data)
+                .ok_or_else(|| format!("unknown field '{}', expected id, sa or data[N]", s)),
+        }
+    }
+}
+
+/// Assignments applied to the frames matching all conditions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    conditions: Vec<(Field, u32)>,
+    assignments: Vec<(Field, u32)>,
+}
+
+impl RewriteRule {
+    fn matches(&self, frame: &LogFrame) -> bool {
+        self.conditions
+            .iter()
+            .all(|&(field, value)| field.get(frame) == Some(value))
+    }
+
+    fn apply(&self, frame: &mut LogFrame) {
+        if self.matches(frame) {
+            for &(field, value) in &self.assignments {
+                field.set(frame, value);
+            }
+        }
+    }
+}
+
+/// Parse "<field>==<value> [&& ...] => <field>=<value>[; ...]", `*` matching every frame
+impl FromStr for RewriteRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (conditions, assignments) = s
+            .split_once("=>")
+            .ok_or_else(|| format!("expected '<condition> => <assignments>', got '{}'", s))?;
+
+        let conditions = match conditions.trim() {
+            "*" => vec![],
+            conditions => conditions
+                .split("&&")
+                .map(|condition| {
+                    let (field, value) = condition
+                        .split_once("==")
+                        .ok_or_else(|| format!("expected <field>==<value>, got '{}'", condition))?;
+                    field_value(field, value)
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        let assignments = assignments
+            .split(';')
+            .filter(|assignment| !assignment.trim().is_empty())
+            .map(|assignment| {
+                let (field, value) = assignment
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected <field>=<value>, got '{}'", assignment))?;
+                field_value(field, value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if assignments.is_empty() {
+            return Err(format!("no assignments in '{}'", s));
+        }
+
+        Ok(RewriteRule {
+            conditions,
+            assignments,
+        })
+    }
+}
+
+fn field_value(field: &str, value: &str) -> Result<(Field, u32), String> {
+    let field = field.parse::<Field>()?;
+    Ok((field, parse_value(value, field.max())?))
+}
+
+/// Parse a decimal or 0x prefixed hex value up to `max`
+fn parse_value(s: &str, max: u32) -> Result<u32, String> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+    .filter(|&value| value <= max)
+    .ok_or_else(|| format!("invalid value '{}'", s))
+}
+
+/// ID with the raw value, extended if the original was or the value needs it
+fn make_id(original: Id, raw: u32) -> Id {
+    match original {
+        Id::Standard(_) if raw <= 0x7FF => Id::Standard(StandardId::new(raw as u16).unwrap()),
+        _ => Id::Extended(ExtendedId::new(raw).unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn frame(id: Id, data: &[u8]) -> LogFrame {
+        LogFrame::new(Duration::ZERO, id, data)
+    }
+
+    fn extended(id: u32) -> Id {
+        ExtendedId::new(id).unwrap().into()
+    }
+
+    fn standard(id: u16) -> Id {
+        StandardId::new(id).unwrap().into()
+    }
+
+    #[test]
+    fn parse_rules() {
+        let rule = "sa==0x00 && data[0]==1 => sa=0x21; data[1]=0xFF"
+            .parse::<RewriteRule>()
+            .unwrap();
+        assert_eq!(rule.conditions, vec![(Field::Sa, 0), (Field::Data(0), 1)]);
+        assert_eq!(
+            rule.assignments,
+            vec![(Field::Sa, 0x21), (Field::Data(1), 0xFF)]
+        );
+
+        assert!("* => data[0]=1".parse::<RewriteRule>().is_ok());
+        assert!("id==0x100".parse::<RewriteRule>().is_err());
+        assert!("id=0x100 => data[0]=1".parse::<RewriteRule>().is_err());
+        assert!("id==0x100 => data[0]=0x100".parse::<RewriteRule>().is_err());
+        assert!("id==0x100 => crc=1".parse::<RewriteRule>().is_err());
+        assert!("id==0x100 => ".parse::<RewriteRule>().is_err());
+
+        assert_eq!(
+            "0x100=0x200".parse::<IdMap>(),
+            Ok(IdMap {
+                from: 0x100,
+                to: 0x200
+            })
+        );
+        assert!("0x100".parse::<IdMap>().is_err());
+    }
+
+    #[test]
+    fn rewrite_frames() {
+        let args = RewriteArgs {
+            map: vec!["0x100=0x1800".parse().unwrap()],
+            set: vec![
+                "id==0x18F00400 => data[3]=0xFF; data[9]=1".parse().unwrap(),
+                "id==0x18F00400 => sa=0x21".parse().unwrap(),
+            ],
+        };
+
+        let mut eec1 = frame(extended(0x18F00400), &[0, 1, 2, 3]);
+        assert!(args.apply(&mut eec1));
+        assert_eq!(eec1.id, extended(0x18F00421));
+        assert_eq!(eec1.data, vec![0, 1, 2, 0xFF]);
+
+        // Mapped past the standard range
+        let mut mapped = frame(standard(0x100), &[1]);
+        assert!(args.apply(&mut mapped));
+        assert_eq!(mapped.id, extended(0x1800));
+
+        let mut other = frame(standard(0x200), &[1]);
+        assert!(!args.apply(&mut other));
+    }
+}
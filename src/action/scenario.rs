@@ -0,0 +1,683 @@
+//
+// scenario.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Scripted send and expect tests against a device, reported as JUnit XML
+//!
+//! ```toml
+//! name = "engine-ecu"
+//! dbc = "engine.dbc"
+//!
+//! [[test]]
+//! name = "tester present"
+//! steps = [
+//!     { send = "7E0#023E00" },
+//!     { expect = "7E8#027EXX", timeout = "100ms" },
+//! ]
+//!
+//! [[test]]
+//! name = "idle"
+//! steps = [
+//!     { signal = "EngineSpeed", value = 800, tolerance = 25, timeout = "2s" },
+//!     { where = "Gear == PARK" },
+//!     { wait = "500ms" },
+//! ]
+//! ```
+//!
+//! An expectation takes the received frames in order, starting after those taken by the previous
+//! one, until one matches or its timeout passes. `XX` in expected data matches any byte.
+
+use crate::{
+    drivers::CanDriver,
+    format::format_signals,
+    frame::{CanFrame, Direction},
+    logfile::{self, candump, LogFrame, LogWriter, WriteOptions},
+    utils::{self, parse_duration},
+    CommandContext,
+};
+
+use candecode::{Database as SignalDatabase, Expr};
+use clap::Parser;
+use embedded_can::{Frame, Id};
+use tokio::time::{timeout_at, Instant};
+
+use std::{
+    fmt::{self, Write as _},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// Time an expectation waits if the step does not set one
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Arguments for the test command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Scenario file (TOML) of the frames to send and the frames and signals expected back
+    scenario: PathBuf,
+    /// Write the results to a JUnit XML file
+    #[arg(long = "junit")]
+    junit: Option<PathBuf>,
+    /// Record the frames sent and received to a log file (TRC, MF4, candump or Parquet)
+    #[arg(long = "log")]
+    log: Option<PathBuf>,
+}
+
+/// Test cases and the database their signals are decoded with
+struct Scenario {
+    name: String,
+    dbc: Option<SignalDatabase>,
+    tests: Vec<TestCase>,
+}
+
+struct TestCase {
+    name: String,
+    steps: Vec<Step>,
+}
+
+enum Step {
+    Send(CanFrame),
+    Wait(Duration),
+    Expect {
+        expected: Expectation,
+        timeout: Duration,
+    },
+}
+
+/// What an expect step waits for
+enum Expectation {
+    Frame(FramePattern),
+    /// A signal value, within the tolerance
+    Signal {
+        signal: String,
+        value: f64,
+        tolerance: f64,
+    },
+    /// A frame whose signals match the expression
+    Where(Expr),
+}
+
+/// An ID and data, `None` bytes match anything
+#[derive(Debug, PartialEq)]
+struct FramePattern {
+    id: Id,
+    data: Vec<Option<u8>>,
+}
+
+impl FramePattern {
+    fn matches(&self, frame: &CanFrame) -> bool {
+        frame.id() == self.id
+            && frame.data().len() == self.data.len()
+            && frame
+                .data()
+                .iter()
+                .zip(&self.data)
+                .all(|(byte, expected)| expected.is_none_or(|expected| expected == *byte))
+    }
+}
+
+impl fmt::Display for FramePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#", utils::format_id(&self.id))?;
+        for byte in &self.data {
+            match byte {
+                Some(byte) => write!(f, "{:02X}", byte)?,
+                None => write!(f, "XX")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Expectation {
+    /// Whether the frame meets the expectation, noting what it showed in `seen` if relevant
+    fn matches(&self, db: Option<&SignalDatabase>, frame: &CanFrame, seen: &mut String) -> bool {
+        match self {
+            Expectation::Frame(pattern) => {
+                if frame.id() == pattern.id {
+                    *seen = candump::format_frame(&LogFrame::from_frame(Duration::ZERO, frame));
+                }
+                pattern.matches(frame)
+            }
+            Expectation::Signal {
+                signal,
+                value,
+                tolerance,
+            } => {
+                let values = db.map(|db| db.decode(frame.id(), frame.data()));
+                let Some(actual) = values
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|v| v.signal.name == *signal)
+                else {
+                    return false;
+                };
+                *seen = actual.to_string();
+                (actual.value - value).abs() <= *tolerance
+            }
+            Expectation::Where(expr) => db.is_some_and(|db| {
+                let Some(message) = db.message(frame.id()) else {
+                    return false;
+                };
+                let carries = expr.signals().iter().any(|signal| {
+                    signal.message.as_ref().is_none_or(|m| *m == message.name)
+                        && message.signals.iter().any(|s| s.name == signal.signal)
+                });
+                if carries {
+                    *seen = format_signals(db, frame, " ", false).trim().to_string();
+                }
+                expr.matches(db, frame.id(), frame.data())
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expectation::Frame(pattern) => write!(f, "{}", pattern),
+            Expectation::Signal {
+                signal,
+                value,
+                tolerance,
+            } if *tolerance > 0.0 => write!(f, "{} = {} ± {}", signal, value, tolerance),
+            Expectation::Signal { signal, value, .. } => write!(f, "{} = {}", signal, value),
+            Expectation::Where(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl Scenario {
+    /// Load a scenario, its database relative to the file
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Scenario::parse(&text, &name, |dbc| {
+            SignalDatabase::load(dir.join(dbc)).map_err(|e| e.to_string())
+        })
+        .map_err(|e| anyhow::anyhow!("Invalid scenario {}: {}", path.display(), e))
+    }
+
+    fn parse(
+        text: &str,
+        name: &str,
+        load_dbc: impl FnOnce(&str) -> Result<SignalDatabase, String>,
+    ) -> Result<Self, String> {
+        let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+
+        let name = match table.get("name") {
+            Some(name) => name.as_str().ok_or("'name' must be a string")?,
+            None => name,
+        };
+        let dbc = match table.get("dbc") {
+            Some(dbc) => Some(load_dbc(dbc.as_str().ok_or("'dbc' must be a path")?)?),
+            None => None,
+        };
+
+        let tests = table
+            .get("test")
+            .and_then(|tests| tests.as_array())
+            .filter(|tests| !tests.is_empty())
+            .ok_or("no [[test]] cases")?;
+
+        let mut cases = vec![];
+        for (i, test) in tests.iter().enumerate() {
+            let name = test
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(String::from)
+                .unwrap_or_else(|| format!("test {}", i + 1));
+            let steps = test
+                .get("steps")
+                .and_then(|steps| steps.as_array())
+                .ok_or_else(|| format!("{}: no steps", name))?;
+
+            let steps = steps
+                .iter()
+                .enumerate()
+                .map(|(n, step)| {
+                    parse_step(step, dbc.as_ref())
+                        .map_err(|e| format!("{}, step {}: {}", name, n + 1, e))
+                })
+                .collect::<Result<_, _>>()?;
+            cases.push(TestCase { name, steps });
+        }
+
+        Ok(Scenario {
+            name: name.to_string(),
+            dbc,
+            tests: cases,
+        })
+    }
+}
+
+fn parse_step(step: &toml::Value, dbc: Option<&SignalDatabase>) -> Result<Step, String> {
+    let text = |key: &str| match step.get(key) {
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be a string", key)),
+        None => Ok(None),
+    };
+    let number = |key: &str| match step.get(key) {
+        Some(value) => value
+            .as_float()
+            .or_else(|| value.as_integer().map(|i| i as f64))
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be a number", key)),
+        None => Ok(None),
+    };
+    let timeout = text("timeout")?
+        .map(parse_duration)
+        .transpose()?
+        .unwrap_or(DEFAULT_TIMEOUT);
+    let require_dbc = |kind| match dbc {
+        Some(db) => Ok(db),
+        None => Err(format!("{} steps need a dbc", kind)),
+    };
+
+    let expected = if let Some(frame) = text("send")? {
+        let frame = candump::parse_frame(frame)
+            .ok()
+            .and_then(|frame| frame.to_frame())
+            .ok_or_else(|| format!("invalid frame '{}'", frame))?;
+        return Ok(Step::Send(frame));
+    } else if let Some(wait) = text("wait")? {
+        return Ok(Step::Wait(parse_duration(wait)?));
+    } else if let Some(frame) = text("expect")? {
+        Expectation::Frame(parse_pattern(frame)?)
+    } else if let Some(signal) = text("signal")? {
+        let db = require_dbc("signal")?;
+        if !db
+            .messages()
+            .iter()
+            .any(|m| m.signals.iter().any(|s| s.name == signal))
+        {
+            return Err(format!("unknown signal '{}'", signal));
+        }
+        Expectation::Signal {
+            signal: signal.to_string(),
+            value: number("value")?.ok_or("signal steps need a value")?,
+            tolerance: number("tolerance")?.unwrap_or(0.0).abs(),
+        }
+    } else if let Some(expr) = text("where")? {
+        require_dbc("where")?;
+        Expectation::Where(expr.parse::<Expr>().map_err(|e| e.to_string())?)
+    } else {
+        return Err("expected send, wait, expect, signal or where".into());
+    };
+
+    Ok(Step::Expect { expected, timeout })
+}
+
+/// Parse a candump style frame, `XX` data bytes match any value
+fn parse_pattern(text: &str) -> Result<FramePattern, String> {
+    let invalid = || format!("invalid frame '{}'", text);
+    let (id, data) = text.split_once('#').ok_or_else(invalid)?;
+
+    let digits = data.chars().filter(|&c| c != '.').collect::<String>();
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let wildcards = digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| pair.eq_ignore_ascii_case(b"XX"))
+        .collect::<Vec<_>>();
+
+    // Parse with the wildcards zeroed, for the candump rules on IDs, lengths and hex digits
+    let zeroed = digits
+        .as_bytes()
+        .chunks(2)
+        .zip(&wildcards)
+        .flat_map(|(pair, &wildcard)| if wildcard { b"00" } else { pair })
+        .map(|&b| b as char)
+        .collect::<String>();
+    let frame = candump::parse_frame(&format!("{}#{}", id, zeroed)).map_err(|_| invalid())?;
+
+    let data = frame
+        .data
+        .iter()
+        .zip(wildcards)
+        .map(|(byte, wildcard)| (!wildcard).then_some(*byte))
+        .collect();
+    Ok(FramePattern { id: frame.id, data })
+}
+
+/// Outcome of a test case
+struct TestResult {
+    name: String,
+    time: Duration,
+    failure: Option<String>,
+}
+
+/// Runs the steps of test cases on the interface
+struct Runner<'a> {
+    driver: CanDriver,
+    db: Option<&'a SignalDatabase>,
+    log: Option<&'a mut Box<dyn LogWriter>>,
+    start: SystemTime,
+}
+
+impl Runner<'_> {
+    /// Run a test case, returning why it failed
+    async fn run(&mut self, test: &TestCase) -> anyhow::Result<Option<String>> {
+        for (i, step) in test.steps.iter().enumerate() {
+            let failure = match step {
+                Step::Send(frame) => {
+                    self.driver.send(frame.clone()).await;
+                    self.record(&frame.clone().with_direction(Direction::Tx))?;
+                    None
+                }
+                Step::Wait(duration) => {
+                    tokio::time::sleep(*duration).await;
+                    None
+                }
+                Step::Expect { expected, timeout } => self.expect(expected, *timeout).await?,
+            };
+
+            if let Some(failure) = failure {
+                return Ok(Some(format!("step {}: {}", i + 1, failure)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn expect(
+        &mut self,
+        expected: &Expectation,
+        timeout: Duration,
+    ) -> anyhow::Result<Option<String>> {
+        let deadline = Instant::now() + timeout;
+        let mut seen = String::new();
+
+        let res = timeout_at(deadline, async {
+            while let Some(frame) = self.driver.recv().await {
+                self.record(&frame)?;
+                if frame.is_error_frame() || frame.is_tx() {
+                    continue;
+                }
+                if expected.matches(self.db, &frame, &mut seen) {
+                    return Ok(true);
+                }
+            }
+            Ok::<_, anyhow::Error>(false)
+        })
+        .await;
+
+        let failure = match res {
+            Ok(Ok(true)) => return Ok(None),
+            Ok(Ok(false)) => "the interface closed".to_string(),
+            Ok(Err(e)) => return Err(e),
+            Err(_) if seen.is_empty() => "nothing matching received".to_string(),
+            Err(_) => format!("last received {}", seen),
+        };
+        Ok(Some(format!(
+            "expected {} within {:?}, {}",
+            expected, timeout, failure
+        )))
+    }
+
+    fn record(&mut self, frame: &CanFrame) -> anyhow::Result<()> {
+        if let Some(log) = self.log.as_mut() {
+            let timestamp = frame
+                .timestamp()
+                .unwrap_or_else(SystemTime::now)
+                .duration_since(self.start)
+                .unwrap_or_default();
+            log.write(&LogFrame::from_frame(timestamp, frame))?;
+        }
+        Ok(())
+    }
+}
+
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let scenario = Scenario::load(&args.scenario)?;
+
+    let options = WriteOptions {
+        database: scenario.dbc.clone(),
+        ..Default::default()
+    };
+    let mut log = args
+        .log
+        .map(|path| logfile::create(path, &options))
+        .transpose()?;
+
+    let mut runner = Runner {
+        driver: ctx.driver,
+        db: scenario.dbc.as_ref(),
+        log: log.as_mut(),
+        start: options.start_time,
+    };
+    let results = run_tests(&mut runner, &scenario.tests).await;
+    drop(runner);
+
+    if let Some(log) = log.as_mut() {
+        log.finish()?;
+    }
+    let results = results?;
+    if let Some(path) = &args.junit {
+        fs::write(path, junit(&scenario.name, &results))?;
+    }
+
+    let failed = results.iter().filter(|r| r.failure.is_some()).count();
+    println!("{} passed, {} failed", results.len() - failed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} tests failed", failed, results.len());
+    }
+
+    Ok(())
+}
+
+async fn run_tests(runner: &mut Runner<'_>, tests: &[TestCase]) -> anyhow::Result<Vec<TestResult>> {
+    let mut results = vec![];
+    for test in tests {
+        let start = Instant::now();
+        let failure = runner.run(test).await?;
+        let time = start.elapsed();
+
+        match &failure {
+            None => println!("PASS {} ({:.3}s)", test.name, time.as_secs_f64()),
+            Some(failure) => println!(
+                "FAIL {} ({:.3}s): {}",
+                test.name,
+                time.as_secs_f64(),
+                failure
+            ),
+        }
+        results.push(TestResult {
+            name: test.name.clone(),
+            time,
+            failure,
+        });
+    }
+
+    Ok(results)
+}
+
+/// JUnit XML report of one suite
+fn junit(suite: &str, results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|r| r.failure.is_some()).count();
+    let time = results.iter().map(|r| r.time.as_secs_f64()).sum::<f64>();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        results.len(),
+        failures,
+        time
+    );
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        escape(suite),
+        results.len(),
+        failures,
+        time
+    );
+    for result in results {
+        let _ = write!(
+            out,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&result.name),
+            escape(suite),
+            result.time.as_secs_f64()
+        );
+        let _ = match &result.failure {
+            Some(failure) => writeln!(
+                out,
+                ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                escape(failure)
+            ),
+            None => writeln!(out, "/>"),
+        };
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::drivers::loopback::LoopbackDriver;
+
+    use embedded_can::StandardId;
+
+    const DBC: &str = r#"
+BO_ 256 Engine: 8 Vector__XXX
+ SG_ EngineSpeed : 0|16@1+ (1,0) [0|65535] "rpm" Vector__XXX
+"#;
+
+    const SCENARIO: &str = r#"
+dbc = "engine.dbc"
+
+[[test]]
+name = "tester present"
+steps = [
+    { send = "7E0#023E00" },
+    { expect = "7E8#027EXX", timeout = "100ms" },
+]
+
+[[test]]
+name = "idle"
+steps = [
+    { wait = "10ms" },
+    { signal = "EngineSpeed", value = 800, tolerance = 25, timeout = "100ms" },
+    { where = "EngineSpeed > 900", timeout = "50ms" },
+]
+
+[[test]]
+name = "no response"
+steps = [{ expect = "7E9#XX", timeout = "10ms" }]
+"#;
+
+    fn scenario(text: &str) -> Result<Scenario, String> {
+        Scenario::parse(text, "engine", |_| {
+            SignalDatabase::from_dbc(DBC).map_err(|e| e.to_string())
+        })
+    }
+
+    fn frame(id: u16, data: &[u8]) -> CanFrame {
+        CanFrame::new(StandardId::new(id).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn parse_scenarios() {
+        let scenario = scenario(SCENARIO).unwrap();
+        assert_eq!(scenario.name, "engine");
+        assert_eq!(scenario.tests.len(), 3);
+        assert_eq!(scenario.tests[1].steps.len(), 3);
+
+        assert!(self::scenario("").is_err());
+        assert!(self::scenario("[[test]]\nsteps = [{ send = \"7E0\" }]").is_err());
+        assert!(self::scenario("[[test]]\nsteps = [{ beep = \"7E0\" }]").is_err());
+        assert!(Scenario::parse(
+            "[[test]]\nsteps = [{ signal = \"EngineSpeed\", value = 1 }]",
+            "engine",
+            |_| unreachable!()
+        )
+        .is_err());
+        assert!(self::scenario(
+            "dbc = \"a.dbc\"\n[[test]]\nsteps = [{ signal = \"Missing\", value = 1 }]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn frame_patterns() {
+        let pattern = parse_pattern("7E8#02.7E.xx").unwrap();
+        assert_eq!(pattern.to_string(), "7E8#027EXX");
+        assert!(pattern.matches(&frame(0x7E8, &[0x02, 0x7E, 0x55])));
+        assert!(!pattern.matches(&frame(0x7E8, &[0x02, 0x7F, 0x55])));
+        assert!(!pattern.matches(&frame(0x7E8, &[0x02, 0x7E])));
+        assert!(!pattern.matches(&frame(0x7E0, &[0x02, 0x7E, 0x55])));
+
+        assert!(parse_pattern("7E8#0").is_err());
+        assert!(parse_pattern("7E8").is_err());
+        assert!(parse_pattern("7E8#aé1").is_err());
+        assert!(parse_pattern("7E8#éé").is_err());
+    }
+
+    #[tokio::test]
+    async fn run_scenario() {
+        let scenario = scenario(SCENARIO).unwrap();
+        let (a, mut ecu) = LoopbackDriver::pair();
+
+        // Answers tester present and reports an idle speed of 790 rpm
+        tokio::spawn(async move {
+            while let Some(request) = ecu.recv().await {
+                if request.data() == [0x02, 0x3E, 0x00] {
+                    ecu.send(frame(0x7E8, &[0x02, 0x7E, 0x00])).await;
+                    ecu.send(frame(0x100, &790u16.to_le_bytes())).await;
+                    ecu.send(frame(0x100, &795u16.to_le_bytes())).await;
+                }
+            }
+        });
+
+        let mut runner = Runner {
+            driver: a.into(),
+            db: scenario.dbc.as_ref(),
+            log: None,
+            start: SystemTime::now(),
+        };
+        let results = run_tests(&mut runner, &scenario.tests).await.unwrap();
+
+        assert_eq!(results[0].failure, None);
+        assert_eq!(
+            results[1].failure.as_deref(),
+            Some(
+                "step 3: expected EngineSpeed > 900 within 50ms, last received \
+                 Engine EngineSpeed = 795 rpm"
+            )
+        );
+        assert_eq!(
+            results[2].failure.as_deref(),
+            Some("step 1: expected 7E9#XX within 10ms, nothing matching received")
+        );
+
+        let report = junit(&scenario.name, &results);
+        assert!(report.contains("<testsuite name=\"engine\" tests=\"3\" failures=\"2\""));
+        assert!(report.contains("<failure message=\"step 3: expected EngineSpeed &gt; 900"));
+    }
+}
@@ -0,0 +1,225 @@
+//
+// script.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Rhai scripts rewriting forwarded frames
+//!
+//! A script defines `on_frame(frame)`, taking a map with `id`, `extended`, `remote` and `data`
+//! (a blob) and returning the frame to forward, or `()` to drop it.
+
+use crate::frame::CanFrame;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use rhai::{Blob, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+use thiserror::Error;
+
+use std::path::{Path, PathBuf};
+
+/// Name of the function called for every frame
+const ENTRY_POINT: &str = "on_frame";
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Failed to load script {0}: {1}")]
+    Load(PathBuf, String),
+    #[error("Script does not define {ENTRY_POINT}(frame)")]
+    MissingEntryPoint,
+    #[error("Script error: {0}")]
+    Eval(#[from] Box<EvalAltResult>),
+    #[error("Script returned an invalid frame: {0}")]
+    InvalidFrame(&'static str),
+}
+
+/// A compiled script, called for every frame
+pub struct FrameScript {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+}
+
+impl FrameScript {
+    pub fn load(path: &Path) -> Result<Self, ScriptError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| ScriptError::Load(path.to_owned(), e.to_string()))?;
+        Self::compile(&source).map_err(|e| match e {
+            ScriptError::Eval(e) => ScriptError::Load(path.to_owned(), e.to_string()),
+            e => e,
+        })
+    }
+
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(Box::<EvalAltResult>::from)?;
+
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == ENTRY_POINT && f.params.len() == 1)
+        {
+            return Err(ScriptError::MissingEntryPoint);
+        }
+
+        // Top level statements run once, e.g. to set up lookup tables
+        let mut scope = Scope::new();
+        engine.run_ast_with_scope(&mut scope, &ast)?;
+
+        Ok(FrameScript { engine, ast, scope })
+    }
+
+    /// Run the script on a frame, `None` if it was dropped
+    pub fn apply(&mut self, frame: &CanFrame) -> Result<Option<CanFrame>, ScriptError> {
+        let options = CallFnOptions::new().eval_ast(false);
+        let res: Dynamic = self.engine.call_fn_with_options(
+            options,
+            &mut self.scope,
+            &self.ast,
+            ENTRY_POINT,
+            (to_map(frame),),
+        )?;
+
+        if res.is_unit() {
+            return Ok(None);
+        }
+        let map = res
+            .try_cast::<Map>()
+            .ok_or(ScriptError::InvalidFrame("expected a frame map or ()"))?;
+
+        from_map(&map).map(Some)
+    }
+}
+
+fn to_map(frame: &CanFrame) -> Map {
+    let (id, extended) = match frame.id() {
+        Id::Standard(id) => (id.as_raw() as INT, false),
+        Id::Extended(id) => (id.as_raw() as INT, true),
+    };
+
+    let mut map = Map::new();
+    map.insert("id".into(), id.into());
+    map.insert("extended".into(), extended.into());
+    map.insert("remote".into(), frame.is_remote_frame().into());
+    map.insert("dlc".into(), (frame.raw_dlc() as INT).into());
+    map.insert("data".into(), Dynamic::from_blob(frame.data().to_vec()));
+    map
+}
+
+fn from_map(map: &Map) -> Result<CanFrame, ScriptError> {
+    let field = |name: &str| map.get(name).cloned().unwrap_or(Dynamic::UNIT);
+
+    let id = field("id")
+        .as_int()
+        .map_err(|_| ScriptError::InvalidFrame("id must be an integer"))?;
+    let extended = field("extended").as_bool().unwrap_or(false);
+    let remote = field("remote").as_bool().unwrap_or(false);
+    let dlc = match field("dlc") {
+        dlc if dlc.is_unit() => None,
+        dlc => Some(
+            dlc.as_int()
+                .ok()
+                .and_then(|dlc| u8::try_from(dlc).ok())
+                .filter(|&dlc| dlc <= 15)
+                .ok_or(ScriptError::InvalidFrame(
+                    "dlc must be an integer from 0 to 15",
+                ))?,
+        ),
+    };
+    let data = field("data")
+        .try_cast::<Blob>()
+        .ok_or(ScriptError::InvalidFrame("data must be a blob"))?;
+
+    let id: Id = match extended {
+        true => u32::try_from(id)
+            .ok()
+            .and_then(ExtendedId::new)
+            .map(Id::Extended),
+        false => u16::try_from(id)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard),
+    }
+    .ok_or(ScriptError::InvalidFrame("id out of range"))?;
+
+    let frame = match remote {
+        true => CanFrame::new_remote(id, data.len()),
+        false => CanFrame::new(id, &data),
+    }
+    .ok_or(ScriptError::InvalidFrame("more than 8 data bytes"))?;
+
+    // A DLC above 8 is kept while the frame has the 8 bytes it implies, the data decides otherwise
+    Ok(match dlc {
+        Some(dlc) if dlc > 8 => frame.clone().with_raw_dlc(dlc).unwrap_or(frame),
+        _ => frame,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: u16, data: &[u8]) -> CanFrame {
+        CanFrame::new(StandardId::new(id).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn rewrite_and_drop() {
+        let mut script = FrameScript::compile(
+            r#"
+            fn on_frame(frame) {
+                if frame.id == 0x200 {
+                    return ();
+                }
+                if frame.id == 0x100 {
+                    frame.data[0] = 0x55;
+                    // Recompute the XOR checksum in the last byte
+                    let sum = 0;
+                    for i in 0..frame.data.len() - 1 {
+                        sum ^= frame.data[i];
+                    }
+                    frame.data[frame.data.len() - 1] = sum;
+                }
+                frame
+            }
+            "#,
+        )
+        .unwrap();
+
+        let out = script.apply(&frame(0x100, &[0, 1, 2, 0])).unwrap().unwrap();
+        assert_eq!(out.data(), &[0x55, 1, 2, 0x55 ^ 1 ^ 2]);
+        assert_eq!(out.id(), frame(0x100, &[]).id());
+
+        assert!(script.apply(&frame(0x200, &[1])).unwrap().is_none());
+
+        let out = script.apply(&frame(0x300, &[7])).unwrap().unwrap();
+        assert_eq!(out.data(), &[7]);
+    }
+
+    #[test]
+    fn raw_dlc() {
+        let mut script = FrameScript::compile("fn on_frame(frame) { frame }").unwrap();
+        let dlc15 = frame(0x100, &[0; 8]).with_raw_dlc(15).unwrap();
+        assert_eq!(script.apply(&dlc15).unwrap().unwrap().raw_dlc(), 15);
+
+        // The data decides once it is no longer 8 bytes
+        let mut script =
+            FrameScript::compile("fn on_frame(frame) { frame.data.pop(); frame }").unwrap();
+        let out = script.apply(&dlc15).unwrap().unwrap();
+        assert_eq!(out.raw_dlc(), 7);
+    }
+
+    #[test]
+    fn invalid_scripts() {
+        assert!(matches!(
+            FrameScript::compile("fn other(frame) { frame }"),
+            Err(ScriptError::MissingEntryPoint)
+        ));
+
+        let mut script =
+            FrameScript::compile("fn on_frame(frame) { frame.id = 0x800; frame }").unwrap();
+        assert!(matches!(
+            script.apply(&frame(0x100, &[])),
+            Err(ScriptError::InvalidFrame(_))
+        ));
+    }
+}
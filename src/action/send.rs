@@ -5,10 +5,16 @@
 // @date Jan 16 2023
 //
 
-use crate::{drivers::AsyncCanDriverPtr, frame::CanFrame, CommandContext};
+use crate::{
+    drivers::CanDriver,
+    frame::{CanFrame, Direction},
+    logfile::candump,
+    supervisor::Supervisor,
+    CommandContext,
+};
 use clap::Parser;
+use embedded_can::Frame;
 
-use embedded_can::{Frame, StandardId};
 use std::time::Duration;
 use thiserror::Error;
 
@@ -16,6 +22,8 @@ use thiserror::Error;
 pub enum SendError {
     #[error("Failed to parse frame from input")]
     SyntaxError,
+    #[error("Frame was not confirmed as transmitted within {0:?}")]
+    NotConfirmed(Duration),
 }
 
 #[derive(Parser, Debug)]
@@ -23,6 +31,10 @@ pub struct Args {
     frame: String,
     #[clap(short = 'r', long = "rate", value_parser)]
     rate: Option<f32>,
+    /// Wait for the driver to confirm every frame was transmitted, failing after this many
+    /// milliseconds
+    #[arg(long = "confirm", num_args = 0..=1, default_missing_value = "1000")]
+    confirm: Option<u64>,
 }
 
 pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
@@ -32,22 +44,42 @@ pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
         .rate
         .filter(|&r| r != 0.0)
         .map(|r| Duration::from_secs_f32(1.0 / r));
+    let confirm = args.confirm.map(Duration::from_millis);
 
-    tokio::spawn(send_task(ctx.driver, frame, period));
-    tokio::signal::ctrl_c().await?;
+    let mut driver = ctx.driver;
+    if confirm.is_some() {
+        driver.receive_own_messages(true)?;
+    }
 
-    Ok(())
+    // A single frame ends the command once sent, periodic sends run until stopped or a failed
+    // confirmation
+    let mut supervisor = Supervisor::new();
+    let task = send_task(driver, frame, period, confirm);
+    match period {
+        Some(_) => supervisor.spawn(task),
+        None => supervisor.spawn_main(task),
+    }
+    supervisor.run().await
 }
 
 async fn send_task(
-    mut driver: AsyncCanDriverPtr,
+    mut driver: CanDriver,
     frame: CanFrame,
     dur: Option<Duration>,
+    confirm: Option<Duration>,
 ) -> anyhow::Result<()> {
     loop {
-        driver.send(frame.clone().into()).await;
+        driver.send(frame.clone()).await;
+
+        if let Some(timeout) = confirm {
+            confirm_sent(&mut driver, &frame, timeout).await?;
+        }
 
         match dur {
+            None if confirm.is_some() => {
+                println!("Transmission confirmed");
+                break;
+            }
             None => break,
             Some(dur) => tokio::time::sleep(dur).await,
         }
@@ -56,39 +88,46 @@ async fn send_task(
     Ok(())
 }
 
-fn build_frame(text: &str) -> Result<CanFrame, SendError> {
-    let parts: Vec<_> = text.split('#').collect();
-    if parts.len() == 2 {
-        let id = u16::from_str_radix(parts[0], 16).map_err(|_| SendError::SyntaxError)?;
-
-        let body = parts[1];
-        if body.len() % 2 == 0 {
-            let data = body
-                .chars()
-                .collect::<Vec<char>>()
-                .chunks(2)
-                .map(|s| {
-                    u8::from_str_radix(String::from_iter(s).as_str(), 16)
-                        .map_err(|_| SendError::SyntaxError)
-                })
-                .collect::<Result<Vec<u8>, SendError>>()?;
-
-            StandardId::new(id)
-                .map(|id| CanFrame::new(id, &data[..]))
-                .flatten()
-                .ok_or(SendError::SyntaxError)
-        } else {
-            Err(SendError::SyntaxError)
+/// Wait for the driver to echo a sent frame back as `Tx`, skipping received frames
+async fn confirm_sent(
+    driver: &mut CanDriver,
+    frame: &CanFrame,
+    timeout: Duration,
+) -> Result<(), SendError> {
+    let echo = async {
+        while let Some(echo) = driver.recv().await {
+            if echo.direction() == Direction::Tx
+                && echo.id() == frame.id()
+                && echo.is_remote_frame() == frame.is_remote_frame()
+                && echo.data() == frame.data()
+            {
+                return true;
+            }
         }
-    } else {
-        Err(SendError::SyntaxError)
+        false
+    };
+
+    match tokio::time::timeout(timeout, echo).await {
+        Ok(true) => Ok(()),
+        _ => Err(SendError::NotConfirmed(timeout)),
     }
 }
 
+fn build_frame(text: &str) -> Result<CanFrame, SendError> {
+    candump::parse_frame(text)
+        .ok()
+        .and_then(|frame| frame.to_frame())
+        .ok_or(SendError::SyntaxError)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::drivers::loopback::LoopbackDriver;
+
+    use embedded_can::{ExtendedId, StandardId};
+
     #[test]
     fn build_standard_frame1() {
         let text = "705#05";
@@ -102,6 +141,13 @@ mod tests {
         assert_eq!(frame.data(), &[0x05]);
     }
 
+    #[test]
+    fn build_raw_dlc_frame() {
+        let frame = build_frame("705#0102030405060708_C").unwrap();
+        assert_eq!(frame.dlc(), 8);
+        assert_eq!(frame.raw_dlc(), 0xC);
+    }
+
     #[test]
     fn build_standard_frame2() {
         let text = "705#0102";
@@ -114,4 +160,35 @@ mod tests {
         assert_eq!(frame.dlc(), 2);
         assert_eq!(frame.data(), &[0x01, 0x02]);
     }
+
+    #[test]
+    fn build_extended_and_remote_frames() {
+        let frame = build_frame("18EFC034#AABB").unwrap();
+        assert_eq!(
+            frame.id(),
+            embedded_can::Id::Extended(ExtendedId::new(0x18EFC034).unwrap())
+        );
+
+        let frame = build_frame("123#R2").unwrap();
+        assert!(frame.is_remote_frame());
+        assert_eq!(frame.dlc(), 2);
+
+        assert!(build_frame("123##1AABB").is_err());
+        assert!(build_frame("705#0").is_err());
+    }
+
+    #[tokio::test]
+    async fn confirm_transmission() {
+        let frame = build_frame("123#01").unwrap();
+        let timeout = Duration::from_millis(50);
+
+        let (a, _b) = LoopbackDriver::pair();
+        let mut driver = CanDriver::from(a);
+        driver.send(frame.clone()).await;
+        assert!(confirm_sent(&mut driver, &frame, timeout).await.is_err());
+
+        driver.receive_own_messages(true).unwrap();
+        driver.send(frame.clone()).await;
+        assert!(confirm_sent(&mut driver, &frame, timeout).await.is_ok());
+    }
 }
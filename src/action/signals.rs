@@ -0,0 +1,266 @@
+//
+// signals.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{drivers::AsyncCanDriverPtr, CommandContext};
+
+use candecode::{Database, Expr, Message, SignalValue};
+use clap::Parser;
+use embedded_can::Frame;
+use thiserror::Error;
+
+use std::{io::Write, path::PathBuf};
+
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+#[derive(Debug, Error)]
+pub enum SignalsError {
+    #[error("Threshold crossed: {0}")]
+    ThresholdCrossed(String),
+    #[error("Only http:// webhooks are supported")]
+    UnsupportedWebhook,
+}
+
+/// Arguments for the signals command
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Signal database (DBC or KCD)
+    #[arg(long = "dbc")]
+    dbc: PathBuf,
+    /// Alert when the expression becomes true, e.g. "EngineTemp>95". Can be repeated.
+    #[arg(short = 'w', long = "watch")]
+    watch: Vec<Expr>,
+    /// Ring the terminal bell on alerts
+    #[arg(long = "bell")]
+    bell: bool,
+    /// POST alerts as JSON to an http:// URL
+    #[arg(long = "webhook")]
+    webhook: Option<String>,
+    /// Exit with an error on the first alert
+    #[arg(long = "exit-on-alert")]
+    exit_on_alert: bool,
+}
+
+/// A watched expression and whether it is currently true
+struct Watch {
+    expr: Expr,
+    active: bool,
+}
+
+impl Watch {
+    /// Evaluate against a message, returning the new state if it changed
+    ///
+    /// Messages that do not carry every signal of the expression leave the state unchanged.
+    fn update(&mut self, message: &Message, values: &[SignalValue]) -> Option<bool> {
+        let carries_signals = self.expr.signals().iter().all(|signal| {
+            signal.message.as_ref().is_none_or(|m| *m == message.name)
+                && values.iter().any(|v| v.signal.name == signal.signal)
+        });
+
+        if !carries_signals {
+            return None;
+        }
+
+        let active = self.expr.evaluate(&message.name, values);
+        if active == self.active {
+            return None;
+        }
+
+        self.active = active;
+        Some(active)
+    }
+}
+
+pub async fn run(ctx: CommandContext, args: Args) -> anyhow::Result<()> {
+    let db = Database::load(&args.dbc)?;
+
+    if args
+        .webhook
+        .as_ref()
+        .is_some_and(|url| !url.starts_with("http://"))
+    {
+        return Err(SignalsError::UnsupportedWebhook.into());
+    }
+
+    tokio::select! {
+        res = signals_task(ctx.driver.into(), db, args) => res,
+        res = tokio::signal::ctrl_c() => Ok(res?),
+    }
+}
+
+async fn signals_task(
+    mut driver: AsyncCanDriverPtr,
+    db: Database,
+    args: Args,
+) -> anyhow::Result<()> {
+    let mut watches: Vec<Watch> = args
+        .watch
+        .iter()
+        .cloned()
+        .map(|expr| Watch {
+            expr,
+            active: false,
+        })
+        .collect();
+
+    while let Some(frame) = driver.recv().await {
+        let Some(message) = db.message(frame.id()) else {
+            continue;
+        };
+
+        let values = message.decode(frame.data());
+
+        // Only show messages relevant to the watches, if there are any
+        let watched = watches.is_empty()
+            || watches.iter().any(|w| {
+                w.expr
+                    .signals()
+                    .iter()
+                    .any(|s| values.iter().any(|v| v.signal.name == s.signal))
+            });
+
+        if watched {
+            let line = values
+                .iter()
+                .fold(message.name.clone(), |s, v| format!("{}  {}", s, v));
+            println!("{}", line);
+        }
+
+        for watch in watches.iter_mut() {
+            let Some(active) = watch.update(message, &values) else {
+                continue;
+            };
+
+            let state = if active { "ALERT" } else { "CLEARED" };
+            let detail = values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            eprintln!("{}: {} ({})", state, watch.expr, detail);
+
+            if active && args.bell {
+                eprint!("\x07");
+                let _ = std::io::stderr().flush();
+            }
+
+            if let Some(url) = &args.webhook {
+                let body = serde_json::json!({
+                    "state": state,
+                    "watch": watch.expr.to_string(),
+                    "message": message.name,
+                    "values": detail,
+                })
+                .to_string();
+
+                // Don't hold up frame processing on a slow endpoint
+                let url = url.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = post_webhook(&url, &body).await {
+                        eprintln!("Webhook failed: {}", e);
+                    }
+                });
+            }
+
+            if active && args.exit_on_alert {
+                return Err(SignalsError::ThresholdCrossed(watch.expr.to_string()).into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Split an http:// URL into host:port and path
+fn parse_url(url: &str) -> Option<(String, String)> {
+    let rest = url.strip_prefix("http://")?;
+
+    let (host, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+
+    let host = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+
+    Some((host, path.to_string()))
+}
+
+async fn post_webhook(url: &str, body: &str) -> anyhow::Result<()> {
+    let (host, path) = parse_url(url).ok_or(SignalsError::UnsupportedWebhook)?;
+
+    let mut stream = TcpStream::connect(&host).await?;
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{Id, StandardId};
+
+    const DBC: &str = r#"
+BO_ 100 Engine: 8 Vector__XXX
+ SG_ EngineTemp : 0|8@1+ (1,-40) [-40|215] "degC" Vector__XXX
+BO_ 101 Oil: 8 Vector__XXX
+ SG_ OilPressure : 0|8@1+ (0.1,0) [0|25.5] "bar" Vector__XXX
+"#;
+
+    #[test]
+    fn watch_edges() {
+        let db = Database::from_dbc(DBC).unwrap();
+        let engine = db
+            .message(Id::Standard(StandardId::new(100).unwrap()))
+            .unwrap();
+        let oil = db
+            .message(Id::Standard(StandardId::new(101).unwrap()))
+            .unwrap();
+
+        let mut watch = Watch {
+            expr: "EngineTemp>95".parse().unwrap(),
+            active: false,
+        };
+
+        assert_eq!(watch.update(engine, &engine.decode(&[130])), None);
+        assert_eq!(watch.update(engine, &engine.decode(&[140])), Some(true));
+        // Still above the threshold
+        assert_eq!(watch.update(engine, &engine.decode(&[141])), None);
+        // Other messages don't clear the alert
+        assert_eq!(watch.update(oil, &oil.decode(&[0])), None);
+        assert_eq!(watch.update(engine, &engine.decode(&[100])), Some(false));
+    }
+
+    #[test]
+    fn webhook_url() {
+        assert_eq!(
+            parse_url("http://localhost:8080/alerts"),
+            Some((String::from("localhost:8080"), String::from("/alerts")))
+        );
+        assert_eq!(
+            parse_url("http://example.com"),
+            Some((String::from("example.com:80"), String::from("/")))
+        );
+        assert_eq!(parse_url("https://example.com"), None);
+    }
+}
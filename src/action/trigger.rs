@@ -0,0 +1,344 @@
+//
+// trigger.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Actions run when a filter expression becomes true
+//!
+//! ```text
+//! --on-trigger "EngineSpeed > 3000 => gpio:17=1"
+//! --on-trigger "Gear == REVERSE => webhook:http://rig.local/event"
+//! --on-trigger "BrakePressed == 1 => cmd:echo $ICAN_ID $ICAN_DATA >> brakes.log"
+//! ```
+//!
+//! A trigger fires on the frame that makes its expression true, and again only after a frame of
+//! the same signals made it false, so a scope or rig sees one event per bus event.
+
+use crate::{action::alert::spawn, frame::CanFrame, utils};
+
+use candecode::{Database, Expr};
+use clap::Args;
+use embedded_can::Frame;
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::PathBuf,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Trigger options shared by the receiving commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct TriggerArgs {
+    /// Run an action when a signal expression becomes true, as "<expression> => <action>". The
+    /// action is cmd:<shell command>, webhook:<url> or gpio:[<chip>:]<line>[=0|1], toggling the
+    /// line without a level. Can be repeated.
+    #[arg(long = "on-trigger", value_parser = parse_trigger, requires = "dbc")]
+    pub on_trigger: Vec<Trigger>,
+}
+
+/// What a trigger does when it fires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerAction {
+    /// Run a shell command with the frame in ICAN_ID, ICAN_DATA, ICAN_INTERFACE and ICAN_TRIGGER
+    Command(String),
+    /// POST the frame as JSON to a URL, using curl
+    Webhook(String),
+    /// Set or toggle a GPIO line
+    Gpio { line: GpioLine, level: Option<bool> },
+}
+
+/// A GPIO line, by sysfs number or by chip and offset through libgpiod's gpioset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpioLine {
+    Sysfs(u32),
+    Chip(String, u32),
+}
+
+impl GpioLine {
+    fn set(&self, level: bool) {
+        match self {
+            GpioLine::Sysfs(line) => {
+                let path = PathBuf::from(format!("/sys/class/gpio/gpio{}/value", line));
+                // Written in place rather than spawned, to keep the latency to the frame low
+                let _ = fs::write(path, if level { "1" } else { "0" });
+            }
+            GpioLine::Chip(chip, line) => {
+                let mut command = Command::new("gpioset");
+                command.arg(chip).arg(format!("{}={}", line, level as u8));
+                spawn(command);
+            }
+        }
+    }
+
+    /// Make sure a sysfs line is exported as an output
+    fn prepare(&self) -> anyhow::Result<()> {
+        let GpioLine::Sysfs(line) = self else {
+            return Ok(());
+        };
+        let dir = PathBuf::from(format!("/sys/class/gpio/gpio{}", line));
+        if !dir.exists() {
+            fs::write("/sys/class/gpio/export", line.to_string())
+                .map_err(|e| anyhow::anyhow!("Failed to export GPIO {}: {}", line, e))?;
+        }
+        fs::write(dir.join("direction"), "out")
+            .map_err(|e| anyhow::anyhow!("Failed to make GPIO {} an output: {}", line, e))?;
+        Ok(())
+    }
+}
+
+/// An expression and the action it triggers
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub expr: Expr,
+    pub action: TriggerAction,
+    /// The expression as written, passed to the action
+    pub text: String,
+}
+
+/// Parse "<expression> => <action>"
+pub fn parse_trigger(s: &str) -> Result<Trigger, String> {
+    let (expr, action) = s
+        .split_once("=>")
+        .ok_or_else(|| format!("expected '<expression> => <action>', got '{}'", s))?;
+    let text = expr.trim().to_string();
+    let expr = text.parse::<Expr>().map_err(|e| e.to_string())?;
+
+    let action = action.trim();
+    let action = match action.split_once(':') {
+        Some(("cmd", cmd)) if !cmd.trim().is_empty() => TriggerAction::Command(cmd.to_string()),
+        Some(("webhook", url)) if !url.trim().is_empty() => {
+            TriggerAction::Webhook(url.trim().to_string())
+        }
+        Some(("gpio", gpio)) => parse_gpio(gpio)?,
+        _ => {
+            return Err(format!(
+                "unknown action '{}', expected cmd:, webhook: or gpio:",
+                action
+            ))
+        }
+    };
+
+    Ok(Trigger { expr, action, text })
+}
+
+/// Parse "[<chip>:]<line>[=0|1]"
+fn parse_gpio(s: &str) -> Result<TriggerAction, String> {
+    let (line, level) = match s.split_once('=') {
+        Some((line, "1")) => (line, Some(true)),
+        Some((line, "0")) => (line, Some(false)),
+        Some((_, level)) => return Err(format!("GPIO level must be 0 or 1, got '{}'", level)),
+        None => (s, None),
+    };
+
+    let number = |line: &str| {
+        line.trim()
+            .parse::<u32>()
+            .map_err(|_| format!("invalid GPIO line '{}'", line))
+    };
+    let line = match line.split_once(':') {
+        Some((chip, line)) => GpioLine::Chip(chip.trim().to_string(), number(line)?),
+        None => GpioLine::Sysfs(number(line)?),
+    };
+
+    Ok(TriggerAction::Gpio { line, level })
+}
+
+/// Evaluates the triggers against received frames and runs their actions
+pub struct Triggers {
+    triggers: Vec<Trigger>,
+    /// Whether each expression was true on the last frame carrying its signals
+    active: Vec<bool>,
+    /// Level of each GPIO line, for toggling
+    levels: Vec<bool>,
+    interface: String,
+}
+
+impl Triggers {
+    /// Set up the triggers, exporting their sysfs GPIO lines
+    pub fn new(args: TriggerArgs, interface: String) -> anyhow::Result<Self> {
+        for trigger in &args.on_trigger {
+            if let TriggerAction::Gpio { line, .. } = &trigger.action {
+                line.prepare()?;
+            }
+        }
+
+        let count = args.on_trigger.len();
+        Ok(Triggers {
+            triggers: args.on_trigger,
+            active: vec![false; count],
+            levels: vec![false; count],
+            interface,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Run the actions of the triggers the frame fires
+    pub fn update(&mut self, db: &Database, frame: &CanFrame) {
+        for i in self.fired(db, frame) {
+            self.run(i, frame);
+        }
+    }
+
+    /// Triggers whose expression the frame made true
+    fn fired(&mut self, db: &Database, frame: &CanFrame) -> Vec<usize> {
+        let Some(message) = db.message(frame.id()) else {
+            return vec![];
+        };
+        let values = message.decode(frame.data());
+
+        let mut fired = vec![];
+        for (i, trigger) in self.triggers.iter().enumerate() {
+            // Frames without the expression's signals leave its state alone
+            let carries = trigger.expr.signals().iter().any(|signal| {
+                signal.message.as_ref().is_none_or(|m| *m == message.name)
+                    && values.iter().any(|v| v.signal.name == signal.signal)
+            });
+            if !carries {
+                continue;
+            }
+
+            let now = trigger.expr.evaluate(&message.name, &values);
+            if now && !self.active[i] {
+                fired.push(i);
+            }
+            self.active[i] = now;
+        }
+
+        fired
+    }
+
+    fn run(&mut self, i: usize, frame: &CanFrame) {
+        let trigger = &self.triggers[i];
+        let id = utils::format_id(&frame.id());
+        let data = frame.data().iter().fold(String::new(), |mut s, b| {
+            let _ = write!(s, "{:02X}", b);
+            s
+        });
+
+        match &trigger.action {
+            TriggerAction::Command(cmd) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(cmd)
+                    .env("ICAN_INTERFACE", &self.interface)
+                    .env("ICAN_TRIGGER", &trigger.text)
+                    .env("ICAN_ID", &id)
+                    .env("ICAN_DATA", &data);
+                spawn(command);
+            }
+            TriggerAction::Webhook(url) => {
+                let time = frame
+                    .timestamp()
+                    .unwrap_or_else(SystemTime::now)
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                let body = serde_json::json!({
+                    "time": time,
+                    "interface": self.interface,
+                    "trigger": trigger.text,
+                    "id": id,
+                    "data": data,
+                });
+                let mut command = Command::new("curl");
+                command
+                    .args(["-s", "-X", "POST", "-H", "Content-Type: application/json"])
+                    .arg("-d")
+                    .arg(body.to_string())
+                    .arg(url);
+                spawn(command);
+            }
+            TriggerAction::Gpio { line, level } => {
+                let level = level.unwrap_or(!self.levels[i]);
+                self.levels[i] = level;
+                line.set(level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    const DBC: &str = r#"
+BO_ 100 Engine: 8 Vector__XXX
+ SG_ Speed : 0|16@1+ (1,0) [0|65535] "rpm" Vector__XXX
+
+BO_ 200 Other: 8 Vector__XXX
+ SG_ Gear : 0|8@1+ (1,0) [0|255] "" Vector__XXX
+"#;
+
+    #[test]
+    fn parse_actions() {
+        let trigger = parse_trigger("Speed > 3000 => gpio:17=1").unwrap();
+        assert_eq!(trigger.text, "Speed > 3000");
+        assert_eq!(
+            trigger.action,
+            TriggerAction::Gpio {
+                line: GpioLine::Sysfs(17),
+                level: Some(true)
+            }
+        );
+
+        let trigger = parse_trigger("Speed >= 1 => gpio:gpiochip0:4").unwrap();
+        assert_eq!(
+            trigger.action,
+            TriggerAction::Gpio {
+                line: GpioLine::Chip("gpiochip0".into(), 4),
+                level: None
+            }
+        );
+
+        let trigger = parse_trigger("Speed > 1 => webhook:http://rig.local/event").unwrap();
+        assert_eq!(
+            trigger.action,
+            TriggerAction::Webhook("http://rig.local/event".into())
+        );
+        let trigger = parse_trigger("Speed > 1 => cmd:echo a:b").unwrap();
+        assert_eq!(trigger.action, TriggerAction::Command("echo a:b".into()));
+
+        assert!(parse_trigger("Speed > 1").is_err());
+        assert!(parse_trigger("Speed > 1 => beep:").is_err());
+        assert!(parse_trigger("Speed > 1 => gpio:17=2").is_err());
+        assert!(parse_trigger("Speed >> 1 => cmd:true").is_err());
+    }
+
+    #[test]
+    fn fires_on_rising_edge() {
+        let db = Database::from_dbc(DBC).unwrap();
+        let args = TriggerArgs {
+            on_trigger: vec![parse_trigger("Speed > 3000 => cmd:true").unwrap()],
+        };
+        let mut triggers = Triggers::new(args, "vcan0".into()).unwrap();
+
+        let frame = |id, speed: u16| {
+            CanFrame::new(StandardId::new(id).unwrap(), &speed.to_le_bytes()).unwrap()
+        };
+        let fired = [
+            frame(100, 1000),
+            frame(100, 4000),
+            // Stays true, and frames of other messages do not reset it
+            frame(100, 5000),
+            frame(200, 0),
+            frame(100, 4500),
+            frame(100, 2000),
+            frame(100, 3500),
+        ]
+        .iter()
+        .map(|frame| !triggers.fired(&db, frame).is_empty())
+        .collect::<Vec<_>>();
+
+        assert_eq!(fired, vec![false, true, false, false, false, false, true]);
+    }
+}
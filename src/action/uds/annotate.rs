@@ -0,0 +1,149 @@
+//
+// annotate.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    format::{CanFrameFormatter, DataFormatMode},
+    frame::CanFrame,
+    isotp::{IsoTpFrame, Reassembler},
+    uds, utils,
+};
+
+use super::parse_id;
+
+use embedded_can::{Frame, Id};
+
+/// Request and response IDs of a diagnostic channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsoTpPair {
+    pub request: Id,
+    pub response: Id,
+}
+
+/// Parse an ID pair such as "7E0:7E8"
+pub fn parse_pair(s: &str) -> Result<IsoTpPair, String> {
+    let (request, response) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected REQUEST:RESPONSE, got '{}'", s))?;
+
+    // IDs are written in hex, as in candump
+    let hex = |id: &str| match id.starts_with("0x") {
+        true => parse_id(id),
+        false => parse_id(&format!("0x{}", id)),
+    };
+
+    Ok(IsoTpPair {
+        request: hex(request)?,
+        response: hex(response)?,
+    })
+}
+
+/// Annotates ISO-TP flows with the UDS service they carry
+///
+/// Segmented messages are collected and printed once as the reassembled message.
+#[derive(Debug, Default)]
+pub struct Annotator {
+    flows: Vec<(Id, Reassembler)>,
+}
+
+impl Annotator {
+    pub fn new(pairs: &[IsoTpPair]) -> Self {
+        Self {
+            flows: pairs
+                .iter()
+                .flat_map(|pair| [pair.request, pair.response])
+                .map(|id| (id, Reassembler::new()))
+                .collect(),
+        }
+    }
+
+    /// Check if frames of this ID belong to a configured ISO-TP flow
+    pub fn matches(&self, id: Id) -> bool {
+        self.flows.iter().any(|(flow, _)| *flow == id)
+    }
+
+    /// Get the annotated line of a frame, `None` if the frame is flow control, part of an
+    /// unfinished message or not part of a configured flow
+    pub fn annotate(&mut self, frame: &CanFrame) -> Option<String> {
+        let (_, reassembler) = self.flows.iter_mut().find(|(id, _)| *id == frame.id())?;
+
+        let result = IsoTpFrame::parse(frame.data()).and_then(|tp| reassembler.process(&tp));
+
+        let message = match result {
+            Ok(message) => message?,
+            Err(e) => {
+                let fmt: CanFrameFormatter = (frame, DataFormatMode::Hex).into();
+                return Some(format!("{}  {}", fmt, e));
+            }
+        };
+
+        let id = utils::format_id(&frame.id());
+        let data = message
+            .iter()
+            .map(|b| format!(" {:02X}", b))
+            .collect::<String>();
+
+        Some(format!(
+            "{} [{}] {}  {}",
+            id,
+            message.len(),
+            data,
+            uds::describe(&message)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn frame(raw_id: u16, data: &[u8]) -> CanFrame {
+        CanFrame::new(StandardId::new(raw_id).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn pairs() {
+        let pair = parse_pair("7E0:0x7E8").unwrap();
+        assert_eq!(pair.request, Id::Standard(StandardId::new(0x7E0).unwrap()));
+        assert_eq!(pair.response, Id::Standard(StandardId::new(0x7E8).unwrap()));
+        assert!(parse_pair("7E0").is_err());
+    }
+
+    #[test]
+    fn request_response() {
+        let mut annotator = Annotator::new(&[parse_pair("7E0:7E8").unwrap()]);
+        assert!(annotator.matches(frame(0x7E8, &[]).id()));
+        assert_eq!(annotator.annotate(&frame(0x123, &[0x02, 0x10, 0x03])), None);
+
+        let request = annotator.annotate(&frame(0x7E0, &[0x03, 0x22, 0xF1, 0x90, 0xCC, 0xCC]));
+        assert_eq!(
+            request.as_deref(),
+            Some("7E0 [3]  22 F1 90  ReadDataByIdentifier DID 0xF190")
+        );
+
+        let first = frame(0x7E8, &[0x10, 0x0A, 0x62, 0xF1, 0x90, 0x31, 0x47, 0x31]);
+        assert_eq!(annotator.annotate(&first), None);
+        // Flow control from the tester
+        assert_eq!(annotator.annotate(&frame(0x7E0, &[0x30, 0x00, 0x00])), None);
+
+        let response = annotator.annotate(&frame(0x7E8, &[0x21, 0x4A, 0x43, 0x35, 0x34, 0xCC]));
+        assert_eq!(
+            response.as_deref(),
+            Some(
+                "7E8 [10]  62 F1 90 31 47 31 4A 43 35 34  \
+                 ReadDataByIdentifier positive response DID 0xF190"
+            )
+        );
+
+        let negative = annotator.annotate(&frame(0x7E8, &[0x03, 0x7F, 0x22, 0x31]));
+        assert_eq!(
+            negative.as_deref(),
+            Some("7E8 [3]  7F 22 31  ReadDataByIdentifier negative response: requestOutOfRange")
+        );
+    }
+}
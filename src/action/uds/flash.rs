@@ -0,0 +1,119 @@
+//
+// flash.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    uds::{
+        flash::{flash, FlashOptions},
+        UdsClient,
+    },
+    CommandContext,
+};
+
+use super::{parse_hex, parse_u32, parse_u8, TransportArgs};
+
+use clap::Parser;
+
+use std::{io::Write, path::PathBuf, process::Command, sync::Arc};
+
+use tokio::sync::Mutex;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Firmware image to download
+    #[arg(short = 'f', long = "file")]
+    file: PathBuf,
+    /// Memory address of the image
+    #[arg(short = 'a', long = "address", value_parser = parse_u32, default_value = "0")]
+    address: u32,
+    #[command(flatten)]
+    transport: TransportArgs,
+    /// Security level (request seed sub-function) to unlock before programming
+    #[arg(long = "level", value_parser = parse_u8, requires = "seed_key")]
+    level: Option<u8>,
+    /// Program computing the key, called with the level and seed as hex and printing the key as hex
+    #[arg(long = "seed-key")]
+    seed_key: Option<String>,
+    /// Data identifier to write before the download, e.g. F15A=0102. Can be repeated.
+    #[arg(long = "did", value_parser = parse_did)]
+    did: Vec<(u16, Vec<u8>)>,
+    /// Do not reset the ECU after the download
+    #[arg(long = "no-reset")]
+    no_reset: bool,
+}
+
+fn parse_did(s: &str) -> Result<(u16, Vec<u8>), String> {
+    let (did, data) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected DID=HEX, got '{}'", s))?;
+    let did = u16::from_str_radix(did.trim_start_matches("0x"), 16)
+        .map_err(|_| format!("invalid DID '{}'", did))?;
+
+    Ok((did, parse_hex(data)?))
+}
+
+/// Run the external seed-key program
+fn external_key(program: &str, level: u8, seed: &[u8]) -> Vec<u8> {
+    let seed = seed
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<String>();
+
+    let output = Command::new(program)
+        .arg(format!("{:02X}", level))
+        .arg(seed)
+        .output();
+
+    // An empty key is rejected by the ECU, which reports the failure
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_hex(String::from_utf8_lossy(&output.stdout).trim()).unwrap_or_default()
+        }
+        Ok(output) => {
+            eprintln!("Seed-key program failed: {}", output.status);
+            vec![]
+        }
+        Err(e) => {
+            eprintln!("Failed to run seed-key program: {}", e);
+            vec![]
+        }
+    }
+}
+
+pub async fn run(args: Args, ctx: CommandContext) -> anyhow::Result<()> {
+    let image = std::fs::read(&args.file)?;
+
+    let options = FlashOptions {
+        address: args.address,
+        security_level: args.level,
+        data_identifiers: args.did,
+        reset: !args.no_reset,
+    };
+
+    let program = args.seed_key.unwrap_or_default();
+    let seed_key = |level: u8, seed: &[u8]| external_key(&program, level, seed);
+
+    let channel = args.transport.channel(ctx)?;
+    let client = Arc::new(Mutex::new(UdsClient::new(channel)));
+
+    let name = args.file.display().to_string();
+    let res = flash(&client, &image, &options, &seed_key, |done, total| {
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        eprint!(
+            "\rFlashing {}: {:>3}% ({}/{} bytes)",
+            name, percent, done, total
+        );
+        let _ = std::io::stderr().flush();
+    })
+    .await;
+
+    eprintln!();
+    res?;
+
+    println!("Download complete");
+
+    Ok(())
+}
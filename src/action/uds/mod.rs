@@ -0,0 +1,236 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+pub mod annotate;
+pub mod flash;
+pub mod request;
+
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+use crate::isotp::kernel::KernelSocket;
+use crate::{
+    isotp::{IsoTpChannel, IsoTpConfig},
+    CommandContext,
+};
+
+use clap::{Args, Subcommand};
+use embedded_can::{ExtendedId, Id, StandardId};
+
+use std::{str::FromStr, time::Duration};
+
+/// UDS subcommands
+#[derive(Debug, Subcommand)]
+pub enum UdsCommands {
+    /// Download a firmware image using the UDS programming sequence
+    Flash(flash::Args),
+    /// Send a request and print the response, or the response of every ECU for functional
+    /// requests
+    Request(request::Args),
+}
+
+pub async fn run(cmd: UdsCommands, ctx: CommandContext) -> anyhow::Result<()> {
+    match cmd {
+        UdsCommands::Flash(args) => Ok(flash::run(args, ctx).await?),
+        UdsCommands::Request(args) => Ok(request::run(args, ctx).await?),
+    }
+}
+
+/// ISO-TP addressing, flow control and stress options
+#[derive(Debug, Args)]
+pub struct TransportArgs {
+    /// Request ID (physical addressing)
+    #[arg(long = "tx-id", value_parser = parse_id, default_value = "0x7E0")]
+    tx_id: Id,
+    /// Response ID
+    #[arg(long = "rx-id", value_parser = parse_id, default_value = "0x7E8")]
+    rx_id: Id,
+    /// Block size requested from the ECU in flow control frames, 0 for no limit
+    #[arg(long = "block-size", value_parser = parse_u8, default_value = "0")]
+    block_size: u8,
+    /// Separation time (STmin) requested from the ECU in flow control frames
+    #[arg(long = "st-min", value_parser = parse_u8, default_value = "0")]
+    st_min: u8,
+    /// Send blocks of this size, ignoring the ECU's flow control
+    #[arg(long = "tx-block-size", value_parser = parse_u8)]
+    tx_block_size: Option<u8>,
+    /// Send with this separation time (STmin), ignoring the ECU's flow control
+    #[arg(long = "tx-st-min", value_parser = parse_u8)]
+    tx_st_min: Option<u8>,
+    /// Delay in milliseconds before each flow control frame
+    #[arg(long = "fc-delay", default_value = "0")]
+    fc_delay: u64,
+    /// Send the nth consecutive frame of each message with a wrong sequence number
+    #[arg(long = "wrong-sequence")]
+    wrong_sequence: Option<usize>,
+    /// ISO-TP implementation: kernel CAN_ISOTP sockets, userspace, or auto to use the kernel
+    /// where the can-isotp module is loaded
+    #[arg(long = "isotp-backend", default_value = "auto")]
+    backend: IsoTpBackend,
+}
+
+/// Where ISO-TP segmentation and flow control happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsoTpBackend {
+    /// The kernel where available, otherwise userspace
+    Auto,
+    Kernel,
+    Userspace,
+}
+
+impl FromStr for IsoTpBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(IsoTpBackend::Auto),
+            "kernel" => Ok(IsoTpBackend::Kernel),
+            "userspace" => Ok(IsoTpBackend::Userspace),
+            _ => Err(format!("unknown ISO-TP backend '{}'", s)),
+        }
+    }
+}
+
+impl TransportArgs {
+    pub fn config(&self) -> IsoTpConfig {
+        let mut config = IsoTpConfig::new(self.tx_id, self.rx_id);
+        config.block_size = self.block_size;
+        config.st_min = self.st_min;
+        config.stress.block_size = self.tx_block_size;
+        config.stress.st_min = self.tx_st_min;
+        config.stress.fc_delay = Duration::from_millis(self.fc_delay);
+        config.stress.wrong_sequence = self.wrong_sequence;
+        config
+    }
+
+    /// Open a channel with the selected backend
+    ///
+    /// Auto picks userspace for stress tests and audited sessions, since frames the kernel sends
+    /// never pass through the driver.
+    pub fn channel(&self, ctx: CommandContext) -> anyhow::Result<IsoTpChannel> {
+        let config = self.config();
+        let channel = IsoTpChannel::new(ctx.driver.into(), config.clone());
+
+        if self.backend == IsoTpBackend::Kernel {
+            anyhow::ensure!(
+                ctx.tx.audit.is_none(),
+                "Frames sent by the kernel ISO-TP backend cannot be audited"
+            );
+        }
+
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        {
+            let usable = KernelSocket::supports(&config) && ctx.tx.audit.is_none();
+            match self.backend {
+                IsoTpBackend::Kernel => {
+                    anyhow::ensure!(
+                        KernelSocket::supports(&config),
+                        "The kernel ISO-TP backend cannot send with stress options or extended \
+                         addressing in one direction only"
+                    );
+                    let socket = KernelSocket::open(&ctx.interface, &config).map_err(|e| {
+                        anyhow::anyhow!(
+                            "Failed to open a CAN_ISOTP socket on {}: {}",
+                            ctx.interface,
+                            e
+                        )
+                    })?;
+                    Ok(channel.with_kernel(socket))
+                }
+                IsoTpBackend::Auto if usable => {
+                    match KernelSocket::open(&ctx.interface, &config) {
+                        Ok(socket) => Ok(channel.with_kernel(socket)),
+                        // No can-isotp module, or not a SocketCAN interface
+                        Err(_) => Ok(channel),
+                    }
+                }
+                _ => Ok(channel),
+            }
+        }
+
+        #[cfg(not(all(target_os = "linux", feature = "socketcan")))]
+        match self.backend {
+            IsoTpBackend::Kernel => {
+                Err(crate::drivers::DriverError::Unavailable("kernel ISO-TP sockets").into())
+            }
+            _ => Ok(channel),
+        }
+    }
+}
+
+/// Parse a decimal or hex (0x prefixed) number
+pub fn parse_u32(s: &str) -> Result<u32, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+pub fn parse_u8(s: &str) -> Result<u8, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// Parse a CAN ID, IDs above 0x7FF are extended
+pub fn parse_id(s: &str) -> Result<Id, String> {
+    let raw = parse_u32(s).map_err(|e| e.to_string())?;
+
+    match u16::try_from(raw).ok().and_then(StandardId::new) {
+        Some(id) => Ok(Id::Standard(id)),
+        None => ExtendedId::new(raw)
+            .map(Id::Extended)
+            .ok_or_else(|| format!("0x{:X} is not a valid CAN ID", raw)),
+    }
+}
+
+/// Parse a hex string such as "0102AB"
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    if !s.len().is_multiple_of(2) {
+        return Err(format!("'{}' has an odd number of digits", s));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("'{}' is not a hex string", s))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids() {
+        assert_eq!(
+            parse_id("0x7E0"),
+            Ok(Id::Standard(StandardId::new(0x7E0).unwrap()))
+        );
+        assert_eq!(
+            parse_id("0x18DA10F1"),
+            Ok(Id::Extended(ExtendedId::new(0x18DA10F1).unwrap()))
+        );
+        assert!(parse_id("0x20000000").is_err());
+    }
+
+    #[test]
+    fn backends() {
+        assert_eq!("auto".parse(), Ok(IsoTpBackend::Auto));
+        assert_eq!("kernel".parse(), Ok(IsoTpBackend::Kernel));
+        assert!("kernal".parse::<IsoTpBackend>().is_err());
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_hex("0102ab"), Ok(vec![0x01, 0x02, 0xAB]));
+        assert!(parse_hex("012").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//
+// request.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::{
+    isotp::FunctionalChannel,
+    uds::{self, FunctionalClient, Nrc, UdsClient, UdsError, POSITIVE_RESPONSE_OFFSET},
+    utils, CommandContext,
+};
+
+use super::{parse_hex, parse_id, TransportArgs};
+
+use clap::Parser;
+use embedded_can::Id;
+
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Request as hex, starting with the service identifier, e.g. 22F190
+    #[arg(value_parser = parse_hex)]
+    // The full path makes clap treat the parsed bytes as one value
+    data: std::vec::Vec<u8>,
+    #[command(flatten)]
+    transport: TransportArgs,
+    /// Send the request to this functional ID, e.g. 0x7DF, and print the response of every ECU
+    #[arg(long = "functional", value_parser = parse_id)]
+    functional: Option<Id>,
+    /// Time in milliseconds to collect responses to a functional request
+    #[arg(long = "window", default_value = "100")]
+    window: u64,
+}
+
+pub async fn run(args: Args, ctx: CommandContext) -> anyhow::Result<()> {
+    anyhow::ensure!(!args.data.is_empty(), "The request is empty");
+
+    let sid = args.data[0];
+    let mut config = args.transport.config();
+    let rx_id = config.rx_id;
+
+    let Some(functional) = args.functional else {
+        let mut client = UdsClient::new(args.transport.channel(ctx)?);
+        let result = match client.request(&args.data).await {
+            Ok(response) => Ok(response),
+            Err(UdsError::Negative { nrc, .. }) => Err(nrc),
+            Err(e) => return Err(e.into()),
+        };
+        println!("{}", response_line(&rx_id, sid, &result));
+        return Ok(());
+    };
+
+    config.tx_id = functional;
+    let channel = FunctionalChannel::new(ctx.driver.into(), config);
+    let mut client = FunctionalClient::new(channel, Duration::from_millis(args.window));
+
+    let responses = client.request(&args.data).await?;
+    if responses.is_empty() {
+        println!("No responses");
+    }
+
+    for response in responses {
+        println!("{}", response_line(&response.id, sid, &response.result));
+    }
+
+    Ok(())
+}
+
+fn response_line(id: &Id, sid: u8, result: &Result<Vec<u8>, Nrc>) -> String {
+    match result {
+        Ok(data) => {
+            let mut response = vec![sid.wrapping_add(POSITIVE_RESPONSE_OFFSET)];
+            response.extend(data);
+
+            let hex = response
+                .iter()
+                .map(|b| format!(" {:02X}", b))
+                .collect::<String>();
+            format!(
+                "{} [{}] {}  {}",
+                utils::format_id(id),
+                response.len(),
+                hex,
+                uds::describe(&response)
+            )
+        }
+        Err(nrc) => format!(
+            "{}  {} rejected: {}",
+            utils::format_id(id),
+            uds::service_name(sid).unwrap_or("request"),
+            nrc
+        ),
+    }
+}
@@ -0,0 +1,182 @@
+//
+// vcan.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use clap::{Parser, Subcommand};
+use neli::err::NlError;
+use socketcan::CanInterface;
+use thiserror::Error;
+
+use std::{
+    io, mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+/// MTU of CAN FD interfaces
+const CANFD_MTU: i32 = 72;
+
+#[derive(Debug, Error)]
+pub enum VcanError {
+    #[error("Managing interfaces requires CAP_NET_ADMIN, run as root or grant it with `sudo setcap cap_net_admin+ep $(which ican)`")]
+    PermissionDenied,
+    #[error("Interface name '{0}' is longer than {max} characters", max = libc::IFNAMSIZ - 1)]
+    NameTooLong(String),
+    #[error("Interface {0} already exists")]
+    Exists(String),
+    #[error("No interface named {0}")]
+    NotFound(String),
+    #[error("The kernel does not support virtual CAN interfaces, load the module with `sudo modprobe vcan`")]
+    Unsupported,
+    #[error("Failed to {0}: {1}")]
+    Failed(&'static str, String),
+}
+
+impl VcanError {
+    /// Map an error number reported by the kernel
+    fn from_errno(errno: i32, name: &str, action: &'static str) -> Self {
+        match errno {
+            libc::EPERM | libc::EACCES => VcanError::PermissionDenied,
+            libc::EEXIST => VcanError::Exists(name.to_string()),
+            libc::ENODEV => VcanError::NotFound(name.to_string()),
+            // The link kind is unknown without the vcan module
+            libc::EOPNOTSUPP => VcanError::Unsupported,
+            errno => VcanError::Failed(action, io::Error::from_raw_os_error(errno).to_string()),
+        }
+    }
+
+    fn from_netlink(err: NlError, name: &str, action: &'static str) -> Self {
+        match err {
+            // Netlink reports negated error numbers
+            NlError::Nlmsgerr(err) => VcanError::from_errno(-err.error, name, action),
+            err => VcanError::Failed(action, err.to_string()),
+        }
+    }
+}
+
+/// Virtual CAN interface subcommands
+#[derive(Debug, Subcommand)]
+pub enum VcanCommands {
+    /// Create a virtual CAN interface and bring it up
+    Create(CreateArgs),
+    /// Remove a virtual CAN interface
+    Remove(RemoveArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct CreateArgs {
+    /// Interface name, e.g. vcan0
+    name: String,
+    /// Allow CAN FD frames on the interface
+    #[arg(long = "fd")]
+    fd: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct RemoveArgs {
+    /// Interface name, e.g. vcan0
+    name: String,
+}
+
+pub fn run(cmd: VcanCommands) -> anyhow::Result<()> {
+    match cmd {
+        VcanCommands::Create(args) => create(&args.name, args.fd)?,
+        VcanCommands::Remove(args) => remove(&args.name)?,
+    }
+
+    Ok(())
+}
+
+fn check_name(name: &str) -> Result<(), VcanError> {
+    match name.len() < libc::IFNAMSIZ {
+        true => Ok(()),
+        false => Err(VcanError::NameTooLong(name.to_string())),
+    }
+}
+
+fn create(name: &str, fd: bool) -> Result<(), VcanError> {
+    check_name(name)?;
+
+    let interface = CanInterface::create_vcan(name, None)
+        .map_err(|e| VcanError::from_netlink(e, name, "create the interface"))?;
+
+    // The MTU can only be changed while the interface is down
+    let configured = match fd {
+        true => set_mtu(name, CANFD_MTU)
+            .map_err(|e| VcanError::from_errno(e.raw_os_error().unwrap_or(0), name, "set the MTU")),
+        false => Ok(()),
+    }
+    .and_then(|_| {
+        interface
+            .bring_up()
+            .map_err(|e| VcanError::from_netlink(e, name, "bring the interface up"))
+    });
+
+    if let Err(e) = configured {
+        // Don't leave a half configured interface behind
+        let _ = interface.delete();
+        return Err(e);
+    }
+
+    println!("Created {}{}", name, if fd { " with CAN FD" } else { "" });
+
+    Ok(())
+}
+
+fn remove(name: &str) -> Result<(), VcanError> {
+    check_name(name)?;
+
+    let interface = CanInterface::open(name).map_err(|_| VcanError::NotFound(name.to_string()))?;
+    interface
+        .delete()
+        .map_err(|(_, e)| VcanError::from_netlink(e, name, "remove the interface"))?;
+
+    println!("Removed {}", name);
+
+    Ok(())
+}
+
+/// Set the MTU of an interface with the SIOCSIFMTU ioctl
+fn set_mtu(name: &str, mtu: i32) -> io::Result<()> {
+    let mut req: libc::ifreq = unsafe { mem::zeroed() };
+    for (dst, src) in req.ifr_name.iter_mut().zip(name.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    req.ifr_ifru.ifru_mtu = mtu;
+
+    let socket = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if socket < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Closed when dropped
+    let socket = unsafe { OwnedFd::from_raw_fd(socket) };
+
+    match unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFMTU as _, &req) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            VcanError::from_errno(libc::EPERM, "vcan0", "create the interface"),
+            VcanError::PermissionDenied
+        ));
+        assert!(matches!(
+            VcanError::from_errno(libc::EEXIST, "vcan0", "create the interface"),
+            VcanError::Exists(_)
+        ));
+        assert!(check_name("vcan0").is_ok());
+        assert!(matches!(
+            check_name("a_very_long_vcan_name"),
+            Err(VcanError::NameTooLong(_))
+        ));
+    }
+}
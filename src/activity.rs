@@ -0,0 +1,101 @@
+//
+// activity.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Byte change activity of an ID over a sliding window
+//!
+//! Bytes that change often carry live signals, bytes that never change are constants or unused.
+
+use std::{collections::VecDeque, time::Duration};
+
+/// Counts how often each data byte of an ID changed within a window
+#[derive(Debug, Clone)]
+pub struct ByteActivity {
+    window: Duration,
+    last: Option<Vec<u8>>,
+    /// Time of every frame in the window, with a bit set for each byte it changed
+    frames: VecDeque<(Duration, u8)>,
+    changes: [u32; 8],
+}
+
+impl ByteActivity {
+    pub fn new(window: Duration) -> Self {
+        ByteActivity {
+            window,
+            last: None,
+            frames: VecDeque::new(),
+            changes: [0; 8],
+        }
+    }
+
+    /// Apply a frame received at `time`
+    pub fn update(&mut self, time: Duration, data: &[u8]) {
+        let mut mask = 0u8;
+        if let Some(last) = &self.last {
+            for (i, byte) in data.iter().enumerate().take(8) {
+                if last.get(i) != Some(byte) {
+                    mask |= 1 << i;
+                    self.changes[i] += 1;
+                }
+            }
+        }
+        self.last = Some(data.to_vec());
+        self.frames.push_back((time, mask));
+
+        self.expire(time);
+    }
+
+    /// Drop the frames that left the window at `now`
+    pub fn expire(&mut self, now: Duration) {
+        let start = now.saturating_sub(self.window);
+        while let Some(&(time, mask)) = self.frames.front() {
+            if time >= start {
+                break;
+            }
+            for (i, count) in self.changes.iter_mut().enumerate() {
+                if mask & (1 << i) != 0 {
+                    *count -= 1;
+                }
+            }
+            self.frames.pop_front();
+        }
+    }
+
+    /// Fraction of the frames in the window that changed each byte
+    pub fn change_rates(&self) -> [f64; 8] {
+        let frames = self.frames.len().max(1) as f64;
+        self.changes.map(|count| count as f64 / frames)
+    }
+
+    /// Data of the last frame
+    pub fn last(&self) -> &[u8] {
+        self.last.as_deref().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_window() {
+        let mut activity = ByteActivity::new(Duration::from_secs(1));
+        for i in 0..4u8 {
+            // Byte 0 is a counter, byte 1 is constant, byte 2 toggles every other frame
+            let time = Duration::from_millis(100 * i as u64);
+            activity.update(time, &[i, 0xAA, i / 2]);
+        }
+
+        assert_eq!(activity.change_rates()[..3], [0.75, 0.0, 0.25]);
+        assert_eq!(activity.last(), &[3, 0xAA, 1]);
+
+        // Everything but the last frame leaves the window
+        activity.expire(Duration::from_millis(1300));
+        assert_eq!(activity.change_rates()[..3], [1.0, 0.0, 0.0]);
+        activity.expire(Duration::from_secs(5));
+        assert_eq!(activity.change_rates(), [0.0; 8]);
+    }
+}
@@ -0,0 +1,489 @@
+//
+// analysis.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Payload analysis for reverse engineering undocumented buses
+//!
+//! Collects per-byte statistics of every ID, then classifies bytes as constant, counters or
+//! checksums and suggests where signals start and end from how often each bit flips.
+
+use embedded_can::Id;
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write as _},
+};
+
+/// Share of transitions or frames that must agree for a counter or checksum
+const AGREEMENT: f64 = 0.95;
+/// Fewest frames an ID needs before its bytes are classified
+const MIN_FRAMES: u64 = 8;
+
+/// Checksum algorithms checked for each byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// XOR of the other bytes
+    Xor,
+    /// Sum of the other bytes, modulo 256
+    Sum,
+    /// CRC-8 SAE J1850 of the other bytes
+    Crc8J1850,
+}
+
+impl Checksum {
+    const ALL: [Checksum; 3] = [Checksum::Xor, Checksum::Sum, Checksum::Crc8J1850];
+
+    fn compute(self, data: &[u8], skip: usize) -> u8 {
+        let others = data
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != skip)
+            .map(|(_, &b)| b);
+        match self {
+            Checksum::Xor => others.fold(0, |acc, b| acc ^ b),
+            Checksum::Sum => others.fold(0u8, |acc, b| acc.wrapping_add(b)),
+            Checksum::Crc8J1850 => {
+                let crc = others.fold(0xFFu8, |mut crc, b| {
+                    crc ^= b;
+                    for _ in 0..8 {
+                        crc = match crc & 0x80 {
+                            0 => crc << 1,
+                            _ => (crc << 1) ^ 0x1D,
+                        };
+                    }
+                    crc
+                });
+                crc ^ 0xFF
+            }
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Checksum::Xor => "XOR",
+            Checksum::Sum => "sum",
+            Checksum::Crc8J1850 => "CRC-8 J1850",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What a byte appears to carry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ByteKind {
+    /// Never changed
+    Constant(u8),
+    /// Increments by one every frame
+    Counter,
+    /// Low nibble increments by one every frame
+    NibbleCounter,
+    /// Checksum over the other bytes
+    Checksum(Checksum),
+    /// Changes, with the entropy of its values in bits
+    Live(f64),
+}
+
+impl fmt::Display for ByteKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteKind::Constant(value) => write!(f, "constant {:02X}", value),
+            ByteKind::Counter => write!(f, "counter"),
+            ByteKind::NibbleCounter => write!(f, "counter in low nibble"),
+            ByteKind::Checksum(checksum) => write!(f, "{} checksum", checksum),
+            ByteKind::Live(entropy) => write!(f, "live, entropy {:.2} bits", entropy),
+        }
+    }
+}
+
+/// A run of bits that flip together, numbered in Intel order from bit 0 of byte 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalCandidate {
+    pub start_bit: usize,
+    pub length: usize,
+}
+
+impl fmt::Display for SignalCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bits {}-{} ({} bits)",
+            self.start_bit,
+            self.start_bit + self.length - 1,
+            self.length
+        )
+    }
+}
+
+/// Statistics of one byte position
+#[derive(Debug, Clone)]
+struct ByteStats {
+    histogram: Box<[u64; 256]>,
+    frames: u64,
+    increments: u64,
+    nibble_increments: u64,
+    transitions: u64,
+    checksums: [u64; 3],
+}
+
+impl Default for ByteStats {
+    fn default() -> Self {
+        ByteStats {
+            histogram: Box::new([0; 256]),
+            frames: 0,
+            increments: 0,
+            nibble_increments: 0,
+            transitions: 0,
+            checksums: [0; 3],
+        }
+    }
+}
+
+impl ByteStats {
+    fn entropy(&self) -> f64 {
+        let total = self.frames as f64;
+        self.histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    fn constant(&self) -> Option<u8> {
+        let mut values = self.histogram.iter().enumerate().filter(|(_, &c)| c > 0);
+        match (values.next(), values.next()) {
+            (Some((value, _)), None) => Some(value as u8),
+            _ => None,
+        }
+    }
+
+    fn agrees(count: u64, total: u64) -> bool {
+        total > 0 && count as f64 >= total as f64 * AGREEMENT
+    }
+}
+
+/// Statistics of one ID
+#[derive(Debug, Clone, Default)]
+pub struct IdStats {
+    frames: u64,
+    last: Vec<u8>,
+    bytes: Vec<ByteStats>,
+    /// Flips of every bit between consecutive frames
+    bit_flips: Vec<u64>,
+}
+
+impl IdStats {
+    fn update(&mut self, data: &[u8]) {
+        if self.bytes.len() < data.len() {
+            self.bytes.resize_with(data.len(), ByteStats::default);
+            self.bit_flips.resize(data.len() * 8, 0);
+        }
+
+        let first = self.frames == 0;
+        for (i, &byte) in data.iter().enumerate() {
+            let stats = &mut self.bytes[i];
+            stats.histogram[byte as usize] += 1;
+            stats.frames += 1;
+
+            if !first {
+                if let Some(&prev) = self.last.get(i) {
+                    stats.transitions += 1;
+                    if byte == prev.wrapping_add(1) {
+                        stats.increments += 1;
+                    }
+                    if byte & 0x0F == prev.wrapping_add(1) & 0x0F {
+                        stats.nibble_increments += 1;
+                    }
+                    let flipped = byte ^ prev;
+                    for bit in 0..8 {
+                        if flipped & (1 << bit) != 0 {
+                            self.bit_flips[i * 8 + bit] += 1;
+                        }
+                    }
+                }
+            }
+
+            for (n, checksum) in Checksum::ALL.iter().enumerate() {
+                if data.len() > 1 && checksum.compute(data, i) == byte {
+                    stats.checksums[n] += 1;
+                }
+            }
+        }
+
+        self.frames += 1;
+        self.last.clear();
+        self.last.extend_from_slice(data);
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Longest payload seen
+    pub fn length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Classification of each byte, `None` until enough frames were seen
+    pub fn classify(&self) -> Option<Vec<ByteKind>> {
+        if self.frames < MIN_FRAMES {
+            return None;
+        }
+
+        let mut kinds = self
+            .bytes
+            .iter()
+            .map(|stats| {
+                if let Some(value) = stats.constant() {
+                    return ByteKind::Constant(value);
+                }
+                if ByteStats::agrees(stats.increments, stats.transitions) {
+                    return ByteKind::Counter;
+                }
+                if ByteStats::agrees(stats.nibble_increments, stats.transitions) {
+                    return ByteKind::NibbleCounter;
+                }
+                for (n, &count) in stats.checksums.iter().enumerate() {
+                    if ByteStats::agrees(count, stats.frames) {
+                        return ByteKind::Checksum(Checksum::ALL[n]);
+                    }
+                }
+                ByteKind::Live(stats.entropy())
+            })
+            .collect::<Vec<_>>();
+
+        // A payload that XORs to zero matches at every byte, the checksum is taken to be the
+        // last one as on most buses
+        for checksum in Checksum::ALL {
+            let matches = (0..kinds.len())
+                .filter(|&i| kinds[i] == ByteKind::Checksum(checksum))
+                .collect::<Vec<_>>();
+            if let Some((_, others)) = matches.split_last() {
+                for &i in others {
+                    kinds[i] = ByteKind::Live(self.bytes[i].entropy());
+                }
+            }
+        }
+
+        Some(kinds)
+    }
+
+    /// Runs of live bits, split where a bit flips much more often than the one below it
+    ///
+    /// The lowest bit of a signal flips the most and each higher bit about half as often, so a
+    /// jump in the flip rate marks the least significant bit of the next signal. Counter and
+    /// checksum bytes are left out.
+    pub fn signal_candidates(&self) -> Vec<SignalCandidate> {
+        let Some(kinds) = self.classify() else {
+            return vec![];
+        };
+
+        let mut candidates = vec![];
+        let mut current: Option<SignalCandidate> = None;
+        let mut prev_flips = 0;
+
+        for (bit, &flips) in self.bit_flips.iter().enumerate() {
+            let live = matches!(kinds[bit / 8], ByteKind::Live(_));
+            let starts_signal = flips > prev_flips.max(1) * 4;
+
+            if !live || flips == 0 || starts_signal {
+                candidates.extend(current.take());
+            }
+            if live && flips > 0 {
+                let signal = current.get_or_insert(SignalCandidate {
+                    start_bit: bit,
+                    length: 0,
+                });
+                signal.length += 1;
+            }
+            prev_flips = flips;
+        }
+        candidates.extend(current);
+
+        candidates
+    }
+}
+
+/// Collects the statistics of every ID
+#[derive(Debug, Default)]
+pub struct PayloadAnalyzer {
+    ids: BTreeMap<(bool, u32), IdStats>,
+}
+
+impl PayloadAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, id: Id, data: &[u8]) {
+        let key = match id {
+            Id::Standard(id) => (false, id.as_raw() as u32),
+            Id::Extended(id) => (true, id.as_raw()),
+        };
+        self.ids.entry(key).or_default().update(data);
+    }
+
+    /// Statistics of every ID, standard IDs first
+    pub fn ids(&self) -> impl Iterator<Item = (Id, &IdStats)> {
+        self.ids.iter().filter_map(|(&(extended, raw), stats)| {
+            let id = match extended {
+                true => embedded_can::ExtendedId::new(raw).map(Id::Extended),
+                false => embedded_can::StandardId::new(raw as u16).map(Id::Standard),
+            };
+            id.map(|id| (id, stats))
+        })
+    }
+
+    /// A starter DBC with one message per ID, signals for the counters and checksums found and
+    /// placeholder signals for the candidates
+    pub fn dbc_skeleton(&self) -> String {
+        let mut out = String::from("VERSION \"\"\n\nNS_ :\n\nBS_:\n\nBU_:\n");
+        let mut comments = String::new();
+
+        for (&(extended, raw), stats) in &self.ids {
+            let dbc_id = if extended { raw | 0x8000_0000 } else { raw };
+            let _ = write!(
+                out,
+                "\nBO_ {} MSG_{:X}: {} Vector__XXX\n",
+                dbc_id,
+                raw,
+                stats.length()
+            );
+
+            let kinds = stats.classify().unwrap_or_default();
+            for (i, kind) in kinds.iter().enumerate() {
+                let (name, length) = match kind {
+                    ByteKind::Counter => ("Counter", 8),
+                    ByteKind::NibbleCounter => ("Counter", 4),
+                    ByteKind::Checksum(checksum) => {
+                        let _ = writeln!(
+                            comments,
+                            "CM_ SG_ {} Checksum_B{} \"{} checksum of the other bytes\";",
+                            dbc_id, i, checksum
+                        );
+                        ("Checksum", 8)
+                    }
+                    _ => continue,
+                };
+                let _ = writeln!(
+                    out,
+                    " SG_ {}_B{} : {}|{}@1+ (1,0) [0|{}] \"\" Vector__XXX",
+                    name,
+                    i,
+                    i * 8,
+                    length,
+                    (1u64 << length) - 1
+                );
+            }
+            for candidate in stats.signal_candidates() {
+                let _ = writeln!(
+                    out,
+                    " SG_ Signal_{} : {}|{}@1+ (1,0) [0|{}] \"\" Vector__XXX",
+                    candidate.start_bit,
+                    candidate.start_bit,
+                    candidate.length,
+                    (1u64 << candidate.length) - 1
+                );
+            }
+        }
+
+        if !comments.is_empty() {
+            out.push('\n');
+            out.push_str(&comments);
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn analyze(frames: impl Iterator<Item = Vec<u8>>) -> IdStats {
+        let mut stats = IdStats::default();
+        for data in frames {
+            stats.update(&data);
+        }
+        stats
+    }
+
+    #[test]
+    fn classify_bytes() {
+        let stats = analyze((0..200u32).map(|i| {
+            // Counter, constant, a 12 bit signal over bytes 2 and 3, then a checksum
+            let signal = ((i * 7) % 4096) as u16;
+            let mut data = vec![i as u8, 0x55, signal as u8, (signal >> 8) as u8];
+            data.push(Checksum::Xor.compute(&data, data.len()));
+            data
+        }));
+
+        let kinds = stats.classify().unwrap();
+        assert_eq!(kinds[0], ByteKind::Counter);
+        assert_eq!(kinds[1], ByteKind::Constant(0x55));
+        assert!(matches!(kinds[2], ByteKind::Live(e) if e > 7.0));
+        assert!(matches!(kinds[3], ByteKind::Live(_)));
+        assert_eq!(kinds[4], ByteKind::Checksum(Checksum::Xor));
+
+        let candidates = stats.signal_candidates();
+        assert_eq!(candidates[0].start_bit, 16);
+        assert!(candidates[0].length <= 12);
+    }
+
+    #[test]
+    fn dbc_skeleton() {
+        let mut analyzer = PayloadAnalyzer::new();
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        for i in 0..200u32 {
+            let signal = ((i * 7) % 4096) as u16;
+            let mut data = vec![i as u8, 0x55, signal as u8, (signal >> 8) as u8];
+            data.push(Checksum::Xor.compute(&data, data.len()));
+            analyzer.update(id, &data);
+        }
+        let id = Id::Extended(embedded_can::ExtendedId::new(0x18FEF100).unwrap());
+        analyzer.update(id, &[1]);
+
+        let db = candecode::Database::from_dbc(&analyzer.dbc_skeleton()).unwrap();
+        assert_eq!(db.messages().len(), 2);
+
+        let message = db.message_by_name("MSG_123").unwrap();
+        assert_eq!(message.dlc, 5);
+        let names = message
+            .signals
+            .iter()
+            .map(|signal| signal.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names[..2], ["Counter_B0", "Checksum_B4"]);
+        assert!(names[2..].iter().all(|name| name.starts_with("Signal_")));
+        assert!(message.signals[1].comment.is_some());
+
+        let message = db.message_by_name("MSG_18FEF100").unwrap();
+        assert!(message.signals.is_empty());
+    }
+
+    #[test]
+    fn crc8_j1850() {
+        // Check value of CRC-8/SAE-J1850 for "123456789"
+        assert_eq!(Checksum::Crc8J1850.compute(b"123456789", usize::MAX), 0x4B);
+    }
+
+    #[test]
+    fn too_few_frames() {
+        let mut analyzer = PayloadAnalyzer::new();
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        analyzer.update(id, &[1, 2]);
+
+        let (found, stats) = analyzer.ids().next().unwrap();
+        assert_eq!(found, id);
+        assert!(stats.classify().is_none());
+    }
+}
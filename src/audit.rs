@@ -0,0 +1,179 @@
+//
+// audit.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Session audit log of the frames ican transmits
+//!
+//! Every sent frame is appended to a JSON lines file with its time, interface and the command
+//! line that sent it, so bench sessions can be reviewed and reproduced.
+
+use crate::{
+    drivers::{AsyncCanDriver, CanDriver, DriverError},
+    frame::CanFrame,
+};
+
+use async_trait::async_trait;
+use embedded_can::{Frame, Id};
+
+use std::{
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Audit log shared by the drivers of a command
+#[derive(Clone)]
+pub struct AuditLog {
+    out: Arc<Mutex<BufWriter<File>>>,
+    command: Arc<str>,
+}
+
+impl AuditLog {
+    /// Open a log, appending to it if it exists, for frames sent by `command`
+    pub fn create(path: &Path, command: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog {
+            out: Arc::new(Mutex::new(BufWriter::new(file))),
+            command: command.into(),
+        })
+    }
+
+    /// Append a sent frame, flushed so the log survives the session ending abruptly
+    pub fn record(&self, interface: &str, frame: &CanFrame) -> io::Result<()> {
+        let line = record(SystemTime::now(), &self.command, interface, frame);
+
+        let mut out = self.out.lock().unwrap();
+        writeln!(out, "{}", line)?;
+        out.flush()
+    }
+
+    /// Wrap a driver, recording the frames sent through it
+    pub fn wrap(&self, driver: CanDriver, interface: &str) -> CanDriver {
+        let audited = AuditedDriver {
+            driver,
+            log: self.clone(),
+            interface: interface.to_string(),
+        };
+        CanDriver::Plugin(Box::new(audited))
+    }
+}
+
+fn record(time: SystemTime, command: &str, interface: &str, frame: &CanFrame) -> String {
+    let (id, extended) = match frame.id() {
+        Id::Standard(id) => (format!("{:03X}", id.as_raw()), false),
+        Id::Extended(id) => (format!("{:08X}", id.as_raw()), true),
+    };
+    // Remote frames carry a DLC but no data
+    let data = match frame.is_remote_frame() {
+        true => &[][..],
+        false => frame.data(),
+    };
+    let data = data.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{:02X}", b);
+        s
+    });
+    let time = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+
+    serde_json::json!({
+        "time": time,
+        "command": command,
+        "interface": interface,
+        "id": id,
+        "extended": extended,
+        "remote": frame.is_remote_frame(),
+        "dlc": frame.raw_dlc(),
+        "data": data,
+    })
+    .to_string()
+}
+
+struct AuditedDriver {
+    driver: CanDriver,
+    log: AuditLog,
+    interface: String,
+}
+
+#[async_trait]
+impl AsyncCanDriver for AuditedDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        self.driver.recv().await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        self.driver.recv_many(buf).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        if let Err(e) = self.log.record(&self.interface, &frame) {
+            eprintln!("Failed to write the audit log: {}", e);
+        }
+        self.driver.send(frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        self.driver.receive_own_messages(enabled)
+    }
+
+    fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        self.driver.receive_error_frames(enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::drivers::loopback::LoopbackDriver;
+
+    use embedded_can::{ExtendedId, StandardId};
+
+    use std::time::Duration;
+
+    #[test]
+    fn record_format() {
+        let time = UNIX_EPOCH + Duration::from_millis(1500);
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 0xAB]).unwrap();
+        assert_eq!(
+            record(time, "ican vcan0 send 123#01AB", "vcan0", &frame),
+            r#"{"command":"ican vcan0 send 123#01AB","data":"01AB","dlc":2,"extended":false,"id":"123","interface":"vcan0","remote":false,"time":1.5}"#
+        );
+
+        let frame = CanFrame::new_remote(ExtendedId::new(0x18FEF100).unwrap(), 8).unwrap();
+        let line: serde_json::Value =
+            serde_json::from_str(&record(time, "", "can0", &frame)).unwrap();
+        assert_eq!(line["id"], "18FEF100");
+        assert_eq!(line["remote"], true);
+        assert_eq!(line["data"], "");
+    }
+
+    #[tokio::test]
+    async fn records_sent_frames() {
+        let path = std::env::temp_dir().join(format!("ican-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let (a, mut b) = LoopbackDriver::pair();
+        let log = AuditLog::create(&path, "ican test").unwrap();
+        let mut a = log.wrap(a.into(), "loopback");
+
+        let frame = CanFrame::new(StandardId::new(0x100).unwrap(), &[1]).unwrap();
+        a.send(frame.clone()).await;
+        a.send(frame).await;
+        assert!(b.recv().await.is_some());
+
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines
+            .lines()
+            .all(|l| l.contains(r#""command":"ican test""#)));
+    }
+}
@@ -0,0 +1,175 @@
+//
+// busstate.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Controller error states, decoded from error frames
+//!
+//! Error frames follow the SocketCAN layout: the error class is in the ID and the controller
+//! status in the second data byte.
+
+use crate::frame::CanFrame;
+
+use embedded_can::{Frame, Id};
+
+use std::fmt;
+
+/// Controller problems, the status is in data byte 1
+const CAN_ERR_CRTL: u32 = 0x0004;
+/// The controller went bus-off
+const CAN_ERR_BUSOFF: u32 = 0x0040;
+/// The controller was restarted after bus-off
+const CAN_ERR_RESTARTED: u32 = 0x0100;
+
+const CAN_ERR_CRTL_RX_WARNING: u8 = 0x04;
+const CAN_ERR_CRTL_TX_WARNING: u8 = 0x08;
+const CAN_ERR_CRTL_RX_PASSIVE: u8 = 0x10;
+const CAN_ERR_CRTL_TX_PASSIVE: u8 = 0x20;
+const CAN_ERR_CRTL_ACTIVE: u8 = 0x40;
+
+/// Error state of a CAN controller, from best to worst
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BusState {
+    #[default]
+    ErrorActive,
+    ErrorWarning,
+    ErrorPassive,
+    BusOff,
+}
+
+impl BusState {
+    /// State reported by an error frame, `None` for errors that do not change the state
+    pub fn from_error_frame(frame: &CanFrame) -> Option<BusState> {
+        if !frame.is_error_frame() {
+            return None;
+        }
+        let class = match frame.id() {
+            Id::Extended(id) => id.as_raw(),
+            Id::Standard(id) => id.as_raw() as u32,
+        };
+
+        if class & CAN_ERR_BUSOFF != 0 {
+            return Some(BusState::BusOff);
+        }
+        if class & CAN_ERR_CRTL != 0 {
+            let status = frame.data().get(1).copied().unwrap_or_default();
+            if status & (CAN_ERR_CRTL_RX_PASSIVE | CAN_ERR_CRTL_TX_PASSIVE) != 0 {
+                return Some(BusState::ErrorPassive);
+            }
+            if status & (CAN_ERR_CRTL_RX_WARNING | CAN_ERR_CRTL_TX_WARNING) != 0 {
+                return Some(BusState::ErrorWarning);
+            }
+            if status & CAN_ERR_CRTL_ACTIVE != 0 {
+                return Some(BusState::ErrorActive);
+            }
+        }
+        if class & CAN_ERR_RESTARTED != 0 {
+            return Some(BusState::ErrorActive);
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for BusState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BusState::ErrorActive => "error-active",
+            BusState::ErrorWarning => "error-warning",
+            BusState::ErrorPassive => "error-passive",
+            BusState::BusOff => "bus-off",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A change of the controller state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: BusState,
+    pub to: BusState,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+/// Follows the controller state through the error frames of a bus
+#[derive(Debug, Default)]
+pub struct BusStateTracker {
+    state: BusState,
+    bus_off_count: usize,
+}
+
+impl BusStateTracker {
+    pub fn state(&self) -> BusState {
+        self.state
+    }
+
+    /// Number of times the controller went bus-off
+    pub fn bus_off_count(&self) -> usize {
+        self.bus_off_count
+    }
+
+    /// Apply a frame, returning the transition it caused
+    pub fn update(&mut self, frame: &CanFrame) -> Option<Transition> {
+        let to = BusState::from_error_frame(frame)?;
+        if to == self.state {
+            return None;
+        }
+
+        let from = std::mem::replace(&mut self.state, to);
+        if to == BusState::BusOff {
+            self.bus_off_count += 1;
+        }
+
+        Some(Transition { from, to })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn controller(status: u8) -> CanFrame {
+        CanFrame::new_error(CAN_ERR_CRTL, &[0, status, 0, 0, 0, 0, 0, 0]).unwrap()
+    }
+
+    #[test]
+    fn transitions() {
+        let mut tracker = BusStateTracker::default();
+
+        let data = CanFrame::new(StandardId::new(0x40).unwrap(), &[]).unwrap();
+        assert_eq!(tracker.update(&data), None);
+
+        let states = [
+            controller(CAN_ERR_CRTL_TX_WARNING),
+            controller(CAN_ERR_CRTL_RX_PASSIVE),
+            CanFrame::new_error(CAN_ERR_BUSOFF, &[0; 8]).unwrap(),
+            CanFrame::new_error(CAN_ERR_BUSOFF, &[0; 8]).unwrap(),
+            CanFrame::new_error(CAN_ERR_RESTARTED, &[0; 8]).unwrap(),
+        ]
+        .iter()
+        .filter_map(|frame| tracker.update(frame))
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            states,
+            vec![
+                "error-active -> error-warning",
+                "error-warning -> error-passive",
+                "error-passive -> bus-off",
+                "bus-off -> error-active",
+            ]
+        );
+        assert_eq!(tracker.bus_off_count(), 1);
+        assert_eq!(tracker.state(), BusState::ErrorActive);
+    }
+}
@@ -0,0 +1,220 @@
+//
+// cli.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Command line of the `ican` binary
+
+use crate::{
+    action,
+    audit::AuditLog,
+    config::Config,
+    drivers::{ratelimit::RateLimitedDriver, CanDriver},
+    DriverOpts,
+};
+
+use clap::{Parser, Subcommand};
+
+use std::path::PathBuf;
+
+/// ican provides several common CAN commands
+#[derive(Parser, Debug)]
+#[command(author = "Natesh Narain", version, about = "Modern CAN tools")]
+pub struct Args {
+    /// The CAN interface to use (with driver options if applicable)
+    #[arg(value_enum, value_parser = clap::value_parser!(DriverOpts))]
+    pub interface: Option<DriverOpts>,
+    #[command(subcommand)]
+    pub cmd: Command,
+    #[arg(short = 't', long = "tick-rate", default_value = "200")]
+    pub tui_tick_rate: u64,
+    /// Receive on this many SocketCAN sockets, each read on its own thread with a share of the
+    /// IDs, for buses too busy for one core. A power of two, frames of different IDs may be
+    /// reordered. Frames ican sends are told from others by the kernel's echo flag.
+    #[arg(long = "rx-queues", default_value = "1")]
+    pub rx_queues: usize,
+    /// Also receive the frames ican sends once they are transmitted, shown and logged as TX
+    #[arg(long = "own-messages")]
+    pub own_messages: bool,
+    /// Append every frame ican sends to a JSON lines file, with its time and command line
+    #[arg(long = "audit")]
+    pub audit: Option<PathBuf>,
+    /// Most frames per second ican sends on an interface, so a mistake cannot flood the bus.
+    /// 1000 if not set, except for bridge which is only limited when set.
+    #[arg(long = "tx-rate-limit")]
+    pub tx_rate_limit: Option<u32>,
+    /// Send without the rate limit, read-only mode still applies
+    #[arg(long = "force")]
+    pub force: bool,
+    /// Refuse to run commands that transmit frames, for observation-only sessions on production
+    /// systems. The default when the config file sets `read_only = true`.
+    #[arg(long = "read-only")]
+    pub read_only: bool,
+    /// Config file with named filters, `$XDG_CONFIG_HOME/ican/config.toml` if not set
+    #[arg(long = "config")]
+    pub config: Option<PathBuf>,
+}
+
+/// Frames per second sent when `--tx-rate-limit` is not set
+pub const DEFAULT_TX_RATE_LIMIT: u32 = 1000;
+
+/// Command to run
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print CAN frames to console
+    Dump(action::dump::Args),
+    /// TUI displaying CAN frames and decoded signals
+    Monitor(action::monitor::Args),
+    /// Send CAN frames to the selected interface
+    Send(action::send::Args),
+    /// Bridge different CAN interfaces together
+    Bridge(action::bridge::Args),
+    /// Send the frames of a log file (BLF, TRC, candump, CSV) with their logged timing
+    Replay(action::replay::Args),
+    /// Print decoded signals and alert when watched thresholds are crossed
+    Signals(action::signals::Args),
+    /// Find constant, counter and checksum bytes and candidate signals, from a log file or the
+    /// interface
+    Analyze(action::analyze::Args),
+    /// Send scripted frames and check the frames and signals received, reporting as JUnit XML
+    Test(action::scenario::Args),
+    /// J1939 subcommands
+    #[command(subcommand)]
+    J1939(action::j1939::J1939Commands),
+    /// UDS diagnostic subcommands
+    #[command(subcommand)]
+    Uds(action::uds::UdsCommands),
+    /// CANopen subcommands
+    #[command(subcommand)]
+    Canopen(action::canopen::CanOpenCommands),
+    /// Signal database subcommands, no interface required
+    #[command(subcommand)]
+    Dbc(action::dbc::DbcCommands),
+    /// Convert a log file to another format, no interface required
+    Convert(action::convert::Args),
+    /// Merge log files into one timeline, no interface required
+    Merge(action::merge::Args),
+    /// Virtual CAN interface subcommands, no interface required
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
+    #[command(subcommand)]
+    Vcan(action::vcan::VcanCommands),
+    /// Print a shell completion script, e.g. `ican completions bash > /etc/bash_completion.d/ican`
+    Completions(action::completions::Args),
+    /// Write man pages for ican and its subcommands to a directory
+    Manpages(action::manpages::Args),
+    /// List the CAN interfaces, used by the completion scripts
+    #[command(hide = true)]
+    ListInterfaces,
+}
+
+impl Command {
+    /// Whether the command sends frames on the interface ican was given
+    pub fn transmits(&self) -> bool {
+        match self {
+            Command::Send(_) | Command::Replay(_) | Command::Test(_) | Command::Uds(_) => true,
+            Command::J1939(cmd) => cmd.transmits(),
+            Command::Canopen(cmd) => cmd.transmits(),
+            // A bridge receives on the interface and sends toward its destination
+            Command::Bridge(_) => false,
+            Command::Dump(_)
+            | Command::Monitor(_)
+            | Command::Signals(_)
+            | Command::Analyze(_)
+            | Command::Dbc(_)
+            | Command::Convert(_)
+            | Command::Merge(_)
+            | Command::Completions(_)
+            | Command::Manpages(_)
+            | Command::ListInterfaces => false,
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            Command::Vcan(_) => false,
+        }
+    }
+
+    /// Fail if the command sends on `interface` in read-only mode, set by `--read-only` or the
+    /// config file. Bridging is refused toward the interface, not from it.
+    pub fn check_read_only(
+        &self,
+        interface: Option<&DriverOpts>,
+        read_only: bool,
+        config: &Config,
+    ) -> anyhow::Result<()> {
+        let transmits = match self {
+            Command::Bridge(args) => interface == Some(&args.interface),
+            cmd => cmd.transmits(),
+        };
+        if transmits && (read_only || config.read_only) {
+            anyhow::bail!(
+                "Read-only mode refuses commands that transmit frames, it is set by {}",
+                match read_only {
+                    true => "--read-only",
+                    false => "read_only in the config file",
+                }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Subcommand context
+pub struct CommandContext {
+    pub driver: CanDriver,
+    pub interface: String,
+    pub tick_rate: u64,
+    /// Safeguards for the frames sent, already applied to `driver`
+    pub tx: TxOptions,
+    pub config: Config,
+}
+
+/// Safeguards applied to every driver a command sends on
+#[derive(Clone, Default)]
+pub struct TxOptions {
+    /// Frames per second, unlimited if `None`
+    pub rate_limit: Option<u32>,
+    pub audit: Option<AuditLog>,
+}
+
+impl TxOptions {
+    /// Wrap a driver, recording its frames before limiting their rate
+    pub fn wrap(&self, mut driver: CanDriver, interface: &str) -> CanDriver {
+        if let Some(audit) = &self.audit {
+            driver = audit.wrap(driver, interface);
+        }
+        if let Some(rate) = self.rate_limit {
+            driver = RateLimitedDriver::new(driver, rate).into();
+        }
+        driver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(args: &[&str]) -> anyhow::Result<()> {
+        let args = Args::try_parse_from(["ican", "--read-only", "vcan0"].iter().chain(args))?;
+        args.cmd
+            .check_read_only(args.interface.as_ref(), args.read_only, &Config::default())
+    }
+
+    #[test]
+    fn read_only() {
+        assert!(check(&["dump"]).is_ok());
+        assert!(check(&["j1939", "dtc"]).is_ok());
+        assert!(check(&["canopen", "expect", "--node", "1", "--state", "operational"]).is_ok());
+        // Bridging from the interface, not toward it
+        assert!(check(&["bridge", "vcan1"]).is_ok());
+
+        // DM2 is requested
+        for args in [
+            &["j1939", "dtc", "--previous"][..],
+            &["send", "123#01"],
+            &["bridge", "socketcan://vcan0"],
+        ] {
+            let err = check(args).unwrap_err().to_string();
+            assert!(err.starts_with("Read-only mode refuses"), "{}", err);
+        }
+    }
+}
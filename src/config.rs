@@ -0,0 +1,247 @@
+//
+// config.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! The ican configuration file
+//!
+//! Read from `--config`, or from `$XDG_CONFIG_HOME/ican/config.toml` (`~/.config/ican/config.toml`)
+//! when it exists. Named filters are sets of IDs the receiving commands can be limited to, so a
+//! team can share its standard views. `read_only` makes read-only mode the default, so commands
+//! that transmit refuse to run:
+//!
+//! ```toml
+//! read_only = true
+//!
+//! [filters.powertrain]
+//! ids = ["0x100-0x1FF", "0x7E8"]
+//! ```
+
+use crate::utils;
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Largest extended ID
+const MAX_ID: u32 = 0x1FFF_FFFF;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config {0}: {1}")]
+    Read(PathBuf, io::Error),
+    #[error("Invalid config {0}: {1}")]
+    Invalid(PathBuf, String),
+}
+
+/// Settings from the configuration file
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// ID filters by name
+    pub filters: BTreeMap<String, IdFilter>,
+    /// Refuse to transmit, as with `--read-only`
+    pub read_only: bool,
+}
+
+impl Config {
+    /// Load a config file, or the default one if it exists
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if !required && e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Config::default())
+            }
+            Err(e) => return Err(ConfigError::Read(path, e)),
+        };
+        text.parse()
+            .map_err(|e: String| ConfigError::Invalid(path, e))
+    }
+
+    /// Filter of the given name
+    pub fn filter(&self, name: &str) -> Option<&IdFilter> {
+        self.filters.get(name)
+    }
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table = s.parse::<toml::Table>().map_err(|e| e.to_string())?;
+
+        let mut config = Config::default();
+        if let Some(read_only) = table.get("read_only") {
+            config.read_only = read_only
+                .as_bool()
+                .ok_or("'read_only' must be true or false")?;
+        }
+
+        let Some(filters) = table.get("filters") else {
+            return Ok(config);
+        };
+        let filters = filters
+            .as_table()
+            .ok_or("'filters' must be a table of named filters")?;
+
+        for (name, filter) in filters {
+            let ids = filter
+                .get("ids")
+                .and_then(|ids| ids.as_array())
+                .ok_or_else(|| format!("filter '{}' needs an 'ids' list", name))?;
+
+            let mut ranges = vec![];
+            for range in ids {
+                let range = range
+                    .as_str()
+                    .ok_or_else(|| format!("filter '{}': IDs must be strings", name))?;
+                ranges.push(parse_range(range).map_err(|e| format!("filter '{}': {}", name, e))?);
+            }
+            config.filters.insert(name.clone(), IdFilter { ranges });
+        }
+
+        Ok(config)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ican/config.toml`, falling back to `~/.config`
+fn default_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("ican").join("config.toml"))
+}
+
+/// A set of ID ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdFilter {
+    ranges: Vec<RangeInclusive<u32>>,
+}
+
+impl IdFilter {
+    /// Whether the ID is in any of the ranges
+    pub fn matches(&self, id: Id) -> bool {
+        let raw = utils::id_to_raw(&id);
+        self.ranges.iter().any(|range| range.contains(&raw))
+    }
+
+    /// Also match the IDs of another filter
+    pub fn extend(&mut self, other: &IdFilter) {
+        self.ranges.extend(other.ranges.iter().cloned());
+    }
+}
+
+/// Comma separated IDs and ID ranges, e.g. "0x100-0x1FF,0x7E8"
+impl FromStr for IdFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = s.split(',').map(parse_range).collect::<Result<_, _>>()?;
+        Ok(IdFilter { ranges })
+    }
+}
+
+/// Parse a hex ID or an inclusive range of them, with or without the 0x prefix
+fn parse_range(s: &str) -> Result<RangeInclusive<u32>, String> {
+    let id = |s: &str| {
+        let s = s.trim();
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .filter(|id| *id <= MAX_ID)
+            .ok_or_else(|| format!("invalid ID '{}'", s))
+    };
+
+    let (start, end) = match s.split_once('-') {
+        Some((start, end)) => (id(start)?, id(end)?),
+        None => (id(s)?, id(s)?),
+    };
+    if start > end {
+        return Err(format!("empty ID range '{}'", s.trim()));
+    }
+
+    Ok(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, StandardId};
+
+    fn standard(id: u16) -> Id {
+        StandardId::new(id).unwrap().into()
+    }
+
+    #[test]
+    fn named_filters() {
+        let config = r#"
+[filters.powertrain]
+ids = ["0x100-0x1FF", "7E8"]
+
+[filters.j1939]
+ids = ["0x18FEF100"]
+"#
+        .parse::<Config>()
+        .unwrap();
+
+        let powertrain = config.filter("powertrain").unwrap();
+        assert!(powertrain.matches(standard(0x100)));
+        assert!(powertrain.matches(standard(0x1FF)));
+        assert!(powertrain.matches(standard(0x7E8)));
+        assert!(!powertrain.matches(standard(0x200)));
+
+        let j1939 = config.filter("j1939").unwrap();
+        assert!(j1939.matches(ExtendedId::new(0x18FEF100).unwrap().into()));
+        assert!(config.filter("body").is_none());
+    }
+
+    #[test]
+    fn read_only() {
+        assert!(!"".parse::<Config>().unwrap().read_only);
+        assert!("read_only = true".parse::<Config>().unwrap().read_only);
+        assert!("read_only = \"yes\"".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn invalid_filters() {
+        assert!("".parse::<Config>().unwrap().filters.is_empty());
+        assert!("[filters.a]\nid = [\"0x100\"]".parse::<Config>().is_err());
+        assert!("[filters.a]\nids = [256]".parse::<Config>().is_err());
+        assert!("[filters.a]\nids = [\"0x200-0x100\"]"
+            .parse::<Config>()
+            .is_err());
+        assert!("[filters.a]\nids = [\"0x20000000\"]"
+            .parse::<Config>()
+            .is_err());
+    }
+
+    #[test]
+    fn inline_ranges() {
+        let filter = "0x100-0x10F, 0x7E8".parse::<IdFilter>().unwrap();
+        assert!(filter.matches(standard(0x105)));
+        assert!(filter.matches(standard(0x7E8)));
+        assert!(!filter.matches(standard(0x7E0)));
+        assert!("powertrain".parse::<IdFilter>().is_err());
+    }
+}
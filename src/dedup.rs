@@ -0,0 +1,122 @@
+//
+// dedup.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Duplicate frame suppression
+//!
+//! Mirrored topologies deliver the same frame more than once: aggregated interfaces see it on
+//! each, gateways echo it back. A frame identical to one seen within the window is a duplicate.
+//! The window is measured from the first copy, so it should stay below the period of the fastest
+//! ID, or its repeats are taken as duplicates too.
+
+use crate::frame::CanFrame;
+
+use embedded_can::{Frame, Id};
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// ID, remote and error flags, DLC and data of a frame
+type FrameKey = (Id, bool, bool, u8, [u8; 8]);
+
+/// Drops frames seen within a window and counts them
+#[derive(Debug, Clone)]
+pub struct Deduplicator {
+    window: Duration,
+    /// When each frame was first seen
+    seen: HashMap<FrameKey, Instant>,
+    last_prune: Option<Instant>,
+    duplicates: u64,
+}
+
+impl Deduplicator {
+    pub fn new(window: Duration) -> Self {
+        Deduplicator {
+            window,
+            seen: HashMap::new(),
+            last_prune: None,
+            duplicates: 0,
+        }
+    }
+
+    /// Whether the frame, received at `now`, repeats one within the window
+    pub fn is_duplicate(&mut self, frame: &CanFrame, now: Instant) -> bool {
+        self.prune(now);
+
+        let mut data = [0u8; 8];
+        data[..frame.data().len()].copy_from_slice(frame.data());
+        let key = (
+            frame.id(),
+            frame.is_remote_frame(),
+            frame.is_error_frame(),
+            frame.raw_dlc(),
+            data,
+        );
+
+        match self.seen.get(&key) {
+            Some(&first) if now.saturating_duration_since(first) < self.window => {
+                self.duplicates += 1;
+                true
+            }
+            _ => {
+                self.seen.insert(key, now);
+                false
+            }
+        }
+    }
+
+    /// Frames dropped as duplicates
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Forget the frames past the window, once per window
+    fn prune(&mut self, now: Instant) {
+        let last = *self.last_prune.get_or_insert(now);
+        if now.saturating_duration_since(last) < self.window {
+            return;
+        }
+
+        let window = self.window;
+        self.seen
+            .retain(|_, first| now.saturating_duration_since(*first) < window);
+        self.last_prune = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn frame(data: &[u8]) -> CanFrame {
+        CanFrame::new(StandardId::new(0x100).unwrap(), data).unwrap()
+    }
+
+    #[test]
+    fn duplicates_within_window() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut dedup = Deduplicator::new(Duration::from_millis(5));
+
+        assert!(!dedup.is_duplicate(&frame(&[1]), at(0)));
+        // The echo from a gateway, and the same frame on a second interface
+        assert!(dedup.is_duplicate(&frame(&[1]), at(1)));
+        assert!(dedup.is_duplicate(&frame(&[1]), at(2)));
+        // Other data is another frame
+        assert!(!dedup.is_duplicate(&frame(&[2]), at(2)));
+        // The next period
+        assert!(!dedup.is_duplicate(&frame(&[1]), at(10)));
+        assert!(dedup.is_duplicate(&frame(&[1]), at(11)));
+
+        assert_eq!(dedup.duplicates(), 3);
+        // Only the frames of the current window are kept
+        assert_eq!(dedup.seen.len(), 1);
+    }
+}
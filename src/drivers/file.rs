@@ -0,0 +1,170 @@
+//
+// file.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Frames read from a log file, so captures are decoded by the same commands as an interface
+
+use crate::{
+    drivers::{AsyncCanDriver, DriverError},
+    frame::{CanFrame, Direction},
+    logfile::{self, LogError, LogReader, ReadOptions},
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+use std::{path::Path, sync::mpsc::sync_channel, thread};
+
+/// Frames read ahead of the receiver
+const QUEUE_SIZE: usize = 1024;
+
+/// Receives the frames of a log file, in file order
+///
+/// Frames sent are dropped. Without following, receiving ends at the end of the file.
+pub struct FileDriver {
+    rx: Receiver<CanFrame>,
+}
+
+impl FileDriver {
+    /// Read a log file, waiting for frames appended to it if `follow` is set
+    pub fn open(path: &Path, options: &ReadOptions, follow: bool) -> Result<Self, LogError> {
+        let (path, options) = (path.to_path_buf(), options.clone());
+        let (opened_tx, opened) = sync_channel(1);
+        let (tx, rx) = channel(QUEUE_SIZE);
+
+        // Readers are not `Send`, so the file is opened on the thread reading it. A thread rather
+        // than a blocking task, a followed file waiting for data must not hold up the runtime
+        // shutting down.
+        thread::spawn(move || {
+            let log = match follow {
+                true => logfile::follow_with(&path, &options),
+                false => logfile::open_with(&path, &options),
+            };
+            match log {
+                Ok(log) => {
+                    let _ = opened_tx.send(Ok(()));
+                    read_frames(log, tx);
+                }
+                Err(e) => {
+                    let _ = opened_tx.send(Err(e));
+                }
+            }
+        });
+        // The thread always reports whether the file opened
+        opened.recv().unwrap()?;
+
+        Ok(FileDriver { rx })
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for FileDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        self.rx.recv().await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        let Some(frame) = self.rx.recv().await else {
+            return 0;
+        };
+        buf.push(frame);
+
+        let mut count = 1;
+        while let Ok(frame) = self.rx.try_recv() {
+            buf.push(frame);
+            count += 1;
+        }
+        count
+    }
+
+    async fn send(&mut self, _frame: CanFrame) {}
+
+    /// The frames sent by the recording interface are read as logged, marked as `Tx`
+    fn receive_own_messages(&mut self, _enabled: bool) -> Result<(), DriverError> {
+        Ok(())
+    }
+
+    /// Error frames are not passed on
+    fn receive_error_frames(&mut self, _enabled: bool) -> Result<(), DriverError> {
+        Ok(())
+    }
+}
+
+fn read_frames(mut log: LogReader, tx: Sender<CanFrame>) {
+    while let Some(entry) = log.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("Failed to read the log file: {}", e);
+                return;
+            }
+        };
+
+        // CAN FD and error frames have no classic frame to pass on
+        let Some(mut frame) = entry.to_frame() else {
+            continue;
+        };
+        if let Some(start) = log.start_time() {
+            frame = frame.with_timestamp(start + entry.timestamp);
+        }
+        if entry.direction == Some(Direction::Tx) {
+            frame = frame.with_direction(Direction::Tx);
+        }
+
+        // The receiver went away
+        if tx.blocking_send(frame).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::Frame;
+
+    use std::{fs::OpenOptions, io::Write, time::Duration};
+
+    #[tokio::test]
+    async fn follow_appended_frames() {
+        let path = std::env::temp_dir().join(format!("ican-follow-{}.log", std::process::id()));
+        std::fs::write(&path, "(1700000000.000000) can0 123#01\n").unwrap();
+
+        let mut driver = FileDriver::open(&path, &ReadOptions::default(), true).unwrap();
+        let frame = driver.recv().await.unwrap();
+        assert_eq!(frame.data(), [0x01]);
+
+        // A line written in two parts is read once complete
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "(1700000000.100000) can0 12").unwrap();
+        file.flush().unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        writeln!(file, "4#0203").unwrap();
+
+        let frame = tokio::time::timeout(Duration::from_secs(2), driver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data(), [0x02, 0x03]);
+        assert!(frame.timestamp().is_some());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ends_with_the_file() {
+        let path = std::env::temp_dir().join(format!("ican-read-{}.log", std::process::id()));
+        std::fs::write(&path, "(1.0) can0 123#01\n(2.0) can0 123#02\n").unwrap();
+
+        let mut driver = FileDriver::open(&path, &ReadOptions::default(), false).unwrap();
+        let mut frames = vec![];
+        while driver.recv_many(&mut frames).await > 0 {}
+        assert_eq!(frames.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,395 @@
+//
+// device.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 17 2026
+//
+
+use super::{
+    decode_frame, encode_frame, BitTiming, BitTimingConst, DeviceSelector, GsUsbOpts, DEVICE_IDS,
+    OVERFLOW_FLAG, RX_ECHO_ID,
+};
+use crate::drivers::{AsyncCanDriver, DriverError, DriverErrors};
+use crate::frame::{CanFrame, Direction};
+
+use async_trait::async_trait;
+use rusb::{
+    Device, DeviceHandle, Direction as UsbDirection, GlobalContext, Recipient, RequestType,
+    TransferType,
+};
+use thiserror::Error;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+/// USB interface of the CAN channels
+const INTERFACE: u8 = 0;
+/// Frames buffered between the reader thread and the driver
+const RX_QUEUE_SIZE: usize = 1024;
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(1);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a read waits, and so how soon the reader sees the driver was dropped
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+/// Largest bulk transfer, a host frame with timestamp fits
+const MAX_TRANSFER: usize = 64;
+
+// Vendor requests
+const BREQ_HOST_FORMAT: u8 = 0;
+const BREQ_BITTIMING: u8 = 1;
+const BREQ_MODE: u8 = 2;
+const BREQ_BT_CONST: u8 = 4;
+const BREQ_DEVICE_CONFIG: u8 = 5;
+
+/// Byte order marker of the host, the adapter answers in little endian
+const HOST_FORMAT: u32 = 0x0000_beef;
+const MODE_RESET: u32 = 0;
+const MODE_START: u32 = 1;
+
+/// gs_usb driver errors
+#[derive(Error, Debug)]
+pub enum GsUsbError {
+    #[error("No gs_usb adapter {0} attached")]
+    NotFound(DeviceSelector),
+    #[error("USB error: {0}")]
+    UsbError(#[from] rusb::Error),
+    #[error("The adapter has {count} channels, there is no channel {channel}")]
+    InvalidChannel { channel: u8, count: u8 },
+    #[error("The adapter's clock cannot divide into {0} bit/s")]
+    InvalidBitrate(u32),
+    #[error("The adapter has no bulk endpoints")]
+    NoEndpoints,
+    #[error("Invalid response to the {0} request")]
+    InvalidResponse(&'static str),
+}
+
+/// Attached gs_usb adapters, in bus order
+fn adapters() -> rusb::Result<Vec<Device<GlobalContext>>> {
+    let adapters = rusb::devices()?
+        .iter()
+        .filter(|device| {
+            device
+                .device_descriptor()
+                .is_ok_and(|d| DEVICE_IDS.contains(&(d.vendor_id(), d.product_id())))
+        })
+        .collect();
+    Ok(adapters)
+}
+
+/// Serial number of an adapter, `None` if it cannot be opened
+fn serial_number(device: &Device<GlobalContext>) -> Option<String> {
+    let descriptor = device.device_descriptor().ok()?;
+    let handle = device.open().ok()?;
+    handle.read_serial_number_string_ascii(&descriptor).ok()
+}
+
+/// The attached adapters, by serial number or by index where it cannot be read
+pub fn list() -> Vec<String> {
+    adapters()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(i, device)| serial_number(device).unwrap_or_else(|| i.to_string()))
+        .collect()
+}
+
+/// Flags the driver and its reader thread share
+#[derive(Default)]
+struct Shared {
+    own_messages: AtomicBool,
+    error_frames: AtomicBool,
+    stop: AtomicBool,
+    errors: DriverErrors,
+}
+
+/// A channel of a gs_usb adapter
+///
+/// Frames are read by a thread, as libusb transfers block. The adapter returns every sent frame
+/// once transmitted, these echoes are dropped unless own messages are received.
+pub struct GsUsbDriver {
+    handle: Arc<DeviceHandle<GlobalContext>>,
+    out_endpoint: u8,
+    channel: u8,
+    rx: Receiver<CanFrame>,
+    shared: Arc<Shared>,
+    reader: Option<JoinHandle<()>>,
+    next_echo_id: u32,
+}
+
+impl GsUsbDriver {
+    /// Open the adapter and start its channel at the bitrate
+    pub fn open(opts: &GsUsbOpts) -> Result<Self, GsUsbError> {
+        let adapters = adapters()?;
+        let device = match &opts.device {
+            DeviceSelector::Index(index) => adapters.get(*index),
+            DeviceSelector::Serial(serial) => adapters
+                .iter()
+                .find(|device| serial_number(device).as_ref() == Some(serial)),
+        }
+        .ok_or_else(|| GsUsbError::NotFound(opts.device.clone()))?;
+
+        let (in_endpoint, out_endpoint) = bulk_endpoints(device)?;
+        let handle = device.open()?;
+        // Takes the adapter from the Linux gs_usb driver, not supported on other platforms
+        let _ = handle.set_auto_detach_kernel_driver(true);
+        handle.claim_interface(INTERFACE)?;
+        configure(&handle, opts)?;
+
+        let handle = Arc::new(handle);
+        let interface: Arc<str> = format!("gsusb://{}", opts).into();
+        let shared = Arc::new(Shared::default());
+        let (tx, rx) = channel(RX_QUEUE_SIZE);
+
+        let reader = {
+            let (handle, shared) = (handle.clone(), shared.clone());
+            let channel = opts.channel;
+            thread::spawn(move || {
+                read_frames(&handle, in_endpoint, channel, &interface, &shared, tx)
+            })
+        };
+
+        Ok(GsUsbDriver {
+            handle,
+            out_endpoint,
+            channel: opts.channel,
+            rx,
+            shared,
+            reader: Some(reader),
+            next_echo_id: 0,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        self.rx.recv().await
+    }
+
+    pub async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        let Some(frame) = self.rx.recv().await else {
+            return 0;
+        };
+        buf.push(frame);
+
+        let mut n = 1;
+        while let Ok(frame) = self.rx.try_recv() {
+            buf.push(frame);
+            n += 1;
+        }
+        n
+    }
+
+    /// Send a frame, waiting while the adapter's transmit queue is full
+    pub async fn send(&mut self, frame: CanFrame) {
+        let buf = encode_frame(&frame, self.next_echo_id, self.channel);
+        self.next_echo_id = self.next_echo_id.wrapping_add(1) % RX_ECHO_ID;
+
+        let (handle, endpoint) = (self.handle.clone(), self.out_endpoint);
+        let sent =
+            tokio::task::spawn_blocking(move || handle.write_bulk(endpoint, &buf, WRITE_TIMEOUT))
+                .await;
+        if let Ok(Err(e)) = sent {
+            self.shared.errors.record(format_args!("send: {}", e));
+        }
+    }
+
+    /// Failed sends and receives and receive buffer overflows, counted as the driver carries on
+    pub fn errors(&self) -> DriverErrors {
+        self.shared.errors.clone()
+    }
+
+    /// Receive the echo of sent frames, marked as `Tx`
+    pub fn receive_own_messages(&mut self, enabled: bool) {
+        self.shared.own_messages.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Receive the error frames the adapter reports
+    pub fn receive_error_frames(&mut self, enabled: bool) {
+        self.shared.error_frames.store(enabled, Ordering::Relaxed);
+    }
+}
+
+impl Drop for GsUsbDriver {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        // Lets a reader waiting on a full queue go
+        self.rx.close();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+
+        let _ = set_mode(&self.handle, self.channel, MODE_RESET);
+        let _ = self.handle.release_interface(INTERFACE);
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for GsUsbDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        GsUsbDriver::recv(self).await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        GsUsbDriver::recv_many(self, buf).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        GsUsbDriver::send(self, frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        GsUsbDriver::receive_own_messages(self, enabled);
+        Ok(())
+    }
+
+    fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        GsUsbDriver::receive_error_frames(self, enabled);
+        Ok(())
+    }
+}
+
+/// Bulk IN and OUT endpoints of the CAN interface
+fn bulk_endpoints(device: &Device<GlobalContext>) -> Result<(u8, u8), GsUsbError> {
+    let config = device.active_config_descriptor()?;
+    let endpoints = config
+        .interfaces()
+        .filter(|interface| interface.number() == INTERFACE)
+        .flat_map(|interface| interface.descriptors())
+        .flat_map(|descriptor| descriptor.endpoint_descriptors().collect::<Vec<_>>())
+        .filter(|endpoint| endpoint.transfer_type() == TransferType::Bulk)
+        .collect::<Vec<_>>();
+
+    let address = |direction| {
+        endpoints
+            .iter()
+            .find(|endpoint| endpoint.direction() == direction)
+            .map(|endpoint| endpoint.address())
+    };
+    address(UsbDirection::In)
+        .zip(address(UsbDirection::Out))
+        .ok_or(GsUsbError::NoEndpoints)
+}
+
+/// Set the host format, check the channel and start it at the bitrate
+fn configure(handle: &DeviceHandle<GlobalContext>, opts: &GsUsbOpts) -> Result<(), GsUsbError> {
+    control_out(handle, BREQ_HOST_FORMAT, 1, 0, &HOST_FORMAT.to_le_bytes())?;
+
+    // Reserved bytes, the number of channels less one and the software and hardware versions
+    let mut config = [0; 12];
+    if control_in(handle, BREQ_DEVICE_CONFIG, 0, &mut config)? < config.len() {
+        return Err(GsUsbError::InvalidResponse("device config"));
+    }
+    let count = config[3].saturating_add(1);
+    if opts.channel >= count {
+        return Err(GsUsbError::InvalidChannel {
+            channel: opts.channel,
+            count,
+        });
+    }
+
+    let mut buf = [0; BitTimingConst::SIZE];
+    let n = control_in(handle, BREQ_BT_CONST, opts.channel as u16, &mut buf)?;
+    let limits =
+        BitTimingConst::decode(&buf[..n]).ok_or(GsUsbError::InvalidResponse("bit timing"))?;
+    let timing = BitTiming::calculate(opts.bitrate, &limits)
+        .ok_or(GsUsbError::InvalidBitrate(opts.bitrate))?;
+
+    // The channel may still run from an earlier session
+    set_mode(handle, opts.channel, MODE_RESET)?;
+    control_out(
+        handle,
+        BREQ_BITTIMING,
+        opts.channel as u16,
+        opts.channel as u16,
+        &timing.encode(),
+    )?;
+    set_mode(handle, opts.channel, MODE_START)
+}
+
+fn set_mode(
+    handle: &DeviceHandle<GlobalContext>,
+    channel: u8,
+    mode: u32,
+) -> Result<(), GsUsbError> {
+    // The mode and its flags
+    let mut buf = [0; 8];
+    buf[0..4].copy_from_slice(&mode.to_le_bytes());
+    control_out(handle, BREQ_MODE, channel as u16, channel as u16, &buf)
+}
+
+fn control_out(
+    handle: &DeviceHandle<GlobalContext>,
+    request: u8,
+    value: u16,
+    index: u16,
+    data: &[u8],
+) -> Result<(), GsUsbError> {
+    let request_type =
+        rusb::request_type(UsbDirection::Out, RequestType::Vendor, Recipient::Interface);
+    handle.write_control(request_type, request, value, index, data, CONTROL_TIMEOUT)?;
+    Ok(())
+}
+
+fn control_in(
+    handle: &DeviceHandle<GlobalContext>,
+    request: u8,
+    index: u16,
+    buf: &mut [u8],
+) -> Result<usize, GsUsbError> {
+    let request_type =
+        rusb::request_type(UsbDirection::In, RequestType::Vendor, Recipient::Interface);
+    Ok(handle.read_control(request_type, request, 0, index, buf, CONTROL_TIMEOUT)?)
+}
+
+/// Read host frames until the driver is dropped or the adapter fails
+fn read_frames(
+    handle: &DeviceHandle<GlobalContext>,
+    endpoint: u8,
+    channel: u8,
+    interface: &Arc<str>,
+    shared: &Shared,
+    tx: Sender<CanFrame>,
+) {
+    let mut buf = [0; MAX_TRANSFER];
+    while !shared.stop.load(Ordering::Relaxed) {
+        let n = match handle.read_bulk(endpoint, &mut buf, READ_TIMEOUT) {
+            Ok(n) => n,
+            Err(rusb::Error::Timeout) => continue,
+            Err(e) => {
+                shared.errors.record(format_args!("receive: {}", e));
+                return;
+            }
+        };
+        let Some(host) = decode_frame(&buf[..n]).filter(|host| host.channel == channel) else {
+            continue;
+        };
+        if host.flags & OVERFLOW_FLAG != 0 {
+            shared
+                .errors
+                .record("the adapter's receive buffer overflowed");
+        }
+
+        let frame = match host.is_echo() {
+            true if shared.own_messages.load(Ordering::Relaxed) => {
+                host.frame.with_direction(Direction::Tx)
+            }
+            // The echo of a sent frame, not a frame of the bus
+            true => continue,
+            false => host.frame,
+        };
+        if frame.is_error_frame() && !shared.error_frames.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let frame = frame
+            .with_interface(interface.clone())
+            .with_timestamp(SystemTime::now());
+        if tx.blocking_send(frame).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 17 2026
+//
+
+//! gs_usb adapters, the candleLight, CANable and other firmwares of the Linux `gs_usb` driver
+//!
+//! The adapter is configured with vendor control requests and exchanges frames on a pair of
+//! bulk endpoints as host frames:
+//!
+//! ```text
+//! | echo ID (4) | ID and flags (4) | DLC (1) | channel (1) | flags (1) | reserved (1) | data (8) |
+//! ```
+//!
+//! Fields are little endian, the ID uses the SocketCAN flags. Frames received from the bus carry
+//! the echo ID [`RX_ECHO_ID`], frames sent by the host come back with the echo ID they were sent
+//! with once transmitted.
+//!
+//! The protocol lives here so it builds everywhere, the USB driver needs the `gsusb` feature.
+
+#[cfg(feature = "gsusb")]
+mod device;
+
+#[cfg(feature = "gsusb")]
+pub use device::{list, GsUsbDriver, GsUsbError};
+
+use crate::frame::CanFrame;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+
+use std::{fmt, str::FromStr};
+
+/// Vendor and product IDs of the gs_usb adapters, as listed by the Linux driver
+pub const DEVICE_IDS: [(u16, u16); 4] = [
+    // Geschwister Schneider USB/CAN, candleLight on the OpenMoko IDs, CANable
+    (0x1d50, 0x606f),
+    // candleLight on the pid.codes IDs
+    (0x1209, 0x2323),
+    // CES CANext FD
+    (0x1cd2, 0x606f),
+    // ABE CANdebugger FD
+    (0x16d0, 0x10b8),
+];
+
+/// Echo ID of the frames received from the bus
+pub const RX_ECHO_ID: u32 = 0xFFFF_FFFF;
+/// Size of a classic host frame without timestamp
+pub const HOST_FRAME_SIZE: usize = 20;
+/// Host frame flag set when the adapter's receive buffer overflowed
+pub const OVERFLOW_FLAG: u8 = 0x01;
+
+const EXTENDED_FLAG: u32 = 0x8000_0000;
+const REMOTE_FLAG: u32 = 0x4000_0000;
+const ERROR_FLAG: u32 = 0x2000_0000;
+
+/// Bitrate when none is given
+const DEFAULT_BITRATE: u32 = 500_000;
+/// Sample point aimed for, in per mille as recommended by CiA 301
+const SAMPLE_POINT: u32 = 875;
+
+/// Adapter to open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// Position among the attached adapters
+    Index(usize),
+    /// USB serial number
+    Serial(String),
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceSelector::Index(index) => write!(f, "{}", index),
+            DeviceSelector::Serial(serial) => f.write_str(serial),
+        }
+    }
+}
+
+/// Options of the gs_usb driver, as `<serial-or-index>[?bitrate=<bps>&channel=<n>]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GsUsbOpts {
+    pub device: DeviceSelector,
+    pub bitrate: u32,
+    /// CAN channel of adapters with several
+    pub channel: u8,
+}
+
+impl FromStr for GsUsbOpts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (device, query) = s.split_once('?').unwrap_or((s, ""));
+        if device.is_empty() {
+            return Err(String::from("expected a serial number or an index"));
+        }
+
+        // Serial numbers are long enough not to be taken for an index
+        let device = match device.parse::<usize>() {
+            Ok(index) => DeviceSelector::Index(index),
+            Err(_) => DeviceSelector::Serial(device.to_string()),
+        };
+        let mut opts = GsUsbOpts {
+            device,
+            bitrate: DEFAULT_BITRATE,
+            channel: 0,
+        };
+
+        for option in query.split('&').filter(|option| !option.is_empty()) {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("invalid option '{}', expected <key>=<value>", option))?;
+            let invalid = |_| format!("invalid {} '{}'", key, value);
+            match key {
+                "bitrate" => opts.bitrate = value.parse().map_err(invalid)?,
+                "channel" => opts.channel = value.parse().map_err(invalid)?,
+                _ => {
+                    return Err(format!(
+                        "unknown option '{}', expected bitrate or channel",
+                        key
+                    ))
+                }
+            }
+        }
+        if opts.bitrate == 0 {
+            return Err(String::from("the bitrate must not be zero"));
+        }
+
+        Ok(opts)
+    }
+}
+
+impl fmt::Display for GsUsbOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.device)?;
+        if self.bitrate != DEFAULT_BITRATE {
+            write!(f, "?bitrate={}", self.bitrate)?;
+        }
+        if self.channel != 0 {
+            let sep = if self.bitrate != DEFAULT_BITRATE {
+                '&'
+            } else {
+                '?'
+            };
+            write!(f, "{}channel={}", sep, self.channel)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bit timing limits of a channel, the `BT_CONST` request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitTimingConst {
+    pub feature: u32,
+    /// CAN clock in Hz
+    pub fclk_can: u32,
+    pub tseg1_min: u32,
+    pub tseg1_max: u32,
+    pub tseg2_min: u32,
+    pub tseg2_max: u32,
+    pub sjw_max: u32,
+    pub brp_min: u32,
+    pub brp_max: u32,
+    pub brp_inc: u32,
+}
+
+impl BitTimingConst {
+    /// Size of the request's response
+    pub const SIZE: usize = 40;
+
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let field = |i: usize| {
+            buf.get(i * 4..i * 4 + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        };
+        Some(BitTimingConst {
+            feature: field(0)?,
+            fclk_can: field(1)?,
+            tseg1_min: field(2)?,
+            tseg1_max: field(3)?,
+            tseg2_min: field(4)?,
+            tseg2_max: field(5)?,
+            sjw_max: field(6)?,
+            brp_min: field(7)?,
+            brp_max: field(8)?,
+            brp_inc: field(9)?.max(1),
+        })
+    }
+}
+
+/// Bit timing of a channel, the `BITTIMING` request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitTiming {
+    pub prop_seg: u32,
+    pub phase_seg1: u32,
+    pub phase_seg2: u32,
+    pub sjw: u32,
+    pub brp: u32,
+}
+
+impl BitTiming {
+    /// Timing of a bitrate with the sample point closest to 87.5%, `None` if the clock cannot
+    /// divide into it exactly
+    ///
+    /// Prescalers with the same sample point are tried from the smallest, for the most time
+    /// quanta per bit.
+    pub fn calculate(bitrate: u32, limits: &BitTimingConst) -> Option<Self> {
+        let mut best: Option<(u32, BitTiming)> = None;
+
+        let mut brp = limits.brp_min.max(1);
+        while brp <= limits.brp_max {
+            let divisor = brp as u64 * bitrate as u64;
+            if (limits.fclk_can as u64).is_multiple_of(divisor) {
+                let quanta = (limits.fclk_can as u64 / divisor) as u32;
+                if let Some((error, timing)) = Self::split(quanta, brp, limits) {
+                    if best.is_none_or(|(best, _)| error < best) {
+                        best = Some((error, timing));
+                    }
+                }
+            }
+            brp += limits.brp_inc;
+        }
+
+        best.map(|(_, timing)| timing)
+    }
+
+    /// Segments of a bit of `quanta` time quanta, with the distance of its sample point from
+    /// the aimed one
+    fn split(quanta: u32, brp: u32, limits: &BitTimingConst) -> Option<(u32, BitTiming)> {
+        // The sync segment is one quantum
+        let tseg = quanta.checked_sub(1)?;
+        if limits.tseg1_min > limits.tseg1_max {
+            return None;
+        }
+        let tseg1 = ((quanta as u64 * SAMPLE_POINT as u64 / 1000) as u32)
+            .saturating_sub(1)
+            .clamp(limits.tseg1_min, limits.tseg1_max)
+            .min(tseg.checked_sub(limits.tseg2_min)?);
+        let tseg2 = tseg - tseg1;
+        if tseg1 < limits.tseg1_min || !(limits.tseg2_min..=limits.tseg2_max).contains(&tseg2) {
+            return None;
+        }
+
+        let sample_point = ((1 + tseg1 as u64) * 1000 / quanta as u64) as u32;
+        // Adapters add the propagation and first phase segments
+        let prop_seg = tseg1 / 2;
+        let timing = BitTiming {
+            prop_seg,
+            phase_seg1: tseg1 - prop_seg,
+            phase_seg2: tseg2,
+            sjw: 1,
+            brp,
+        };
+        Some((sample_point.abs_diff(SAMPLE_POINT), timing))
+    }
+
+    pub fn encode(&self) -> [u8; 20] {
+        let mut buf = [0; 20];
+        let fields = [
+            self.prop_seg,
+            self.phase_seg1,
+            self.phase_seg2,
+            self.sjw,
+            self.brp,
+        ];
+        for (chunk, field) in buf.chunks_exact_mut(4).zip(fields) {
+            chunk.copy_from_slice(&field.to_le_bytes());
+        }
+        buf
+    }
+}
+
+/// Host frame of a frame to send on a channel
+pub fn encode_frame(frame: &CanFrame, echo_id: u32, channel: u8) -> [u8; HOST_FRAME_SIZE] {
+    let mut id = match frame.id() {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | EXTENDED_FLAG,
+    };
+    if frame.is_remote_frame() {
+        id |= REMOTE_FLAG;
+    }
+
+    let mut buf = [0; HOST_FRAME_SIZE];
+    buf[0..4].copy_from_slice(&echo_id.to_le_bytes());
+    buf[4..8].copy_from_slice(&id.to_le_bytes());
+    buf[8] = frame.raw_dlc();
+    buf[9] = channel;
+    if !frame.is_remote_frame() {
+        buf[12..12 + frame.data().len()].copy_from_slice(frame.data());
+    }
+    buf
+}
+
+/// A host frame received from the adapter
+#[derive(Debug, Clone)]
+pub struct HostFrame {
+    /// [`RX_ECHO_ID`] for frames from the bus, the echo ID of a sent frame otherwise
+    pub echo_id: u32,
+    pub channel: u8,
+    pub flags: u8,
+    pub frame: CanFrame,
+}
+
+impl HostFrame {
+    /// Whether this is a sent frame coming back once transmitted
+    pub fn is_echo(&self) -> bool {
+        self.echo_id != RX_ECHO_ID
+    }
+}
+
+/// Decode a host frame, `None` if it is malformed
+pub fn decode_frame(buf: &[u8]) -> Option<HostFrame> {
+    if buf.len() < HOST_FRAME_SIZE {
+        return None;
+    }
+    let echo_id = u32::from_le_bytes(buf[0..4].try_into().ok()?);
+    let raw = u32::from_le_bytes(buf[4..8].try_into().ok()?);
+    let dlc = buf[8];
+    let len = (dlc as usize).min(8);
+    let data = &buf[12..12 + len];
+
+    let frame = if raw & ERROR_FLAG != 0 {
+        CanFrame::new_error(raw & 0x1FFF_FFFF, data)?
+    } else {
+        let id: Id = match raw & EXTENDED_FLAG != 0 {
+            true => ExtendedId::new(raw & 0x1FFF_FFFF)?.into(),
+            false => StandardId::new((raw & 0x7FF) as u16)?.into(),
+        };
+        match raw & REMOTE_FLAG != 0 {
+            true => CanFrame::new_remote(id, len)?,
+            false => CanFrame::new(id, data)?,
+        }
+    };
+    // Classic frames may be sent with a DLC of 9 to 15 for their 8 bytes
+    let frame = match dlc > 8 {
+        true => frame.clone().with_raw_dlc(dlc).unwrap_or(frame),
+        false => frame,
+    };
+
+    Some(HostFrame {
+        echo_id,
+        channel: buf[9],
+        flags: buf[10],
+        frame,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// candleLight on an STM32F072
+    const CANDLELIGHT: BitTimingConst = BitTimingConst {
+        feature: 0,
+        fclk_can: 48_000_000,
+        tseg1_min: 1,
+        tseg1_max: 16,
+        tseg2_min: 1,
+        tseg2_max: 8,
+        sjw_max: 4,
+        brp_min: 1,
+        brp_max: 1024,
+        brp_inc: 1,
+    };
+
+    #[test]
+    fn parse_opts() {
+        let opts = "0".parse::<GsUsbOpts>().unwrap();
+        assert_eq!(opts.device, DeviceSelector::Index(0));
+        assert_eq!(opts.bitrate, 500_000);
+        assert_eq!(opts.to_string(), "0");
+
+        let opts = "003A00245542500A20353832?bitrate=250000&channel=1"
+            .parse::<GsUsbOpts>()
+            .unwrap();
+        assert_eq!(
+            opts,
+            GsUsbOpts {
+                device: DeviceSelector::Serial("003A00245542500A20353832".into()),
+                bitrate: 250_000,
+                channel: 1,
+            }
+        );
+        assert_eq!(
+            opts.to_string(),
+            "003A00245542500A20353832?bitrate=250000&channel=1"
+        );
+
+        assert!("".parse::<GsUsbOpts>().is_err());
+        assert!("0?bitrate=fast".parse::<GsUsbOpts>().is_err());
+        assert!("0?bitrate=0".parse::<GsUsbOpts>().is_err());
+        assert!("0?speed=1".parse::<GsUsbOpts>().is_err());
+    }
+
+    #[test]
+    fn bit_timing() {
+        // 16 quanta with the sample point on 87.5%
+        let timing = BitTiming::calculate(500_000, &CANDLELIGHT).unwrap();
+        assert_eq!(timing.brp, 6);
+        assert_eq!(timing.prop_seg + timing.phase_seg1, 13);
+        assert_eq!(timing.phase_seg2, 2);
+
+        let timing = BitTiming::calculate(1_000_000, &CANDLELIGHT).unwrap();
+        assert_eq!(timing.brp, 3);
+        assert_eq!(
+            timing.prop_seg + timing.phase_seg1 + timing.phase_seg2 + 1,
+            16
+        );
+
+        // The clock does not divide into it
+        assert!(BitTiming::calculate(33_333, &CANDLELIGHT).is_none());
+
+        let encoded = timing.encode();
+        assert_eq!(&encoded[16..20], &3u32.to_le_bytes());
+    }
+
+    #[test]
+    fn bit_timing_const() {
+        let mut buf = [0u8; BitTimingConst::SIZE];
+        for (i, chunk) in buf.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&(i as u32).to_le_bytes());
+        }
+        let limits = BitTimingConst::decode(&buf).unwrap();
+        assert_eq!(limits.fclk_can, 1);
+        assert_eq!(limits.brp_inc, 9);
+        assert!(BitTimingConst::decode(&buf[..36]).is_none());
+    }
+
+    #[test]
+    fn host_frames() {
+        let id = ExtendedId::new(0x18FEF100).unwrap();
+        let frame = CanFrame::new(id, &[1, 2, 3]).unwrap();
+
+        let buf = encode_frame(&frame, 7, 1);
+        assert_eq!(&buf[4..8], &(0x18FEF100u32 | EXTENDED_FLAG).to_le_bytes());
+        let decoded = decode_frame(&buf).unwrap();
+        assert!(decoded.is_echo());
+        assert_eq!(decoded.echo_id, 7);
+        assert_eq!(decoded.channel, 1);
+        assert_eq!(decoded.frame.id(), Id::Extended(id));
+        assert_eq!(decoded.frame.data(), [1, 2, 3]);
+
+        let remote = CanFrame::new_remote(StandardId::new(0x7DF).unwrap(), 8).unwrap();
+        let decoded = decode_frame(&encode_frame(&remote, RX_ECHO_ID, 0)).unwrap();
+        assert!(!decoded.is_echo());
+        assert!(decoded.frame.is_remote_frame());
+        assert_eq!(decoded.frame.dlc(), 8);
+
+        assert!(decode_frame(&buf[..HOST_FRAME_SIZE - 1]).is_none());
+    }
+}
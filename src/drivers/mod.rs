@@ -4,45 +4,399 @@
 // @author Natesh Narain <nnaraindev@gmail.com>
 // @date Sep 29 2023
 //
+pub mod file;
+pub mod gsusb;
+pub mod loopback;
+pub mod ratelimit;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
 pub mod socketcan;
+pub mod udp;
 
-use socketcan::{SocketCanDriver, SocketCanDriverError};
+#[cfg(feature = "gsusb")]
+use gsusb::{GsUsbDriver, GsUsbError};
+use loopback::LoopbackDriver;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+use socketcan::{SocketCanDriver, SocketCanDriverError, SocketCanQueues};
+use udp::UdpDriver;
 
 use crate::frame::CanFrame;
+use crate::timesync::SyncHandle;
 use crate::DriverOpts;
 
 use async_trait::async_trait;
 use thiserror::Error;
 
+use std::{
+    fmt, io,
+    sync::{Arc, Mutex},
+};
+
 /// Driver errors
 #[derive(Error, Debug)]
 pub enum DriverError {
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
     #[error("Error initializing socketcan driver: {0}")]
     SocketCanError(#[from] SocketCanDriverError),
+    #[cfg(feature = "gsusb")]
+    #[error("Error initializing gs_usb driver: {0}")]
+    GsUsbError(#[from] GsUsbError),
+    #[error("Error opening UDP socket: {0}")]
+    UdpError(#[source] io::Error),
+    #[error("{0} is not supported by this driver")]
+    Unsupported(&'static str),
+    #[error("The {0} driver is not available on this platform or build")]
+    Unavailable(&'static str),
+}
+
+/// Failures of a driver that keeps running, counted rather than printed over the monitor
+#[derive(Debug, Clone, Default)]
+pub struct DriverErrors(Arc<Mutex<(u64, Option<String>)>>);
+
+impl DriverErrors {
+    pub fn record(&self, error: impl fmt::Display) {
+        let mut errors = self.0.lock().unwrap();
+        errors.0 += 1;
+        errors.1 = Some(error.to_string());
+    }
+
+    /// Errors recorded so far
+    pub fn count(&self) -> u64 {
+        self.0.lock().unwrap().0
+    }
+}
+
+impl fmt::Display for DriverErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.0.lock().unwrap();
+        write!(f, "{} errors", errors.0)?;
+        if let Some(last) = &errors.1 {
+            write!(f, ", the last: {}", last)?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
 pub trait AsyncCanDriver {
     /// Recieve CAN frame from the driver
     async fn recv(&mut self) -> Option<CanFrame>;
+    /// Receive the frames available at once, waiting for at least one
+    ///
+    /// Frames are appended to `buf`. Returns the number received, zero when the driver is closed.
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        match self.recv().await {
+            Some(frame) => {
+                buf.push(frame);
+                1
+            }
+            None => 0,
+        }
+    }
     /// Send CAN frame
     async fn send(&mut self, frame: CanFrame);
+    /// Also receive the frames sent through this driver, once transmitted, marked as `Tx`
+    fn receive_own_messages(&mut self, _enabled: bool) -> Result<(), DriverError> {
+        Err(DriverError::Unsupported("Receiving own messages"))
+    }
+    /// Also receive the error frames reported by the controller, see [`crate::busstate`]
+    fn receive_error_frames(&mut self, _enabled: bool) -> Result<(), DriverError> {
+        Err(DriverError::Unsupported("Receiving error frames"))
+    }
 }
 pub type AsyncCanDriverPtr = Box<dyn AsyncCanDriver + Sync + Send>;
 
+/// Driver dispatched over the built-in backends
+///
+/// Calls go straight to the backend, without the boxed future of every `AsyncCanDriver` call, so
+/// hot paths like dump and bridge use it. Other drivers are wrapped as `Plugin`.
+pub enum CanDriver {
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
+    SocketCan(SocketCanDriver),
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
+    SocketCanQueues(SocketCanQueues),
+    #[cfg(feature = "gsusb")]
+    GsUsb(GsUsbDriver),
+    Udp(UdpDriver),
+    Loopback(LoopbackDriver),
+    Plugin(AsyncCanDriverPtr),
+}
+
+impl CanDriver {
+    /// Recieve CAN frame from the driver
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCan(driver) => driver.recv().await,
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCanQueues(driver) => driver.recv().await,
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => driver.recv().await,
+            CanDriver::Udp(driver) => driver.recv().await,
+            CanDriver::Loopback(driver) => driver.recv().await,
+            CanDriver::Plugin(driver) => driver.recv().await,
+        }
+    }
+
+    /// Receive the frames available at once, see [`AsyncCanDriver::recv_many`]
+    pub async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCan(driver) => driver.recv_many(buf).await,
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCanQueues(driver) => driver.recv_many(buf).await,
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => driver.recv_many(buf).await,
+            CanDriver::Udp(driver) => AsyncCanDriver::recv_many(driver, buf).await,
+            CanDriver::Loopback(driver) => driver.recv_many(buf).await,
+            CanDriver::Plugin(driver) => driver.recv_many(buf).await,
+        }
+    }
+
+    /// Send CAN frame
+    pub async fn send(&mut self, frame: CanFrame) {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCan(driver) => driver.send(frame).await,
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCanQueues(driver) => driver.send(frame).await,
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => driver.send(frame).await,
+            CanDriver::Udp(driver) => driver.send(frame).await,
+            CanDriver::Loopback(driver) => driver.send(frame).await,
+            CanDriver::Plugin(driver) => driver.send(frame).await,
+        }
+    }
+
+    /// Also receive sent frames, see [`AsyncCanDriver::receive_own_messages`]
+    pub fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCan(driver) => Ok(driver.receive_own_messages(enabled)?),
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCanQueues(driver) => {
+                driver.receive_own_messages(enabled);
+                Ok(())
+            }
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => {
+                driver.receive_own_messages(enabled);
+                Ok(())
+            }
+            // The peer does not confirm the frames it received
+            CanDriver::Udp(_) => Err(DriverError::Unsupported("Receiving own messages over UDP")),
+            CanDriver::Loopback(driver) => {
+                driver.receive_own_messages(enabled);
+                Ok(())
+            }
+            CanDriver::Plugin(driver) => driver.receive_own_messages(enabled),
+        }
+    }
+
+    /// Also receive error frames, see [`AsyncCanDriver::receive_error_frames`]
+    pub fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCan(driver) => Ok(driver.receive_error_frames(enabled)?),
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            CanDriver::SocketCanQueues(_) => Err(DriverError::Unsupported(
+                "Receiving error frames with several receive queues",
+            )),
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => {
+                driver.receive_error_frames(enabled);
+                Ok(())
+            }
+            // Error frames sent on the other end are passed through as is
+            CanDriver::Udp(_) | CanDriver::Loopback(_) => Ok(()),
+            CanDriver::Plugin(driver) => driver.receive_error_frames(enabled),
+        }
+    }
+
+    /// Quality of the sender's clock sync, for drivers that receive sender timestamps
+    pub fn clock_sync(&self) -> Option<SyncHandle> {
+        match self {
+            CanDriver::Udp(driver) => Some(driver.sync_handle()),
+            _ => None,
+        }
+    }
+
+    /// Send and receive failures the driver carried on after
+    pub fn errors(&self) -> Option<DriverErrors> {
+        match self {
+            #[cfg(feature = "gsusb")]
+            CanDriver::GsUsb(driver) => Some(driver.errors()),
+            CanDriver::Udp(driver) => Some(driver.errors()),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for CanDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        CanDriver::recv(self).await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        CanDriver::recv_many(self, buf).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        CanDriver::send(self, frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        CanDriver::receive_own_messages(self, enabled)
+    }
+
+    fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        CanDriver::receive_error_frames(self, enabled)
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+impl From<SocketCanDriver> for CanDriver {
+    fn from(driver: SocketCanDriver) -> Self {
+        CanDriver::SocketCan(driver)
+    }
+}
+
+#[cfg(feature = "gsusb")]
+impl From<GsUsbDriver> for CanDriver {
+    fn from(driver: GsUsbDriver) -> Self {
+        CanDriver::GsUsb(driver)
+    }
+}
+
+impl From<UdpDriver> for CanDriver {
+    fn from(driver: UdpDriver) -> Self {
+        CanDriver::Udp(driver)
+    }
+}
+
+impl From<LoopbackDriver> for CanDriver {
+    fn from(driver: LoopbackDriver) -> Self {
+        CanDriver::Loopback(driver)
+    }
+}
+
+impl From<AsyncCanDriverPtr> for CanDriver {
+    fn from(driver: AsyncCanDriverPtr) -> Self {
+        CanDriver::Plugin(driver)
+    }
+}
+
+impl From<CanDriver> for AsyncCanDriverPtr {
+    fn from(driver: CanDriver) -> Self {
+        match driver {
+            CanDriver::Plugin(driver) => driver,
+            driver => Box::new(driver),
+        }
+    }
+}
+
+impl CanDriver {
+    /// Open a driver, receiving on `rx_queues` sockets where the backend supports it
+    #[cfg_attr(
+        not(all(target_os = "linux", feature = "socketcan")),
+        allow(unused_variables)
+    )]
+    pub fn open(opts: DriverOpts, rx_queues: usize) -> Result<Self, DriverError> {
+        match opts {
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            DriverOpts::SocketCan(can_interface) if rx_queues > 1 => {
+                SocketCanQueues::new(&can_interface, rx_queues)
+                    .map(CanDriver::SocketCanQueues)
+                    .map_err(DriverError::SocketCanError)
+            }
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            DriverOpts::SocketCan(can_interface) => SocketCanDriver::new(&can_interface)
+                .map(CanDriver::SocketCan)
+                .map_err(DriverError::SocketCanError),
+            #[cfg(not(all(target_os = "linux", feature = "socketcan")))]
+            DriverOpts::SocketCan(_) => Err(DriverError::Unavailable("SocketCAN")),
+            #[cfg(feature = "gsusb")]
+            DriverOpts::GsUsb(opts) => GsUsbDriver::open(&opts)
+                .map(CanDriver::GsUsb)
+                .map_err(DriverError::GsUsbError),
+            #[cfg(not(feature = "gsusb"))]
+            DriverOpts::GsUsb(_) => Err(DriverError::Unavailable("gs_usb")),
+            DriverOpts::Udp(opts) => UdpDriver::open(&opts)
+                .map(CanDriver::Udp)
+                .map_err(DriverError::UdpError),
+        }
+    }
+}
+
+impl TryFrom<DriverOpts> for CanDriver {
+    type Error = DriverError;
+
+    fn try_from(value: DriverOpts) -> Result<Self, Self::Error> {
+        CanDriver::open(value, 1)
+    }
+}
+
 impl TryFrom<DriverOpts> for AsyncCanDriverPtr {
     type Error = DriverError;
 
     fn try_from(value: DriverOpts) -> Result<Self, Self::Error> {
-        match value {
-            DriverOpts::SocketCan(can_interface) => SocketCanDriver::new(&can_interface)
-                .map(|driver| upcast(Box::new(driver)))
-                .map_err(|e| DriverError::SocketCanError(e)),
-            DriverOpts::Udp(_, _) => unimplemented!(),
-        }
+        CanDriver::try_from(value).map(Into::into)
     }
 }
 
-fn upcast<T: AsyncCanDriver + Sync + Send + 'static>(a: Box<T>) -> AsyncCanDriverPtr {
-    a
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::frame::Direction;
+
+    use embedded_can::{Frame, StandardId};
+
+    #[tokio::test]
+    async fn dispatch() {
+        let (a, b) = LoopbackDriver::pair();
+        let mut a = CanDriver::from(a);
+        // A boxed driver is dispatched as a plugin
+        let mut b = CanDriver::from(AsyncCanDriverPtr::from(CanDriver::from(b)));
+        assert!(matches!(b, CanDriver::Plugin(_)));
+
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2]).unwrap();
+        a.send(frame.clone()).await;
+        a.send(frame.clone()).await;
+
+        let mut frames = vec![];
+        assert_eq!(b.recv_many(&mut frames).await, 2);
+        assert!(frames.iter().all(|f| f.data() == frame.data()));
+    }
+
+    #[tokio::test]
+    async fn own_messages() {
+        let (a, mut b) = LoopbackDriver::pair();
+        let mut a = CanDriver::from(a);
+        a.receive_own_messages(true).unwrap();
+
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1]).unwrap();
+        a.send(frame.clone().with_direction(Direction::Tx)).await;
+
+        // The sender sees its frame as sent, the other end as received
+        assert_eq!(a.recv().await.unwrap().direction(), Direction::Tx);
+        assert_eq!(b.recv().await.unwrap().direction(), Direction::Rx);
+    }
+
+    #[tokio::test]
+    async fn metadata_passes_through() {
+        let (a, b) = LoopbackDriver::pair();
+        let (mut a, mut b) = (CanDriver::from(a), CanDriver::from(b));
+
+        let time = std::time::SystemTime::now();
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1])
+            .unwrap()
+            .with_interface("can0".into())
+            .with_timestamp(time);
+        a.send(frame).await;
+
+        let frame = b.recv().await.unwrap();
+        assert_eq!(frame.interface(), Some("can0"));
+        assert_eq!(frame.timestamp(), Some(time));
+    }
 }
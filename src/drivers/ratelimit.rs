@@ -0,0 +1,131 @@
+//
+// ratelimit.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use crate::drivers::{AsyncCanDriver, CanDriver, DriverError};
+use crate::frame::CanFrame;
+
+use async_trait::async_trait;
+use tokio::time::{self, Duration, Instant};
+
+/// Limits the rate of frames, allowing bursts of a tenth of a second's worth
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    burst: Duration,
+    /// Time the next frame is due at the limited rate
+    next: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a limiter for a rate in frames per second
+    pub fn new(rate: u32) -> Self {
+        let rate = rate.max(1);
+        let interval = Duration::from_secs(1) / rate;
+        RateLimiter {
+            interval,
+            burst: interval * (rate / 10).max(1),
+            next: None,
+        }
+    }
+
+    /// Account for a frame sent at `now`, returning how long to wait before sending it
+    pub fn delay(&mut self, now: Instant) -> Option<Duration> {
+        let next = self.next.map_or(now, |next| next.max(now));
+        self.next = Some(next + self.interval);
+
+        let ahead = next - now;
+        (ahead >= self.burst).then(|| ahead - self.burst + self.interval)
+    }
+}
+
+/// Driver wrapper holding back sends above a rate, see [`RateLimiter`]
+pub struct RateLimitedDriver {
+    driver: CanDriver,
+    limiter: RateLimiter,
+    rate: u32,
+    warned: bool,
+}
+
+impl RateLimitedDriver {
+    pub fn new(driver: CanDriver, rate: u32) -> Self {
+        RateLimitedDriver {
+            driver,
+            limiter: RateLimiter::new(rate),
+            rate,
+            warned: false,
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for RateLimitedDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        self.driver.recv().await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        self.driver.recv_many(buf).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        if let Some(delay) = self.limiter.delay(Instant::now()) {
+            if !self.warned {
+                eprintln!(
+                    "Sending is limited to {} frames/s, use --tx-rate-limit or --force to change it",
+                    self.rate
+                );
+                self.warned = true;
+            }
+            time::sleep(delay).await;
+        }
+        self.driver.send(frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        self.driver.receive_own_messages(enabled)
+    }
+
+    fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        self.driver.receive_error_frames(enabled)
+    }
+}
+
+impl From<RateLimitedDriver> for CanDriver {
+    fn from(driver: RateLimitedDriver) -> Self {
+        CanDriver::Plugin(Box::new(driver))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_limit() {
+        // 100 frames/s, bursts of 10 frames
+        let mut limiter = RateLimiter::new(100);
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            assert_eq!(limiter.delay(start), None);
+        }
+        assert_eq!(limiter.delay(start), Some(Duration::from_millis(10)));
+        assert_eq!(limiter.delay(start), Some(Duration::from_millis(20)));
+
+        // Idle time refills the burst
+        let later = start + Duration::from_secs(1);
+        for _ in 0..10 {
+            assert_eq!(limiter.delay(later), None);
+        }
+
+        // Sending at the limit never waits
+        let mut limiter = RateLimiter::new(100);
+        for i in 0..100 {
+            assert_eq!(limiter.delay(start + Duration::from_millis(10 * i)), None);
+        }
+    }
+}
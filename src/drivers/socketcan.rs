@@ -5,16 +5,36 @@
 // @date Sep 29 2023
 //
 
-use crate::drivers::AsyncCanDriver;
-use crate::frame::CanFrame;
+use crate::drivers::{AsyncCanDriver, DriverError};
+use crate::frame::{CanFrame, Direction};
 
 use async_trait::async_trait;
-use embedded_can::Frame;
-use futures_util::StreamExt;
-use socketcan::{tokio::CanSocket, CanFrame as SocketCanFrame};
+use embedded_can::{Frame, Id};
+use socketcan::{CanFilter, CanFrame as SocketCanFrame, CanSocket, Socket, SocketOptions};
 use thiserror::Error;
+use tokio::{
+    io::unix::AsyncFd,
+    sync::mpsc::{channel, Receiver},
+    task::JoinHandle,
+};
 
-use std::io;
+use std::{
+    io, mem,
+    os::fd::AsRawFd,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+    vec,
+};
+
+/// Most frames read per readiness event, so a busy bus does not starve other tasks
+const MAX_BATCH_SIZE: usize = 1024;
+/// Most receive queues, each is a socket and a task
+pub const MAX_RX_QUEUES: usize = 64;
+/// Batches buffered between the receive queues and the reader
+const QUEUE_BATCHES: usize = 64;
 
 impl From<SocketCanFrame> for CanFrame {
     fn from(value: SocketCanFrame) -> Self {
@@ -33,30 +53,407 @@ impl From<CanFrame> for SocketCanFrame {
 pub enum SocketCanDriverError {
     #[error("Failed to open CAN device")]
     OpenError(#[from] io::Error),
+    #[error("Invalid number of receive queues {0}, expected a power of two up to {MAX_RX_QUEUES}")]
+    InvalidQueues(usize),
+    #[error("Failed to configure CAN socket")]
+    ConfigureError(#[source] io::Error),
+}
+
+/// Read a frame with `recvmsg`, which flags the echo of a frame sent on the same socket
+///
+/// The frame is tagged with the interface and the kernel's receive time.
+fn read_frame(socket: &CanSocket, interface: &Arc<str>) -> io::Result<CanFrame> {
+    let mut frame: libc::can_frame = unsafe { mem::zeroed() };
+    let mut iov = libc::iovec {
+        iov_base: &mut frame as *mut _ as *mut libc::c_void,
+        iov_len: mem::size_of::<libc::can_frame>(),
+    };
+    // Room for the SCM_TIMESTAMP control message, aligned for its header
+    let mut control = [0u64; 8];
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = mem::size_of_val(&control) as _;
+
+    let n = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if n as usize != mem::size_of::<libc::can_frame>() {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let direction = match msg.msg_flags & libc::MSG_CONFIRM {
+        0 => Direction::Rx,
+        _ => Direction::Tx,
+    };
+    let timestamp = receive_time(&msg).unwrap_or_else(SystemTime::now);
+
+    let frame = if frame.can_id & libc::CAN_ERR_FLAG != 0 {
+        let len = (frame.can_dlc as usize).min(frame.data.len());
+        CanFrame::new_error(frame.can_id & libc::CAN_ERR_MASK, &frame.data[..len])
+            .ok_or(io::ErrorKind::InvalidData)?
+    } else {
+        // Set by the kernel for classic frames of 8 bytes sent with a DLC above 8
+        let raw_dlc = match frame.len8_dlc {
+            dlc @ 9..=15 if frame.can_dlc == 8 => dlc,
+            _ => frame.can_dlc,
+        };
+        CanFrame::from(SocketCanFrame::from(frame))
+            .with_direction(direction)
+            .with_raw_dlc(raw_dlc)
+            .ok_or(io::ErrorKind::InvalidData)?
+    };
+
+    Ok(frame
+        .with_interface(interface.clone())
+        .with_timestamp(timestamp))
+}
+
+/// Write a frame, passing a DLC above 8 on to controllers with CC_LEN8_DLC enabled
+fn write_frame(socket: &CanSocket, frame: &CanFrame) -> io::Result<()> {
+    let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+    raw.can_id = match frame.id() {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | libc::CAN_EFF_FLAG,
+    };
+    if frame.is_remote_frame() {
+        raw.can_id |= libc::CAN_RTR_FLAG;
+    }
+    raw.can_dlc = frame.dlc() as u8;
+    if frame.raw_dlc() > 8 {
+        raw.len8_dlc = frame.raw_dlc();
+    }
+    raw.data[..frame.data().len()].copy_from_slice(frame.data());
+
+    let n = unsafe {
+        libc::write(
+            socket.as_raw_fd(),
+            &raw as *const _ as *const libc::c_void,
+            mem::size_of::<libc::can_frame>(),
+        )
+    };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
 }
 
-pub struct SocketCanDriver(CanSocket);
+/// Kernel receive time of a message, when `SO_TIMESTAMP` is enabled
+fn receive_time(msg: &libc::msghdr) -> Option<SystemTime> {
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(msg) };
+    while !cmsg.is_null() {
+        let header = unsafe { &*cmsg };
+        if header.cmsg_level == libc::SOL_SOCKET && header.cmsg_type == libc::SCM_TIMESTAMP {
+            let tv = unsafe { (libc::CMSG_DATA(cmsg) as *const libc::timeval).read_unaligned() };
+            let time = Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+            return Some(UNIX_EPOCH + time);
+        }
+        cmsg = unsafe { libc::CMSG_NXTHDR(msg, cmsg) };
+    }
+    None
+}
+
+/// Have the kernel timestamp received frames
+fn enable_timestamps(socket: &CanSocket) -> io::Result<()> {
+    let enabled: libc::c_int = 1;
+    let res = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMP,
+            &enabled as *const _ as *const libc::c_void,
+            mem::size_of_val(&enabled) as libc::socklen_t,
+        )
+    };
+    match res {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// SocketCAN driver, reading the non-blocking socket when tokio reports it readable
+pub struct SocketCanDriver {
+    socket: Arc<AsyncFd<CanSocket>>,
+    interface: Arc<str>,
+}
 
 impl SocketCanDriver {
     pub fn new(can_interface: &str) -> Result<SocketCanDriver, SocketCanDriverError> {
-        CanSocket::open(can_interface)
-            .map(|socket| SocketCanDriver(socket))
-            .map_err(|e| SocketCanDriverError::OpenError(e))
+        let socket = CanSocket::open(can_interface)?;
+        socket.set_nonblocking(true)?;
+        enable_timestamps(&socket).map_err(SocketCanDriverError::ConfigureError)?;
+        Ok(SocketCanDriver {
+            socket: Arc::new(AsyncFd::new(socket)?),
+            interface: can_interface.into(),
+        })
+    }
+
+    /// Another driver on the same socket, to send from one task while another receives
+    fn share(&self) -> SocketCanDriver {
+        SocketCanDriver {
+            socket: self.socket.clone(),
+            interface: self.interface.clone(),
+        }
+    }
+
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        loop {
+            let mut guard = self.socket.readable().await.ok()?;
+            match guard.try_io(|socket| read_frame(socket.get_ref(), &self.interface)) {
+                Ok(frame) => return frame.ok(),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    pub async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        let start = buf.len();
+
+        while buf.len() == start {
+            let Ok(mut guard) = self.socket.readable().await else {
+                return 0;
+            };
+
+            // Drain what the socket has queued, readiness is cleared once it would block
+            while buf.len() - start < MAX_BATCH_SIZE {
+                match guard.try_io(|socket| read_frame(socket.get_ref(), &self.interface)) {
+                    Ok(Ok(frame)) => buf.push(frame),
+                    Ok(Err(_)) if buf.len() == start => return 0,
+                    Ok(Err(_)) | Err(_) => break,
+                }
+            }
+        }
+
+        buf.len() - start
+    }
+
+    pub async fn send(&mut self, frame: CanFrame) {
+        loop {
+            let mut guard = self.socket.writable().await.unwrap();
+            match guard.try_io(|socket| write_frame(socket.get_ref(), &frame)) {
+                // TODO(nnarain): Error handling
+                Ok(result) => return result.unwrap(),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Have the kernel echo sent frames back once transmitted, marked as `Tx`
+    pub fn receive_own_messages(&mut self, enabled: bool) -> Result<(), SocketCanDriverError> {
+        self.socket
+            .get_ref()
+            .set_recv_own_msgs(enabled)
+            .map_err(SocketCanDriverError::ConfigureError)
+    }
+
+    /// Have the kernel report controller errors as error frames
+    pub fn receive_error_frames(&mut self, enabled: bool) -> Result<(), SocketCanDriverError> {
+        let socket = self.socket.get_ref();
+        match enabled {
+            true => socket.set_error_filter_accept_all(),
+            false => socket.set_error_filter_drop_all(),
+        }
+        .map_err(SocketCanDriverError::ConfigureError)
     }
 }
 
 #[async_trait]
 impl AsyncCanDriver for SocketCanDriver {
     async fn recv(&mut self) -> Option<CanFrame> {
-        self.0
-            .next()
-            .await
-            .map(|frame| frame.ok().map(|frame| frame.into()))
-            .flatten()
+        SocketCanDriver::recv(self).await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        SocketCanDriver::recv_many(self, buf).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        SocketCanDriver::send(self, frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        Ok(SocketCanDriver::receive_own_messages(self, enabled)?)
+    }
+
+    fn receive_error_frames(&mut self, enabled: bool) -> Result<(), DriverError> {
+        Ok(SocketCanDriver::receive_error_frames(self, enabled)?)
+    }
+}
+
+/// Kernel filters splitting the IDs into disjoint queues by their low bits, as (ID, mask)
+fn queue_filters(queues: usize) -> Vec<(u32, u32)> {
+    let mask = queues as u32 - 1;
+    (0..queues as u32).map(|queue| (queue, mask)).collect()
+}
+
+/// Queue receiving an ID, the one whose filter matches its low bits
+fn queue_of(id: Id, queues: usize) -> usize {
+    let raw = match id {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw(),
+    };
+    (raw & (queues as u32 - 1)) as usize
+}
+
+/// SocketCAN driver receiving on several sockets of an interface
+///
+/// Every socket gets a disjoint share of the IDs through kernel filters and is read by its own
+/// task, so receiving spreads across the runtime's worker threads. Frames of an ID stay in order,
+/// frames of different IDs may be reordered between queues. A frame is sent on the socket whose
+/// filter receives its ID, so the kernel echoes it back flagged as sent on that socket only.
+pub struct SocketCanQueues {
+    sockets: Vec<SocketCanDriver>,
+    rx: Receiver<Vec<CanFrame>>,
+    pending: vec::IntoIter<CanFrame>,
+    own_messages: Arc<AtomicBool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl SocketCanQueues {
+    pub fn new(can_interface: &str, queues: usize) -> Result<Self, SocketCanDriverError> {
+        if !queues.is_power_of_two() || queues > MAX_RX_QUEUES {
+            return Err(SocketCanDriverError::InvalidQueues(queues));
+        }
+
+        let (batches, rx) = channel(QUEUE_BATCHES);
+        let own_messages = Arc::new(AtomicBool::new(false));
+        let mut sockets = vec![];
+        let mut tasks = vec![];
+        for (id, mask) in queue_filters(queues) {
+            let mut queue = SocketCanDriver::new(can_interface)?;
+            queue
+                .socket
+                .get_ref()
+                .set_filters(&[CanFilter::new(id, mask)])?;
+            // Echoes are always received, to be told apart and dropped unless asked for
+            queue.receive_own_messages(true)?;
+            sockets.push(queue.share());
+
+            let batches = batches.clone();
+            let own_messages = own_messages.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut buf = vec![];
+                while queue.recv_many(&mut buf).await > 0 {
+                    if !own_messages.load(Ordering::Relaxed) {
+                        buf.retain(|frame| !frame.is_tx());
+                    }
+                    if buf.is_empty() {
+                        continue;
+                    }
+                    if batches.send(std::mem::take(&mut buf)).await.is_err() {
+                        break;
+                    }
+                }
+            }));
+        }
+
+        Ok(SocketCanQueues {
+            sockets,
+            rx,
+            pending: vec![].into_iter(),
+            own_messages,
+            tasks,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        loop {
+            if let Some(frame) = self.pending.next() {
+                return Some(frame);
+            }
+            self.pending = self.rx.recv().await?.into_iter();
+        }
+    }
+
+    pub async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        let start = buf.len();
+
+        buf.extend(&mut self.pending);
+        if buf.len() == start {
+            match self.rx.recv().await {
+                Some(batch) => buf.extend(batch),
+                None => return 0,
+            }
+        }
+        while let Ok(batch) = self.rx.try_recv() {
+            buf.extend(batch);
+        }
+
+        buf.len() - start
+    }
+
+    pub async fn send(&mut self, frame: CanFrame) {
+        let queue = queue_of(frame.id(), self.sockets.len());
+        self.sockets[queue].send(frame).await
+    }
+
+    /// Receive the frames sent, marked as `Tx`, rather than dropping them
+    pub fn receive_own_messages(&mut self, enabled: bool) {
+        self.own_messages.store(enabled, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SocketCanQueues {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for SocketCanQueues {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        SocketCanQueues::recv(self).await
+    }
+
+    async fn recv_many(&mut self, buf: &mut Vec<CanFrame>) -> usize {
+        SocketCanQueues::recv_many(self, buf).await
     }
 
     async fn send(&mut self, frame: CanFrame) {
-        // TODO(nnarain): Error handling
-        self.0.write_frame(frame.into()).unwrap().await.unwrap();
+        SocketCanQueues::send(self, frame).await
+    }
+
+    fn receive_own_messages(&mut self, enabled: bool) -> Result<(), DriverError> {
+        SocketCanQueues::receive_own_messages(self, enabled);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, StandardId};
+
+    #[test]
+    fn disjoint_queues() {
+        let filters = queue_filters(4);
+        for id in [0x000, 0x123, 0x7FF, 0x18FEF100, 0x1FFFFFFF] {
+            let matches = filters
+                .iter()
+                .filter(|(filter, mask)| id & mask == filter & mask)
+                .count();
+            assert_eq!(matches, 1, "{:X}", id);
+        }
+
+        // A single queue takes every ID
+        assert_eq!(queue_filters(1), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn send_queue() {
+        // Frames are sent on the queue receiving their ID
+        let filters = queue_filters(8);
+        for id in [0x000, 0x123, 0x7FF] {
+            let (filter, mask) = filters[queue_of(Id::Standard(StandardId::new(id).unwrap()), 8)];
+            assert_eq!(id as u32 & mask, filter);
+        }
+        let id = ExtendedId::new(0x18FEF10D).unwrap();
+        assert_eq!(queue_of(Id::Extended(id), 8), 5);
+        assert_eq!(queue_of(Id::Extended(id), 1), 0);
     }
 }
@@ -0,0 +1,519 @@
+//
+// udp.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! CAN frames tunneled over UDP, one frame per datagram
+//!
+//! ```text
+//! | sender time (8) | ID and flags (4) | DLC (1) | data (0-8) |
+//! ```
+//!
+//! Fields are big endian. The sender time is in microseconds since the Unix epoch, zero if the
+//! sender has no clock. The ID uses the SocketCAN flags, bit 31 for extended IDs, bit 30 for
+//! remote frames and bit 29 for error frames. Sender times are mapped onto the local clock with
+//! [`ClockSync`], so captures from several hosts line up.
+//!
+//! Frames can also be sent to a multicast group or a broadcast address, feeding every listener
+//! of a lab network. The sender receives its own datagrams back from these, they are recognized
+//! and dropped.
+
+use crate::drivers::{AsyncCanDriver, DriverError, DriverErrors};
+use crate::frame::CanFrame;
+use crate::timesync::{ClockSync, SyncHandle, SyncQuality};
+
+use async_trait::async_trait;
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const EXTENDED_FLAG: u32 = 0x8000_0000;
+const REMOTE_FLAG: u32 = 0x4000_0000;
+const ERROR_FLAG: u32 = 0x2000_0000;
+
+/// Bytes before the data
+const HEADER_SIZE: usize = 13;
+/// Largest datagram of a classic frame
+const MAX_DATAGRAM: usize = HEADER_SIZE + 8;
+/// Sent datagrams remembered to recognize them coming back from a group or broadcast
+const SENT_HISTORY: usize = 64;
+
+/// How the peer is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UdpMode {
+    /// A single host
+    #[default]
+    Unicast,
+    /// A multicast group, joined to also receive from it
+    Multicast,
+    /// A broadcast address
+    Broadcast,
+}
+
+impl UdpMode {
+    /// Option naming the peer in this mode
+    fn key(&self) -> &'static str {
+        match self {
+            UdpMode::Unicast => "peer",
+            UdpMode::Multicast => "group",
+            UdpMode::Broadcast => "broadcast",
+        }
+    }
+}
+
+/// Addresses of the UDP driver, as `<bind>[?peer=<address>|group=<address>|broadcast=<address>]`
+/// with an optional `ttl=<hops>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdpOpts {
+    /// Local address frames are received on
+    pub bind: SocketAddr,
+    /// Address frames are sent to, the last sender heard from if not set
+    pub peer: Option<SocketAddr>,
+    pub mode: UdpMode,
+    /// Hops sent datagrams may take, the system default if not set
+    pub ttl: Option<u32>,
+}
+
+impl FromStr for UdpOpts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let addr = |s: &str| {
+            s.parse::<SocketAddr>()
+                .map_err(|_| format!("invalid address '{}', expected <ip>:<port>", s))
+        };
+
+        let (bind, query) = s.split_once('?').unwrap_or((s, ""));
+        let mut opts = UdpOpts {
+            bind: addr(bind)?,
+            peer: None,
+            mode: UdpMode::Unicast,
+            ttl: None,
+        };
+
+        for option in query.split('&').filter(|option| !option.is_empty()) {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            let mode = match key {
+                "peer" => UdpMode::Unicast,
+                "group" => UdpMode::Multicast,
+                "broadcast" => UdpMode::Broadcast,
+                "ttl" => {
+                    let ttl = value
+                        .parse()
+                        .map_err(|_| format!("invalid ttl '{}', expected a hop count", value))?;
+                    opts.ttl = Some(ttl);
+                    continue;
+                }
+                _ => {
+                    return Err(format!(
+                        "unknown option '{}', expected peer, group, broadcast or ttl",
+                        option
+                    ))
+                }
+            };
+            if opts.peer.is_some() {
+                return Err(String::from(
+                    "only one of peer, group and broadcast can be given",
+                ));
+            }
+            let peer = addr(value)?;
+            if mode == UdpMode::Multicast && !peer.ip().is_multicast() {
+                return Err(format!("{} is not a multicast address", peer.ip()));
+            }
+            opts.peer = Some(peer);
+            opts.mode = mode;
+        }
+
+        Ok(opts)
+    }
+}
+
+impl fmt::Display for UdpOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bind)?;
+        let mut sep = '?';
+        if let Some(peer) = self.peer {
+            write!(f, "{}{}={}", sep, self.mode.key(), peer)?;
+            sep = '&';
+        }
+        if let Some(ttl) = self.ttl {
+            write!(f, "{}ttl={}", sep, ttl)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends and receives frames as UDP datagrams
+pub struct UdpDriver {
+    socket: UdpSocket,
+    peer: Option<SocketAddr>,
+    /// Whether the peer was given, rather than learned from received datagrams
+    fixed_peer: bool,
+    /// Datagrams sent to a group or broadcast address, which come back to us
+    sent: Option<VecDeque<Vec<u8>>>,
+    interface: Arc<str>,
+    sync: ClockSync,
+    errors: DriverErrors,
+}
+
+impl UdpDriver {
+    /// Bind the local address, joining the multicast group, needs to be called within the
+    /// runtime
+    pub fn open(opts: &UdpOpts) -> io::Result<Self> {
+        let socket = Socket::new(
+            Domain::for_address(opts.bind),
+            Type::DGRAM,
+            Some(Protocol::UDP),
+        )?;
+        // Listeners of a group on the same host share its port
+        if opts.mode == UdpMode::Multicast {
+            socket.set_reuse_address(true)?;
+        }
+        socket.bind(&opts.bind.into())?;
+        socket.set_nonblocking(true)?;
+
+        match (opts.mode, opts.peer.map(|peer| peer.ip())) {
+            (UdpMode::Multicast, Some(IpAddr::V4(group))) => {
+                socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)?;
+                if let Some(ttl) = opts.ttl {
+                    socket.set_multicast_ttl_v4(ttl)?;
+                }
+            }
+            (UdpMode::Multicast, Some(IpAddr::V6(group))) => {
+                socket.join_multicast_v6(&group, 0)?;
+                if let Some(hops) = opts.ttl {
+                    socket.set_multicast_hops_v6(hops)?;
+                }
+            }
+            (mode, peer) => {
+                if mode == UdpMode::Broadcast {
+                    socket.set_broadcast(true)?;
+                }
+                match (peer, opts.ttl) {
+                    (Some(IpAddr::V6(_)), Some(hops)) => socket.set_unicast_hops_v6(hops)?,
+                    (_, Some(ttl)) => socket.set_ttl(ttl)?,
+                    (_, None) => {}
+                }
+            }
+        }
+
+        Ok(UdpDriver {
+            socket: UdpSocket::from_std(socket.into())?,
+            peer: opts.peer,
+            fixed_peer: opts.peer.is_some(),
+            sent: (opts.mode != UdpMode::Unicast).then(VecDeque::new),
+            interface: format!("udp://{}", opts).into(),
+            sync: ClockSync::new(),
+            errors: DriverErrors::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// How well the sender's clock is tracked, `None` before a timestamped frame is received
+    pub fn sync_quality(&self) -> Option<SyncQuality> {
+        self.sync.quality()
+    }
+
+    /// Handle to the sync quality, for reporting it once the driver is moved into a task
+    pub fn sync_handle(&self) -> SyncHandle {
+        self.sync.handle()
+    }
+
+    /// Failed sends and receives, counted as the driver carries on
+    pub fn errors(&self) -> DriverErrors {
+        self.errors.clone()
+    }
+
+    /// Receive the next frame, skipping malformed datagrams. `None` if the socket fails.
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        let mut buf = [0u8; MAX_DATAGRAM];
+        loop {
+            let (n, from) = match self.socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                // Reported for an earlier datagram the peer did not take
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset
+                    ) =>
+                {
+                    self.errors.record(format_args!("receive: {}", e));
+                    continue;
+                }
+                Err(e) => {
+                    self.errors.record(format_args!("receive: {}", e));
+                    return None;
+                }
+            };
+            let local = SystemTime::now();
+
+            // Our own datagram, back from the group or broadcast address
+            if let Some(sent) = &mut self.sent {
+                if let Some(i) = sent.iter().position(|datagram| datagram[..] == buf[..n]) {
+                    sent.remove(i);
+                    continue;
+                }
+            }
+
+            let Some((frame, remote)) = decode(&buf[..n]) else {
+                continue;
+            };
+            if !self.fixed_peer {
+                self.peer = Some(from);
+            }
+
+            let timestamp = match remote {
+                Some(remote) => {
+                    self.sync.update(remote, local);
+                    self.sync.correct(remote).unwrap_or(local)
+                }
+                None => local,
+            };
+
+            return Some(
+                frame
+                    .with_interface(self.interface.clone())
+                    .with_timestamp(timestamp),
+            );
+        }
+    }
+
+    /// Send a frame to the peer, dropped until one is known
+    pub async fn send(&mut self, frame: CanFrame) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        let datagram = encode(&frame, SystemTime::now());
+        if let Err(e) = self.socket.send_to(&datagram, peer).await {
+            self.errors.record(format_args!("send to {}: {}", peer, e));
+            return;
+        }
+        if let Some(sent) = &mut self.sent {
+            if sent.len() == SENT_HISTORY {
+                sent.pop_front();
+            }
+            sent.push_back(datagram);
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCanDriver for UdpDriver {
+    async fn recv(&mut self) -> Option<CanFrame> {
+        UdpDriver::recv(self).await
+    }
+
+    async fn send(&mut self, frame: CanFrame) {
+        UdpDriver::send(self, frame).await
+    }
+
+    /// Error frames of the sender are passed through as is
+    fn receive_error_frames(&mut self, _enabled: bool) -> Result<(), DriverError> {
+        Ok(())
+    }
+}
+
+/// Datagram of a frame sent at `time`
+fn encode(frame: &CanFrame, time: SystemTime) -> Vec<u8> {
+    let micros = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+
+    let mut id = match frame.id() {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | EXTENDED_FLAG,
+    };
+    if frame.is_remote_frame() {
+        id |= REMOTE_FLAG;
+    }
+    if frame.is_error_frame() {
+        id |= ERROR_FLAG;
+    }
+
+    let mut datagram = Vec::with_capacity(MAX_DATAGRAM);
+    datagram.extend(micros.to_be_bytes());
+    datagram.extend(id.to_be_bytes());
+    datagram.push(frame.raw_dlc());
+    if !frame.is_remote_frame() {
+        datagram.extend(frame.data());
+    }
+    datagram
+}
+
+/// Frame and sender time of a datagram, `None` if it is malformed
+fn decode(datagram: &[u8]) -> Option<(CanFrame, Option<SystemTime>)> {
+    if datagram.len() < HEADER_SIZE || datagram.len() > MAX_DATAGRAM {
+        return None;
+    }
+    let micros = u64::from_be_bytes(datagram[0..8].try_into().ok()?);
+    let raw = u32::from_be_bytes(datagram[8..12].try_into().ok()?);
+    let dlc = datagram[12];
+    let data = &datagram[HEADER_SIZE..];
+
+    let frame = if raw & ERROR_FLAG != 0 {
+        CanFrame::new_error(raw & 0x1FFF_FFFF, data)?
+    } else {
+        let id: Id = match raw & EXTENDED_FLAG != 0 {
+            true => ExtendedId::new(raw & 0x1FFF_FFFF)?.into(),
+            false => StandardId::new((raw & 0x7FF) as u16)?.into(),
+        };
+        match raw & REMOTE_FLAG != 0 {
+            true => CanFrame::new_remote(id, dlc.min(8) as usize)?,
+            false => CanFrame::new(id, data)?,
+        }
+    };
+    // Classic frames may be sent with a DLC of 9 to 15 for their 8 bytes
+    let frame = match dlc > 8 {
+        true => frame.clone().with_raw_dlc(dlc).unwrap_or(frame),
+        false => frame,
+    };
+
+    let time = (micros != 0).then(|| UNIX_EPOCH + Duration::from_micros(micros));
+    Some((frame, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(s: &str) -> UdpOpts {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_opts() {
+        assert_eq!(
+            opts("0.0.0.0:8000?peer=10.0.0.2:8001"),
+            UdpOpts {
+                bind: "0.0.0.0:8000".parse().unwrap(),
+                peer: Some("10.0.0.2:8001".parse().unwrap()),
+                mode: UdpMode::Unicast,
+                ttl: None,
+            }
+        );
+        assert_eq!(opts("127.0.0.1:8000").peer, None);
+        assert!("127.0.0.1".parse::<UdpOpts>().is_err());
+        assert!("127.0.0.1:8000?port=1".parse::<UdpOpts>().is_err());
+    }
+
+    #[test]
+    fn parse_group_and_broadcast() {
+        let group = opts("0.0.0.0:8000?group=239.0.0.1:8000&ttl=4");
+        assert_eq!(group.mode, UdpMode::Multicast);
+        assert_eq!(group.peer, Some("239.0.0.1:8000".parse().unwrap()));
+        assert_eq!(group.ttl, Some(4));
+        assert_eq!(group.to_string(), "0.0.0.0:8000?group=239.0.0.1:8000&ttl=4");
+
+        let broadcast = opts("0.0.0.0:8000?broadcast=192.168.1.255:8000");
+        assert_eq!(broadcast.mode, UdpMode::Broadcast);
+        assert_eq!(
+            broadcast.to_string(),
+            "0.0.0.0:8000?broadcast=192.168.1.255:8000"
+        );
+        assert_eq!(opts("0.0.0.0:8000?ttl=2").to_string(), "0.0.0.0:8000?ttl=2");
+
+        assert!("0.0.0.0:8000?group=10.0.0.1:8000"
+            .parse::<UdpOpts>()
+            .is_err());
+        assert!("0.0.0.0:8000?peer=10.0.0.1:1&group=239.0.0.1:1"
+            .parse::<UdpOpts>()
+            .is_err());
+        assert!("0.0.0.0:8000?ttl=many".parse::<UdpOpts>().is_err());
+    }
+
+    #[test]
+    fn datagrams() {
+        let time = UNIX_EPOCH + Duration::from_micros(1_800_000_000_123_456);
+        let id = ExtendedId::new(0x18FEF100).unwrap();
+        let frame = CanFrame::new(id, &[1, 2, 3]).unwrap();
+
+        let datagram = encode(&frame, time);
+        assert_eq!(datagram.len(), HEADER_SIZE + 3);
+        let (decoded, sent) = decode(&datagram).unwrap();
+        assert_eq!(decoded.id(), Id::Extended(id));
+        assert_eq!(decoded.data(), [1, 2, 3]);
+        assert_eq!(sent, Some(time));
+
+        let remote = CanFrame::new_remote(StandardId::new(0x7DF).unwrap(), 8).unwrap();
+        let (decoded, _) = decode(&encode(&remote, time)).unwrap();
+        assert!(decoded.is_remote_frame());
+        assert_eq!(decoded.dlc(), 8);
+
+        assert!(decode(&datagram[..HEADER_SIZE - 1]).is_none());
+        assert!(decode(&[0; MAX_DATAGRAM + 1]).is_none());
+    }
+
+    #[tokio::test]
+    async fn tunnel() {
+        let mut a = UdpDriver::open(&opts("127.0.0.1:0")).unwrap();
+        let peer = a.local_addr().unwrap();
+        let mut b = UdpDriver::open(&UdpOpts {
+            bind: "127.0.0.1:0".parse().unwrap(),
+            peer: Some(peer),
+            mode: UdpMode::Unicast,
+            ttl: Some(8),
+        })
+        .unwrap();
+
+        // Nothing to send to before the peer is heard from
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2]).unwrap();
+        a.send(frame.clone()).await;
+
+        b.send(frame.clone()).await;
+        let received = a.recv().await.unwrap();
+        assert_eq!(received.data(), frame.data());
+        assert!(received.timestamp().is_some());
+        assert!(received.interface().unwrap().starts_with("udp://"));
+        assert_eq!(a.sync_quality().unwrap().samples, 1);
+
+        // Replies go to the last sender
+        a.send(frame.clone()).await;
+        assert_eq!(b.recv().await.unwrap().id(), frame.id());
+    }
+
+    #[tokio::test]
+    async fn own_datagrams_dropped() {
+        // A broadcast sender hears itself, here by sending to its own address
+        let mut a = UdpDriver::open(&opts("127.0.0.1:0?broadcast=127.0.0.1:9")).unwrap();
+        a.peer = Some(a.local_addr().unwrap());
+        let mut b = UdpDriver::open(&opts("127.0.0.1:0")).unwrap();
+        b.peer = a.peer;
+
+        let id = StandardId::new(0x123).unwrap();
+        a.send(CanFrame::new(id, &[1]).unwrap()).await;
+        b.send(CanFrame::new(id, &[2]).unwrap()).await;
+
+        assert_eq!(a.recv().await.unwrap().data(), [2]);
+        assert!(a.sent.as_ref().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_errors_counted() {
+        // An IPv4 socket cannot send to an IPv6 peer
+        let mut a = UdpDriver::open(&opts("127.0.0.1:0?peer=[::1]:9")).unwrap();
+        let errors = a.errors();
+        let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1]).unwrap();
+        a.send(frame.clone()).await;
+        a.send(frame).await;
+
+        assert_eq!(errors.count(), 2);
+        assert!(errors
+            .to_string()
+            .starts_with("2 errors, the last: send to [::1]:9: "));
+    }
+}
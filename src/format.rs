@@ -5,10 +5,11 @@
 // @date Oct 01 2023
 //
 
-use crate::{frame::CanFrame, utils};
+use crate::frame::CanFrame;
 
-use std::fmt;
+use std::fmt::{self, Write};
 
+use candecode::Database;
 use embedded_can::{Frame, Id};
 
 #[derive(Debug, Clone, Copy)]
@@ -18,13 +19,15 @@ pub enum DataFormatMode {
 }
 
 /// Data for formatting a CAN frame
-pub struct CanFrameFormatter {
-    frame: CanFrame,
+///
+/// Formats straight into the output, so writing into a reused buffer does not allocate.
+pub struct CanFrameFormatter<'a> {
+    frame: &'a CanFrame,
     data_format_mode: DataFormatMode,
 }
 
-impl From<(CanFrame, DataFormatMode)> for CanFrameFormatter {
-    fn from(value: (CanFrame, DataFormatMode)) -> Self {
+impl<'a> From<(&'a CanFrame, DataFormatMode)> for CanFrameFormatter<'a> {
+    fn from(value: (&'a CanFrame, DataFormatMode)) -> Self {
         CanFrameFormatter {
             frame: value.0,
             data_format_mode: value.1,
@@ -32,24 +35,100 @@ impl From<(CanFrame, DataFormatMode)> for CanFrameFormatter {
     }
 }
 
-impl fmt::Display for CanFrameFormatter {
+impl fmt::Display for CanFrameFormatter<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let id = utils::id_to_raw(&self.frame.id());
-        let id = match self.frame.id() {
-            Id::Standard(_) => format!("{:03X}", id),
-            Id::Extended(_) => format!("{:08X}", id),
-        };
-
-        let dlc = self.frame.dlc();
-        let data_string =
-            self.frame
-                .data()
-                .iter()
-                .fold(String::from(""), |a, b| match self.data_format_mode {
-                    DataFormatMode::Hex => format!("{} {:02X}", a, b),
-                    DataFormatMode::Binary => format!("{} {:08b}", a, b),
-                });
-
-        write!(f, "{} [{}] {}", id, dlc, data_string)
+        match self.frame.id() {
+            Id::Standard(id) => write!(f, "{:03X}", id.as_raw())?,
+            Id::Extended(id) => write!(f, "{:08X}", id.as_raw())?,
+        }
+        // The DLC as sent, 9 to 15 on some classic frames of 8 bytes
+        write!(f, " [{}] ", self.frame.raw_dlc())?;
+
+        for b in self.frame.data() {
+            match self.data_format_mode {
+                DataFormatMode::Hex => write!(f, " {:02X}", b)?,
+                DataFormatMode::Binary => write!(f, " {:08b}", b)?,
+            }
+        }
+
+        // Frames sent by ican itself, when own messages are received
+        if self.frame.is_tx() {
+            write!(f, "  TX")?;
+        }
+        if self.frame.is_error_frame() {
+            write!(f, "  ERROR")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Format the message name and decoded signals of a frame, each prefixed with `separator`
+///
+/// Enumerated signals show their label, `raw_values` adds the raw value after the label.
+pub fn format_signals(
+    db: &Database,
+    frame: &CanFrame,
+    separator: &str,
+    raw_values: bool,
+) -> String {
+    let mut out = String::new();
+    // Writing to a string cannot fail
+    let _ = write_signals(&mut out, db, frame, separator, raw_values);
+    out
+}
+
+/// Write the message name and decoded signals of a frame, as formatted by [`format_signals`]
+pub fn write_signals(
+    out: &mut impl Write,
+    db: &Database,
+    frame: &CanFrame,
+    separator: &str,
+    raw_values: bool,
+) -> fmt::Result {
+    let Some(message) = db.message(frame.id()) else {
+        return Ok(());
+    };
+
+    write!(out, "  {}", message.name)?;
+    for signal in message.decode(frame.data()) {
+        if raw_values {
+            write!(out, "{}{:#}", separator, signal)?;
+        } else {
+            write!(out, "{}{}", separator, signal)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, StandardId};
+
+    #[test]
+    fn format_frames() {
+        let id = StandardId::new(0x7E8).unwrap();
+        let frame = CanFrame::new(id, &[0x01, 0xAB]).unwrap();
+        let fmt: CanFrameFormatter = (&frame, DataFormatMode::Hex).into();
+        assert_eq!(fmt.to_string(), "7E8 [2]  01 AB");
+
+        let fmt: CanFrameFormatter = (&frame, DataFormatMode::Binary).into();
+        assert_eq!(fmt.to_string(), "7E8 [2]  00000001 10101011");
+
+        let id = ExtendedId::new(0x1F1).unwrap();
+        let frame = CanFrame::new(id, &[]).unwrap();
+        let fmt: CanFrameFormatter = (&frame, DataFormatMode::Hex).into();
+        assert_eq!(fmt.to_string(), "000001F1 [0] ");
+
+        let frame = frame.with_direction(crate::frame::Direction::Tx);
+        let fmt: CanFrameFormatter = (&frame, DataFormatMode::Hex).into();
+        assert_eq!(fmt.to_string(), "000001F1 [0]   TX");
+
+        let frame = CanFrame::new(id, &[0; 8]).unwrap().with_raw_dlc(9).unwrap();
+        let fmt: CanFrameFormatter = (&frame, DataFormatMode::Hex).into();
+        assert_eq!(fmt.to_string(), "000001F1 [9]  00 00 00 00 00 00 00 00");
     }
 }
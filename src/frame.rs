@@ -5,16 +5,110 @@
 // @date Sep 29 2023
 //
 
-use embedded_can::{Frame, Id};
+use embedded_can::{ExtendedId, Frame, Id};
+
+use std::{sync::Arc, time::SystemTime};
+
+/// Direction of a frame, as seen by the receiving device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from the bus
+    #[default]
+    Rx,
+    /// Sent by this device, confirmed by the driver
+    Tx,
+}
 
 /// Independent CAN frame type common to all drivers
+///
+/// Besides the frame itself, drivers record the interface it was received on and when, so frames
+/// from several interfaces can be told apart further down the pipeline.
 #[derive(Debug, Clone)]
 pub struct CanFrame {
     id: Id,
     is_extended: bool,
     is_remote: bool,
     dlc: usize,
+    /// DLC as sent on the bus, classic frames may use 9 to 15 for 8 data bytes
+    raw_dlc: u8,
     data: [u8; 8],
+    direction: Direction,
+    is_error: bool,
+    interface: Option<Arc<str>>,
+    timestamp: Option<SystemTime>,
+}
+
+impl CanFrame {
+    /// Create an error frame reported by the controller, with the error class as its ID
+    ///
+    /// Error classes and data follow the SocketCAN error frame layout, see [`crate::busstate`].
+    pub fn new_error(class: u32, data: &[u8]) -> Option<Self> {
+        let mut frame = CanFrame::new(ExtendedId::new(class)?, data)?;
+        frame.is_error = true;
+        Some(frame)
+    }
+
+    /// An error frame, rather than a frame received from the bus
+    pub fn is_error_frame(&self) -> bool {
+        self.is_error
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Set the direction, drivers mark the echo of frames they sent as `Tx`
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// The echo of a frame sent through this driver
+    pub fn is_tx(&self) -> bool {
+        self.direction == Direction::Tx
+    }
+
+    /// DLC code as sent on the bus, from 9 to 15 for some 8 byte frames
+    ///
+    /// [`Frame::dlc`] is always the number of data bytes, at most 8.
+    pub fn raw_dlc(&self) -> u8 {
+        self.raw_dlc
+    }
+
+    /// Set a DLC code above 8, only valid for frames of 8 bytes. `None` if it does not match
+    /// the length of the frame.
+    pub fn with_raw_dlc(mut self, dlc: u8) -> Option<Self> {
+        let valid = match dlc {
+            0..=8 => dlc as usize == self.dlc,
+            9..=15 => self.dlc == 8,
+            _ => false,
+        };
+        valid.then(|| {
+            self.raw_dlc = dlc;
+            self
+        })
+    }
+
+    /// Name of the interface the frame was received on, if the driver records it
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    /// Set the interface, shared between the frames of a driver
+    pub fn with_interface(mut self, interface: Arc<str>) -> Self {
+        self.interface = Some(interface);
+        self
+    }
+
+    /// Time the frame was received, from the kernel where the driver supports it
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
 }
 
 impl Frame for CanFrame {
@@ -23,14 +117,19 @@ impl Frame for CanFrame {
             let id: Id = id.into();
 
             let mut payload = [0u8; 8];
-            payload[..data.len()].copy_from_slice(&data[..]);
+            payload[..data.len()].copy_from_slice(data);
 
             Some(CanFrame {
                 id,
                 is_extended: matches!(id, Id::Extended(_)),
                 is_remote: false,
                 dlc: data.len(),
+                raw_dlc: data.len() as u8,
                 data: payload,
+                direction: Direction::Rx,
+                is_error: false,
+                interface: None,
+                timestamp: None,
             })
         } else {
             None
@@ -44,7 +143,12 @@ impl Frame for CanFrame {
                 is_extended: false,
                 is_remote: true,
                 dlc,
+                raw_dlc: dlc as u8,
                 data: [0u8; 8],
+                direction: Direction::Rx,
+                is_error: false,
+                interface: None,
+                timestamp: None,
             })
         } else {
             None
@@ -71,3 +175,37 @@ impl Frame for CanFrame {
         &self.data[..self.dlc]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    #[test]
+    fn raw_dlc() {
+        let id = StandardId::new(0x123).unwrap();
+
+        let frame = CanFrame::new(id, &[0; 8])
+            .unwrap()
+            .with_raw_dlc(15)
+            .unwrap();
+        assert_eq!(frame.raw_dlc(), 15);
+        assert_eq!(frame.dlc(), 8);
+        assert_eq!(frame.data().len(), 8);
+
+        assert_eq!(CanFrame::new(id, &[1, 2]).unwrap().raw_dlc(), 2);
+        assert!(CanFrame::new(id, &[1, 2])
+            .unwrap()
+            .with_raw_dlc(9)
+            .is_none());
+        assert!(CanFrame::new(id, &[0; 8])
+            .unwrap()
+            .with_raw_dlc(16)
+            .is_none());
+        assert!(CanFrame::new_remote(id, 8)
+            .unwrap()
+            .with_raw_dlc(9)
+            .is_some());
+    }
+}
@@ -0,0 +1,350 @@
+//
+// channel.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::{
+    frame::{self, st_min_to_duration, to_can_frame, FlowStatus, IsoTpFrame},
+    IsoTpConfig, IsoTpError, Reassembler,
+};
+
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+use super::kernel::KernelSocket;
+use crate::drivers::AsyncCanDriverPtr;
+
+use embedded_can::Frame;
+use tokio::time::{self, Duration, Instant};
+
+/// Flow control wait frames accepted before giving up on a transfer
+const MAX_WAIT_FRAMES: usize = 10;
+
+/// Bidirectional ISO-TP connection over a CAN driver
+pub struct IsoTpChannel {
+    driver: AsyncCanDriverPtr,
+    config: IsoTpConfig,
+    reassembler: Reassembler,
+    /// Kernel socket carrying the messages instead of the driver
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
+    kernel: Option<KernelSocket>,
+}
+
+impl IsoTpChannel {
+    pub fn new(driver: AsyncCanDriverPtr, config: IsoTpConfig) -> Self {
+        IsoTpChannel {
+            driver,
+            config,
+            reassembler: Reassembler::new(),
+            #[cfg(all(target_os = "linux", feature = "socketcan"))]
+            kernel: None,
+        }
+    }
+
+    /// Send and receive through a kernel CAN_ISOTP socket, keeping the driver for `into_inner`
+    #[cfg(all(target_os = "linux", feature = "socketcan"))]
+    pub fn with_kernel(mut self, socket: KernelSocket) -> Self {
+        self.kernel = Some(socket);
+        self
+    }
+
+    /// Whether the kernel carries the messages
+    pub fn is_kernel(&self) -> bool {
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        return self.kernel.is_some();
+        #[cfg(not(all(target_os = "linux", feature = "socketcan")))]
+        false
+    }
+
+    pub fn config(&self) -> &IsoTpConfig {
+        &self.config
+    }
+
+    /// Release the underlying driver
+    pub fn into_inner(self) -> AsyncCanDriverPtr {
+        self.driver
+    }
+
+    /// Send a message, segmenting it if it does not fit in a single frame
+    pub async fn send(&mut self, data: &[u8]) -> Result<(), IsoTpError> {
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        if let Some(socket) = &self.kernel {
+            return socket.send(data).await;
+        }
+
+        let capacity = 8 - self.config.tx_address.is_some() as usize;
+        let mut frames = frame::segment(data, capacity)?;
+
+        // The first frame precedes the consecutive frames
+        if let Some(n) = self.config.stress.wrong_sequence {
+            if let Some(IsoTpFrame::Consecutive { sequence, .. }) = frames.get_mut(n) {
+                *sequence = (*sequence + 1) & 0x0F;
+            }
+        }
+
+        let mut frames = frames.into_iter().peekable();
+
+        if let Some(frame) = frames.next() {
+            self.send_frame(&frame).await?;
+        }
+
+        while frames.peek().is_some() {
+            let (block_size, st_min) = self.wait_flow_control().await?;
+            let block_size = self.config.stress.block_size.unwrap_or(block_size);
+            let st_min = self.config.stress.st_min.map_or(st_min, st_min_to_duration);
+            let mut sent = 0;
+
+            while let Some(frame) = frames.next() {
+                self.send_frame(&frame).await?;
+                sent += 1;
+
+                if frames.peek().is_none() || sent == block_size as usize {
+                    break;
+                }
+
+                if !st_min.is_zero() {
+                    time::sleep(st_min).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Receive the next complete message
+    ///
+    /// Waits indefinitely for the start of a message, and up to the configured timeout for each
+    /// consecutive frame after that.
+    pub async fn recv(&mut self) -> Result<Vec<u8>, IsoTpError> {
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        if let Some(socket) = &self.kernel {
+            return socket.recv().await;
+        }
+
+        let mut deadline = None;
+        let mut received = 0usize;
+
+        loop {
+            let frame = self.recv_frame(deadline, "consecutive frame").await?;
+
+            if let Some(data) = self.reassembler.process(&frame)? {
+                return Ok(data);
+            }
+
+            match frame {
+                IsoTpFrame::First { .. } => {
+                    received = 0;
+                    self.send_flow_control(FlowStatus::ContinueToSend).await?;
+                }
+                IsoTpFrame::Consecutive { .. } if self.reassembler.in_progress() => {
+                    received += 1;
+                    if received == self.config.block_size as usize {
+                        received = 0;
+                        self.send_flow_control(FlowStatus::ContinueToSend).await?;
+                    }
+                }
+                _ => continue,
+            }
+
+            deadline = Some(Instant::now() + self.config.timeout);
+        }
+    }
+
+    async fn send_flow_control(&mut self, status: FlowStatus) -> Result<(), IsoTpError> {
+        if !self.config.stress.fc_delay.is_zero() {
+            time::sleep(self.config.stress.fc_delay).await;
+        }
+
+        let frame = IsoTpFrame::FlowControl {
+            status,
+            block_size: self.config.block_size,
+            st_min: self.config.st_min,
+        };
+        self.send_frame(&frame).await
+    }
+
+    /// Wait for the receiver to allow the next block, returning the block size and separation
+    /// time
+    async fn wait_flow_control(&mut self) -> Result<(u8, Duration), IsoTpError> {
+        let mut deadline = Instant::now() + self.config.timeout;
+        let mut waits = 0;
+
+        loop {
+            let frame = self.recv_frame(Some(deadline), "flow control").await?;
+
+            match frame {
+                IsoTpFrame::FlowControl {
+                    status: FlowStatus::ContinueToSend,
+                    block_size,
+                    st_min,
+                } => return Ok((block_size, st_min_to_duration(st_min))),
+                IsoTpFrame::FlowControl {
+                    status: FlowStatus::Wait,
+                    ..
+                } => {
+                    waits += 1;
+                    if waits > MAX_WAIT_FRAMES {
+                        return Err(IsoTpError::Timeout("flow control"));
+                    }
+                    deadline = Instant::now() + self.config.timeout;
+                }
+                IsoTpFrame::FlowControl {
+                    status: FlowStatus::Overflow,
+                    ..
+                } => return Err(IsoTpError::Overflow),
+                _ => {}
+            }
+        }
+    }
+
+    async fn send_frame(&mut self, frame: &IsoTpFrame) -> Result<(), IsoTpError> {
+        let frame = to_can_frame(
+            self.config.tx_id,
+            self.config.tx_address,
+            self.config.padding,
+            frame,
+        )?;
+        self.driver.send(frame).await;
+
+        Ok(())
+    }
+
+    /// Receive the next ISO-TP frame addressed to this channel
+    async fn recv_frame(
+        &mut self,
+        deadline: Option<Instant>,
+        waiting_for: &'static str,
+    ) -> Result<IsoTpFrame, IsoTpError> {
+        loop {
+            let frame = match deadline {
+                Some(deadline) => time::timeout_at(deadline, self.driver.recv())
+                    .await
+                    .map_err(|_| IsoTpError::Timeout(waiting_for))?,
+                None => self.driver.recv().await,
+            }
+            .ok_or(IsoTpError::Closed)?;
+
+            if frame.id() != self.config.rx_id {
+                continue;
+            }
+
+            let data = match self.config.rx_address {
+                Some(address) => match frame.data().split_first() {
+                    Some((&target, data)) if target == address => data,
+                    _ => continue,
+                },
+                None => frame.data(),
+            };
+
+            // Malformed frames are ignored like frames of other channels
+            if let Ok(frame) = IsoTpFrame::parse(data) {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::drivers::loopback::LoopbackDriver;
+
+    use embedded_can::StandardId;
+
+    fn pair(a: IsoTpConfig, b: IsoTpConfig) -> (IsoTpChannel, IsoTpChannel) {
+        let (a_driver, b_driver) = LoopbackDriver::pair();
+
+        (
+            IsoTpChannel::new(Box::new(a_driver), a),
+            IsoTpChannel::new(Box::new(b_driver), b),
+        )
+    }
+
+    fn id(raw: u16) -> StandardId {
+        StandardId::new(raw).unwrap()
+    }
+
+    #[tokio::test]
+    async fn multi_frame_transfer() {
+        let mut tester = IsoTpConfig::new(id(0x7E0), id(0x7E8));
+        tester.block_size = 2;
+        let (mut tester, mut ecu) = pair(tester, IsoTpConfig::new(id(0x7E8), id(0x7E0)));
+
+        let message: Vec<u8> = (0..100).collect();
+
+        let (sent, received) = tokio::join!(tester.send(&message), ecu.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), message);
+
+        let (sent, received) = tokio::join!(ecu.send(&[0x50, 0x03]), tester.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), vec![0x50, 0x03]);
+    }
+
+    #[tokio::test]
+    async fn extended_addressing() {
+        let mut tester = IsoTpConfig::new(id(0x600), id(0x601));
+        tester.tx_address = Some(0x10);
+        tester.rx_address = Some(0xF1);
+        let mut ecu = IsoTpConfig::new(id(0x601), id(0x600));
+        ecu.tx_address = Some(0xF1);
+        ecu.rx_address = Some(0x10);
+
+        let (mut tester, mut ecu) = pair(tester, ecu);
+        let message: Vec<u8> = (0..30).collect();
+
+        let (sent, received) = tokio::join!(tester.send(&message), ecu.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), message);
+    }
+
+    #[tokio::test]
+    async fn stress_options() {
+        let mut tester = IsoTpConfig::new(id(0x7E0), id(0x7E8));
+        tester.stress.block_size = Some(0);
+        tester.stress.wrong_sequence = Some(2);
+        let mut ecu = IsoTpConfig::new(id(0x7E8), id(0x7E0));
+        ecu.block_size = 1;
+        let (mut tester, mut ecu) = pair(tester, ecu);
+
+        // The override ignores the receiver's block size, so no further flow control is awaited
+        let (sent, received) = tokio::join!(tester.send(&[0u8; 30]), ecu.recv());
+        sent.unwrap();
+        assert!(matches!(
+            received.unwrap_err(),
+            IsoTpError::SequenceNumber {
+                expected: 2,
+                actual: 3
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn delayed_flow_control() {
+        let mut tester = IsoTpConfig::new(id(0x7E0), id(0x7E8));
+        tester.timeout = Duration::from_millis(10);
+        let mut ecu = IsoTpConfig::new(id(0x7E8), id(0x7E0));
+        ecu.stress.fc_delay = Duration::from_millis(50);
+        let (mut tester, mut ecu) = pair(tester, ecu);
+
+        let (sent, _) = tokio::join!(
+            tester.send(&[0u8; 20]),
+            time::timeout(Duration::from_millis(100), ecu.recv())
+        );
+        assert!(matches!(
+            sent.unwrap_err(),
+            IsoTpError::Timeout("flow control")
+        ));
+    }
+
+    #[tokio::test]
+    async fn flow_control_timeout() {
+        let mut config = IsoTpConfig::new(id(0x7E0), id(0x7E8));
+        config.timeout = Duration::from_millis(10);
+        let (mut tester, _ecu) = pair(config, IsoTpConfig::new(id(0x7E8), id(0x7E0)));
+
+        let err = tester.send(&[0u8; 20]).await.unwrap_err();
+        assert!(matches!(err, IsoTpError::Timeout("flow control")));
+    }
+}
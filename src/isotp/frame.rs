@@ -0,0 +1,302 @@
+//
+// frame.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::IsoTpError;
+
+use crate::frame::CanFrame;
+
+use embedded_can::{Frame, Id};
+
+use std::time::Duration;
+
+// Protocol control information types
+const SINGLE: u8 = 0x0;
+const FIRST: u8 = 0x1;
+const CONSECUTIVE: u8 = 0x2;
+const FLOW_CONTROL: u8 = 0x3;
+
+/// Largest length of a first frame without the 32-bit escape sequence
+pub const MAX_SHORT_LENGTH: usize = 0xFFF;
+
+/// Flow control status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowStatus {
+    ContinueToSend,
+    Wait,
+    Overflow,
+}
+
+/// An ISO-TP (ISO 15765-2) frame, without addressing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsoTpFrame {
+    Single {
+        data: Vec<u8>,
+    },
+    First {
+        length: usize,
+        data: Vec<u8>,
+    },
+    Consecutive {
+        sequence: u8,
+        data: Vec<u8>,
+    },
+    FlowControl {
+        status: FlowStatus,
+        block_size: u8,
+        st_min: u8,
+    },
+}
+
+impl IsoTpFrame {
+    /// Parse the payload of a CAN frame, after any extended address byte
+    pub fn parse(data: &[u8]) -> Result<Self, IsoTpError> {
+        let pci = *data
+            .first()
+            .ok_or(IsoTpError::InvalidFrame("empty frame"))?;
+
+        match pci >> 4 {
+            SINGLE => {
+                let length = (pci & 0x0F) as usize;
+                if length == 0 || length >= data.len() {
+                    return Err(IsoTpError::InvalidFrame("invalid single frame length"));
+                }
+                Ok(IsoTpFrame::Single {
+                    data: data[1..=length].to_vec(),
+                })
+            }
+            FIRST => {
+                if data.len() < 2 {
+                    return Err(IsoTpError::InvalidFrame("first frame too short"));
+                }
+
+                let length = u16::from_be_bytes([pci & 0x0F, data[1]]) as usize;
+
+                // Lengths above 4095 bytes are escaped with a zero length and a 32-bit length
+                let (length, offset) = if length == 0 {
+                    if data.len() < 6 {
+                        return Err(IsoTpError::InvalidFrame("first frame too short"));
+                    }
+                    let length = u32::from_be_bytes([data[2], data[3], data[4], data[5]]);
+                    (length as usize, 6)
+                } else {
+                    (length, 2)
+                };
+
+                Ok(IsoTpFrame::First {
+                    length,
+                    data: data[offset..].to_vec(),
+                })
+            }
+            CONSECUTIVE => Ok(IsoTpFrame::Consecutive {
+                sequence: pci & 0x0F,
+                data: data[1..].to_vec(),
+            }),
+            FLOW_CONTROL => {
+                if data.len() < 3 {
+                    return Err(IsoTpError::InvalidFrame("flow control too short"));
+                }
+
+                let status = match pci & 0x0F {
+                    0 => FlowStatus::ContinueToSend,
+                    1 => FlowStatus::Wait,
+                    2 => FlowStatus::Overflow,
+                    _ => return Err(IsoTpError::InvalidFrame("invalid flow status")),
+                };
+
+                Ok(IsoTpFrame::FlowControl {
+                    status,
+                    block_size: data[1],
+                    st_min: data[2],
+                })
+            }
+            _ => Err(IsoTpError::InvalidFrame("unknown frame type")),
+        }
+    }
+
+    /// Encode the frame, without padding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            IsoTpFrame::Single { data } => {
+                let mut buf = vec![(SINGLE << 4) | data.len() as u8];
+                buf.extend_from_slice(data);
+                buf
+            }
+            IsoTpFrame::First { length, data } => {
+                let mut buf = if *length > MAX_SHORT_LENGTH {
+                    let mut buf = vec![FIRST << 4, 0];
+                    buf.extend_from_slice(&(*length as u32).to_be_bytes());
+                    buf
+                } else {
+                    vec![(FIRST << 4) | (*length >> 8) as u8, *length as u8]
+                };
+                buf.extend_from_slice(data);
+                buf
+            }
+            IsoTpFrame::Consecutive { sequence, data } => {
+                let mut buf = vec![(CONSECUTIVE << 4) | (sequence & 0x0F)];
+                buf.extend_from_slice(data);
+                buf
+            }
+            IsoTpFrame::FlowControl {
+                status,
+                block_size,
+                st_min,
+            } => {
+                let status = match status {
+                    FlowStatus::ContinueToSend => 0,
+                    FlowStatus::Wait => 1,
+                    FlowStatus::Overflow => 2,
+                };
+                vec![(FLOW_CONTROL << 4) | status, *block_size, *st_min]
+            }
+        }
+    }
+}
+
+/// Build the CAN frame carrying an ISO-TP frame
+///
+/// `address` is prepended for extended addressing, `padding` fills the frame to 8 bytes.
+pub fn to_can_frame(
+    id: Id,
+    address: Option<u8>,
+    padding: Option<u8>,
+    frame: &IsoTpFrame,
+) -> Result<CanFrame, IsoTpError> {
+    let mut data: Vec<u8> = address.into_iter().collect();
+    data.extend(frame.to_bytes());
+
+    if let Some(padding) = padding {
+        data.resize(8, padding);
+    }
+
+    CanFrame::new(id, &data).ok_or(IsoTpError::InvalidFrame("frame too long"))
+}
+
+/// Separation time encoded in a flow control frame
+pub fn st_min_to_duration(st_min: u8) -> Duration {
+    match st_min {
+        0x00..=0x7F => Duration::from_millis(st_min as u64),
+        0xF1..=0xF9 => Duration::from_micros((st_min - 0xF0) as u64 * 100),
+        // Reserved values are treated as the longest separation time
+        _ => Duration::from_millis(0x7F),
+    }
+}
+
+/// Split a message into a single frame or a first frame followed by consecutive frames
+///
+/// `capacity` is the number of payload bytes available per CAN frame.
+pub fn segment(data: &[u8], capacity: usize) -> Result<Vec<IsoTpFrame>, IsoTpError> {
+    if data.is_empty() {
+        return Err(IsoTpError::InvalidFrame("empty message"));
+    }
+
+    if data.len() < capacity {
+        return Ok(vec![IsoTpFrame::Single {
+            data: data.to_vec(),
+        }]);
+    }
+
+    if data.len() > u32::MAX as usize {
+        return Err(IsoTpError::TooLong(data.len()));
+    }
+
+    let header = if data.len() > MAX_SHORT_LENGTH { 6 } else { 2 };
+    let (first, rest) = data.split_at(capacity - header);
+
+    let mut frames = vec![IsoTpFrame::First {
+        length: data.len(),
+        data: first.to_vec(),
+    }];
+
+    frames.extend(rest.chunks(capacity - 1).enumerate().map(|(i, chunk)| {
+        IsoTpFrame::Consecutive {
+            sequence: ((i + 1) & 0x0F) as u8,
+            data: chunk.to_vec(),
+        }
+    }));
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_frames() {
+        assert_eq!(
+            IsoTpFrame::parse(&[0x03, 0x22, 0xF1, 0x90, 0xCC, 0xCC, 0xCC, 0xCC]).unwrap(),
+            IsoTpFrame::Single {
+                data: vec![0x22, 0xF1, 0x90]
+            }
+        );
+        assert_eq!(
+            IsoTpFrame::parse(&[0x10, 0x14, 0x62, 0xF1, 0x90, 0x57, 0x30, 0x4C]).unwrap(),
+            IsoTpFrame::First {
+                length: 20,
+                data: vec![0x62, 0xF1, 0x90, 0x57, 0x30, 0x4C]
+            }
+        );
+        assert_eq!(
+            IsoTpFrame::parse(&[0x30, 0x08, 0xF5]).unwrap(),
+            IsoTpFrame::FlowControl {
+                status: FlowStatus::ContinueToSend,
+                block_size: 8,
+                st_min: 0xF5
+            }
+        );
+        assert!(IsoTpFrame::parse(&[0x05, 0x01]).is_err());
+        assert!(IsoTpFrame::parse(&[0x34, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn segmentation() {
+        let data: Vec<u8> = (0..20).collect();
+        let frames = segment(&data, 8).unwrap();
+
+        let bytes: Vec<Vec<u8>> = frames.iter().map(IsoTpFrame::to_bytes).collect();
+        assert_eq!(
+            bytes,
+            vec![
+                vec![0x10, 20, 0, 1, 2, 3, 4, 5],
+                vec![0x21, 6, 7, 8, 9, 10, 11, 12],
+                vec![0x22, 13, 14, 15, 16, 17, 18, 19],
+            ]
+        );
+
+        // Extended addressing leaves one byte less per frame
+        assert_eq!(
+            segment(&[1, 2, 3, 4, 5, 6, 7], 7).unwrap()[0].to_bytes(),
+            vec![0x10, 7, 1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn long_first_frame() {
+        let data = vec![0xAA; 5000];
+        let frames = segment(&data, 8).unwrap();
+
+        let first = frames[0].to_bytes();
+        assert_eq!(first, vec![0x10, 0x00, 0x00, 0x00, 0x13, 0x88, 0xAA, 0xAA]);
+        assert_eq!(
+            IsoTpFrame::parse(&first).unwrap(),
+            IsoTpFrame::First {
+                length: 5000,
+                data: vec![0xAA, 0xAA]
+            }
+        );
+        // Sequence numbers wrap after 15
+        assert_eq!(frames[16].to_bytes()[0], 0x20);
+    }
+
+    #[test]
+    fn separation_time() {
+        assert_eq!(st_min_to_duration(0x0A), Duration::from_millis(10));
+        assert_eq!(st_min_to_duration(0xF3), Duration::from_micros(300));
+        assert_eq!(st_min_to_duration(0xFF), Duration::from_millis(127));
+    }
+}
@@ -0,0 +1,233 @@
+//
+// functional.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::{
+    frame::{to_can_frame, FlowStatus, IsoTpFrame},
+    IsoTpConfig, IsoTpError, Reassembler,
+};
+
+use crate::drivers::AsyncCanDriverPtr;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use tokio::time::{self, Instant};
+
+/// Standard functional request ID of OBD-II (ISO 15765-4)
+pub const OBD_FUNCTIONAL_ID: u16 = 0x7DF;
+/// Extended functional request ID of OBD-II
+pub const OBD_FUNCTIONAL_EXTENDED_ID: u32 = 0x18DB33F1;
+
+/// Physical request ID of the ECU sending responses on `response`, following the ISO 15765-4
+/// assignment
+///
+/// 11-bit responses 0x7E8-0x7EF map to requests 0x7E0-0x7E7. 29-bit responses 0x18DAF1xx map
+/// to requests 0x18DAxxF1.
+pub fn physical_request_id(response: Id) -> Option<Id> {
+    match response {
+        Id::Standard(id) => match id.as_raw() {
+            raw @ 0x7E8..=0x7EF => StandardId::new(raw - 8).map(Id::Standard),
+            _ => None,
+        },
+        Id::Extended(id) => match id.as_raw() {
+            raw if raw & 0xFFFF_FF00 == 0x18DA_F100 => {
+                let ecu = raw & 0xFF;
+                ExtendedId::new(0x18DA_00F1 | (ecu << 8)).map(Id::Extended)
+            }
+            _ => None,
+        },
+    }
+}
+
+/// Sends functional (broadcast) requests and receives the physical responses of every ECU
+///
+/// Functional requests must fit in a single frame. Responses are accepted on any ID known to
+/// `physical_request_id`, which is also used to send flow control to each responding ECU.
+pub struct FunctionalChannel {
+    driver: AsyncCanDriverPtr,
+    config: IsoTpConfig,
+    responders: Vec<(Id, Reassembler)>,
+}
+
+impl FunctionalChannel {
+    /// Create a channel sending requests on `config.tx_id`
+    ///
+    /// The flow control parameters of `config` are used for all ECUs, `config.rx_id` is unused.
+    pub fn new(driver: AsyncCanDriverPtr, config: IsoTpConfig) -> Self {
+        FunctionalChannel {
+            driver,
+            config,
+            responders: vec![],
+        }
+    }
+
+    pub fn config(&self) -> &IsoTpConfig {
+        &self.config
+    }
+
+    /// Release the underlying driver
+    pub fn into_inner(self) -> AsyncCanDriverPtr {
+        self.driver
+    }
+
+    /// Send a functional request, discarding partial responses to earlier requests
+    pub async fn send(&mut self, data: &[u8]) -> Result<(), IsoTpError> {
+        // Single frames carry up to 7 bytes
+        if data.is_empty() || data.len() > 7 {
+            return Err(IsoTpError::TooLong(data.len()));
+        }
+
+        self.responders.clear();
+
+        let frame = IsoTpFrame::Single {
+            data: data.to_vec(),
+        };
+        let frame = to_can_frame(self.config.tx_id, None, self.config.padding, &frame)?;
+        self.driver.send(frame).await;
+
+        Ok(())
+    }
+
+    /// Receive the next complete response from any ECU, `None` once the deadline has passed
+    ///
+    /// Responses that are still being received at the deadline are given the configured timeout
+    /// per consecutive frame to complete.
+    pub async fn recv_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<Option<(Id, Vec<u8>)>, IsoTpError> {
+        let mut extended = deadline;
+
+        loop {
+            let in_progress = self.responders.iter().any(|(_, rx)| rx.in_progress());
+            let until = if in_progress { extended } else { deadline };
+
+            let frame = match time::timeout_at(until, self.driver.recv()).await {
+                Ok(frame) => frame.ok_or(IsoTpError::Closed)?,
+                Err(_) => return Ok(None),
+            };
+
+            let Some(request_id) = physical_request_id(frame.id()) else {
+                continue;
+            };
+            let Ok(tp) = IsoTpFrame::parse(frame.data()) else {
+                continue;
+            };
+
+            let index = match self.responders.iter().position(|(id, _)| *id == frame.id()) {
+                Some(index) => index,
+                None => {
+                    self.responders.push((frame.id(), Reassembler::new()));
+                    self.responders.len() - 1
+                }
+            };
+
+            // A broken response from one ECU does not affect the others
+            let message = match self.responders[index].1.process(&tp) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+
+            if let Some(message) = message {
+                return Ok(Some((frame.id(), message)));
+            }
+
+            if matches!(tp, IsoTpFrame::First { .. }) {
+                self.send_flow_control(request_id).await?;
+            }
+
+            if self.responders[index].1.in_progress() {
+                extended = extended.max(Instant::now() + self.config.timeout);
+            }
+        }
+    }
+
+    /// Let an ECU send its whole response without further flow control
+    async fn send_flow_control(&mut self, id: Id) -> Result<(), IsoTpError> {
+        let frame = IsoTpFrame::FlowControl {
+            status: FlowStatus::ContinueToSend,
+            block_size: 0,
+            st_min: self.config.st_min,
+        };
+        let frame = to_can_frame(id, None, self.config.padding, &frame)?;
+        self.driver.send(frame).await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{drivers::loopback::LoopbackDriver, isotp::IsoTpChannel};
+
+    use tokio::time::Duration;
+
+    fn id(raw: u16) -> Id {
+        Id::Standard(StandardId::new(raw).unwrap())
+    }
+
+    #[test]
+    fn physical_ids() {
+        assert_eq!(physical_request_id(id(0x7E9)), Some(id(0x7E1)));
+        assert_eq!(physical_request_id(id(0x7E0)), None);
+        assert_eq!(
+            physical_request_id(Id::Extended(ExtendedId::new(0x18DAF110).unwrap())),
+            Some(Id::Extended(ExtendedId::new(0x18DA10F1).unwrap()))
+        );
+    }
+
+    #[tokio::test]
+    async fn multiple_responses() {
+        let (tester, ecus) = LoopbackDriver::pair();
+        let mut tester = FunctionalChannel::new(
+            Box::new(tester),
+            IsoTpConfig::new(id(OBD_FUNCTIONAL_ID), id(0x7E8)),
+        );
+
+        // Both ECUs share the loopback, responding in turn
+        let mut ecus: AsyncCanDriverPtr = Box::new(ecus);
+        tokio::spawn(async move {
+            let request = ecus.recv().await.unwrap();
+            assert_eq!(request.id(), id(0x7DF));
+            assert_eq!(&request.data()[..3], &[0x02, 0x09, 0x02]);
+
+            // The first ECU receives flow control on its physical request ID
+            let mut ecu = IsoTpChannel::new(ecus, IsoTpConfig::new(id(0x7E8), id(0x7E0)));
+            let mut vin = vec![0x49, 0x02, 0x01];
+            vin.extend(b"1G1JC5444R7252367");
+            ecu.send(&vin).await.unwrap();
+
+            let mut driver = ecu.into_inner();
+            let response = to_can_frame(
+                id(0x7E9),
+                None,
+                None,
+                &IsoTpFrame::Single {
+                    data: vec![0x7F, 0x09, 0x11],
+                },
+            )
+            .unwrap();
+            driver.send(response).await;
+
+            // Keep the loopback open until the window has passed
+            time::sleep(Duration::from_millis(100)).await;
+        });
+
+        tester.send(&[0x09, 0x02]).await.unwrap();
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let mut responses = vec![];
+        while let Some(response) = tester.recv_until(deadline).await.unwrap() {
+            responses.push(response);
+        }
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].0, id(0x7E8));
+        assert_eq!(&responses[0].1[3..], b"1G1JC5444R7252367");
+        assert_eq!(responses[1], (id(0x7E9), vec![0x7F, 0x09, 0x11]));
+    }
+}
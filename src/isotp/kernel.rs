@@ -0,0 +1,282 @@
+//
+// kernel.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! ISO-TP through the kernel's CAN_ISOTP sockets
+//!
+//! The kernel segments, reassembles and answers flow control on its own, so transfers do not
+//! wake the process for every frame. Needs the can-isotp module, mainline since Linux 5.10.
+
+use super::{IsoTpConfig, IsoTpError};
+
+use embedded_can::Id;
+use tokio::io::{unix::AsyncFd, Interest};
+
+use std::{
+    ffi::CString,
+    io, mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+const SOL_CAN_ISOTP: libc::c_int = libc::SOL_CAN_BASE + libc::CAN_ISOTP;
+const CAN_ISOTP_OPTS: libc::c_int = 1;
+const CAN_ISOTP_RECV_FC: libc::c_int = 2;
+
+const CAN_ISOTP_EXTEND_ADDR: u32 = 0x002;
+const CAN_ISOTP_TX_PADDING: u32 = 0x004;
+const CAN_ISOTP_RX_EXT_ADDR: u32 = 0x200;
+
+/// Largest message read, the kernel's limit for escaped first frames
+const MAX_MESSAGE: usize = 1 << 16;
+
+/// `struct can_isotp_options`
+#[repr(C)]
+#[derive(Default)]
+struct IsoTpOptions {
+    flags: u32,
+    frame_txtime: u32,
+    ext_address: u8,
+    txpad_content: u8,
+    rxpad_content: u8,
+    rx_ext_address: u8,
+}
+
+/// `struct can_isotp_fc_options`
+#[repr(C)]
+struct FlowControlOptions {
+    bs: u8,
+    stmin: u8,
+    wftmax: u8,
+}
+
+/// A CAN_ISOTP socket bound to a request and response ID
+pub struct KernelSocket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl KernelSocket {
+    /// Whether the kernel can carry a channel as configured
+    ///
+    /// Stress options deviate from the protocol on purpose, which only the userspace
+    /// implementation does. Extended addressing needs an address in both directions.
+    pub fn supports(config: &IsoTpConfig) -> bool {
+        let stress = &config.stress;
+        let deviates = stress.block_size.is_some()
+            || stress.st_min.is_some()
+            || !stress.fc_delay.is_zero()
+            || stress.wrong_sequence.is_some();
+
+        !deviates && config.tx_address.is_some() == config.rx_address.is_some()
+    }
+
+    /// Open a socket on an interface, failing if the can-isotp module is not available
+    pub fn open(interface: &str, config: &IsoTpConfig) -> io::Result<Self> {
+        let name = CString::new(interface)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid interface name"))?;
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = unsafe {
+            libc::socket(
+                libc::PF_CAN,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::CAN_ISOTP,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mut options = IsoTpOptions::default();
+        if let (Some(tx), Some(rx)) = (config.tx_address, config.rx_address) {
+            options.flags |= CAN_ISOTP_EXTEND_ADDR | CAN_ISOTP_RX_EXT_ADDR;
+            options.ext_address = tx;
+            options.rx_ext_address = rx;
+        }
+        if let Some(padding) = config.padding {
+            options.flags |= CAN_ISOTP_TX_PADDING;
+            options.txpad_content = padding;
+        }
+        set_option(&fd, CAN_ISOTP_OPTS, &options)?;
+
+        let flow_control = FlowControlOptions {
+            bs: config.block_size,
+            stmin: config.st_min,
+            wftmax: 0,
+        };
+        set_option(&fd, CAN_ISOTP_RECV_FC, &flow_control)?;
+
+        let mut addr: libc::sockaddr_can = unsafe { mem::zeroed() };
+        addr.can_family = libc::AF_CAN as libc::sa_family_t;
+        addr.can_ifindex = index as libc::c_int;
+        addr.can_addr.tp = libc::__c_anonymous_sockaddr_can_tp {
+            rx_id: raw_id(config.rx_id),
+            tx_id: raw_id(config.tx_id),
+        };
+        let res = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const _ as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if res < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(KernelSocket {
+            fd: AsyncFd::new(fd)?,
+        })
+    }
+
+    /// Send a message, waiting until the kernel finished the transfer
+    pub async fn send(&self, data: &[u8]) -> Result<(), IsoTpError> {
+        self.fd
+            .async_io(Interest::WRITABLE, |fd| {
+                let n = unsafe {
+                    libc::write(
+                        fd.as_raw_fd(),
+                        data.as_ptr() as *const libc::c_void,
+                        data.len(),
+                    )
+                };
+                match n {
+                    n if n < 0 => Err(io::Error::last_os_error()),
+                    _ => Ok(()),
+                }
+            })
+            .await
+            .map_err(socket_error)?;
+
+        // The socket is writable again once the last consecutive frame went out, a flow control
+        // timeout is reported as a pending error
+        loop {
+            let mut guard = self.fd.writable().await.map_err(socket_error)?;
+            let mut poll = libc::pollfd {
+                fd: self.fd.as_raw_fd(),
+                events: libc::POLLOUT,
+                revents: 0,
+            };
+            let done = unsafe { libc::poll(&mut poll, 1, 0) } > 0
+                && poll.revents & (libc::POLLOUT | libc::POLLERR) != 0;
+            if done {
+                break;
+            }
+            guard.clear_ready();
+        }
+        self.pending_error()
+    }
+
+    /// Receive the next complete message
+    pub async fn recv(&self) -> Result<Vec<u8>, IsoTpError> {
+        let mut buf = vec![0u8; MAX_MESSAGE];
+        let n = self
+            .fd
+            .async_io(Interest::READABLE, |fd| {
+                let n = unsafe {
+                    libc::read(
+                        fd.as_raw_fd(),
+                        buf.as_mut_ptr() as *mut libc::c_void,
+                        buf.len(),
+                    )
+                };
+                match n {
+                    n if n < 0 => Err(io::Error::last_os_error()),
+                    n => Ok(n as usize),
+                }
+            })
+            .await
+            .map_err(socket_error)?;
+
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    fn pending_error(&self) -> Result<(), IsoTpError> {
+        let mut error: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let res = unsafe {
+            libc::getsockopt(
+                self.fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut error as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        match (res, error) {
+            (res, _) if res < 0 => Err(socket_error(io::Error::last_os_error())),
+            (_, 0) => Ok(()),
+            (_, error) => Err(socket_error(io::Error::from_raw_os_error(error))),
+        }
+    }
+}
+
+fn set_option<T>(fd: &OwnedFd, name: libc::c_int, value: &T) -> io::Result<()> {
+    let res = unsafe {
+        libc::setsockopt(
+            fd.as_raw_fd(),
+            SOL_CAN_ISOTP,
+            name,
+            value as *const T as *const libc::c_void,
+            mem::size_of::<T>() as libc::socklen_t,
+        )
+    };
+    match res {
+        res if res < 0 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+fn raw_id(id: Id) -> libc::canid_t {
+    match id {
+        Id::Standard(id) => id.as_raw() as libc::canid_t,
+        Id::Extended(id) => id.as_raw() | libc::CAN_EFF_FLAG,
+    }
+}
+
+/// The kernel reports protocol failures, such as missing flow control, as `ECOMM`
+fn socket_error(e: io::Error) -> IsoTpError {
+    match e.raw_os_error() {
+        Some(libc::ECOMM) | Some(libc::ETIMEDOUT) => {
+            IsoTpError::Timeout("the kernel ISO-TP transfer")
+        }
+        _ => IsoTpError::Socket(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+
+    fn config() -> IsoTpConfig {
+        let id = |raw| StandardId::new(raw).unwrap();
+        IsoTpConfig::new(id(0x7E0), id(0x7E8))
+    }
+
+    #[test]
+    fn stress_needs_userspace() {
+        assert!(KernelSocket::supports(&config()));
+
+        let mut stressed = config();
+        stressed.stress.wrong_sequence = Some(1);
+        assert!(!KernelSocket::supports(&stressed));
+
+        let mut one_sided = config();
+        one_sided.tx_address = Some(0x10);
+        assert!(!KernelSocket::supports(&one_sided));
+    }
+
+    #[test]
+    fn missing_interface() {
+        assert!(KernelSocket::open("ican-missing0", &config()).is_err());
+    }
+}
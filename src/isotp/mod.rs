@@ -0,0 +1,215 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! ISO-TP (ISO 15765-2) transport layer
+
+pub mod channel;
+pub mod frame;
+pub mod functional;
+#[cfg(all(target_os = "linux", feature = "socketcan"))]
+pub mod kernel;
+
+pub use channel::IsoTpChannel;
+pub use frame::{FlowStatus, IsoTpFrame};
+pub use functional::FunctionalChannel;
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::time::Duration;
+
+/// Most bytes reserved for a message before its data is received
+const MAX_RESERVED: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum IsoTpError {
+    #[error("Invalid ISO-TP frame: {0}")]
+    InvalidFrame(&'static str),
+    #[error("Message of {0} bytes is too long")]
+    TooLong(usize),
+    #[error("Timed out waiting for {0}")]
+    Timeout(&'static str),
+    #[error("Receiver reported an overflow")]
+    Overflow,
+    #[error("Unexpected sequence number {actual}, expected {expected}")]
+    SequenceNumber { expected: u8, actual: u8 },
+    #[error("Driver closed")]
+    Closed,
+    #[error("ISO-TP socket error: {0}")]
+    Socket(#[from] std::io::Error),
+}
+
+/// Addressing and flow control parameters of an ISO-TP channel
+#[derive(Debug, Clone)]
+pub struct IsoTpConfig {
+    /// ID of transmitted frames, the ID type selects 11 or 29-bit addressing
+    pub tx_id: Id,
+    /// ID of received frames
+    pub rx_id: Id,
+    /// Target address prepended to transmitted frames (extended addressing)
+    pub tx_address: Option<u8>,
+    /// Address expected as the first byte of received frames (extended addressing)
+    pub rx_address: Option<u8>,
+    /// Pad transmitted frames to 8 bytes with this value
+    pub padding: Option<u8>,
+    /// Block size sent in flow control frames, 0 for no limit
+    pub block_size: u8,
+    /// Separation time sent in flow control frames
+    pub st_min: u8,
+    /// Time to wait for flow control or consecutive frames
+    pub timeout: Duration,
+    /// Deliberate protocol deviations used to test the peer
+    pub stress: StressOptions,
+}
+
+/// Deviations from the peer's flow control, used to test the robustness of receivers
+#[derive(Debug, Clone, Default)]
+pub struct StressOptions {
+    /// Block size used when sending, instead of the one requested by the receiver
+    pub block_size: Option<u8>,
+    /// Separation time used when sending, instead of the one requested by the receiver
+    pub st_min: Option<u8>,
+    /// Delay before each flow control frame is sent
+    pub fc_delay: Duration,
+    /// Send the nth consecutive frame (starting at 1) with a wrong sequence number
+    pub wrong_sequence: Option<usize>,
+}
+
+impl IsoTpConfig {
+    pub fn new(tx_id: impl Into<Id>, rx_id: impl Into<Id>) -> Self {
+        IsoTpConfig {
+            tx_id: tx_id.into(),
+            rx_id: rx_id.into(),
+            tx_address: None,
+            rx_address: None,
+            padding: Some(0xCC),
+            block_size: 0,
+            st_min: 0,
+            timeout: Duration::from_millis(1000),
+            stress: StressOptions::default(),
+        }
+    }
+}
+
+/// Reassembles a single ISO-TP message stream
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    length: usize,
+    next_sequence: u8,
+    data: Vec<u8>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a multi-frame message is partially received
+    pub fn in_progress(&self) -> bool {
+        self.length > 0
+    }
+
+    /// Process a frame, returning the message once it is complete
+    ///
+    /// A single or first frame aborts any message in progress. Flow control frames are ignored.
+    pub fn process(&mut self, frame: &IsoTpFrame) -> Result<Option<Vec<u8>>, IsoTpError> {
+        match frame {
+            IsoTpFrame::Single { data } => {
+                self.reset();
+                Ok(Some(data.clone()))
+            }
+            IsoTpFrame::First { length, data } => {
+                self.length = *length;
+                self.next_sequence = 1;
+                // The length is untrusted until the data arrives
+                self.data = Vec::with_capacity((*length).min(MAX_RESERVED));
+                self.data.extend_from_slice(data);
+                Ok(None)
+            }
+            IsoTpFrame::Consecutive { sequence, data } => {
+                if !self.in_progress() {
+                    return Ok(None);
+                }
+
+                if *sequence != self.next_sequence {
+                    let expected = self.next_sequence;
+                    self.reset();
+                    return Err(IsoTpError::SequenceNumber {
+                        expected,
+                        actual: *sequence,
+                    });
+                }
+
+                self.next_sequence = (self.next_sequence + 1) & 0x0F;
+                self.data.extend_from_slice(data);
+
+                if self.data.len() < self.length {
+                    return Ok(None);
+                }
+
+                // The last frame may be padded
+                let mut data = std::mem::take(&mut self.data);
+                data.truncate(self.length);
+                self.reset();
+
+                Ok(Some(data))
+            }
+            IsoTpFrame::FlowControl { .. } => Ok(None),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.length = 0;
+        self.next_sequence = 0;
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reassembly() {
+        let mut rx = Reassembler::new();
+
+        let first = IsoTpFrame::parse(&[0x10, 0x0A, 0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(rx.process(&first).unwrap(), None);
+        assert!(rx.in_progress());
+
+        let cf = IsoTpFrame::parse(&[0x21, 6, 7, 8, 9, 0xCC, 0xCC, 0xCC]).unwrap();
+        assert_eq!(
+            rx.process(&cf).unwrap(),
+            Some(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
+        );
+        assert!(!rx.in_progress());
+    }
+
+    #[test]
+    fn wrong_sequence() {
+        let mut rx = Reassembler::new();
+
+        rx.process(&IsoTpFrame::parse(&[0x10, 0x14, 0, 1, 2, 3, 4, 5]).unwrap())
+            .unwrap();
+
+        let err = rx
+            .process(&IsoTpFrame::parse(&[0x22, 0, 0, 0, 0, 0, 0, 0]).unwrap())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected sequence number 2, expected 1");
+        assert!(!rx.in_progress());
+    }
+
+    #[test]
+    fn escaped_first_frame() {
+        // A first frame of 4 GiB only reserves a little
+        let mut rx = Reassembler::new();
+        rx.process(&IsoTpFrame::parse(&[0x10, 0, 0xFF, 0xFF, 0xFF, 0xFF, 1, 2]).unwrap())
+            .unwrap();
+        assert!(rx.in_progress());
+        assert!(rx.data.capacity() <= MAX_RESERVED);
+    }
+}
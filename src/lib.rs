@@ -4,18 +4,83 @@
 // @author Natesh Narain <nnaraindev@gmail.com>
 // @date Jul 15 2022
 //
+
+//! CAN tools as a library
+//!
+//! The pieces behind the `ican` command line, for applications that embed them:
+//!
+//! - [`drivers`]: the [`AsyncCanDriver`] abstraction and the built-in backends, dispatched
+//!   through [`CanDriver`]
+//! - [`frame`]: the driver independent [`CanFrame`]
+//! - [`logfile`]: reading and writing BLF, TRC, candump, CSV, MF4 and Parquet logs
+//! - [`format`]: formatting frames and their decoded signals, using [`candecode`] databases
+//! - [`isotp`], [`uds`] and [`obd`]: diagnostic transport and services
+//!
+//! The command line itself is behind the default `cli` feature. Without it, clap and the
+//! terminal UI are not built:
+//!
+//! ```toml
+//! ican = { version = "0.1", default-features = false, features = ["socketcan"] }
+//! ```
+//!
+//! The `socketcan` feature builds the SocketCAN driver on Linux; elsewhere opening it fails with
+//! [`drivers::DriverError::Unavailable`]. The `gsusb` feature builds the driver of gs_usb
+//! (candleLight) adapters on every platform, through libusb.
+//!
+//! ```no_run
+//! use ican::{frame::CanFrame, CanDriver, DriverOpts};
+//! use embedded_can::{Frame, StandardId};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut driver = CanDriver::open(DriverOpts::SocketCan("vcan0".into()), 1)?;
+//!
+//! let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2, 3]).unwrap();
+//! driver.send(frame).await;
+//!
+//! while let Some(frame) = driver.recv().await {
+//!     println!("{:?}", frame);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "cli")]
 pub mod action;
+pub mod activity;
+pub mod analysis;
+#[cfg(feature = "cli")]
+pub mod audit;
+pub mod busstate;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod dedup;
 pub mod drivers;
 pub mod format;
 pub mod frame;
+pub mod isotp;
+pub mod logfile;
+#[cfg(feature = "cli")]
+pub mod names;
+pub mod obd;
+pub mod period;
+pub mod ringbuf;
+pub mod supervisor;
+pub mod timesync;
+pub mod uds;
 pub mod utils;
 
-use crate::drivers::AsyncCanDriverPtr;
+pub use candecode;
+pub use drivers::{AsyncCanDriver, CanDriver};
+pub use frame::{CanFrame, Direction};
+
+#[cfg(feature = "cli")]
+pub use cli::{Args, Command, CommandContext};
 
-use regex::Regex;
+use drivers::{gsusb::GsUsbOpts, udp::UdpOpts};
 use std::{fmt, str::FromStr};
 
-use clap::{Parser, Subcommand};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -29,25 +94,32 @@ pub enum IcanParseErrors {
 pub enum DriverOpts {
     /// SocketCAN driver. Options: interface
     SocketCan(String),
-    /// UDP tunneling. Options: IP, port
-    Udp(String, u16),
+    /// UDP tunneling. Options: bind address, peer address
+    Udp(UdpOpts),
+    /// gs_usb (candleLight) adapter. Options: serial number or index, bitrate, channel
+    GsUsb(GsUsbOpts),
 }
 
 impl FromStr for DriverOpts {
     type Err = IcanParseErrors;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"([\w]+):\/\/([\w]+)").unwrap();
-
         // Attempt to match the specified driver
         // This takes the form:
         //   - socketcan://interface
-        //   - udp://127.0.0.1:8000
-        if let Some(caps) = re.captures(s) {
-            let (_, [driver, opts]) = caps.extract();
-
+        //   - udp://0.0.0.0:8000?peer=10.0.0.2:8000
+        //   - gsusb://0?bitrate=250000
+        if let Some((driver, opts)) = s.split_once("://") {
             match driver {
-                "socketcan" => Ok(DriverOpts::SocketCan(opts.to_string())),
+                "socketcan" if !opts.is_empty() => Ok(DriverOpts::SocketCan(opts.to_string())),
+                "udp" => opts
+                    .parse()
+                    .map(DriverOpts::Udp)
+                    .map_err(|_| IcanParseErrors::InvalidDriver),
+                "gsusb" => opts
+                    .parse()
+                    .map(DriverOpts::GsUsb)
+                    .map_err(|_| IcanParseErrors::InvalidDriver),
                 _ => Err(IcanParseErrors::InvalidDriver),
             }
         } else {
@@ -61,47 +133,12 @@ impl fmt::Display for DriverOpts {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
             DriverOpts::SocketCan(interface) => write!(f, "socketcan://{}", interface),
-            DriverOpts::Udp(ip, addr) => write!(f, "udp://{}:{}", ip, addr),
+            DriverOpts::Udp(opts) => write!(f, "udp://{}", opts),
+            DriverOpts::GsUsb(opts) => write!(f, "gsusb://{}", opts),
         }
     }
 }
 
-/// ican provides several common CAN commands
-#[derive(Parser, Debug)]
-#[command(author = "Natesh Narain", version, about = "Modern CAN tools")]
-pub struct Args {
-    /// The CAN interface to use (with driver options if applicable)
-    #[arg(value_enum, value_parser = clap::value_parser!(DriverOpts))]
-    pub interface: DriverOpts,
-    #[command(subcommand)]
-    pub cmd: Command,
-    #[arg(short = 't', long = "tick-rate", default_value = "200")]
-    pub tui_tick_rate: u64,
-}
-
-/// Command to run
-#[derive(Subcommand, Debug)]
-pub enum Command {
-    /// Print CAN frames to console
-    Dump,
-    /// TUI displaying CAN frames and decoded signals
-    Monitor,
-    /// Send CAN frames to the selected interface
-    Send(action::send::Args),
-    /// Bridge different CAN interfaces together
-    Bridge(action::bridge::Args),
-    // /// CANopen subcommands
-    // #[clap(subcommand)]
-    // Canopen(action::canopen::CanOpenCommands),
-}
-
-/// Subcommand context
-pub struct CommandContext {
-    pub driver: AsyncCanDriverPtr,
-    pub interface: String,
-    pub tick_rate: u64,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +154,26 @@ mod tests {
         let opts = DriverOpts::from_str("vcan0").unwrap();
         assert_eq!(opts, DriverOpts::SocketCan("vcan0".to_owned()))
     }
+
+    #[test]
+    fn udp_driver_opt() {
+        let opts = DriverOpts::from_str("udp://127.0.0.1:8000").unwrap();
+        assert_eq!(opts.to_string(), "udp://127.0.0.1:8000");
+        assert!(matches!(opts, DriverOpts::Udp(UdpOpts { peer: None, .. })));
+
+        assert!(DriverOpts::from_str("udp://127.0.0.1").is_err());
+        assert!(DriverOpts::from_str("serial://ttyUSB0").is_err());
+    }
+
+    #[test]
+    fn gsusb_driver_opt() {
+        let opts = DriverOpts::from_str("gsusb://0?bitrate=250000").unwrap();
+        assert_eq!(opts.to_string(), "gsusb://0?bitrate=250000");
+        assert!(matches!(
+            opts,
+            DriverOpts::GsUsb(GsUsbOpts { channel: 0, .. })
+        ));
+
+        assert!(DriverOpts::from_str("gsusb://").is_err());
+    }
 }
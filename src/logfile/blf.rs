@@ -0,0 +1,494 @@
+//
+// blf.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Vector binary logging format (BLF) reader
+
+use super::{dlc_to_len, id_from_raw, Direction, LogError, LogFrame, LogSource};
+
+use flate2::read::ZlibDecoder;
+
+use std::{
+    io::{self, Read},
+    time::{Duration, SystemTime},
+};
+
+const FILE_SIGNATURE: &[u8] = b"LOGG";
+const OBJECT_SIGNATURE: &[u8] = b"LOBJ";
+
+/// Size of the header common to all objects
+const BASE_HEADER_SIZE: usize = 16;
+/// Size of the log container header following the base header
+const CONTAINER_HEADER_SIZE: usize = 16;
+
+// Object types
+const CAN_MESSAGE: u32 = 1;
+const CAN_ERROR: u32 = 2;
+const LOG_CONTAINER: u32 = 10;
+const CAN_ERROR_EXT: u32 = 73;
+const CAN_MESSAGE2: u32 = 86;
+const CAN_FD_MESSAGE: u32 = 100;
+const CAN_FD_MESSAGE_64: u32 = 101;
+
+// Container compression methods
+const NO_COMPRESSION: u16 = 0;
+const ZLIB_DEFLATE: u16 = 2;
+
+// Object timestamp resolution flags
+const TIME_TEN_MICS: u32 = 0x1;
+
+// Message flags
+const DIR_TX: u8 = 0x01;
+const REMOTE: u8 = 0x80;
+const FD_EDL: u8 = 0x01;
+const FD_BRS: u8 = 0x02;
+const FD_ESI: u8 = 0x04;
+const FD64_REMOTE: u32 = 0x0010;
+const FD64_EDL: u32 = 0x1000;
+const FD64_BRS: u32 = 0x2000;
+const FD64_ESI: u32 = 0x4000;
+
+fn invalid(reason: impl Into<String>) -> LogError {
+    LogError::Invalid {
+        format: "BLF",
+        reason: reason.into(),
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Measurement start time, a SYSTEMTIME at offset 40 of the file header
+fn measurement_start(statistics: &[u8]) -> Option<SystemTime> {
+    let field = |i: usize| u16_at(statistics, 32 + 2 * i);
+    let (year, month, day) = (field(0)?, field(1)?, field(3)?);
+    let time = Duration::from_secs(field(4)? as u64 * 3600 + field(5)? as u64 * 60)
+        + Duration::from_secs(field(6)? as u64)
+        + Duration::from_millis(field(7)? as u64);
+
+    super::civil_time(year as i64, month as u32, day as u32, time)
+}
+
+/// Streaming BLF reader
+///
+/// Log containers are decompressed one at a time. Objects other than CAN messages and error
+/// frames are skipped.
+pub struct BlfReader<R> {
+    reader: R,
+    /// Uncompressed object data, objects may span containers
+    buffer: Vec<u8>,
+    pos: usize,
+    start_time: Option<SystemTime>,
+}
+
+impl<R: Read> BlfReader<R> {
+    /// Read the file header
+    pub fn new(mut reader: R) -> Result<Self, LogError> {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+
+        if &header[..4] != FILE_SIGNATURE {
+            return Err(invalid("missing LOGG signature"));
+        }
+
+        let size = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        let mut statistics = vec![0u8; size.saturating_sub(header.len())];
+        reader.read_exact(&mut statistics)?;
+
+        Ok(BlfReader {
+            reader,
+            buffer: vec![],
+            pos: 0,
+            start_time: measurement_start(&statistics),
+        })
+    }
+
+    /// Start of the measurement, from the file header
+    pub fn start_time(&self) -> Option<SystemTime> {
+        self.start_time
+    }
+
+    fn next_frame(&mut self) -> Result<Option<LogFrame>, LogError> {
+        loop {
+            while let Some(object) = self.next_object()? {
+                if let Some(frame) = parse_object(&object)? {
+                    return Ok(Some(frame));
+                }
+            }
+
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Take the next complete object from the buffer
+    fn next_object(&mut self) -> Result<Option<Vec<u8>>, LogError> {
+        let data = &self.buffer[self.pos..];
+
+        // Objects are padded, the padding depends on the object type
+        let Some(start) = data
+            .windows(OBJECT_SIGNATURE.len())
+            .take(4)
+            .position(|w| w == OBJECT_SIGNATURE)
+        else {
+            return match data.len() < 8 {
+                true => Ok(None),
+                false => Err(invalid("missing object signature")),
+            };
+        };
+
+        let data = &data[start..];
+        let Some(size) = u32_at(data, 8) else {
+            return Ok(None);
+        };
+        let size = size as usize;
+
+        if size < BASE_HEADER_SIZE {
+            return Err(invalid("object too short"));
+        }
+        if data.len() < size {
+            return Ok(None);
+        }
+
+        let object = data[..size].to_vec();
+        self.pos += start + size;
+
+        Ok(Some(object))
+    }
+
+    /// Read the next top level object into the buffer, `false` at the end of the file
+    fn fill(&mut self) -> Result<bool, LogError> {
+        let mut header = [0u8; BASE_HEADER_SIZE];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        if &header[..4] != OBJECT_SIGNATURE {
+            return Err(invalid("missing object signature"));
+        }
+
+        let size = u32_at(&header, 8).unwrap() as usize;
+        let object_type = u32_at(&header, 12).unwrap();
+
+        let mut body = vec![0u8; size.saturating_sub(BASE_HEADER_SIZE)];
+        self.reader.read_exact(&mut body)?;
+        // Top level objects are padded to 4 bytes, the last one may not be
+        io::copy(
+            &mut (&mut self.reader).take((size % 4) as u64),
+            &mut io::sink(),
+        )?;
+
+        // Drop consumed data before appending
+        self.buffer.drain(..self.pos);
+        self.pos = 0;
+
+        if object_type != LOG_CONTAINER {
+            self.buffer.extend_from_slice(&header);
+            self.buffer.extend_from_slice(&body);
+            return Ok(true);
+        }
+
+        let method = u16_at(&body, 0).ok_or_else(|| invalid("container too short"))?;
+        let uncompressed = u32_at(&body, 8).unwrap_or_default() as usize;
+        let data = body
+            .get(CONTAINER_HEADER_SIZE..)
+            .ok_or_else(|| invalid("container too short"))?;
+
+        match method {
+            NO_COMPRESSION => self.buffer.extend_from_slice(data),
+            ZLIB_DEFLATE => {
+                self.buffer.reserve(uncompressed);
+                ZlibDecoder::new(data)
+                    .read_to_end(&mut self.buffer)
+                    .map_err(|e| invalid(format!("container decompression failed: {}", e)))?;
+            }
+            other => return Err(invalid(format!("unknown compression method {}", other))),
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for BlfReader<R> {
+    type Item = Result<LogFrame, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl<R: Read> LogSource for BlfReader<R> {
+    fn start_time(&self) -> Option<SystemTime> {
+        self.start_time
+    }
+}
+
+/// Parse a CAN object, `None` for other object types
+fn parse_object(object: &[u8]) -> Result<Option<LogFrame>, LogError> {
+    let truncated = || invalid("truncated object");
+
+    let header_size = u16_at(object, 4).ok_or_else(truncated)? as usize;
+    let object_type = u32_at(object, 12).ok_or_else(truncated)?;
+
+    // Both header versions share the flags and timestamp offsets
+    let flags = u32_at(object, 16).ok_or_else(truncated)?;
+    let time = u64_at(object, 24).ok_or_else(truncated)?;
+    let timestamp = if flags & TIME_TEN_MICS != 0 {
+        let micros = time
+            .checked_mul(10)
+            .ok_or_else(|| invalid("timestamp out of range"))?;
+        Duration::from_micros(micros)
+    } else {
+        Duration::from_nanos(time)
+    };
+
+    let body = object.get(header_size..).ok_or_else(truncated)?;
+
+    let frame = match object_type {
+        CAN_MESSAGE | CAN_MESSAGE2 => {
+            let channel = u16_at(body, 0).ok_or_else(truncated)?;
+            let flags = *body.get(2).ok_or_else(truncated)?;
+            let dlc = *body.get(3).ok_or_else(truncated)?;
+            let id = u32_at(body, 4).ok_or_else(truncated)?;
+            let len = if flags & REMOTE != 0 {
+                0
+            } else {
+                dlc_to_len(dlc).min(8)
+            };
+            let data = body.get(8..8 + len).ok_or_else(truncated)?;
+
+            LogFrame {
+                dlc,
+                remote: flags & REMOTE != 0,
+                direction: Some(direction(flags & DIR_TX != 0)),
+                ..frame(timestamp, channel, id, data)?
+            }
+        }
+        CAN_FD_MESSAGE => {
+            let channel = u16_at(body, 0).ok_or_else(truncated)?;
+            let flags = *body.get(2).ok_or_else(truncated)?;
+            let dlc = *body.get(3).ok_or_else(truncated)?;
+            let id = u32_at(body, 4).ok_or_else(truncated)?;
+            let fd_flags = *body.get(13).ok_or_else(truncated)?;
+            let len = *body.get(14).ok_or_else(truncated)? as usize;
+            let data = body.get(20..20 + len).ok_or_else(truncated)?;
+
+            LogFrame {
+                dlc,
+                remote: flags & REMOTE != 0,
+                fd: fd_flags & FD_EDL != 0,
+                brs: fd_flags & FD_BRS != 0,
+                esi: fd_flags & FD_ESI != 0,
+                direction: Some(direction(flags & DIR_TX != 0)),
+                ..frame(timestamp, channel, id, data)?
+            }
+        }
+        CAN_FD_MESSAGE_64 => {
+            let channel = *body.first().ok_or_else(truncated)?;
+            let dlc = *body.get(1).ok_or_else(truncated)?;
+            let len = *body.get(2).ok_or_else(truncated)? as usize;
+            let id = u32_at(body, 4).ok_or_else(truncated)?;
+            let fd_flags = u32_at(body, 12).ok_or_else(truncated)?;
+            let tx = *body.get(34).ok_or_else(truncated)? != 0;
+            let data = body.get(40..40 + len).ok_or_else(truncated)?;
+
+            LogFrame {
+                dlc,
+                remote: fd_flags & FD64_REMOTE != 0,
+                fd: fd_flags & FD64_EDL != 0,
+                brs: fd_flags & FD64_BRS != 0,
+                esi: fd_flags & FD64_ESI != 0,
+                direction: Some(direction(tx)),
+                ..frame(timestamp, channel as u16, id, data)?
+            }
+        }
+        CAN_ERROR_EXT => {
+            let channel = u16_at(body, 0).ok_or_else(truncated)?;
+            let dlc = *body.get(10).ok_or_else(truncated)?;
+            let id = u32_at(body, 16).ok_or_else(truncated)?;
+            let data = body
+                .get(24..24 + dlc_to_len(dlc).min(8))
+                .unwrap_or_default();
+
+            LogFrame {
+                dlc,
+                error: true,
+                ..frame(timestamp, channel, id, data)?
+            }
+        }
+        CAN_ERROR => {
+            let channel = u16_at(body, 0).ok_or_else(truncated)?;
+
+            LogFrame {
+                error: true,
+                ..frame(timestamp, channel, 0, &[])?
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(frame))
+}
+
+fn frame(timestamp: Duration, channel: u16, id: u32, data: &[u8]) -> Result<LogFrame, LogError> {
+    let id = id_from_raw(id).ok_or_else(|| invalid(format!("invalid CAN ID 0x{:X}", id)))?;
+
+    // BLF channels start at 1
+    Ok(LogFrame {
+        channel: channel
+            .checked_sub(1)
+            .and_then(|channel| u8::try_from(channel).ok()),
+        ..LogFrame::new(timestamp, id, data)
+    })
+}
+
+fn direction(tx: bool) -> Direction {
+    if tx {
+        Direction::Tx
+    } else {
+        Direction::Rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, Id, StandardId};
+    use flate2::{write::ZlibEncoder, Compression};
+
+    use std::{io::Write, time::UNIX_EPOCH};
+
+    fn object(object_type: u32, time: u64, body: &[u8]) -> Vec<u8> {
+        let size = 32 + body.len();
+        let mut data = OBJECT_SIGNATURE.to_vec();
+        data.extend(32u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend((size as u32).to_le_bytes());
+        data.extend(object_type.to_le_bytes());
+        // Timestamps in nanoseconds
+        data.extend(2u32.to_le_bytes());
+        data.extend([0u8; 4]);
+        data.extend(time.to_le_bytes());
+        data.extend(body);
+        data
+    }
+
+    fn container(objects: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(objects).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut body = ZLIB_DEFLATE.to_le_bytes().to_vec();
+        body.extend([0u8; 6]);
+        body.extend((objects.len() as u32).to_le_bytes());
+        body.extend([0u8; 4]);
+        body.extend(compressed);
+
+        let size = BASE_HEADER_SIZE + body.len();
+        let mut data = OBJECT_SIGNATURE.to_vec();
+        data.extend(16u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend((size as u32).to_le_bytes());
+        data.extend(LOG_CONTAINER.to_le_bytes());
+        data.extend(body);
+        data.resize(data.len() + size % 4, 0);
+        data
+    }
+
+    fn file(containers: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = FILE_SIGNATURE.to_vec();
+        data.extend(144u32.to_le_bytes());
+        data.resize(40, 0);
+        // Measurement start, 2026-10-16 12:30:15.250
+        for field in [2026u16, 10, 5, 16, 12, 30, 15, 250] {
+            data.extend(field.to_le_bytes());
+        }
+        data.resize(144, 0);
+        for container in containers {
+            data.extend(container);
+        }
+        data
+    }
+
+    #[test]
+    fn read_messages() {
+        let mut can = 1u16.to_le_bytes().to_vec();
+        can.extend([DIR_TX, 3]);
+        can.extend(0x123u32.to_le_bytes());
+        can.extend([1, 2, 3, 0, 0, 0, 0, 0]);
+
+        let mut fd = 2u16.to_le_bytes().to_vec();
+        fd.extend([0, 9]);
+        fd.extend(0x8000_0456u32.to_le_bytes());
+        fd.extend([0u8; 5]);
+        fd.extend([FD_EDL | FD_BRS, 12]);
+        fd.extend([0u8; 5]);
+        fd.extend(0..64u8);
+
+        let mut objects = object(CAN_MESSAGE, 1_000_000, &can);
+        // An object type that is skipped, padded to 4 bytes
+        objects.extend(object(65, 1_500_000, &[0xAA, 0xBB]));
+        objects.extend([0, 0]);
+        objects.extend(object(CAN_FD_MESSAGE, 2_000_000, &fd));
+
+        // The FD message is split across two containers
+        let split = objects.len() - 20;
+        let data = file(&[container(&objects[..split]), container(&objects[split..])]);
+
+        let mut reader = BlfReader::new(data.as_slice()).unwrap();
+        assert_eq!(
+            reader.start_time(),
+            Some(UNIX_EPOCH + Duration::from_millis(1_792_153_815_250))
+        );
+        let frames = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(frames.len(), 2);
+
+        assert_eq!(frames[0].timestamp, Duration::from_millis(1));
+        assert_eq!(frames[0].channel, Some(0));
+        assert_eq!(frames[0].id, Id::Standard(StandardId::new(0x123).unwrap()));
+        assert_eq!(frames[0].data, vec![1, 2, 3]);
+        assert_eq!(frames[0].direction, Some(Direction::Tx));
+
+        assert_eq!(frames[1].channel, Some(1));
+        assert_eq!(frames[1].id, Id::Extended(ExtendedId::new(0x456).unwrap()));
+        assert_eq!(frames[1].data, (0..12).collect::<Vec<u8>>());
+        assert!(frames[1].fd && frames[1].brs && !frames[1].esi);
+        assert_eq!(frames[1].direction, Some(Direction::Rx));
+    }
+
+    #[test]
+    fn timestamp_out_of_range() {
+        let mut data = object(CAN_MESSAGE, u64::MAX, &[0; 16]);
+        // Timestamps in units of 10 µs
+        data[16..20].copy_from_slice(&TIME_TEN_MICS.to_le_bytes());
+        assert!(parse_object(&data).is_err());
+    }
+
+    #[test]
+    fn invalid_file() {
+        assert!(BlfReader::new(&b"LOGX\x90\x00\x00\x00"[..]).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//
+// candump.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! candump log files (`candump -l`), as read by canplayer
+//!
+//! Each line holds an absolute timestamp, the interface and the frame in the compact
+//! `ID#DATA` notation, e.g. `(1436509052.249713) vcan0 044#2A366C2BBA`.
+
+use super::{
+    dlc_to_len, len_to_dlc, parse_hex, Direction, LogError, LogFrame, LogSource, LogWriter,
+};
+
+use embedded_can::{ExtendedId, Id, StandardId};
+
+use std::{
+    io::{BufRead, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Error frame flag of a SocketCAN ID
+const CAN_ERR_FLAG: u32 = 0x2000_0000;
+
+fn invalid(reason: impl Into<String>) -> LogError {
+    LogError::Invalid {
+        format: "candump",
+        reason: reason.into(),
+    }
+}
+
+/// Parse a frame in `ID#DATA` notation
+///
+/// Supports extended IDs (8 digits), error frames, remote frames (`123#R`, `123#R4`), DLCs above 8
+/// for 8 byte classic frames (`123#1122334455667788_E`) and CAN FD frames (`123##1AABB`, where the
+/// digit after `##` holds the BRS and ESI flags).
+pub fn parse_frame(text: &str) -> Result<LogFrame, LogError> {
+    frame(text).map_err(invalid)
+}
+
+/// Format a frame in `ID#DATA` notation
+pub fn format_frame(frame: &LogFrame) -> String {
+    let id = match frame.id {
+        Id::Standard(id) => format!("{:03X}", id.as_raw()),
+        Id::Extended(id) if frame.error => format!("{:08X}", id.as_raw() | CAN_ERR_FLAG),
+        Id::Extended(id) => format!("{:08X}", id.as_raw()),
+    };
+    let data: String = frame.data.iter().map(|b| format!("{:02X}", b)).collect();
+
+    if frame.fd {
+        let flags = frame.brs as u8 | (frame.esi as u8) << 1;
+        format!("{}##{:X}{}", id, flags, data)
+    } else if frame.remote {
+        match frame.dlc {
+            0 => format!("{}#R", id),
+            dlc => format!("{}#R{:X}", id, dlc),
+        }
+    } else if frame.dlc > 8 && frame.data.len() == 8 {
+        format!("{}#{}_{:X}", id, data, frame.dlc)
+    } else {
+        format!("{}#{}", id, data)
+    }
+}
+
+fn frame(text: &str) -> Result<LogFrame, String> {
+    let (id, body) = text
+        .split_once('#')
+        .ok_or(format!("missing '#' in frame '{}'", text))?;
+
+    let invalid_id = || format!("invalid ID '{}'", id);
+    let raw = u32::from_str_radix(id, 16).map_err(|_| invalid_id())?;
+
+    let mut error = false;
+    let id = match id.len() {
+        3 => u16::try_from(raw)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard),
+        8 => {
+            error = raw & CAN_ERR_FLAG != 0;
+            ExtendedId::new(raw & !CAN_ERR_FLAG).map(Id::Extended)
+        }
+        _ => None,
+    }
+    .ok_or_else(invalid_id)?;
+
+    let mut frame = LogFrame::new(Duration::ZERO, id, &[]);
+    frame.error = error;
+
+    if let Some(body) = body.strip_prefix('#') {
+        let flags = body
+            .get(..1)
+            .and_then(|flags| u8::from_str_radix(flags, 16).ok())
+            .ok_or(format!("missing CAN FD flags in '{}'", text))?;
+
+        frame.data = parse_data(&body[1..])?;
+        if frame.data.len() > 64 || dlc_to_len(len_to_dlc(frame.data.len())) != frame.data.len() {
+            return Err(format!("invalid CAN FD length {}", frame.data.len()));
+        }
+        frame.fd = true;
+        frame.brs = flags & 0x01 != 0;
+        frame.esi = flags & 0x02 != 0;
+        frame.dlc = len_to_dlc(frame.data.len());
+    } else if let Some(dlc) = body.strip_prefix(['R', 'r']) {
+        frame.remote = true;
+        frame.dlc = match dlc {
+            "" => 0,
+            dlc => u8::from_str_radix(dlc, 16)
+                .ok()
+                .filter(|&dlc| dlc <= 8)
+                .ok_or(format!("invalid DLC '{}'", dlc))?,
+        };
+    } else {
+        let (data, dlc) = match body.split_once('_') {
+            Some((data, dlc)) => (data, Some(dlc)),
+            None => (body, None),
+        };
+
+        frame.data = parse_data(data)?;
+        if frame.data.len() > 8 {
+            return Err(format!("too many data bytes in '{}'", text));
+        }
+        frame.dlc = frame.data.len() as u8;
+
+        // DLCs above 8 are only valid with 8 data bytes
+        if let Some(dlc) = dlc {
+            frame.dlc = u8::from_str_radix(dlc, 16)
+                .ok()
+                .filter(|&dlc| (9..=15).contains(&dlc) && frame.data.len() == 8)
+                .ok_or(format!("invalid DLC '{}'", dlc))?;
+        }
+    }
+
+    Ok(frame)
+}
+
+fn parse_data(hex: &str) -> Result<Vec<u8>, String> {
+    // Bytes may be separated by dots, e.g. 01.02.03
+    let hex: String = hex.chars().filter(|&c| c != '.').collect();
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("odd number of digits in data '{}'", hex));
+    }
+
+    parse_hex(&hex).ok_or_else(|| format!("invalid data '{}'", hex))
+}
+
+/// Parse an absolute timestamp in seconds, with up to nanosecond precision
+fn parse_timestamp(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid timestamp '{}'", s);
+
+    let (secs, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let secs = secs.parse::<u64>().map_err(|_| invalid())?;
+    let ns = format!("{:0<9}", fraction)
+        .parse::<u32>()
+        .unwrap_or_default();
+
+    Ok(Duration::new(secs, ns))
+}
+
+/// Streaming candump log reader
+///
+/// Interfaces are numbered as channels in order of first appearance. Timestamps are relative to
+/// the first frame, whose absolute time is available from `start_time`.
+pub struct CandumpReader<R> {
+    reader: R,
+    tolerant: bool,
+    interfaces: Vec<String>,
+    start: Option<Duration>,
+    line_number: usize,
+    skipped: usize,
+}
+
+impl<R: BufRead> CandumpReader<R> {
+    pub fn new(reader: R) -> Self {
+        CandumpReader {
+            reader,
+            tolerant: false,
+            interfaces: vec![],
+            start: None,
+            line_number: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Skip malformed lines instead of failing on them
+    pub fn tolerant(mut self) -> Self {
+        self.tolerant = true;
+        self
+    }
+
+    /// Number of malformed lines skipped in tolerant mode
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Interface names, indexed by channel
+    pub fn interfaces(&self) -> &[String] {
+        &self.interfaces
+    }
+
+    /// Absolute time of the first frame
+    pub fn start_time(&self) -> Option<SystemTime> {
+        self.start.map(|start| UNIX_EPOCH + start)
+    }
+
+    fn next_frame(&mut self) -> Result<Option<LogFrame>, LogError> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            match self.parse_line(line) {
+                Ok(frame) => return Ok(Some(frame)),
+                Err(_) if self.tolerant => self.skipped += 1,
+                Err(reason) => {
+                    return Err(invalid(format!("line {}: {}", self.line_number, reason)))
+                }
+            }
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<LogFrame, String> {
+        let mut tokens = line.split_whitespace();
+        let mut next = |what: &str| tokens.next().ok_or(format!("missing {}", what));
+
+        let timestamp = next("timestamp")?;
+        let timestamp = timestamp
+            .strip_prefix('(')
+            .and_then(|t| t.strip_suffix(')'))
+            .ok_or(format!("invalid timestamp '{}'", timestamp))
+            .and_then(parse_timestamp)?;
+        let interface = next("interface")?;
+        let mut frame = frame(next("frame")?)?;
+
+        // Direction, written by candump -x
+        frame.direction = match tokens.next() {
+            None => None,
+            Some("R") => Some(Direction::Rx),
+            Some("T") => Some(Direction::Tx),
+            Some(other) => return Err(format!("unexpected '{}'", other)),
+        };
+
+        let channel = match self.interfaces.iter().position(|i| i == interface) {
+            Some(channel) => channel,
+            None => {
+                self.interfaces.push(interface.to_string());
+                self.interfaces.len() - 1
+            }
+        };
+        frame.channel = Some(u8::try_from(channel).map_err(|_| "too many interfaces")?);
+
+        let start = *self.start.get_or_insert(timestamp);
+        frame.timestamp = timestamp.saturating_sub(start);
+
+        Ok(frame)
+    }
+}
+
+impl<R: BufRead> Iterator for CandumpReader<R> {
+    type Item = Result<LogFrame, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl<R: BufRead> LogSource for CandumpReader<R> {
+    fn start_time(&self) -> Option<SystemTime> {
+        CandumpReader::start_time(self)
+    }
+}
+
+/// candump log writer
+///
+/// Channels are written as interfaces `can0`, `can1`, ... and timestamps are offset by the start
+/// time.
+pub struct CandumpWriter<W: Write> {
+    writer: W,
+    start: Duration,
+}
+
+impl<W: Write> CandumpWriter<W> {
+    pub fn new(writer: W, start_time: SystemTime) -> Self {
+        CandumpWriter {
+            writer,
+            start: start_time.duration_since(UNIX_EPOCH).unwrap_or_default(),
+        }
+    }
+}
+
+impl<W: Write + Send> LogWriter for CandumpWriter<W> {
+    fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError> {
+        let timestamp = self.start + frame.timestamp;
+        let direction = match frame.direction {
+            Some(Direction::Rx) => " R",
+            Some(Direction::Tx) => " T",
+            None => "",
+        };
+
+        writeln!(
+            self.writer,
+            "({}.{:06}) can{} {}{}",
+            timestamp.as_secs(),
+            timestamp.subsec_micros(),
+            frame.channel.unwrap_or(0),
+            format_frame(frame),
+            direction
+        )?;
+
+        Ok(true)
+    }
+
+    fn finish(&mut self) -> Result<(), LogError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_notation() {
+        for text in [
+            "123#",
+            "123#0102",
+            "18EFC034#0011223344556677",
+            "123#R",
+            "123#R4",
+            "123#1122334455667788_E",
+            "123##1AABBCCDDEEFF001122334455",
+            "20000080#0000000000000000",
+        ] {
+            assert_eq!(format_frame(&parse_frame(text).unwrap()), text);
+        }
+
+        let fd = parse_frame("18DB33F1##30102030405060708090A0B0C").unwrap();
+        assert!(fd.fd && fd.brs && fd.esi);
+        assert_eq!(fd.dlc, 9);
+        assert!(parse_frame("20000080#00").unwrap().error);
+
+        for text in [
+            "123",
+            "1234#00",
+            "123#010",
+            "123#010203040506070809",
+            "123##1010",
+            "123#aé1",
+        ] {
+            assert!(parse_frame(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn read_log() {
+        let log = "(1436509052.249713) vcan0 044#2A366C2BBA\n\
+                   garbage\n\
+                   (1436509052.449847) vcan1 5D0#1B R\n\
+                   \n\
+                   (1436509053.000000) vcan0 123##0AA T\n";
+
+        let mut reader = CandumpReader::new(log.as_bytes());
+        let frame = reader.next().unwrap().unwrap();
+        assert_eq!(frame.timestamp, Duration::ZERO);
+        assert_eq!(frame.channel, Some(0));
+        assert_eq!(frame.data, vec![0x2A, 0x36, 0x6C, 0x2B, 0xBA]);
+        assert!(reader.next().unwrap().is_err());
+
+        let mut reader = CandumpReader::new(log.as_bytes()).tolerant();
+        let frames = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(reader.skipped(), 1);
+
+        // Non-ASCII data is an error, skipped when tolerant
+        let bad = "(1.000000) can0 123#aé1\n";
+        assert!(CandumpReader::new(bad.as_bytes()).next().unwrap().is_err());
+        let mut tolerant = CandumpReader::new(bad.as_bytes()).tolerant();
+        assert!(tolerant.next().is_none());
+        assert_eq!(tolerant.skipped(), 1);
+        assert_eq!(reader.interfaces(), &["vcan0", "vcan1"]);
+        assert_eq!(
+            reader.start_time(),
+            Some(UNIX_EPOCH + Duration::new(1436509052, 249_713_000))
+        );
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].timestamp, Duration::from_micros(200_134));
+        assert_eq!(frames[1].channel, Some(1));
+        assert_eq!(frames[1].direction, Some(Direction::Rx));
+        assert!(frames[2].fd);
+        assert_eq!(frames[2].direction, Some(Direction::Tx));
+
+        let start = UNIX_EPOCH + Duration::new(1436509052, 249_713_000);
+        let mut buf = vec![];
+        let mut writer = CandumpWriter::new(&mut buf, start);
+        for frame in &frames {
+            assert!(writer.write(frame).unwrap());
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "(1436509052.249713) can0 044#2A366C2BBA\n\
+             (1436509052.449847) can1 5D0#1B R\n\
+             (1436509053.000000) can0 123##0AA T\n"
+        );
+    }
+}
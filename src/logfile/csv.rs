@@ -0,0 +1,526 @@
+//
+// csv.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! CSV frame tables, as exported from spreadsheets and third-party loggers
+//!
+//! Columns are found by their header names, or mapped explicitly with [`CsvColumns`]. IDs are hex,
+//! with an optional `0x` prefix or `x` suffix for extended IDs. Data is hex bytes, either in one
+//! column (`01 02 03` or `010203`) or one byte per column.
+
+use super::{len_to_dlc, parse_hex, LogError, LogFrame, LogSource};
+
+use embedded_can::{ExtendedId, Id, StandardId};
+
+use std::{io::BufRead, str::FromStr, time::Duration, time::SystemTime};
+
+fn invalid(reason: impl Into<String>) -> LogError {
+    LogError::Invalid {
+        format: "CSV",
+        reason: reason.into(),
+    }
+}
+
+/// A column, by header name or zero-based index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    Name(String),
+    Index(usize),
+}
+
+impl CsvColumn {
+    fn find(&self, header: Option<&[String]>) -> Result<usize, LogError> {
+        match self {
+            CsvColumn::Index(index) => Ok(*index),
+            CsvColumn::Name(name) => header
+                .and_then(|header| header.iter().position(|h| h.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| invalid(format!("no column named '{}'", name))),
+        }
+    }
+}
+
+impl FromStr for CsvColumn {
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().parse() {
+            Ok(index) => CsvColumn::Index(index),
+            Err(_) => CsvColumn::Name(s.trim().to_string()),
+        })
+    }
+}
+
+/// Mapping of the frame fields to columns
+///
+/// Parsed from `FIELD=COLUMN` pairs, e.g. `timestamp=Time,id=ID,dlc=DLC,data=Payload`. Data spread
+/// over several columns is given as `data=B0+B1+B2`. Fields without a column are found by header
+/// name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvColumns {
+    pub timestamp: Option<CsvColumn>,
+    pub channel: Option<CsvColumn>,
+    pub id: Option<CsvColumn>,
+    pub dlc: Option<CsvColumn>,
+    pub data: Vec<CsvColumn>,
+}
+
+impl FromStr for CsvColumns {
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = CsvColumns::default();
+
+        for pair in s.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (field, column) = pair
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected FIELD=COLUMN, got '{}'", pair)))?;
+
+            match field.trim() {
+                "timestamp" => columns.timestamp = Some(column.parse()?),
+                "channel" => columns.channel = Some(column.parse()?),
+                "id" => columns.id = Some(column.parse()?),
+                "dlc" => columns.dlc = Some(column.parse()?),
+                "data" => {
+                    columns.data = column
+                        .split('+')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?
+                }
+                other => return Err(invalid(format!("unknown field '{}'", other))),
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Unit of the timestamp column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl TimeUnit {
+    fn duration(&self, value: f64) -> Option<Duration> {
+        let seconds = match self {
+            TimeUnit::Seconds => value,
+            TimeUnit::Milliseconds => value / 1e3,
+            TimeUnit::Microseconds => value / 1e6,
+            TimeUnit::Nanoseconds => value / 1e9,
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" => Ok(TimeUnit::Seconds),
+            "ms" => Ok(TimeUnit::Milliseconds),
+            "us" => Ok(TimeUnit::Microseconds),
+            "ns" => Ok(TimeUnit::Nanoseconds),
+            _ => Err(invalid(format!("unknown time unit '{}'", s))),
+        }
+    }
+}
+
+/// Options of CSV files
+#[derive(Debug, Clone, Default)]
+pub struct CsvOptions {
+    pub columns: CsvColumns,
+    pub time_unit: TimeUnit,
+}
+
+/// Header names recognized for each field
+const TIMESTAMP_NAMES: &[&str] = &["timestamp", "time", "time_s", "t"];
+const CHANNEL_NAMES: &[&str] = &["channel", "bus", "interface"];
+const ID_NAMES: &[&str] = &["id", "can id", "can_id", "arbitration_id", "identifier"];
+const DLC_NAMES: &[&str] = &["dlc", "length", "len"];
+const DATA_NAMES: &[&str] = &["data", "payload", "bytes"];
+
+/// Resolved column indexes
+#[derive(Debug)]
+struct Layout {
+    timestamp: usize,
+    channel: Option<usize>,
+    id: usize,
+    dlc: Option<usize>,
+    data: Vec<usize>,
+}
+
+/// Streaming CSV reader
+///
+/// The delimiter (`,`, `;` or tab) is detected from the first line. Timestamps are relative to the
+/// first row.
+pub struct CsvReader<R> {
+    reader: R,
+    options: CsvOptions,
+    tolerant: bool,
+    delimiter: Option<char>,
+    layout: Option<Layout>,
+    start: Option<Duration>,
+    line_number: usize,
+    skipped: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(reader: R, options: CsvOptions) -> Self {
+        CsvReader {
+            reader,
+            options,
+            tolerant: false,
+            delimiter: None,
+            layout: None,
+            start: None,
+            line_number: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Skip malformed rows instead of failing on them
+    pub fn tolerant(mut self) -> Self {
+        self.tolerant = true;
+        self
+    }
+
+    /// Number of malformed rows skipped in tolerant mode
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    fn next_frame(&mut self) -> Result<Option<LogFrame>, LogError> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let delimiter = *self.delimiter.get_or_insert_with(|| detect_delimiter(line));
+            let fields = split_fields(line, delimiter);
+
+            if self.layout.is_none() {
+                // A first row without a hex ID where the ID is expected is a header
+                let layout = self.layout(&fields, true);
+                let is_header = layout
+                    .as_ref()
+                    .map_or(true, |layout| parse_id(field(&fields, layout.id)).is_err());
+                self.layout = Some(match is_header {
+                    true => self.layout(&fields, false)?,
+                    false => layout?,
+                });
+                if is_header {
+                    continue;
+                }
+            }
+
+            match self.parse_row(&fields) {
+                Ok(frame) => return Ok(Some(frame)),
+                Err(_) if self.tolerant => self.skipped += 1,
+                Err(reason) => {
+                    return Err(invalid(format!("line {}: {}", self.line_number, reason)))
+                }
+            }
+        }
+    }
+
+    /// Resolve the columns from the first row, as a header or, without one, by the default order
+    /// timestamp, ID, DLC, data
+    fn layout(&self, first: &[String], headerless: bool) -> Result<Layout, LogError> {
+        let columns = &self.options.columns;
+        let header = (!headerless).then_some(first);
+
+        let find = |column: &Option<CsvColumn>, names: &[&str], index: Option<usize>| match (
+            column, header,
+        ) {
+            (Some(column), _) => column.find(header).map(Some),
+            (None, Some(header)) => Ok(header
+                .iter()
+                .position(|h| names.iter().any(|name| h.eq_ignore_ascii_case(name)))
+                .or(index)),
+            (None, None) => Ok(index),
+        };
+
+        let timestamp = find(&columns.timestamp, TIMESTAMP_NAMES, None)?
+            .or(headerless.then_some(0))
+            .ok_or_else(|| invalid("no timestamp column"))?;
+        let id = find(&columns.id, ID_NAMES, None)?
+            .or(headerless.then_some(1))
+            .ok_or_else(|| invalid("no ID column"))?;
+        let channel = find(&columns.channel, CHANNEL_NAMES, None)?;
+        let dlc = find(&columns.dlc, DLC_NAMES, headerless.then_some(2))?;
+
+        let data = match columns.data.is_empty() {
+            false => columns
+                .data
+                .iter()
+                .map(|column| column.find(header))
+                .collect::<Result<_, _>>()?,
+            true => match find(&None, DATA_NAMES, None)? {
+                Some(data) => vec![data],
+                // Without a data column, the remaining columns after the ID or DLC hold the bytes
+                None => (dlc.unwrap_or(id) + 1..first.len().max(dlc.unwrap_or(id) + 1)).collect(),
+            },
+        };
+
+        Ok(Layout {
+            timestamp,
+            channel,
+            id,
+            dlc,
+            data,
+        })
+    }
+
+    fn parse_row(&mut self, fields: &[String]) -> Result<LogFrame, String> {
+        let Some(layout) = &self.layout else {
+            return Err("missing header".into());
+        };
+
+        let timestamp = field(fields, layout.timestamp);
+        let timestamp = timestamp
+            .parse::<f64>()
+            .ok()
+            .and_then(|t| self.options.time_unit.duration(t))
+            .ok_or(format!("invalid timestamp '{}'", timestamp))?;
+
+        let id = parse_id(field(fields, layout.id))?;
+
+        let mut data = vec![];
+        for &column in &layout.data {
+            data.extend(parse_data(field(fields, column))?);
+        }
+
+        let mut frame = LogFrame::new(Duration::ZERO, id, &[]);
+        let mut raw_dlc = None;
+        if let Some(dlc) = layout.dlc {
+            let dlc = field(fields, dlc);
+            let dlc = dlc
+                .parse::<usize>()
+                .map_err(|_| format!("invalid DLC '{}'", dlc))?;
+            if (9..=15).contains(&dlc) && data.len() == 8 {
+                // Classic frames may send a DLC above 8 for their 8 bytes
+                raw_dlc = Some(dlc as u8);
+            } else {
+                if dlc > data.len() {
+                    return Err(format!("DLC {} with {} data bytes", dlc, data.len()));
+                }
+                // Byte columns past the DLC are left empty or padded
+                data.truncate(dlc);
+            }
+        }
+        if data.len() > 64 {
+            return Err(format!("{} data bytes", data.len()));
+        }
+        frame.fd = data.len() > 8;
+        frame.dlc = raw_dlc.unwrap_or_else(|| len_to_dlc(data.len()));
+        frame.data = data;
+
+        if let Some(channel) = layout.channel {
+            let channel = field(fields, channel);
+            frame.channel = Some(
+                channel
+                    .trim_start_matches(|c: char| !c.is_ascii_digit())
+                    .parse()
+                    .map_err(|_| format!("invalid channel '{}'", channel))?,
+            );
+        }
+
+        let start = *self.start.get_or_insert(timestamp);
+        frame.timestamp = timestamp.saturating_sub(start);
+
+        Ok(frame)
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<LogFrame, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl<R: BufRead> LogSource for CsvReader<R> {
+    fn start_time(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+fn field(fields: &[String], index: usize) -> &str {
+    fields.get(index).map(|f| f.trim()).unwrap_or_default()
+}
+
+fn detect_delimiter(line: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&d| line.matches(d).count())
+        .unwrap_or(',')
+}
+
+/// Split a row, honoring double quoted fields
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Parse a hex ID, extended if it has an `x` suffix or does not fit 11 bits
+fn parse_id(s: &str) -> Result<Id, String> {
+    let invalid = || format!("invalid ID '{}'", s);
+
+    let (digits, extended) = match s.strip_suffix(['x', 'X']) {
+        Some(digits) => (digits, true),
+        None => (s, false),
+    };
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+    let raw = u32::from_str_radix(digits, 16).map_err(|_| invalid())?;
+
+    match extended || raw > 0x7FF {
+        true => ExtendedId::new(raw).map(Id::Extended),
+        false => StandardId::new(raw as u16).map(Id::Standard),
+    }
+    .ok_or_else(invalid)
+}
+
+/// Parse hex bytes, separated by spaces or contiguous
+fn parse_data(s: &str) -> Result<Vec<u8>, String> {
+    let mut digits: String = s.split_whitespace().collect();
+    // A lone digit in a byte column
+    if digits.len() == 1 {
+        digits.insert(0, '0');
+    }
+    parse_hex(&digits).ok_or_else(|| format!("invalid data '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str, options: CsvOptions) -> Result<Vec<LogFrame>, LogError> {
+        CsvReader::new(text.as_bytes(), options).collect()
+    }
+
+    #[test]
+    fn header_names() {
+        let frames = read(
+            "Time,ID,DLC,Data\n\
+             10.5,123,3,01 02 03\n\
+             10.75,18EFC034x,2,\"AABB\"\n",
+            CsvOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].timestamp, Duration::ZERO);
+        assert_eq!(frames[0].id, Id::Standard(StandardId::new(0x123).unwrap()));
+        assert_eq!(frames[0].data, vec![1, 2, 3]);
+        assert_eq!(frames[1].timestamp, Duration::from_millis(250));
+        assert_eq!(
+            frames[1].id,
+            Id::Extended(ExtendedId::new(0x18EFC034).unwrap())
+        );
+        assert_eq!(frames[1].data, vec![0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn column_mapping() {
+        let options = CsvOptions {
+            columns: "timestamp=Stamp,id=Frame,dlc=Len,data=B0+B1+B2"
+                .parse()
+                .unwrap(),
+            time_unit: TimeUnit::Milliseconds,
+        };
+        let frames = read(
+            "Stamp;Frame;Len;B0;B1;B2\n\
+             1000;0x7E8;2;10;2;\n\
+             bad;0x7E8;2;10;2;\n\
+             1500;0x7E8;3;A;B;C\n",
+            options.clone(),
+        );
+        assert!(frames.is_err());
+
+        let mut reader = CsvReader::new(
+            "Stamp;Frame;Len;B0;B1;B2\n\
+             1000;0x7E8;2;10;2;\n\
+             bad;0x7E8;2;10;2;\n\
+             1500;0x7E8;3;A;B;C\n"
+                .as_bytes(),
+            options,
+        )
+        .tolerant();
+        let frames = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(reader.skipped(), 1);
+
+        assert_eq!(frames[0].data, vec![0x10, 0x02]);
+        assert_eq!(frames[1].timestamp, Duration::from_millis(500));
+        assert_eq!(frames[1].data, vec![0x0A, 0x0B, 0x0C]);
+
+        assert!("speed=3".parse::<CsvColumns>().is_err());
+    }
+
+    #[test]
+    fn headerless() {
+        let frames = read("0.0,100,2,01,02\n0.1,200,1,FF,\n", CsvOptions::default()).unwrap();
+        assert_eq!(frames[0].data, vec![1, 2]);
+        assert_eq!(frames[1].id, Id::Standard(StandardId::new(0x200).unwrap()));
+        assert_eq!(frames[1].data, vec![0xFF]);
+
+        // Non-ASCII data is an error, skipped when tolerant
+        assert!(read("0.0,123,aé1\n", CsvOptions::default()).is_err());
+        let mut reader =
+            CsvReader::new("0.0,123,aé1\n".as_bytes(), CsvOptions::default()).tolerant();
+        assert!(reader.next().is_none());
+        assert_eq!(reader.skipped(), 1);
+    }
+
+    #[test]
+    fn classic_dlc_above_8() {
+        let frames = read(
+            "Time,ID,DLC,Data
+0.0,123,15,0102030405060708
+",
+            CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(frames[0].dlc, 15);
+        assert_eq!(frames[0].data.len(), 8);
+        assert!(!frames[0].fd);
+        assert_eq!(frames[0].to_frame().unwrap().raw_dlc(), 15);
+    }
+}
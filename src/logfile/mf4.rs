@@ -0,0 +1,617 @@
+//
+// mf4.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! ASAM MDF 4.10 measurement file writer
+//!
+//! Raw frames are stored as `CAN_DataFrame`, `CAN_RemoteFrame` and `CAN_ErrorFrame` channel groups
+//! following the ASAM MDF bus logging conventions, so tools like asammdf and CANape can decode them
+//! with their own databases. When a signal database is given, every message seen also gets a
+//! channel group of its decoded signals.
+//!
+//! The file is built in memory and written out by `finish`.
+
+use super::{LogError, LogFrame, LogWriter};
+
+use candecode::{Database, Message};
+use embedded_can::Id;
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Size of a raw frame record: timestamp, bus channel, ID, DLC, length, flags and 64 data bytes
+const FRAME_RECORD_SIZE: usize = 80;
+
+// Channel types
+const CN_FIXED: u8 = 0;
+const CN_MASTER: u8 = 2;
+// Channel data types
+const DT_UINT: u8 = 0;
+const DT_FLOAT: u8 = 4;
+const DT_BYTES: u8 = 10;
+// Channel flags
+const CN_INVALIDATION: u32 = 1 << 1;
+const CN_VALUE_RANGE: u32 = 1 << 3;
+const CN_BUS_EVENT: u32 = 1 << 10;
+// Channel group flags
+const CG_BUS_EVENT: u16 = 1 << 1;
+const CG_PLAIN_BUS_EVENT: u16 = 1 << 2;
+
+/// Raw frame records of one bus logging group
+struct FrameGroup {
+    name: &'static str,
+    records: Vec<u8>,
+    count: u64,
+}
+
+impl FrameGroup {
+    fn new(name: &'static str) -> Self {
+        FrameGroup {
+            name,
+            records: vec![],
+            count: 0,
+        }
+    }
+}
+
+/// Decoded signals of one message
+struct MessageGroup {
+    message: Message,
+    records: Vec<u8>,
+    count: u64,
+}
+
+impl MessageGroup {
+    /// Data bytes of a record, the timestamp followed by a 64-bit float per signal
+    fn data_bytes(&self) -> usize {
+        8 + 8 * self.message.signals.len()
+    }
+
+    /// Invalidation bytes of a record, one bit per signal
+    fn invalidation_bytes(&self) -> usize {
+        self.message.signals.len().div_ceil(8)
+    }
+}
+
+/// Writes frames, and optionally their decoded signals, to an MF4 file
+pub struct Mf4Writer<W: Write> {
+    writer: W,
+    database: Option<Database>,
+    start_time: SystemTime,
+    /// Data, remote and error frames
+    frames: [FrameGroup; 3],
+    messages: Vec<MessageGroup>,
+    index: HashMap<Id, usize>,
+}
+
+impl<W: Write> Mf4Writer<W> {
+    /// Create a writer, `start_time` is the absolute time of timestamp zero
+    pub fn new(writer: W, database: Option<Database>, start_time: SystemTime) -> Self {
+        Mf4Writer {
+            writer,
+            database,
+            start_time,
+            frames: [
+                FrameGroup::new("CAN_DataFrame"),
+                FrameGroup::new("CAN_RemoteFrame"),
+                FrameGroup::new("CAN_ErrorFrame"),
+            ],
+            messages: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    fn write_frame(&mut self, time: f64, frame: &LogFrame) {
+        let (raw_id, extended) = match frame.id {
+            Id::Standard(id) => (id.as_raw() as u32, false),
+            Id::Extended(id) => (id.as_raw(), true),
+        };
+        let flags = (frame.direction == Some(super::Direction::Tx)) as u8
+            | (frame.fd as u8) << 1
+            | (frame.brs as u8) << 2
+            | (frame.esi as u8) << 3;
+
+        let mut data = [0u8; 64];
+        let len = frame.data.len().min(64);
+        data[..len].copy_from_slice(&frame.data[..len]);
+
+        let group = match frame {
+            f if f.error => &mut self.frames[2],
+            f if f.remote => &mut self.frames[1],
+            _ => &mut self.frames[0],
+        };
+
+        group.records.extend(time.to_le_bytes());
+        // Bus channels are one-based, zero when the log did not record one
+        group
+            .records
+            .push(frame.channel.map(|c| c.saturating_add(1)).unwrap_or(0));
+        group
+            .records
+            .extend((raw_id | (extended as u32) << 31).to_le_bytes());
+        group.records.push(frame.dlc & 0x0F);
+        group.records.push(len as u8);
+        group.records.push(flags);
+        group.records.extend(data);
+        group.count += 1;
+    }
+
+    fn write_signals(&mut self, time: f64, frame: &LogFrame) {
+        if frame.remote || frame.error {
+            return;
+        }
+        let Some(message) = self.database.as_ref().and_then(|db| db.message(frame.id)) else {
+            return;
+        };
+
+        let index = *self.index.entry(frame.id).or_insert_with(|| {
+            self.messages.push(MessageGroup {
+                message: message.clone(),
+                records: vec![],
+                count: 0,
+            });
+            self.messages.len() - 1
+        });
+        let group = &mut self.messages[index];
+        let values = group.message.decode(&frame.data);
+
+        let mut invalid = vec![0u8; group.invalidation_bytes()];
+        group.records.extend(time.to_le_bytes());
+        for (i, signal) in group.message.signals.iter().enumerate() {
+            // Signals left out of the frame, such as inactive multiplexed signals, are invalid
+            let value = values.iter().find(|v| v.signal.name == signal.name);
+            if value.is_none() {
+                invalid[i / 8] |= 1 << (i % 8);
+            }
+            group
+                .records
+                .extend(value.map(|v| v.value).unwrap_or(0.0).to_le_bytes());
+        }
+        group.records.extend(invalid);
+        group.count += 1;
+    }
+
+    /// Build the complete file
+    fn build(&self) -> Vec<u8> {
+        let mut file = Builder::default();
+        file.header();
+
+        // The header block follows the identification block, its links are filled in last
+        let hd = file.block("HD", &[0; 6], &[0; 32]);
+
+        let mut dg_next = 0;
+        for group in self.messages.iter().rev() {
+            dg_next = file.message_group(group, dg_next);
+        }
+        // The data frame group is always written, the others only when frames were logged to them
+        for (i, group) in self.frames.iter().enumerate().rev() {
+            if i == 0 || group.count > 0 {
+                dg_next = file.frame_group(group, dg_next);
+            }
+        }
+
+        let fh = file.history();
+
+        let start = self
+            .start_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        file.link(hd, 0, dg_next);
+        file.link(hd, 1, fh);
+        file.data(hd, 6, &start.to_le_bytes());
+
+        file.buf
+    }
+}
+
+impl<W: Write + Send> LogWriter for Mf4Writer<W> {
+    fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError> {
+        let time = frame.timestamp.as_secs_f64();
+        self.write_frame(time, frame);
+        self.write_signals(time, frame);
+
+        Ok(true)
+    }
+
+    fn finish(&mut self) -> Result<(), LogError> {
+        let file = self.build();
+        self.writer.write_all(&file)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A channel within a record
+struct Channel<'a> {
+    name: &'a str,
+    channel_type: u8,
+    data_type: u8,
+    byte_offset: u32,
+    bit_offset: u8,
+    bit_count: u32,
+    flags: u32,
+    invalidation_bit: u32,
+    unit: Option<&'a str>,
+    comment: Option<&'a str>,
+    range: Option<(f64, f64)>,
+    /// First channel of a structure composed by this channel
+    composition: u64,
+}
+
+impl<'a> Channel<'a> {
+    fn new(name: &'a str, data_type: u8, byte_offset: u32, bit_count: u32) -> Self {
+        Channel {
+            name,
+            channel_type: CN_FIXED,
+            data_type,
+            byte_offset,
+            bit_offset: 0,
+            bit_count,
+            flags: 0,
+            invalidation_bit: 0,
+            unit: None,
+            comment: None,
+            range: None,
+            composition: 0,
+        }
+    }
+
+    /// Time master channel at the start of every record
+    fn time() -> Self {
+        Channel {
+            channel_type: CN_MASTER,
+            unit: Some("s"),
+            ..Channel::new("Timestamp", DT_FLOAT, 0, 64)
+        }
+    }
+
+    fn bits(name: &'a str, byte_offset: u32, bit_offset: u8, bit_count: u32) -> Self {
+        Channel {
+            bit_offset,
+            ..Channel::new(name, DT_UINT, byte_offset, bit_count)
+        }
+    }
+}
+
+/// Lays out blocks in a file buffer
+#[derive(Default)]
+struct Builder {
+    buf: Vec<u8>,
+}
+
+impl Builder {
+    /// Identification block
+    fn header(&mut self) {
+        self.buf.extend(b"MDF     4.10    ican    ");
+        self.buf.extend([0; 4]);
+        self.buf.extend(410u16.to_le_bytes());
+        self.buf.extend([0; 34]);
+    }
+
+    /// Append a block, returning its offset
+    fn block(&mut self, id: &str, links: &[u64], data: &[u8]) -> u64 {
+        while !self.buf.len().is_multiple_of(8) {
+            self.buf.push(0);
+        }
+
+        let offset = self.buf.len() as u64;
+        let length = 24 + 8 * links.len() + data.len();
+
+        self.buf.extend(b"##");
+        self.buf.extend(id.as_bytes());
+        self.buf.extend([0; 4]);
+        self.buf.extend((length as u64).to_le_bytes());
+        self.buf.extend((links.len() as u64).to_le_bytes());
+        for link in links {
+            self.buf.extend(link.to_le_bytes());
+        }
+        self.buf.extend(data);
+
+        offset
+    }
+
+    /// Set a link of a block written earlier
+    fn link(&mut self, block: u64, index: usize, target: u64) {
+        let at = block as usize + 24 + 8 * index;
+        self.buf[at..at + 8].copy_from_slice(&target.to_le_bytes());
+    }
+
+    /// Overwrite the data section of a block written earlier, after `links` links
+    fn data(&mut self, block: u64, links: usize, data: &[u8]) {
+        let at = block as usize + 24 + 8 * links;
+        self.buf[at..at + data.len()].copy_from_slice(data);
+    }
+
+    /// Zero terminated text block (TX) or XML metadata block (MD)
+    fn text(&mut self, id: &str, text: &str) -> u64 {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        data.resize(data.len().next_multiple_of(8), 0);
+        self.block(id, &[], &data)
+    }
+
+    fn optional_text(&mut self, text: Option<&str>) -> u64 {
+        match text {
+            Some(text) if !text.is_empty() => self.text("TX", text),
+            _ => 0,
+        }
+    }
+
+    /// File history block, recording the tool that created the file
+    fn history(&mut self) -> u64 {
+        let comment = self.text(
+            "MD",
+            &format!(
+                "<FHcomment><TX>Created by ican</TX><tool_id>ican</tool_id>\
+                 <tool_vendor>ican</tool_vendor><tool_version>{}</tool_version></FHcomment>",
+                env!("CARGO_PKG_VERSION")
+            ),
+        );
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        let mut data = now.to_le_bytes().to_vec();
+        data.extend([0; 8]);
+
+        self.block("FH", &[0, comment], &data)
+    }
+
+    fn channel(&mut self, channel: &Channel, next: u64) -> u64 {
+        let name = self.text("TX", channel.name);
+        let unit = self.optional_text(channel.unit);
+        let comment = self.optional_text(channel.comment);
+
+        let (min, max) = channel.range.unwrap_or_default();
+        let flags = channel.flags | channel.range.map_or(0, |_| CN_VALUE_RANGE);
+
+        let mut data = vec![
+            channel.channel_type,
+            // Time synchronization for the master channel
+            (channel.channel_type == CN_MASTER) as u8,
+            channel.data_type,
+            channel.bit_offset,
+        ];
+        data.extend(channel.byte_offset.to_le_bytes());
+        data.extend(channel.bit_count.to_le_bytes());
+        data.extend(flags.to_le_bytes());
+        data.extend(channel.invalidation_bit.to_le_bytes());
+        data.extend([0; 4]);
+        for value in [min, max, 0.0, 0.0, 0.0, 0.0] {
+            data.extend(value.to_le_bytes());
+        }
+
+        self.block(
+            "CN",
+            &[next, channel.composition, name, 0, 0, 0, unit, comment],
+            &data,
+        )
+    }
+
+    /// Write channels as a linked list, returning the first
+    fn channels(&mut self, channels: &[Channel]) -> u64 {
+        channels
+            .iter()
+            .rev()
+            .fold(0, |next, channel| self.channel(channel, next))
+    }
+
+    /// Data group holding a single channel group
+    #[allow(clippy::too_many_arguments)]
+    fn data_group(
+        &mut self,
+        name: &str,
+        comment: Option<&str>,
+        first_channel: u64,
+        source: u64,
+        flags: u16,
+        records: &[u8],
+        count: u64,
+        data_bytes: usize,
+        invalidation_bytes: usize,
+        next: u64,
+    ) -> u64 {
+        let name = self.text("TX", name);
+        let comment = self.optional_text(comment);
+
+        let mut data = 0u64.to_le_bytes().to_vec();
+        data.extend(count.to_le_bytes());
+        data.extend(flags.to_le_bytes());
+        data.extend((b'.' as u16).to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((data_bytes as u32).to_le_bytes());
+        data.extend((invalidation_bytes as u32).to_le_bytes());
+        let cg = self.block("CG", &[0, first_channel, name, source, 0, comment], &data);
+
+        let dt = self.block("DT", &[], records);
+
+        self.block("DG", &[next, cg, dt, 0], &[0; 8])
+    }
+
+    /// Raw frames, following the bus logging layout of `CAN_DataFrame` and the like
+    fn frame_group(&mut self, group: &FrameGroup, next: u64) -> u64 {
+        let name = |field: &str| format!("{}.{}", group.name, field);
+        let names = [
+            "BusChannel",
+            "ID",
+            "IDE",
+            "DLC",
+            "DataLength",
+            "Dir",
+            "EDL",
+            "BRS",
+            "ESI",
+            "DataBytes",
+        ]
+        .map(name);
+        let mut fields = vec![
+            Channel::new(&names[0], DT_UINT, 8, 8),
+            Channel::bits(&names[1], 9, 0, 29),
+            Channel::bits(&names[2], 12, 7, 1),
+            Channel::bits(&names[3], 13, 0, 4),
+            Channel::new(&names[4], DT_UINT, 14, 8),
+            Channel::bits(&names[5], 15, 0, 1),
+            Channel::bits(&names[6], 15, 1, 1),
+            Channel::bits(&names[7], 15, 2, 1),
+            Channel::bits(&names[8], 15, 3, 1),
+            Channel::new(&names[9], DT_BYTES, 16, 64 * 8),
+        ];
+        // Remote frames carry no data
+        if group.name == "CAN_RemoteFrame" {
+            fields.pop();
+        }
+        let fields = self.channels(&fields);
+
+        let frame = Channel {
+            flags: CN_BUS_EVENT,
+            composition: fields,
+            ..Channel::new(group.name, DT_BYTES, 8, (FRAME_RECORD_SIZE as u32 - 8) * 8)
+        };
+        let first = self.channels(&[Channel::time(), frame]);
+
+        // Source information: bus source on a CAN bus
+        let source_name = self.text("TX", "CAN");
+        let source = self.block("SI", &[source_name, 0, 0], &[2, 2, 0, 0, 0, 0, 0, 0]);
+
+        self.data_group(
+            group.name,
+            None,
+            first,
+            source,
+            CG_BUS_EVENT | CG_PLAIN_BUS_EVENT,
+            &group.records,
+            group.count,
+            FRAME_RECORD_SIZE,
+            0,
+            next,
+        )
+    }
+
+    /// Decoded signals of a message, one float channel per signal
+    fn message_group(&mut self, group: &MessageGroup, next: u64) -> u64 {
+        let mut channels = vec![Channel::time()];
+        for (i, signal) in group.message.signals.iter().enumerate() {
+            channels.push(Channel {
+                flags: CN_INVALIDATION,
+                invalidation_bit: i as u32,
+                unit: Some(&signal.unit),
+                comment: signal.comment.as_deref(),
+                range: (signal.min < signal.max).then_some((signal.min, signal.max)),
+                ..Channel::new(&signal.name, DT_FLOAT, 8 + 8 * i as u32, 64)
+            });
+        }
+        let first = self.channels(&channels);
+
+        self.data_group(
+            &group.message.name,
+            group.message.comment.as_deref(),
+            first,
+            0,
+            0,
+            &group.records,
+            group.count,
+            group.data_bytes(),
+            group.invalidation_bytes(),
+            next,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::StandardId;
+    use std::time::Duration;
+
+    fn u64_at(file: &[u8], at: u64) -> u64 {
+        let at = at as usize;
+        u64::from_le_bytes(file[at..at + 8].try_into().unwrap())
+    }
+
+    fn text_at(file: &[u8], block: u64) -> String {
+        let start = block as usize + 24;
+        let end = file[start..].iter().position(|&b| b == 0).unwrap();
+        String::from_utf8(file[start..start + end].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn write_groups() {
+        let db = Database::from_dbc(
+            "VERSION \"\"\n\
+             BU_: ECU\n\
+             BO_ 256 Engine: 8 ECU\n \
+             SG_ Speed : 0|16@1+ (0.5,0) [0|8000] \"rpm\" Vector__XXX\n \
+             SG_ Temp : 16|8@1- (1,-40) [-40|215] \"degC\" Vector__XXX\n",
+        )
+        .unwrap();
+
+        let mut buf = vec![];
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut writer = Mf4Writer::new(&mut buf, Some(db), start);
+
+        let engine = Id::Standard(StandardId::new(0x100).unwrap());
+        let other = Id::Standard(StandardId::new(0x200).unwrap());
+        let mut remote = LogFrame::new(Duration::ZERO, other, &[]);
+        remote.remote = true;
+
+        assert!(writer
+            .write(&LogFrame::new(
+                Duration::from_millis(10),
+                engine,
+                &[0x10, 0x27, 60]
+            ))
+            .unwrap());
+        assert!(writer
+            .write(&LogFrame::new(Duration::from_millis(20), other, &[1]))
+            .unwrap());
+        assert!(writer.write(&remote).unwrap());
+        writer.finish().unwrap();
+
+        assert_eq!(&buf[0..8], b"MDF     ");
+        assert_eq!(&buf[64..68], b"##HD");
+        assert_eq!(u64_at(&buf, 64 + 24 + 48), 1_700_000_000_000_000_000);
+
+        // Raw frames are in the first data group
+        let dg = u64_at(&buf, 64 + 24);
+        let cg = u64_at(&buf, dg + 24 + 8);
+        assert_eq!(text_at(&buf, u64_at(&buf, cg + 24 + 16)), "CAN_DataFrame");
+        assert_eq!(u64_at(&buf, cg + 24 + 48 + 8), 2);
+
+        let dt = u64_at(&buf, dg + 24 + 16);
+        let records = &buf[dt as usize + 24..];
+        assert_eq!(f64::from_le_bytes(records[..8].try_into().unwrap()), 0.01);
+        assert_eq!(&records[9..13], &[0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(&records[14..19], &[3, 0, 0x10, 0x27, 60]);
+
+        // Followed by the remote frames
+        let dg = u64_at(&buf, dg + 24);
+        let cg = u64_at(&buf, dg + 24 + 8);
+        assert_eq!(text_at(&buf, u64_at(&buf, cg + 24 + 16)), "CAN_RemoteFrame");
+        assert_eq!(u64_at(&buf, cg + 24 + 48 + 8), 1);
+
+        // And the decoded signals of the one message in the database
+        let dg = u64_at(&buf, dg + 24);
+        let cg = u64_at(&buf, dg + 24 + 8);
+        assert_eq!(text_at(&buf, u64_at(&buf, cg + 24 + 16)), "Engine");
+        assert_eq!(u64_at(&buf, cg + 24 + 48 + 8), 1);
+        assert_eq!(u64_at(&buf, dg + 24), 0);
+
+        let dt = u64_at(&buf, dg + 24 + 16);
+        let record = &buf[dt as usize + 24..dt as usize + 24 + 25];
+        assert_eq!(
+            f64::from_le_bytes(record[8..16].try_into().unwrap()),
+            5000.0
+        );
+        assert_eq!(f64::from_le_bytes(record[16..24].try_into().unwrap()), 20.0);
+        assert_eq!(record[24], 0);
+    }
+}
@@ -0,0 +1,608 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! CAN log file formats
+
+pub mod blf;
+pub mod candump;
+pub mod csv;
+pub mod mf4;
+pub mod parquet;
+pub mod trc;
+
+use crate::frame::CanFrame;
+pub use crate::frame::Direction;
+
+use candecode::Database;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use thiserror::Error;
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read},
+    path::Path,
+    str::FromStr,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// How often a followed file is checked for new data
+const FOLLOW_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Error)]
+pub enum LogError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid {format} file: {reason}")]
+    Invalid {
+        format: &'static str,
+        reason: String,
+    },
+    #[error("Unsupported log format '{0}'")]
+    UnsupportedFormat(String),
+    #[error("Writing {0} files is not supported")]
+    ReadOnly(&'static str),
+    #[error("Reading {0} files is not supported")]
+    WriteOnly(&'static str),
+    #[error("{0}")]
+    Parquet(#[from] ::parquet::errors::ParquetError),
+}
+
+/// A frame read from a log file, with the metadata the format records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFrame {
+    /// Time since the start of the capture
+    pub timestamp: Duration,
+    /// Zero-based channel, if recorded
+    pub channel: Option<u8>,
+    pub id: Id,
+    /// Raw DLC code
+    pub dlc: u8,
+    pub data: Vec<u8>,
+    pub remote: bool,
+    pub error: bool,
+    /// CAN FD frame
+    pub fd: bool,
+    /// CAN FD bit rate switch
+    pub brs: bool,
+    /// CAN FD error state indicator
+    pub esi: bool,
+    pub direction: Option<Direction>,
+}
+
+impl LogFrame {
+    /// Create a received data frame
+    pub fn new(timestamp: Duration, id: Id, data: &[u8]) -> Self {
+        LogFrame {
+            timestamp,
+            channel: None,
+            id,
+            dlc: len_to_dlc(data.len()),
+            data: data.to_vec(),
+            remote: false,
+            error: false,
+            fd: false,
+            brs: false,
+            esi: false,
+            direction: None,
+        }
+    }
+
+    pub fn from_frame(timestamp: Duration, frame: &CanFrame) -> Self {
+        let mut log = LogFrame::new(timestamp, frame.id(), frame.data());
+        log.remote = frame.is_remote_frame();
+        log.dlc = frame.raw_dlc();
+        log.error = frame.is_error_frame();
+        // Drivers only mark the frames they sent themselves
+        log.direction = frame.is_tx().then_some(Direction::Tx);
+        log
+    }
+
+    /// Kind of frame, as named in reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            f if f.error => "error",
+            f if f.remote => "remote",
+            f if f.fd => "CAN FD",
+            _ => "data",
+        }
+    }
+
+    /// Convert to a classic CAN frame, `None` for CAN FD and error frames
+    pub fn to_frame(&self) -> Option<CanFrame> {
+        if self.fd || self.error {
+            return None;
+        }
+
+        let frame = if self.remote {
+            CanFrame::new_remote(self.id, self.dlc.min(8) as usize)
+        } else {
+            CanFrame::new(self.id, &self.data)
+        }?;
+
+        // Classic frames logged with a DLC above 8 keep it, if they have the 8 bytes it implies
+        match self.dlc > 8 {
+            true => frame.clone().with_raw_dlc(self.dlc).or(Some(frame)),
+            false => Some(frame),
+        }
+    }
+}
+
+/// Number of data bytes of a DLC code, following CAN FD for codes above 8
+pub fn dlc_to_len(dlc: u8) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        9 => 12,
+        10 => 16,
+        11 => 20,
+        12 => 24,
+        13 => 32,
+        14 => 48,
+        _ => 64,
+    }
+}
+
+/// Smallest DLC code covering a number of data bytes
+pub fn len_to_dlc(len: usize) -> u8 {
+    (0..=15).find(|&dlc| dlc_to_len(dlc) >= len).unwrap_or(15)
+}
+
+/// Parse contiguous hex digits, two per byte. `None` if a digit is missing or not hex.
+pub(crate) fn parse_hex(digits: &str) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    // Only ASCII digits remain, so every pair is a valid string
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Build an ID from a raw value with the extended flag in bit 31, as used by most log formats
+pub(crate) fn id_from_raw(raw: u32) -> Option<Id> {
+    if raw & 0x8000_0000 != 0 {
+        ExtendedId::new(raw & 0x1FFF_FFFF).map(Id::Extended)
+    } else {
+        StandardId::new((raw & 0x7FF) as u16).map(Id::Standard)
+    }
+}
+
+/// Absolute time of a calendar date and time of day
+pub(crate) fn civil_time(year: i64, month: u32, day: u32, time: Duration) -> Option<SystemTime> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days since the epoch of the proleptic Gregorian calendar date, with years starting in March
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146_097 + day_of_era - 719_468).ok()?;
+
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400) + time)
+}
+
+/// Supported log formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Vector binary logging format
+    Blf,
+    /// PEAK PCAN-View trace
+    Trc,
+    /// ASAM MDF 4 measurement file
+    Mf4,
+    /// candump log (`.log`)
+    Candump,
+    /// Apache Parquet table
+    Parquet,
+    /// CSV frame table
+    Csv,
+}
+
+impl FromStr for LogFormat {
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "blf" => Ok(LogFormat::Blf),
+            "trc" => Ok(LogFormat::Trc),
+            "mf4" => Ok(LogFormat::Mf4),
+            "log" | "candump" => Ok(LogFormat::Candump),
+            "parquet" => Ok(LogFormat::Parquet),
+            "csv" => Ok(LogFormat::Csv),
+            _ => Err(LogError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+impl LogFormat {
+    /// Select the format from the file extension
+    pub fn from_path(path: &Path) -> Result<Self, LogError> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .parse()
+    }
+
+    /// Detect the format from the start of a file
+    pub fn sniff(header: &[u8]) -> Option<Self> {
+        if header.starts_with(b"LOGG") {
+            return Some(LogFormat::Blf);
+        }
+        if header.starts_with(b"MDF     ") {
+            return Some(LogFormat::Mf4);
+        }
+        if header.starts_with(b"PAR1") {
+            return Some(LogFormat::Parquet);
+        }
+
+        // Text formats are recognized by their first non-empty line
+        let text = String::from_utf8_lossy(header);
+        let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+
+        if line.starts_with(';') {
+            Some(LogFormat::Trc)
+        } else if line.starts_with('(') {
+            Some(LogFormat::Candump)
+        } else if line
+            .split_whitespace()
+            .next()
+            .is_some_and(|number| number.ends_with(')'))
+        {
+            // TRC 1.0 files may lack a header, lines start with the message number
+            Some(LogFormat::Trc)
+        } else if line.contains([',', ';', '\t']) {
+            Some(LogFormat::Csv)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads the frames of a log file, in file order
+pub trait LogSource: Iterator<Item = Result<LogFrame, LogError>> {
+    /// Absolute time of timestamp zero, if the format records it
+    ///
+    /// Some formats only know the start time once the first frame has been read.
+    fn start_time(&self) -> Option<SystemTime>;
+}
+
+/// A log file opened for reading
+pub type LogReader = Box<dyn LogSource>;
+
+/// Frame metadata that not every log format can store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    /// Channels other than the first
+    Channel,
+    Direction,
+    /// Bit rate switch and error state indicator of CAN FD frames
+    FdFlags,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Channel => write!(f, "channel"),
+            Field::Direction => write!(f, "direction"),
+            Field::FdFlags => write!(f, "CAN FD flags"),
+        }
+    }
+}
+
+/// Writes frames to a log file
+pub trait LogWriter: Send {
+    /// Write a frame, returning `false` if the format cannot represent it
+    fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError>;
+    /// Fields of a frame that are lost when it is written
+    fn dropped_fields(&self, _frame: &LogFrame) -> Vec<Field> {
+        vec![]
+    }
+    /// Flush buffered frames and complete the file
+    fn finish(&mut self) -> Result<(), LogError>;
+}
+
+/// Tally of the frames written to a log, and of what the format could not store
+#[derive(Debug, Default)]
+pub struct WriteReport {
+    pub written: usize,
+    /// Frames left out, by kind
+    pub left_out: BTreeMap<&'static str, usize>,
+    /// Written frames that lost a field, by field
+    pub dropped: BTreeMap<Field, usize>,
+}
+
+impl WriteReport {
+    /// Write a frame, recording what is lost
+    pub fn write(&mut self, writer: &mut dyn LogWriter, frame: &LogFrame) -> Result<(), LogError> {
+        let dropped = writer.dropped_fields(frame);
+
+        if writer.write(frame)? {
+            self.written += 1;
+            for field in dropped {
+                *self.dropped.entry(field).or_default() += 1;
+            }
+        } else {
+            *self.left_out.entry(frame.kind()).or_default() += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Check if every frame was written in full
+    pub fn is_lossless(&self) -> bool {
+        self.left_out.is_empty() && self.dropped.is_empty()
+    }
+}
+
+impl fmt::Display for WriteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (kind, count) in &self.left_out {
+            writeln!(
+                f,
+                "Left out {} {} frames the output format cannot represent",
+                count, kind
+            )?;
+        }
+        for (field, count) in &self.dropped {
+            writeln!(
+                f,
+                "Dropped the {} of {} frames, the output format cannot store it",
+                field, count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Options of the read log file
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Format of the file, detected from its content or extension if not set
+    pub format: Option<LogFormat>,
+    /// Skip malformed lines of candump logs and CSV files instead of failing
+    pub tolerant: bool,
+    /// Column mapping of CSV files
+    pub csv: csv::CsvOptions,
+}
+
+/// Open a log file for reading, detecting the format
+pub fn open(path: impl AsRef<Path>) -> Result<LogReader, LogError> {
+    open_with(path, &ReadOptions::default())
+}
+
+/// Open a log file for reading with options
+///
+/// Unless set in the options, the format is detected from the content of the file, falling back to
+/// the extension.
+pub fn open_with(path: impl AsRef<Path>, options: &ReadOptions) -> Result<LogReader, LogError> {
+    let path = path.as_ref();
+    read_with(BufReader::new(File::open(path)?), path, options)
+}
+
+/// Open a log file that is still being written, reading frames as they are appended
+///
+/// Reading waits at the end of the file for more data, so the reader does not end.
+pub fn follow_with(path: impl AsRef<Path>, options: &ReadOptions) -> Result<LogReader, LogError> {
+    let path = path.as_ref();
+    let file = Follow(File::open(path)?);
+    read_with(BufReader::new(file), path, options)
+}
+
+fn read_with<R: Read + 'static>(
+    mut file: BufReader<R>,
+    path: &Path,
+    options: &ReadOptions,
+) -> Result<LogReader, LogError> {
+    let format = match options.format {
+        Some(format) => format,
+        None => match LogFormat::sniff(file.fill_buf()?) {
+            Some(format) => format,
+            None => LogFormat::from_path(path)?,
+        },
+    };
+
+    match format {
+        LogFormat::Blf => Ok(Box::new(blf::BlfReader::new(file)?)),
+        LogFormat::Trc => Ok(Box::new(trc::TrcReader::new(file))),
+        LogFormat::Mf4 => Err(LogError::WriteOnly("MF4")),
+        LogFormat::Parquet => Err(LogError::WriteOnly("Parquet")),
+        LogFormat::Csv => {
+            let reader = csv::CsvReader::new(file, options.csv.clone());
+            match options.tolerant {
+                true => Ok(Box::new(reader.tolerant())),
+                false => Ok(Box::new(reader)),
+            }
+        }
+        LogFormat::Candump => {
+            let reader = candump::CandumpReader::new(file);
+            match options.tolerant {
+                true => Ok(Box::new(reader.tolerant())),
+                false => Ok(Box::new(reader)),
+            }
+        }
+    }
+}
+
+/// Reads a file that is still being written, waiting at its end instead of ending
+struct Follow<R>(R);
+
+impl<R: Read> Read for Follow<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.0.read(buf)? {
+                0 if !buf.is_empty() => thread::sleep(FOLLOW_POLL),
+                n => return Ok(n),
+            }
+        }
+    }
+}
+
+/// Options of the written log file
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    pub trc_version: trc::TrcVersion,
+    /// Signal database used to store decoded signals, by formats that support it
+    pub database: Option<Database>,
+    /// Absolute time of timestamp zero
+    pub start_time: SystemTime,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            trc_version: trc::TrcVersion::V2_1,
+            database: None,
+            start_time: SystemTime::now(),
+        }
+    }
+}
+
+/// Create a log file for writing, selecting the format by extension
+pub fn create(
+    path: impl AsRef<Path>,
+    options: &WriteOptions,
+) -> Result<Box<dyn LogWriter>, LogError> {
+    let path = path.as_ref();
+    let format = LogFormat::from_path(path)?;
+
+    match format {
+        LogFormat::Blf => Err(LogError::ReadOnly("BLF")),
+        LogFormat::Trc => {
+            let file = BufWriter::new(File::create(path)?);
+            Ok(Box::new(trc::TrcWriter::new(
+                file,
+                options.trc_version,
+                options.start_time,
+            )?))
+        }
+        LogFormat::Mf4 => {
+            let file = BufWriter::new(File::create(path)?);
+            Ok(Box::new(mf4::Mf4Writer::new(
+                file,
+                options.database.clone(),
+                options.start_time,
+            )))
+        }
+        LogFormat::Candump => {
+            let file = BufWriter::new(File::create(path)?);
+            Ok(Box::new(candump::CandumpWriter::new(
+                file,
+                options.start_time,
+            )))
+        }
+        LogFormat::Csv => Err(LogError::ReadOnly("CSV")),
+        LogFormat::Parquet => {
+            let file = BufWriter::new(File::create(path)?);
+            Ok(Box::new(parquet::ParquetWriter::new(
+                file,
+                options.database.clone(),
+                options.start_time,
+            )?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dlc_codes() {
+        assert_eq!(dlc_to_len(8), 8);
+        assert_eq!(dlc_to_len(13), 32);
+        assert_eq!(len_to_dlc(5), 5);
+        assert_eq!(len_to_dlc(33), 14);
+        assert_eq!(len_to_dlc(64), 15);
+    }
+
+    #[test]
+    fn frame_conversion() {
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        let mut log = LogFrame::new(Duration::ZERO, id, &[1, 2, 3]);
+        assert_eq!(log.to_frame().unwrap().data(), &[1, 2, 3]);
+
+        log.fd = true;
+        assert!(log.to_frame().is_none());
+
+        assert_eq!(
+            id_from_raw(0x8000_0123),
+            Some(Id::Extended(ExtendedId::new(0x123).unwrap()))
+        );
+    }
+
+    #[test]
+    fn write_report() {
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        let mut tx = LogFrame::new(Duration::ZERO, id, &[1]);
+        tx.direction = Some(Direction::Tx);
+        tx.channel = Some(1);
+        let mut fd = tx.clone();
+        fd.fd = true;
+        let mut error = tx.clone();
+        error.error = true;
+
+        let mut writer =
+            trc::TrcWriter::new(vec![], trc::TrcVersion::V1_0, SystemTime::now()).unwrap();
+        let mut report = WriteReport::default();
+        for frame in [&tx, &tx, &fd, &error] {
+            report.write(&mut writer, frame).unwrap();
+        }
+
+        assert_eq!(report.written, 2);
+        assert_eq!(report.left_out.get("CAN FD"), Some(&1));
+        assert_eq!(report.left_out.get("error"), Some(&1));
+        assert_eq!(report.dropped.get(&Field::Channel), Some(&2));
+        assert_eq!(report.dropped.get(&Field::Direction), Some(&2));
+        assert!(report
+            .to_string()
+            .contains("Dropped the direction of 2 frames"));
+    }
+
+    #[test]
+    fn sniff_formats() {
+        assert_eq!(LogFormat::sniff(b"LOGG\x90\x00"), Some(LogFormat::Blf));
+        assert_eq!(LogFormat::sniff(b"MDF     4.10    "), Some(LogFormat::Mf4));
+        assert_eq!(
+            LogFormat::sniff(b"\n;$FILEVERSION=2.1\n"),
+            Some(LogFormat::Trc)
+        );
+        assert_eq!(
+            LogFormat::sniff(b"     1)      1841  0001  8  00 00 00 00 00 00 00 00\n"),
+            Some(LogFormat::Trc)
+        );
+        assert_eq!(
+            LogFormat::sniff(b"(1436509052.249713) vcan0 044#2A366C2BBA\n"),
+            Some(LogFormat::Candump)
+        );
+        assert_eq!(
+            LogFormat::sniff(b"Time,ID,DLC,Data\n"),
+            Some(LogFormat::Csv)
+        );
+        assert_eq!(LogFormat::sniff(b"date Wed Oct 16\n"), None);
+        assert_eq!(LogFormat::sniff(b""), None);
+    }
+
+    #[test]
+    fn calendar_dates() {
+        assert_eq!(civil_time(1970, 1, 1, Duration::ZERO), Some(UNIX_EPOCH));
+        assert_eq!(
+            civil_time(2024, 2, 29, Duration::from_secs(3600)),
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_168_400))
+        );
+        assert_eq!(civil_time(1969, 12, 31, Duration::ZERO), None);
+        assert_eq!(civil_time(2024, 13, 1, Duration::ZERO), None);
+    }
+}
@@ -0,0 +1,459 @@
+//
+// parquet.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Parquet export of raw frames or decoded signals
+//!
+//! Without a signal database every frame is a row. With one, every decoded signal is a row
+//! (timestamp, message, signal, value, ...), a long table that pandas or DuckDB can pivot.
+//! Rows are written in row groups as they are collected.
+
+use super::{Direction, Field, LogError, LogFrame, LogWriter};
+
+use candecode::Database;
+use embedded_can::Id;
+use parquet::{
+    basic::Compression,
+    data_type::{BoolType, ByteArray, ByteArrayType, DataType, DoubleType, Int32Type, Int64Type},
+    errors::ParquetError,
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    schema::parser::parse_message_type,
+};
+
+use std::{
+    io::Write,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Rows collected before a row group is written
+const ROW_GROUP_SIZE: usize = 256 * 1024;
+
+const FRAME_SCHEMA: &str = "
+message frame {
+    required int64 timestamp (TIMESTAMP(NANOS,true));
+    optional int32 channel (INTEGER(8,false));
+    required int32 id (INTEGER(32,false));
+    required boolean extended;
+    required int32 dlc (INTEGER(8,false));
+    required binary data;
+    required boolean remote;
+    required boolean error;
+    required boolean fd;
+    required boolean brs;
+    required boolean esi;
+    optional binary direction (STRING);
+}";
+
+const SIGNAL_SCHEMA: &str = "
+message signal {
+    required int64 timestamp (TIMESTAMP(NANOS,true));
+    optional int32 channel (INTEGER(8,false));
+    required int32 id (INTEGER(32,false));
+    required binary message (STRING);
+    required binary signal (STRING);
+    required double value;
+    required int64 raw;
+    optional binary label (STRING);
+    optional binary unit (STRING);
+}";
+
+/// Columns of frame rows
+#[derive(Default)]
+struct FrameRows {
+    timestamp: Vec<i64>,
+    channel: Vec<Option<i32>>,
+    id: Vec<i32>,
+    extended: Vec<bool>,
+    dlc: Vec<i32>,
+    data: Vec<ByteArray>,
+    remote: Vec<bool>,
+    error: Vec<bool>,
+    fd: Vec<bool>,
+    brs: Vec<bool>,
+    esi: Vec<bool>,
+    direction: Vec<Option<ByteArray>>,
+}
+
+impl FrameRows {
+    fn push(&mut self, timestamp: i64, frame: &LogFrame) {
+        let (id, extended) = raw_id(frame.id);
+
+        self.timestamp.push(timestamp);
+        self.channel.push(frame.channel.map(i32::from));
+        self.id.push(id);
+        self.extended.push(extended);
+        self.dlc.push(frame.dlc as i32);
+        self.data.push(frame.data.clone().into());
+        self.remote.push(frame.remote);
+        self.error.push(frame.error);
+        self.fd.push(frame.fd);
+        self.brs.push(frame.brs);
+        self.esi.push(frame.esi);
+        self.direction.push(frame.direction.map(|direction| {
+            match direction {
+                Direction::Rx => "Rx",
+                Direction::Tx => "Tx",
+            }
+            .into()
+        }));
+    }
+
+    fn write<W: Write + Send>(
+        &mut self,
+        row_group: &mut SerializedRowGroupWriter<'_, W>,
+    ) -> Result<(), ParquetError> {
+        required::<Int64Type, _>(row_group, &self.timestamp)?;
+        optional::<Int32Type, _>(row_group, &self.channel)?;
+        required::<Int32Type, _>(row_group, &self.id)?;
+        required::<BoolType, _>(row_group, &self.extended)?;
+        required::<Int32Type, _>(row_group, &self.dlc)?;
+        required::<ByteArrayType, _>(row_group, &self.data)?;
+        required::<BoolType, _>(row_group, &self.remote)?;
+        required::<BoolType, _>(row_group, &self.error)?;
+        required::<BoolType, _>(row_group, &self.fd)?;
+        required::<BoolType, _>(row_group, &self.brs)?;
+        required::<BoolType, _>(row_group, &self.esi)?;
+        optional::<ByteArrayType, _>(row_group, &self.direction)?;
+
+        *self = FrameRows::default();
+        Ok(())
+    }
+}
+
+/// Columns of decoded signal rows
+#[derive(Default)]
+struct SignalRows {
+    timestamp: Vec<i64>,
+    channel: Vec<Option<i32>>,
+    id: Vec<i32>,
+    message: Vec<ByteArray>,
+    signal: Vec<ByteArray>,
+    value: Vec<f64>,
+    raw: Vec<i64>,
+    label: Vec<Option<ByteArray>>,
+    unit: Vec<Option<ByteArray>>,
+}
+
+impl SignalRows {
+    /// Add a row per decoded signal, returning `false` if the frame is not a data frame in the
+    /// database
+    fn push(&mut self, database: &Database, timestamp: i64, frame: &LogFrame) -> bool {
+        if frame.remote || frame.error {
+            return false;
+        }
+        let Some(message) = database.message(frame.id) else {
+            return false;
+        };
+
+        for value in message.decode(&frame.data) {
+            let signal = value.signal;
+
+            self.timestamp.push(timestamp);
+            self.channel.push(frame.channel.map(i32::from));
+            self.id.push(raw_id(frame.id).0);
+            self.message.push(message.name.as_str().into());
+            self.signal.push(signal.name.as_str().into());
+            self.value.push(value.value);
+            self.raw.push(value.raw as i64);
+            self.label.push(
+                i64::try_from(value.raw)
+                    .ok()
+                    .and_then(|raw| signal.values.get(&raw))
+                    .map(|label| label.as_str().into()),
+            );
+            self.unit.push(
+                Some(signal.unit.as_str())
+                    .filter(|unit| !unit.is_empty())
+                    .map(Into::into),
+            );
+        }
+
+        true
+    }
+
+    fn write<W: Write + Send>(
+        &mut self,
+        row_group: &mut SerializedRowGroupWriter<'_, W>,
+    ) -> Result<(), ParquetError> {
+        required::<Int64Type, _>(row_group, &self.timestamp)?;
+        optional::<Int32Type, _>(row_group, &self.channel)?;
+        required::<Int32Type, _>(row_group, &self.id)?;
+        required::<ByteArrayType, _>(row_group, &self.message)?;
+        required::<ByteArrayType, _>(row_group, &self.signal)?;
+        required::<DoubleType, _>(row_group, &self.value)?;
+        required::<Int64Type, _>(row_group, &self.raw)?;
+        optional::<ByteArrayType, _>(row_group, &self.label)?;
+        optional::<ByteArrayType, _>(row_group, &self.unit)?;
+
+        *self = SignalRows::default();
+        Ok(())
+    }
+}
+
+/// Raw ID, stored as the bits of an unsigned integer, and whether it is extended
+fn raw_id(id: Id) -> (i32, bool) {
+    match id {
+        Id::Standard(id) => (id.as_raw() as i32, false),
+        Id::Extended(id) => (id.as_raw() as i32, true),
+    }
+}
+
+/// Write the next column of a row group
+fn required<T: DataType, W: Write + Send>(
+    row_group: &mut SerializedRowGroupWriter<'_, W>,
+    values: &[T::T],
+) -> Result<(), ParquetError> {
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("missing column".into()))?;
+    column.typed::<T>().write_batch(values, None, None)?;
+    column.close()
+}
+
+/// Write the next column of a row group, for an optional column
+fn optional<T: DataType, W: Write + Send>(
+    row_group: &mut SerializedRowGroupWriter<'_, W>,
+    values: &[Option<T::T>],
+) -> Result<(), ParquetError> {
+    let levels = values
+        .iter()
+        .map(|value| value.is_some() as i16)
+        .collect::<Vec<_>>();
+    let values = values.iter().flatten().cloned().collect::<Vec<_>>();
+
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("missing column".into()))?;
+    column
+        .typed::<T>()
+        .write_batch(&values, Some(&levels), None)?;
+    column.close()
+}
+
+enum Rows {
+    Frames(FrameRows),
+    Signals(Database, SignalRows),
+}
+
+impl Rows {
+    fn len(&self) -> usize {
+        match self {
+            Rows::Frames(rows) => rows.timestamp.len(),
+            Rows::Signals(_, rows) => rows.timestamp.len(),
+        }
+    }
+}
+
+/// Writes frames, or the signals decoded from them, to a Parquet file
+pub struct ParquetWriter<W: Write + Send> {
+    writer: SerializedFileWriter<W>,
+    rows: Rows,
+    /// Absolute time of timestamp zero, in nanoseconds since the Unix epoch
+    start: i64,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Create a writer, with a row per decoded signal if a database is given or per frame otherwise
+    pub fn new(
+        writer: W,
+        database: Option<Database>,
+        start_time: SystemTime,
+    ) -> Result<Self, LogError> {
+        let (schema, rows) = match database {
+            Some(database) => (
+                SIGNAL_SCHEMA,
+                Rows::Signals(database, SignalRows::default()),
+            ),
+            None => (FRAME_SCHEMA, Rows::Frames(FrameRows::default())),
+        };
+
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_created_by(format!("ican {}", env!("CARGO_PKG_VERSION")))
+            .build();
+        let writer = SerializedFileWriter::new(
+            writer,
+            Arc::new(parse_message_type(schema)?),
+            Arc::new(properties),
+        )?;
+
+        Ok(ParquetWriter {
+            writer,
+            rows,
+            start: start_time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as i64,
+        })
+    }
+
+    fn write_row_group(&mut self) -> Result<(), ParquetError> {
+        let mut row_group = self.writer.next_row_group()?;
+        match &mut self.rows {
+            Rows::Frames(rows) => rows.write(&mut row_group)?,
+            Rows::Signals(_, rows) => rows.write(&mut row_group)?,
+        }
+        row_group.close()?;
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> LogWriter for ParquetWriter<W> {
+    fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError> {
+        let timestamp = self.start + frame.timestamp.as_nanos() as i64;
+
+        let written = match &mut self.rows {
+            Rows::Frames(rows) => {
+                rows.push(timestamp, frame);
+                true
+            }
+            Rows::Signals(database, rows) => rows.push(database, timestamp, frame),
+        };
+
+        if self.rows.len() >= ROW_GROUP_SIZE {
+            self.write_row_group()?;
+        }
+
+        Ok(written)
+    }
+
+    fn dropped_fields(&self, frame: &LogFrame) -> Vec<Field> {
+        // Signal rows have no columns for the direction and FD flags
+        match self.rows {
+            Rows::Frames(_) => vec![],
+            Rows::Signals(..) => [
+                (frame.direction.is_some(), Field::Direction),
+                (frame.fd, Field::FdFlags),
+            ]
+            .into_iter()
+            .filter_map(|(dropped, field)| dropped.then_some(field))
+            .collect(),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), LogError> {
+        if self.rows.len() > 0 {
+            self.write_row_group()?;
+        }
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, StandardId};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::{Row, RowAccessor},
+    };
+    use std::{fs::File, time::Duration};
+
+    /// Write frames to a temporary file and read back the rows
+    fn round_trip(name: &str, database: Option<Database>, frames: &[LogFrame]) -> Vec<Row> {
+        let path =
+            std::env::temp_dir().join(format!("ican-{}-{}.parquet", name, std::process::id()));
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut writer = ParquetWriter::new(File::create(&path).unwrap(), database, start).unwrap();
+        for frame in frames {
+            writer.write(frame).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+        rows
+    }
+
+    #[test]
+    fn write_frames() {
+        let mut fd = LogFrame::new(
+            Duration::from_millis(5),
+            Id::Extended(ExtendedId::new(0x18DAF110).unwrap()),
+            &[0xAA; 12],
+        );
+        fd.fd = true;
+        fd.channel = Some(1);
+        fd.direction = Some(Direction::Tx);
+        let frames = [
+            LogFrame::new(
+                Duration::ZERO,
+                Id::Standard(StandardId::new(0x123).unwrap()),
+                &[1, 2],
+            ),
+            fd,
+        ];
+
+        let rows = round_trip("frames", None, &frames);
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(rows[0].get_long(0).unwrap(), 1_700_000_000_000_000_000);
+        assert_eq!(rows[0].get_uint(2).unwrap(), 0x123);
+        assert!(!rows[0].get_bool(3).unwrap());
+        assert_eq!(rows[0].get_bytes(5).unwrap().data(), &[1, 2]);
+        assert!(rows[0].get_string(11).is_err());
+
+        assert_eq!(rows[1].get_long(0).unwrap(), 1_700_000_000_005_000_000);
+        assert_eq!(rows[1].get_ubyte(1).unwrap(), 1);
+        assert_eq!(rows[1].get_uint(2).unwrap(), 0x18DAF110);
+        assert!(rows[1].get_bool(3).unwrap());
+        assert_eq!(rows[1].get_ubyte(4).unwrap(), 9);
+        assert!(rows[1].get_bool(8).unwrap());
+        assert_eq!(rows[1].get_string(11).unwrap(), "Tx");
+    }
+
+    #[test]
+    fn write_signals() {
+        let db = Database::from_dbc(
+            "VERSION \"\"\n\
+             BU_: ECU\n\
+             BO_ 256 Engine: 8 ECU\n \
+             SG_ Speed : 0|16@1+ (0.5,0) [0|8000] \"rpm\" Vector__XXX\n \
+             SG_ Gear : 16|8@1+ (1,0) [0|5] \"\" Vector__XXX\n\
+             VAL_ 256 Gear 0 \"PARK\" 1 \"REVERSE\" ;\n",
+        )
+        .unwrap();
+
+        let frames = [
+            LogFrame::new(
+                Duration::ZERO,
+                Id::Standard(StandardId::new(0x100).unwrap()),
+                &[0x10, 0x27, 1],
+            ),
+            LogFrame::new(
+                Duration::ZERO,
+                Id::Standard(StandardId::new(0x200).unwrap()),
+                &[1],
+            ),
+        ];
+
+        let rows = round_trip("signals", Some(db), &frames);
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(rows[0].get_string(3).unwrap(), "Engine");
+        assert_eq!(rows[0].get_string(4).unwrap(), "Speed");
+        assert_eq!(rows[0].get_double(5).unwrap(), 5000.0);
+        assert_eq!(rows[0].get_long(6).unwrap(), 10000);
+        assert!(rows[0].get_string(7).is_err());
+        assert_eq!(rows[0].get_string(8).unwrap(), "rpm");
+
+        assert_eq!(rows[1].get_string(4).unwrap(), "Gear");
+        assert_eq!(rows[1].get_string(7).unwrap(), "REVERSE");
+        assert!(rows[1].get_string(8).is_err());
+    }
+}
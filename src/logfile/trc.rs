@@ -0,0 +1,611 @@
+//
+// trc.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! PEAK PCAN-View trace (TRC) reader and writer, file versions 1.0 to 2.1
+
+use super::{dlc_to_len, len_to_dlc, Direction, Field, LogError, LogFrame, LogSource, LogWriter};
+
+use embedded_can::{ExtendedId, Id, StandardId};
+
+use std::{
+    io::{BufRead, Write},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Columns of version 2.0 files without a `$COLUMNS` header
+const DEFAULT_COLUMNS_2_0: &str = "N,O,T,I,d,l,D";
+/// Columns written to version 2.1 files
+const COLUMNS_2_1: &str = "N,O,T,B,I,d,R,L,D";
+/// Days from the `$STARTTIME` epoch, 1899-12-30, to the Unix epoch
+const STARTTIME_UNIX_EPOCH: f64 = 25569.0;
+
+fn invalid(reason: impl Into<String>) -> LogError {
+    LogError::Invalid {
+        format: "TRC",
+        reason: reason.into(),
+    }
+}
+
+/// TRC file version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrcVersion {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+    V2_0,
+    V2_1,
+}
+
+impl FromStr for TrcVersion {
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "1.0" => Ok(TrcVersion::V1_0),
+            "1.1" => Ok(TrcVersion::V1_1),
+            "1.2" => Ok(TrcVersion::V1_2),
+            "1.3" => Ok(TrcVersion::V1_3),
+            "2.0" => Ok(TrcVersion::V2_0),
+            "2.1" => Ok(TrcVersion::V2_1),
+            _ => Err(invalid(format!("unsupported file version '{}'", s))),
+        }
+    }
+}
+
+impl TrcVersion {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TrcVersion::V1_0 => "1.0",
+            TrcVersion::V1_1 => "1.1",
+            TrcVersion::V1_2 => "1.2",
+            TrcVersion::V1_3 => "1.3",
+            TrcVersion::V2_0 => "2.0",
+            TrcVersion::V2_1 => "2.1",
+        }
+    }
+}
+
+/// Streaming TRC reader
+///
+/// Status, error counter and 1.x error lines are skipped.
+pub struct TrcReader<R> {
+    reader: R,
+    version: TrcVersion,
+    columns: Vec<String>,
+    start_time: Option<SystemTime>,
+    line_number: usize,
+}
+
+impl<R: BufRead> TrcReader<R> {
+    pub fn new(reader: R) -> Self {
+        TrcReader {
+            reader,
+            version: TrcVersion::V1_0,
+            columns: vec![],
+            start_time: None,
+            line_number: 0,
+        }
+    }
+
+    /// Start time from the `$STARTTIME` header, known once the header has been read
+    pub fn start_time(&self) -> Option<SystemTime> {
+        self.start_time
+    }
+
+    /// File version, known once the header has been read
+    pub fn version(&self) -> TrcVersion {
+        self.version
+    }
+
+    fn next_frame(&mut self) -> Result<Option<LogFrame>, LogError> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix(';') {
+                self.header(comment)?;
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            let frame = match self.version {
+                TrcVersion::V2_0 | TrcVersion::V2_1 => self.parse_v2(line),
+                _ => self.parse_v1(line),
+            }
+            .map_err(|reason| invalid(format!("line {}: {}", self.line_number, reason)))?;
+
+            if let Some(frame) = frame {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    fn header(&mut self, comment: &str) -> Result<(), LogError> {
+        if let Some(version) = comment.trim().strip_prefix("$FILEVERSION=") {
+            self.version = version.parse()?;
+            if self.version == TrcVersion::V2_0 && self.columns.is_empty() {
+                self.columns = split_columns(DEFAULT_COLUMNS_2_0);
+            }
+        } else if let Some(columns) = comment.trim().strip_prefix("$COLUMNS=") {
+            self.columns = split_columns(columns);
+        } else if let Some(days) = comment.trim().strip_prefix("$STARTTIME=") {
+            // Days since 1899-12-30, with the time of day as the fraction
+            let days = days
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| invalid(format!("invalid start time '{}'", days)))?;
+            self.start_time = Duration::try_from_secs_f64((days - STARTTIME_UNIX_EPOCH) * 86_400.0)
+                .ok()
+                .map(|since_epoch| UNIX_EPOCH + since_epoch);
+        }
+
+        Ok(())
+    }
+
+    fn parse_v1(&self, line: &str) -> Result<Option<LogFrame>, String> {
+        let mut tokens = line.split_whitespace();
+        let mut next = |what: &str| tokens.next().ok_or(format!("missing {}", what));
+
+        if !next("message number")?.ends_with(')') {
+            return Err("invalid message number".into());
+        }
+        let timestamp = parse_offset(next("time offset")?)?;
+
+        let channel = match self.version >= TrcVersion::V1_2 {
+            true => Some(parse_bus(next("bus")?)?),
+            false => None,
+        };
+
+        let direction = match self.version >= TrcVersion::V1_1 {
+            true => match next("type")? {
+                "Rx" => Some(Direction::Rx),
+                "Tx" => Some(Direction::Tx),
+                // Warning and error lines carry no frame
+                _ => return Ok(None),
+            },
+            false => None,
+        };
+
+        let id = parse_id(next("ID")?)?;
+        if self.version >= TrcVersion::V1_3 {
+            next("reserved column")?;
+        }
+        let dlc = parse_number(next("DLC")?)?;
+
+        let rest = tokens.collect::<Vec<_>>();
+        let remote = rest.first() == Some(&"RTR");
+        let data = match remote {
+            true => vec![],
+            false => parse_data(&rest, dlc_to_len(dlc).min(8))?,
+        };
+
+        Ok(Some(LogFrame {
+            channel,
+            dlc,
+            remote,
+            direction,
+            ..LogFrame::new(timestamp, id, &data)
+        }))
+    }
+
+    fn parse_v2(&self, line: &str) -> Result<Option<LogFrame>, String> {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let column = |name: &str| {
+            self.columns
+                .iter()
+                .position(|c| c == name)
+                .and_then(|i| tokens.get(i).copied())
+        };
+
+        let kind = column("T").ok_or("missing type")?;
+        let (fd, brs, esi, remote, error) = match kind {
+            "DT" => (false, false, false, false, false),
+            "FD" => (true, false, false, false, false),
+            "FB" => (true, true, false, false, false),
+            "FE" => (true, false, true, false, false),
+            "BI" => (true, true, true, false, false),
+            "RR" => (false, false, false, true, false),
+            "ER" => (false, false, false, false, true),
+            // Status and error counter lines
+            _ => return Ok(None),
+        };
+
+        let timestamp = parse_offset(column("O").ok_or("missing time offset")?)?;
+        let channel = column("B").map(parse_bus).transpose()?;
+        let direction = match column("d") {
+            Some("Rx") => Some(Direction::Rx),
+            Some("Tx") => Some(Direction::Tx),
+            _ => None,
+        };
+
+        // Error frames have no ID
+        let id = match column("I") {
+            Some(id) if !(error && id == "-") => parse_id(id)?,
+            _ if error => Id::Standard(StandardId::ZERO),
+            _ => return Err("missing ID".into()),
+        };
+
+        let (dlc, len) = if let Some(len) = column("l") {
+            let len = parse_number(len)? as usize;
+            (len_to_dlc(len), len)
+        } else if let Some(dlc) = column("L") {
+            let dlc = parse_number(dlc)?;
+            (dlc, dlc_to_len(dlc))
+        } else {
+            return Err("missing data length".into());
+        };
+
+        let data = match remote {
+            true => vec![],
+            false => {
+                let start = self
+                    .columns
+                    .iter()
+                    .position(|c| c == "D")
+                    .ok_or("missing data column")?;
+                parse_data(tokens.get(start..).unwrap_or_default(), len)?
+            }
+        };
+
+        Ok(Some(LogFrame {
+            channel,
+            dlc,
+            remote,
+            error,
+            fd,
+            brs,
+            esi,
+            direction,
+            ..LogFrame::new(timestamp, id, &data)
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for TrcReader<R> {
+    type Item = Result<LogFrame, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl<R: BufRead> LogSource for TrcReader<R> {
+    fn start_time(&self) -> Option<SystemTime> {
+        self.start_time
+    }
+}
+
+fn split_columns(columns: &str) -> Vec<String> {
+    columns.split(',').map(|c| c.trim().to_string()).collect()
+}
+
+fn parse_number(s: &str) -> Result<u8, String> {
+    s.parse().map_err(|_| format!("invalid number '{}'", s))
+}
+
+/// Parse a time offset in milliseconds, with up to nanosecond precision
+fn parse_offset(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid time offset '{}'", s);
+
+    let (ms, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if fraction.len() > 6 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let ms = ms.parse::<u64>().map_err(|_| invalid())?;
+    let ns = format!("{:0<6}", fraction)
+        .parse::<u64>()
+        .unwrap_or_default();
+
+    Ok(Duration::from_millis(ms) + Duration::from_nanos(ns))
+}
+
+/// Parse a bus number, which starts at 1
+fn parse_bus(s: &str) -> Result<u8, String> {
+    parse_number(s)?
+        .checked_sub(1)
+        .ok_or(format!("invalid bus '{}'", s))
+}
+
+/// Parse an ID, IDs with more than 4 digits are extended
+fn parse_id(s: &str) -> Result<Id, String> {
+    let raw = u32::from_str_radix(s, 16).map_err(|_| format!("invalid ID '{}'", s))?;
+
+    let id = match s.len() > 4 {
+        true => ExtendedId::new(raw).map(Id::Extended),
+        false => u16::try_from(raw)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard),
+    };
+
+    id.ok_or(format!("invalid ID '{}'", s))
+}
+
+fn parse_data(tokens: &[&str], len: usize) -> Result<Vec<u8>, String> {
+    if tokens.len() < len {
+        return Err(format!("expected {} data bytes", len));
+    }
+
+    tokens[..len]
+        .iter()
+        .map(|b| u8::from_str_radix(b, 16).map_err(|_| format!("invalid data byte '{}'", b)))
+        .collect()
+}
+
+/// TRC writer
+///
+/// Versions before 2.0 cannot represent CAN FD and error frames, which are left out.
+pub struct TrcWriter<W: Write> {
+    writer: W,
+    version: TrcVersion,
+    count: usize,
+}
+
+impl<W: Write> TrcWriter<W> {
+    /// Create a writer, writing the file header
+    ///
+    /// `start_time` is the absolute time of timestamp zero, recorded from version 1.1.
+    pub fn new(
+        mut writer: W,
+        version: TrcVersion,
+        start_time: SystemTime,
+    ) -> Result<Self, LogError> {
+        writeln!(writer, ";$FILEVERSION={}", version.as_str())?;
+        if version >= TrcVersion::V1_1 {
+            let days = start_time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64()
+                / 86_400.0;
+            writeln!(writer, ";$STARTTIME={:.10}", days + STARTTIME_UNIX_EPOCH)?;
+        }
+        match version {
+            TrcVersion::V2_0 => writeln!(writer, ";$COLUMNS={}", DEFAULT_COLUMNS_2_0)?,
+            TrcVersion::V2_1 => writeln!(writer, ";$COLUMNS={}", COLUMNS_2_1)?,
+            _ => {}
+        }
+        writeln!(writer, ";")?;
+        writeln!(writer, ";   Generated by ican")?;
+        writeln!(writer, ";{}", "-".repeat(79))?;
+
+        Ok(TrcWriter {
+            writer,
+            version,
+            count: 0,
+        })
+    }
+
+    /// Check if a frame can be written in the file version
+    pub fn supports(&self, frame: &LogFrame) -> bool {
+        self.version >= TrcVersion::V2_0 || !(frame.fd || frame.error)
+    }
+
+    /// Check if the file version has a bus column
+    fn has_bus(&self) -> bool {
+        matches!(
+            self.version,
+            TrcVersion::V1_2 | TrcVersion::V1_3 | TrcVersion::V2_1
+        )
+    }
+}
+
+impl<W: Write + Send> LogWriter for TrcWriter<W> {
+    fn write(&mut self, frame: &LogFrame) -> Result<bool, LogError> {
+        if !self.supports(frame) {
+            return Ok(false);
+        }
+
+        self.count += 1;
+
+        let id = match frame.id {
+            Id::Standard(id) => format!("{:04X}", id.as_raw()),
+            Id::Extended(id) => format!("{:08X}", id.as_raw()),
+        };
+        let data = match frame.remote {
+            true if self.version < TrcVersion::V2_0 => "RTR".to_string(),
+            true => String::new(),
+            false => frame
+                .data
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        let ms = frame.timestamp.as_secs_f64() * 1000.0;
+        let bus = frame.channel.unwrap_or(0) as usize + 1;
+        let direction = match frame.direction {
+            Some(Direction::Tx) => "Tx",
+            _ => "Rx",
+        };
+
+        let line = match self.version {
+            TrcVersion::V1_0 => format!(
+                "{:>6}) {:>10.0} {:>8} {}  {}",
+                self.count, ms, id, frame.dlc, data
+            ),
+            TrcVersion::V1_1 => format!(
+                "{:>6}) {:>11.1}  {:<2} {:>12}  {}  {}",
+                self.count, ms, direction, id, frame.dlc, data
+            ),
+            TrcVersion::V1_2 => format!(
+                "{:>6}) {:>11.1} {}  {:<2} {:>12}  {}  {}",
+                self.count, ms, bus, direction, id, frame.dlc, data
+            ),
+            TrcVersion::V1_3 => format!(
+                "{:>6}) {:>11.1} {}  {:<2} {:>12} -  {}    {}",
+                self.count, ms, bus, direction, id, frame.dlc, data
+            ),
+            TrcVersion::V2_0 | TrcVersion::V2_1 => {
+                let kind = match frame {
+                    f if f.error => "ER",
+                    f if f.remote => "RR",
+                    f if f.fd && f.brs && f.esi => "BI",
+                    f if f.fd && f.brs => "FB",
+                    f if f.fd && f.esi => "FE",
+                    f if f.fd => "FD",
+                    _ => "DT",
+                };
+
+                match self.version {
+                    TrcVersion::V2_0 => format!(
+                        "{:>7} {:>13.3} {} {:>8} {} {:<4} {}",
+                        self.count,
+                        ms,
+                        kind,
+                        id,
+                        direction,
+                        dlc_to_len(frame.dlc),
+                        data
+                    ),
+                    _ => format!(
+                        "{:>7} {:>13.3} {} {:>2} {:>8} {} -  {:<4} {}",
+                        self.count, ms, kind, bus, id, direction, frame.dlc, data
+                    ),
+                }
+            }
+        };
+
+        writeln!(self.writer, "{}", line.trim_end())?;
+
+        Ok(true)
+    }
+
+    fn dropped_fields(&self, frame: &LogFrame) -> Vec<Field> {
+        let mut dropped = vec![];
+        if !self.has_bus() && frame.channel.is_some_and(|channel| channel > 0) {
+            dropped.push(Field::Channel);
+        }
+        if self.version == TrcVersion::V1_0 && frame.direction.is_some() {
+            dropped.push(Field::Direction);
+        }
+        dropped
+    }
+
+    fn finish(&mut self) -> Result<(), LogError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> Vec<LogFrame> {
+        TrcReader::new(text.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn read_versions() {
+        let v1_1 = read(
+            ";$FILEVERSION=1.1\n\
+             ;$STARTTIME=44548.6028595139\n\
+             ;---+--   ----+----  --+--  ----+---  +  -+ -- -- -- -- -- -- --\n\
+             \x20    1)        17.7  Rx         0300  8  00 01 02 03 04 05 06 07\n\
+             \x20    2)        18.5  Warng  FFFFFFFF  4  00 00 00 08  BUSHEAVY\n\
+             \x20    3)        20.0  Tx     18EFC034  0  RTR\n",
+        );
+        assert_eq!(v1_1.len(), 2);
+
+        let mut reader = TrcReader::new(";$STARTTIME=44548.5\n".as_bytes());
+        assert!(reader.next().is_none());
+        assert_eq!(
+            reader.start_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_639_828_800))
+        );
+        assert_eq!(v1_1[0].timestamp, Duration::from_micros(17_700));
+        assert_eq!(v1_1[0].data, (0..8).collect::<Vec<u8>>());
+        assert_eq!(
+            v1_1[1].id,
+            Id::Extended(ExtendedId::new(0x18EFC034).unwrap())
+        );
+        assert!(v1_1[1].remote);
+        assert_eq!(v1_1[1].direction, Some(Direction::Tx));
+
+        let v1_3 = read(
+            ";$FILEVERSION=1.3\n\
+             \x20    1)      1059.9 2  Rx        0300 -  2    AA BB\n",
+        );
+        assert_eq!(v1_3[0].channel, Some(1));
+        assert_eq!(v1_3[0].data, vec![0xAA, 0xBB]);
+
+        let v2_1 = read(
+            ";$FILEVERSION=2.1\n\
+             ;$COLUMNS=N,O,T,B,I,d,R,L,D\n\
+             \x20     1      1059.900 DT  1     0300 Rx -  3    01 02 03\n\
+             \x20     2      1060.000 ST  1            Rx -  4    00 00 00 04\n\
+             \x20     3      1061.000 FB  1 18EFC034 Tx -  9    00 01 02 03 04 05 06 07 08 09 0A 0B\n",
+        );
+        assert_eq!(v2_1.len(), 2);
+        assert_eq!(v2_1[0].channel, Some(0));
+        assert!(v2_1[1].fd && v2_1[1].brs && !v2_1[1].esi);
+        assert_eq!(v2_1[1].data.len(), 12);
+    }
+
+    #[test]
+    fn round_trip() {
+        let id = Id::Standard(StandardId::new(0x123).unwrap());
+        let mut fd = LogFrame::new(Duration::from_millis(5), id, &[0xAB; 16]);
+        fd.fd = true;
+        fd.channel = Some(1);
+        fd.direction = Some(Direction::Tx);
+        let frames = vec![
+            LogFrame::new(Duration::from_micros(1500), id, &[1, 2, 3]),
+            fd,
+        ];
+
+        for version in [
+            TrcVersion::V1_1,
+            TrcVersion::V1_3,
+            TrcVersion::V2_0,
+            TrcVersion::V2_1,
+        ] {
+            let mut out = vec![];
+            let start = UNIX_EPOCH + Duration::from_secs(1_639_828_800);
+            let mut writer = TrcWriter::new(&mut out, version, start).unwrap();
+            assert_eq!(
+                writer.dropped_fields(&frames[1]).contains(&Field::Channel),
+                matches!(version, TrcVersion::V1_1 | TrcVersion::V2_0)
+            );
+            for frame in &frames {
+                writer.write(frame).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let mut reader = TrcReader::new(out.as_slice());
+            let read = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(read[0].data, frames[0].data, "{}", version.as_str());
+            assert_eq!(read[0].timestamp, frames[0].timestamp);
+            let start_time = reader.start_time().unwrap();
+            let error = start_time
+                .duration_since(start)
+                .or(start.duration_since(start_time));
+            assert!(error.unwrap() < Duration::from_millis(1));
+
+            // CAN FD needs version 2.0 or later
+            if version >= TrcVersion::V2_0 {
+                assert_eq!(read[1].data, frames[1].data);
+                assert!(read[1].fd);
+                assert_eq!(read[1].direction, Some(Direction::Tx));
+            } else {
+                assert_eq!(read.len(), 1);
+            }
+        }
+    }
+}
@@ -4,31 +4,111 @@
 // @author Natesh Narain <nnaraindev@gmail.com>
 // @date Jul 15 2022
 //
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser};
 
-use ican::{action, drivers::AsyncCanDriverPtr, Args, Command, CommandContext};
-
-use tokio;
+use ican::{
+    action,
+    audit::AuditLog,
+    cli::{TxOptions, DEFAULT_TX_RATE_LIMIT},
+    config::Config,
+    drivers::CanDriver,
+    Args, Command, CommandContext,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    let interface = args.interface.clone().to_string();
+    // Database, log file, interface setup and packaging commands work offline
+    let cmd = match args.cmd {
+        Command::Dbc(cmd) => return action::dbc::run(cmd),
+        Command::Convert(args) => return action::convert::run(args),
+        Command::Merge(args) => return action::merge::run(args),
+        Command::Analyze(args) if args.log.is_some() => return action::analyze::run_log(args),
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        Command::Vcan(cmd) => return action::vcan::run(cmd),
+        Command::Completions(args) => return action::completions::run(args),
+        Command::Manpages(args) => return action::manpages::run(args),
+        Command::ListInterfaces => return action::completions::list_interfaces(),
+        cmd => cmd,
+    };
+
+    // Dump and monitor can decode a log file in place of the interface
+    let file = match &cmd {
+        Command::Dump(cmd) => cmd.source.open()?,
+        Command::Monitor(cmd) => cmd.source.open()?,
+        _ => None,
+    };
+
+    let config = Config::load(args.config.as_deref())?;
+    cmd.check_read_only(args.interface.as_ref(), args.read_only, &config)?;
     let tick_rate = args.tui_tick_rate;
 
-    let driver: AsyncCanDriverPtr = args.interface.try_into()?;
+    let (interface, mut driver) = match file {
+        Some(file) => file,
+        None => {
+            let Some(opts) = args.interface else {
+                Args::command()
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "The CAN interface is required for this command",
+                    )
+                    .exit();
+            };
+            (opts.to_string(), CanDriver::open(opts, args.rx_queues)?)
+        }
+    };
+    if args.own_messages {
+        driver.receive_own_messages(true)?;
+    }
+
+    let audit = match &args.audit {
+        Some(path) => {
+            let command = std::env::args().collect::<Vec<_>>().join(" ");
+            Some(AuditLog::create(path, &command)?)
+        }
+        None => None,
+    };
+    // A bridge forwards the source bus at its own rate, it is only limited when asked to
+    let rate_limit = match &cmd {
+        _ if args.force => None,
+        Command::Bridge(_) => args.tx_rate_limit,
+        _ => Some(args.tx_rate_limit.unwrap_or(DEFAULT_TX_RATE_LIMIT)),
+    };
+    let tx = TxOptions { rate_limit, audit };
+    // Commands that only receive keep the driver's direct dispatch, as does the source of a
+    // bridge, which sends on its destination
+    if cmd.transmits() {
+        driver = tx.wrap(driver, &interface);
+    }
 
     let context = CommandContext {
         driver,
         interface,
         tick_rate,
+        tx,
+        config,
     };
 
-    match args.cmd {
-        Command::Dump => Ok(action::dump::run(context).await?),
-        Command::Monitor => Ok(action::monitor::run(context).await?),
+    match cmd {
+        Command::Dump(args) => Ok(action::dump::run(context, args).await?),
+        Command::Monitor(args) => Ok(action::monitor::run(context, args).await?),
         Command::Send(args) => Ok(action::send::run(context, args).await?),
         Command::Bridge(args) => Ok(action::bridge::run(context, args).await?),
+        Command::Replay(args) => Ok(action::replay::run(context, args).await?),
+        Command::Signals(args) => Ok(action::signals::run(context, args).await?),
+        Command::Analyze(args) => Ok(action::analyze::run(context, args).await?),
+        Command::Test(args) => Ok(action::scenario::run(context, args).await?),
+        Command::J1939(cmd) => Ok(action::j1939::run(cmd, context).await?),
+        Command::Uds(cmd) => Ok(action::uds::run(cmd, context).await?),
+        Command::Canopen(cmd) => Ok(action::canopen::run(cmd, context).await?),
+        Command::Dbc(_)
+        | Command::Convert(_)
+        | Command::Merge(_)
+        | Command::Completions(_)
+        | Command::Manpages(_)
+        | Command::ListInterfaces => unreachable!(),
+        #[cfg(all(target_os = "linux", feature = "socketcan"))]
+        Command::Vcan(_) => unreachable!(),
     }
 }
@@ -0,0 +1,260 @@
+//
+// names.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Names and descriptions of IDs, for buses without a signal database
+//!
+//! A sidecar file maps each ID to a name and an optional description, as TOML:
+//!
+//! ```toml
+//! 0x100 = "EngineStatus"
+//!
+//! [0x7E8]
+//! name = "EcuResponse"
+//! description = "Diagnostic responses of the engine ECU"
+//! ```
+//!
+//! or as CSV, with an optional header:
+//!
+//! ```text
+//! id,name,description
+//! 0x100,EngineStatus,
+//! 0x7E8,EcuResponse,"Diagnostic responses, engine ECU"
+//! ```
+
+use crate::utils;
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Error, Debug)]
+pub enum NamesError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, io::Error),
+    #[error("Invalid ID names {0}: {1}")]
+    Invalid(PathBuf, String),
+    #[error("Unknown ID names format {0}, expected .toml or .csv")]
+    UnknownFormat(PathBuf),
+}
+
+/// Name and description of an ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdName {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for IdName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(description) = &self.description {
+            write!(f, " ({})", description)?;
+        }
+        Ok(())
+    }
+}
+
+/// ID names from a sidecar file
+#[derive(Debug, Clone, Default)]
+pub struct IdNames {
+    names: BTreeMap<u32, IdName>,
+}
+
+impl IdNames {
+    /// Load a TOML or CSV file, by its extension
+    pub fn load(path: &Path) -> Result<Self, NamesError> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let parse = match ext.as_deref() {
+            Some("toml") => IdNames::from_toml,
+            Some("csv") => IdNames::from_csv,
+            _ => return Err(NamesError::UnknownFormat(path.to_path_buf())),
+        };
+
+        let text = fs::read_to_string(path).map_err(|e| NamesError::Read(path.to_path_buf(), e))?;
+        parse(&text).map_err(|e| NamesError::Invalid(path.to_path_buf(), e))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+
+        let mut names = IdNames::default();
+        for (id, entry) in table {
+            let raw = parse_id(&id)?;
+            let name = match entry {
+                toml::Value::String(name) => IdName {
+                    name,
+                    description: None,
+                },
+                toml::Value::Table(entry) => {
+                    let text = |key| entry.get(key).and_then(|v| v.as_str()).map(String::from);
+                    IdName {
+                        name: text("name").ok_or_else(|| format!("{} needs a name", id))?,
+                        description: text("description"),
+                    }
+                }
+                _ => return Err(format!("{} must be a name or a table", id)),
+            };
+            names.names.insert(raw, name);
+        }
+
+        Ok(names)
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut names = IdNames::default();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = split_csv(line);
+            let (id, name) = match fields.as_slice() {
+                [id, name, ..] => (id.trim(), name.trim()),
+                _ => return Err(format!("line {}: expected id,name[,description]", i + 1)),
+            };
+            let raw = match parse_id(id) {
+                Ok(raw) => raw,
+                // Header
+                Err(_) if i == 0 => continue,
+                Err(e) => return Err(format!("line {}: {}", i + 1, e)),
+            };
+            let description = fields
+                .get(2)
+                .map(|d| d.trim())
+                .filter(|d| !d.is_empty())
+                .map(String::from);
+
+            names.names.insert(
+                raw,
+                IdName {
+                    name: name.to_string(),
+                    description,
+                },
+            );
+        }
+
+        Ok(names)
+    }
+
+    pub fn get(&self, id: Id) -> Option<&IdName> {
+        self.names.get(&utils::id_to_raw(&id))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Write the name of an ID after a frame, if it has one
+    pub fn write(&self, out: &mut impl Write, id: Id) -> fmt::Result {
+        match self.get(id) {
+            Some(name) => write!(out, "  {}", name),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Hex ID, with or without the 0x prefix
+fn parse_id(s: &str) -> Result<u32, String> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .filter(|id| *id <= 0x1FFF_FFFF)
+        .ok_or_else(|| format!("invalid ID '{}'", s))
+}
+
+/// Split a CSV line, honouring double quoted fields
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_can::{ExtendedId, StandardId};
+
+    fn standard(id: u16) -> Id {
+        StandardId::new(id).unwrap().into()
+    }
+
+    #[test]
+    fn toml_names() {
+        let names = IdNames::from_toml(
+            r#"
+0x100 = "EngineStatus"
+18FEF100 = "EEC1"
+
+[0x7E8]
+name = "EcuResponse"
+description = "Diagnostic responses"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            names.get(standard(0x100)).unwrap().to_string(),
+            "EngineStatus"
+        );
+        assert_eq!(
+            names.get(standard(0x7E8)).unwrap().to_string(),
+            "EcuResponse (Diagnostic responses)"
+        );
+        let eec1 = ExtendedId::new(0x18FEF100).unwrap().into();
+        assert_eq!(names.get(eec1).unwrap().name, "EEC1");
+        assert!(names.get(standard(0x200)).is_none());
+
+        assert!(IdNames::from_toml("[0x100]\ndescription = \"x\"").is_err());
+        assert!(IdNames::from_toml("engine = \"x\"").is_err());
+    }
+
+    #[test]
+    fn csv_names() {
+        let names = IdNames::from_csv(
+            "id,name,description\n\
+             0x100,EngineStatus,\n\
+             \n\
+             7E8, EcuResponse ,\"Responses, \"\"engine\"\" ECU\"\n",
+        )
+        .unwrap();
+
+        let engine = names.get(standard(0x100)).unwrap();
+        assert_eq!(engine.description, None);
+        let mut line = String::new();
+        names.write(&mut line, standard(0x7E8)).unwrap();
+        assert_eq!(line, "  EcuResponse (Responses, \"engine\" ECU)");
+
+        assert!(IdNames::from_csv("0x100").is_err());
+        assert!(IdNames::from_csv("0x100,A\nxyz,B").is_err());
+    }
+}
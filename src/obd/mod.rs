@@ -0,0 +1,287 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! OBD-II (SAE J1979) services
+
+pub mod pid;
+
+pub use pid::{pid_info, supported_pids, PidInfo, PidValue};
+
+use crate::uds::{FunctionalClient, UdsClient, UdsError};
+
+use embedded_can::Id;
+use thiserror::Error;
+
+use std::{fmt, str::FromStr};
+
+// Modes (services)
+pub const CURRENT_DATA: u8 = 0x01;
+pub const FREEZE_FRAME_DATA: u8 = 0x02;
+pub const STORED_DTCS: u8 = 0x03;
+pub const CLEAR_DTCS: u8 = 0x04;
+pub const VEHICLE_INFORMATION: u8 = 0x09;
+
+/// Vehicle information type of the VIN (mode 09)
+pub const VIN: u8 = 0x02;
+
+#[derive(Debug, Error)]
+pub enum ObdError {
+    #[error("{0}")]
+    Request(#[from] UdsError),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(&'static str),
+    #[error("Unknown PID 0x{0:02X}")]
+    UnknownPid(u8),
+    #[error("Invalid DTC '{0}'")]
+    InvalidDtc(String),
+}
+
+/// Diagnostic trouble code, e.g. P0301
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dtc(pub u16);
+
+impl fmt::Display for Dtc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let system = ['P', 'C', 'B', 'U'][(self.0 >> 14) as usize];
+        write!(f, "{}{:04X}", system, self.0 & 0x3FFF)
+    }
+}
+
+impl FromStr for Dtc {
+    type Err = ObdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ObdError::InvalidDtc(s.to_string());
+
+        let mut chars = s.chars();
+        let system = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('P') => 0,
+            Some('C') => 1,
+            Some('B') => 2,
+            Some('U') => 3,
+            _ => return Err(invalid()),
+        };
+
+        let code = chars.as_str();
+        if code.len() != 4 || !code.starts_with(['0', '1', '2', '3']) {
+            return Err(invalid());
+        }
+        let code = u16::from_str_radix(code, 16).map_err(|_| invalid())?;
+
+        Ok(Dtc((system << 14) | code))
+    }
+}
+
+/// Parse the DTCs of a mode 03 response, after the service identifier
+pub fn parse_dtcs(data: &[u8]) -> Result<Vec<Dtc>, ObdError> {
+    // CAN responses start with the number of DTCs
+    let (&count, codes) = data
+        .split_first()
+        .ok_or(ObdError::InvalidResponse("missing DTC count"))?;
+
+    if codes.len() < count as usize * 2 {
+        return Err(ObdError::InvalidResponse("truncated DTC list"));
+    }
+
+    Ok(codes
+        .chunks_exact(2)
+        .take(count as usize)
+        .map(|code| Dtc(u16::from_be_bytes([code[0], code[1]])))
+        .collect())
+}
+
+/// OBD-II client for a single ECU
+///
+/// Requests use the same positive/negative response handling as UDS.
+pub struct ObdClient {
+    client: UdsClient,
+}
+
+impl ObdClient {
+    pub fn new(client: UdsClient) -> Self {
+        ObdClient { client }
+    }
+
+    pub fn into_inner(self) -> UdsClient {
+        self.client
+    }
+
+    /// Read the raw data bytes of a mode 01 PID
+    pub async fn current_data_raw(&mut self, pid: u8) -> Result<Vec<u8>, ObdError> {
+        let response = self.client.request(&[CURRENT_DATA, pid]).await?;
+
+        match response.split_first() {
+            Some((&echo, data)) if echo == pid => Ok(data.to_vec()),
+            _ => Err(ObdError::InvalidResponse("PID mismatch")),
+        }
+    }
+
+    /// Read and scale a mode 01 PID
+    pub async fn current_data(&mut self, pid: u8) -> Result<PidValue, ObdError> {
+        let info = pid_info(pid).ok_or(ObdError::UnknownPid(pid))?;
+        let data = self.current_data_raw(pid).await?;
+        info.decode(&data)
+            .ok_or(ObdError::InvalidResponse("PID data too short"))
+    }
+
+    /// Read and scale a PID of a stored freeze frame (mode 02)
+    pub async fn freeze_frame(&mut self, pid: u8, frame: u8) -> Result<PidValue, ObdError> {
+        let info = pid_info(pid).ok_or(ObdError::UnknownPid(pid))?;
+        let response = self
+            .client
+            .request(&[FREEZE_FRAME_DATA, pid, frame])
+            .await?;
+
+        match response.as_slice() {
+            [p, f, data @ ..] if *p == pid && *f == frame => info
+                .decode(data)
+                .ok_or(ObdError::InvalidResponse("PID data too short")),
+            _ => Err(ObdError::InvalidResponse("PID mismatch")),
+        }
+    }
+
+    /// PIDs supported by the ECU in mode 01
+    pub async fn supported_pids(&mut self) -> Result<Vec<u8>, ObdError> {
+        let mut supported = vec![];
+        let mut base = 0x00u8;
+
+        loop {
+            let data = self.current_data_raw(base).await?;
+            let pids = supported_pids(base, &data);
+            let next = base.checked_add(0x20);
+
+            supported.extend(pids.iter().filter(|&&pid| Some(pid) != next));
+
+            // The last PID of each range indicates support for the next range
+            match next {
+                Some(next) if pids.contains(&next) => base = next,
+                _ => break,
+            }
+        }
+
+        Ok(supported)
+    }
+
+    /// Read stored DTCs (mode 03)
+    pub async fn stored_dtcs(&mut self) -> Result<Vec<Dtc>, ObdError> {
+        let response = self.client.request(&[STORED_DTCS]).await?;
+        parse_dtcs(&response)
+    }
+
+    /// Clear DTCs and stored diagnostic information (mode 04)
+    pub async fn clear_dtcs(&mut self) -> Result<(), ObdError> {
+        self.client.request(&[CLEAR_DTCS]).await?;
+        Ok(())
+    }
+
+    /// Read the vehicle identification number (mode 09)
+    pub async fn vin(&mut self) -> Result<String, ObdError> {
+        let response = self.client.request(&[VEHICLE_INFORMATION, VIN]).await?;
+
+        match response.as_slice() {
+            // Info type and number of data items precede the VIN
+            [VIN, _, vin @ ..] => Ok(String::from_utf8_lossy(vin)
+                .trim_matches(char::from(0))
+                .to_string()),
+            _ => Err(ObdError::InvalidResponse("info type mismatch")),
+        }
+    }
+}
+
+/// OBD-II requests broadcast to every emission-related ECU
+///
+/// ECUs that reject a request or send a malformed response are left out of the results.
+pub struct ObdScanner {
+    client: FunctionalClient,
+}
+
+impl ObdScanner {
+    pub fn new(client: FunctionalClient) -> Self {
+        ObdScanner { client }
+    }
+
+    pub fn into_inner(self) -> FunctionalClient {
+        self.client
+    }
+
+    /// Read and scale a mode 01 PID from every ECU supporting it
+    pub async fn current_data(&mut self, pid: u8) -> Result<Vec<(Id, PidValue)>, ObdError> {
+        let info = pid_info(pid).ok_or(ObdError::UnknownPid(pid))?;
+        let responses = self.client.request(&[CURRENT_DATA, pid]).await?;
+
+        Ok(responses
+            .into_iter()
+            .filter_map(|response| match response.result.ok()?.split_first() {
+                Some((&echo, data)) if echo == pid => Some((response.id, info.decode(data)?)),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Read the stored DTCs of every ECU (mode 03)
+    pub async fn stored_dtcs(&mut self) -> Result<Vec<(Id, Vec<Dtc>)>, ObdError> {
+        let responses = self.client.request(&[STORED_DTCS]).await?;
+
+        Ok(responses
+            .into_iter()
+            .filter_map(|response| Some((response.id, parse_dtcs(&response.result.ok()?).ok()?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::uds::tests::server;
+
+    #[test]
+    fn dtc_codes() {
+        assert_eq!(Dtc(0x0301).to_string(), "P0301");
+        assert_eq!(Dtc(0xC155).to_string(), "U0155");
+        assert_eq!("p0301".parse::<Dtc>().unwrap(), Dtc(0x0301));
+        assert_eq!("B1A00".parse::<Dtc>().unwrap(), Dtc(0x9A00));
+        assert!("X0301".parse::<Dtc>().is_err());
+        assert!("P4301".parse::<Dtc>().is_err());
+
+        assert_eq!(
+            parse_dtcs(&[0x02, 0x01, 0x33, 0xC1, 0x55]).unwrap(),
+            vec![Dtc(0x0133), Dtc(0xC155)]
+        );
+        assert!(parse_dtcs(&[0x02, 0x01, 0x33]).is_err());
+    }
+
+    #[tokio::test]
+    async fn requests() {
+        let mut vin = vec![0x49, 0x02, 0x01];
+        vin.extend(b"1G1JC5444R7252367");
+
+        let mut client = ObdClient::new(server(vec![
+            (vec![0x01, 0x0C], vec![vec![0x41, 0x0C, 0x1A, 0xF8]]),
+            (
+                vec![0x01, 0x00],
+                vec![vec![0x41, 0x00, 0x00, 0x18, 0x00, 0x01]],
+            ),
+            (
+                vec![0x01, 0x20],
+                vec![vec![0x41, 0x20, 0x00, 0x02, 0x00, 0x00]],
+            ),
+            (vec![0x03], vec![vec![0x43, 0x01, 0x03, 0x01]]),
+            (vec![0x09, 0x02], vec![vin]),
+        ]));
+
+        let rpm = client.current_data(0x0C).await.unwrap();
+        assert_eq!(rpm.value, 1726.0);
+
+        assert_eq!(
+            client.supported_pids().await.unwrap(),
+            vec![0x0C, 0x0D, 0x2F]
+        );
+        assert_eq!(client.stored_dtcs().await.unwrap(), vec![Dtc(0x0301)]);
+        assert_eq!(client.vin().await.unwrap(), "1G1JC5444R7252367");
+    }
+}
@@ -0,0 +1,162 @@
+//
+// pid.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use std::fmt;
+
+/// Scaling of a standard mode 01/02 parameter ID
+#[derive(Debug)]
+pub struct PidInfo {
+    pub pid: u8,
+    pub name: &'static str,
+    pub unit: &'static str,
+    /// Number of data bytes
+    pub bytes: usize,
+    decode: fn(&[u8]) -> f64,
+}
+
+fn a(data: &[u8]) -> f64 {
+    data[0] as f64
+}
+
+fn ab(data: &[u8]) -> f64 {
+    u16::from_be_bytes([data[0], data[1]]) as f64
+}
+
+fn percent(data: &[u8]) -> f64 {
+    a(data) * 100.0 / 255.0
+}
+
+fn temperature(data: &[u8]) -> f64 {
+    a(data) - 40.0
+}
+
+fn fuel_trim(data: &[u8]) -> f64 {
+    (a(data) - 128.0) * 100.0 / 128.0
+}
+
+#[rustfmt::skip]
+const PIDS: &[PidInfo] = &[
+    PidInfo { pid: 0x04, name: "Calculated engine load", unit: "%", bytes: 1, decode: percent },
+    PidInfo { pid: 0x05, name: "Engine coolant temperature", unit: "degC", bytes: 1, decode: temperature },
+    PidInfo { pid: 0x06, name: "Short term fuel trim bank 1", unit: "%", bytes: 1, decode: fuel_trim },
+    PidInfo { pid: 0x07, name: "Long term fuel trim bank 1", unit: "%", bytes: 1, decode: fuel_trim },
+    PidInfo { pid: 0x08, name: "Short term fuel trim bank 2", unit: "%", bytes: 1, decode: fuel_trim },
+    PidInfo { pid: 0x09, name: "Long term fuel trim bank 2", unit: "%", bytes: 1, decode: fuel_trim },
+    PidInfo { pid: 0x0A, name: "Fuel pressure", unit: "kPa", bytes: 1, decode: |d| a(d) * 3.0 },
+    PidInfo { pid: 0x0B, name: "Intake manifold absolute pressure", unit: "kPa", bytes: 1, decode: a },
+    PidInfo { pid: 0x0C, name: "Engine speed", unit: "rpm", bytes: 2, decode: |d| ab(d) / 4.0 },
+    PidInfo { pid: 0x0D, name: "Vehicle speed", unit: "km/h", bytes: 1, decode: a },
+    PidInfo { pid: 0x0E, name: "Timing advance", unit: "deg", bytes: 1, decode: |d| a(d) / 2.0 - 64.0 },
+    PidInfo { pid: 0x0F, name: "Intake air temperature", unit: "degC", bytes: 1, decode: temperature },
+    PidInfo { pid: 0x10, name: "Mass air flow rate", unit: "g/s", bytes: 2, decode: |d| ab(d) / 100.0 },
+    PidInfo { pid: 0x11, name: "Throttle position", unit: "%", bytes: 1, decode: percent },
+    PidInfo { pid: 0x1F, name: "Run time since engine start", unit: "s", bytes: 2, decode: ab },
+    PidInfo { pid: 0x21, name: "Distance traveled with MIL on", unit: "km", bytes: 2, decode: ab },
+    PidInfo { pid: 0x2F, name: "Fuel tank level", unit: "%", bytes: 1, decode: percent },
+    PidInfo { pid: 0x31, name: "Distance traveled since codes cleared", unit: "km", bytes: 2, decode: ab },
+    PidInfo { pid: 0x33, name: "Absolute barometric pressure", unit: "kPa", bytes: 1, decode: a },
+    PidInfo { pid: 0x42, name: "Control module voltage", unit: "V", bytes: 2, decode: |d| ab(d) / 1000.0 },
+    PidInfo { pid: 0x46, name: "Ambient air temperature", unit: "degC", bytes: 1, decode: temperature },
+    PidInfo { pid: 0x5C, name: "Engine oil temperature", unit: "degC", bytes: 1, decode: temperature },
+    PidInfo { pid: 0x5E, name: "Engine fuel rate", unit: "L/h", bytes: 2, decode: |d| ab(d) / 20.0 },
+];
+
+/// Look up the scaling of a PID
+pub fn pid_info(pid: u8) -> Option<&'static PidInfo> {
+    PIDS.iter().find(|info| info.pid == pid)
+}
+
+/// Check if a PID reports the supported PIDs of the next range (0x00, 0x20, 0x40, ...)
+pub fn is_support_pid(pid: u8) -> bool {
+    pid.is_multiple_of(0x20)
+}
+
+/// A decoded parameter value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidValue {
+    pub info: &'static PidInfo,
+    pub value: f64,
+}
+
+impl PartialEq for PidInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid == other.pid
+    }
+}
+
+impl PidInfo {
+    /// Decode the data bytes of the PID, `None` if too short
+    pub fn decode(&'static self, data: &[u8]) -> Option<PidValue> {
+        (data.len() >= self.bytes).then(|| PidValue {
+            info: self,
+            value: (self.decode)(data),
+        })
+    }
+}
+
+impl fmt::Display for PidValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {:.2} {}",
+            self.info.name, self.value, self.info.unit
+        )
+    }
+}
+
+/// PIDs reported as supported by a support PID (0x00, 0x20, ...) response
+pub fn supported_pids(base: u8, data: &[u8]) -> Vec<u8> {
+    data.iter()
+        .take(4)
+        .enumerate()
+        .flat_map(|(byte, &bits)| {
+            (0..8)
+                .filter(move |bit| bits & (0x80 >> bit) != 0)
+                .map(move |bit| (byte * 8 + bit + 1) as u8)
+        })
+        .map(|offset| base.wrapping_add(offset))
+        .collect()
+}
+
+/// Monitor status (PID 0x01): MIL state and number of confirmed DTCs
+pub fn monitor_status(data: &[u8]) -> Option<(bool, u8)> {
+    data.first().map(|&a| (a & 0x80 != 0, a & 0x7F))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling() {
+        let rpm = pid_info(0x0C).unwrap().decode(&[0x1A, 0xF8]).unwrap();
+        assert_eq!(rpm.value, 1726.0);
+        assert_eq!(rpm.to_string(), "Engine speed = 1726.00 rpm");
+
+        let coolant = pid_info(0x05).unwrap().decode(&[0x7B]).unwrap();
+        assert_eq!(coolant.value, 83.0);
+
+        let trim = pid_info(0x06).unwrap().decode(&[0x80]).unwrap();
+        assert_eq!(trim.value, 0.0);
+
+        assert!(pid_info(0x0C).unwrap().decode(&[0x1A]).is_none());
+        assert!(pid_info(0x02).is_none());
+    }
+
+    #[test]
+    fn support_bitmap() {
+        assert_eq!(
+            supported_pids(0x00, &[0xBE, 0x1F, 0xA8, 0x13]),
+            vec![
+                0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x13, 0x15,
+                0x1C, 0x1F, 0x20
+            ]
+        );
+        assert!(is_support_pid(0x20));
+        assert_eq!(monitor_status(&[0x83, 0x07, 0xE5, 0x00]), Some((true, 3)));
+    }
+}
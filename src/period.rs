@@ -0,0 +1,177 @@
+//
+// period.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Period deviation detection for cyclic IDs
+//!
+//! The nominal period of an ID is learned from its first intervals, once they agree within the
+//! tolerance. Later frames are flagged when they arrive earlier or later than that.
+
+use std::time::Duration;
+
+/// Intervals that must agree before a period is learned
+const LEARN_SAMPLES: usize = 8;
+
+/// How a frame's interval compares to the nominal period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deviation {
+    Early,
+    Late,
+}
+
+/// Learns the period of one ID and counts the frames outside the tolerance
+#[derive(Debug, Clone)]
+pub struct PeriodTracker {
+    /// Allowed deviation, as a fraction of the period
+    tolerance: f64,
+    last: Option<Duration>,
+    samples: Vec<Duration>,
+    nominal: Option<Duration>,
+    early: u64,
+    late: u64,
+    deviation: Option<Deviation>,
+}
+
+impl PeriodTracker {
+    /// Create a tracker allowing `tolerance` percent of deviation from the period
+    pub fn new(tolerance: f64) -> Self {
+        PeriodTracker {
+            tolerance: tolerance / 100.0,
+            last: None,
+            samples: Vec::with_capacity(LEARN_SAMPLES),
+            nominal: None,
+            early: 0,
+            late: 0,
+            deviation: None,
+        }
+    }
+
+    /// Apply a frame received at `time`, returning its deviation if it was early or late
+    pub fn update(&mut self, time: Duration) -> Option<Deviation> {
+        let last = self.last.replace(time)?;
+        let interval = time.saturating_sub(last);
+
+        self.deviation = match self.nominal {
+            Some(nominal) => self.classify(nominal, interval),
+            None => {
+                self.learn(interval);
+                None
+            }
+        };
+        match self.deviation {
+            Some(Deviation::Early) => self.early += 1,
+            Some(Deviation::Late) => self.late += 1,
+            None => {}
+        }
+
+        self.deviation
+    }
+
+    /// Whether the next frame is already late at `now`, e.g. because its sender stopped
+    pub fn overdue(&self, now: Duration) -> bool {
+        match (self.nominal, self.last) {
+            (Some(nominal), Some(last)) => {
+                self.classify(nominal, now.saturating_sub(last)) == Some(Deviation::Late)
+            }
+            _ => false,
+        }
+    }
+
+    /// Learned period, `None` until the ID is found to be cyclic
+    pub fn nominal(&self) -> Option<Duration> {
+        self.nominal
+    }
+
+    /// Deviation of the last frame
+    pub fn deviation(&self) -> Option<Deviation> {
+        self.deviation
+    }
+
+    pub fn early(&self) -> u64 {
+        self.early
+    }
+
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    fn classify(&self, nominal: Duration, interval: Duration) -> Option<Deviation> {
+        let margin = nominal.mul_f64(self.tolerance);
+        if interval + margin < nominal {
+            Some(Deviation::Early)
+        } else if interval > nominal + margin {
+            Some(Deviation::Late)
+        } else {
+            None
+        }
+    }
+
+    /// Collect intervals, keeping the latest until they agree on a period
+    fn learn(&mut self, interval: Duration) {
+        if self.samples.len() == LEARN_SAMPLES {
+            self.samples.remove(0);
+        }
+        self.samples.push(interval);
+        if self.samples.len() < LEARN_SAMPLES {
+            return;
+        }
+
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let median = sorted[LEARN_SAMPLES / 2];
+
+        let cyclic = !median.is_zero()
+            && self
+                .samples
+                .iter()
+                .all(|&sample| self.classify(median, sample).is_none());
+        if cyclic {
+            self.nominal = Some(median);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn learn_and_flag() {
+        let mut tracker = PeriodTracker::new(20.0);
+
+        let mut time = ms(0);
+        for _ in 0..=LEARN_SAMPLES {
+            assert_eq!(tracker.update(time), None);
+            time += ms(100);
+        }
+        assert_eq!(tracker.nominal(), Some(ms(100)));
+
+        // 110ms is within 20%, 150ms is late, 50ms is early
+        assert_eq!(tracker.update(time + ms(10)), None);
+        assert_eq!(tracker.update(time + ms(160)), Some(Deviation::Late));
+        assert_eq!(tracker.update(time + ms(210)), Some(Deviation::Early));
+        assert_eq!((tracker.early(), tracker.late()), (1, 1));
+
+        assert!(!tracker.overdue(time + ms(300)));
+        assert!(tracker.overdue(time + ms(400)));
+    }
+
+    #[test]
+    fn irregular_ids_are_not_learned() {
+        let mut tracker = PeriodTracker::new(20.0);
+        let mut time = ms(0);
+        for i in 0..32 {
+            tracker.update(time);
+            time += ms(if i % 2 == 0 { 10 } else { 500 });
+        }
+        assert_eq!(tracker.nominal(), None);
+        assert!(!tracker.overdue(time + ms(10_000)));
+    }
+}
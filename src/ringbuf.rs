@@ -0,0 +1,109 @@
+//
+// ringbuf.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use std::collections::{vec_deque, VecDeque};
+
+/// Fixed-capacity buffer that overwrites its oldest item when full
+///
+/// Counts the overwritten items, so views built on it can show what was lost.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    overwritten: u64,
+}
+
+impl<T> RingBuffer<T> {
+    /// Create an empty buffer, a capacity of zero keeps no items
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            overwritten: 0,
+        }
+    }
+
+    /// Add an item, returning the oldest item if it was overwritten
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if self.capacity == 0 {
+            self.overwritten += 1;
+            return Some(item);
+        }
+
+        let oldest = match self.items.len() == self.capacity {
+            true => self.items.pop_front(),
+            false => None,
+        };
+        if oldest.is_some() {
+            self.overwritten += 1;
+        }
+        self.items.push_back(item);
+
+        oldest
+    }
+
+    /// Most recent item
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Items from oldest to newest
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of items overwritten since the buffer was created
+    pub fn overwritten(&self) -> u64 {
+        self.overwritten
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrite_oldest() {
+        let mut buf = RingBuffer::new(3);
+        for i in 0..3 {
+            assert_eq!(buf.push(i), None);
+        }
+        assert_eq!(buf.push(3), Some(0));
+        assert_eq!(buf.push(4), Some(1));
+
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(buf.last(), Some(&4));
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.overwritten(), 2);
+
+        let mut empty = RingBuffer::new(0);
+        assert_eq!(empty.push(1), Some(1));
+        assert!(empty.is_empty());
+        assert_eq!(empty.overwritten(), 1);
+    }
+}
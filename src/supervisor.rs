@@ -0,0 +1,125 @@
+//
+// supervisor.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use anyhow::anyhow;
+use tokio::task::JoinSet;
+
+use std::future::Future;
+
+/// Runs the tasks of a command as a group
+///
+/// The group stops when a task fails, a main task completes, or on Ctrl-C. The remaining tasks
+/// are then cancelled, dropping their state, so guards like the terminal's are always restored.
+#[derive(Default)]
+pub struct Supervisor {
+    tasks: JoinSet<(bool, anyhow::Result<()>)>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn a task whose completion stops the group, e.g. a UI the user quits
+    pub fn spawn_main<F>(&mut self, task: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.tasks.spawn(async move { (true, task.await) });
+    }
+
+    /// Spawn a task that only stops the group when it fails
+    pub fn spawn<F>(&mut self, task: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.tasks.spawn(async move { (false, task.await) });
+    }
+
+    /// Wait for the group to stop, returning the first error
+    pub async fn run(self) -> anyhow::Result<()> {
+        self.run_until(tokio::signal::ctrl_c()).await
+    }
+
+    /// Wait for the group to stop, or for `stop` to complete instead of Ctrl-C
+    pub async fn run_until<F>(mut self, stop: F) -> anyhow::Result<()>
+    where
+        F: Future<Output = std::io::Result<()>>,
+    {
+        tokio::pin!(stop);
+
+        let res = loop {
+            tokio::select! {
+                Some(joined) = self.tasks.join_next() => match joined {
+                    Ok((true, res)) | Ok((false, res @ Err(_))) => break res,
+                    Ok((false, Ok(()))) => {}
+                    Err(e) if e.is_panic() => break Err(anyhow!("Task panicked")),
+                    Err(_) => {}
+                },
+                res = &mut stop => break res.map_err(Into::into),
+            }
+        };
+
+        self.tasks.shutdown().await;
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        future::pending,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    /// Sets its flag when dropped, as a cancelled task drops its state
+    struct Guard(Arc<AtomicBool>);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn first_error_cancels_siblings() {
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        let mut supervisor = Supervisor::new();
+        let guard = Guard(dropped.clone());
+        supervisor.spawn(async move {
+            let _guard = guard;
+            pending::<()>().await;
+            Ok(())
+        });
+        supervisor.spawn(async { Ok(()) });
+        supervisor.spawn(async { Err(anyhow!("failed")) });
+
+        let res = supervisor.run_until(pending()).await;
+        assert_eq!(res.unwrap_err().to_string(), "failed");
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn main_task_stops_group() {
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn(pending());
+        supervisor.spawn_main(async { Ok(()) });
+        assert!(supervisor.run_until(pending()).await.is_ok());
+
+        // Background tasks completing leaves the group running until stopped
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn(async { Ok(()) });
+        assert!(supervisor.run_until(async { Ok(()) }).await.is_ok());
+    }
+}
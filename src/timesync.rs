@@ -0,0 +1,203 @@
+//
+// timesync.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! Clock offset and drift between a remote capture host and this one
+//!
+//! Remote drivers stamp each frame with the sender's clock. The difference to the local receive
+//! time is the clock offset plus the network delay, which is never negative, so the smallest
+//! difference within each window is the best view of the offset. A line fitted through those
+//! minima gives the offset and how fast it drifts, and maps sender timestamps onto the local
+//! clock so captures from several hosts line up.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Sender time covered by each minimum
+const WINDOW: f64 = 1.0;
+/// Windows the fit is made over
+const WINDOWS: usize = 64;
+
+/// How well the remote clock is tracked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncQuality {
+    /// Timestamps seen
+    pub samples: u64,
+    /// Local minus remote time now, in seconds
+    pub offset: f64,
+    /// Rate the offset changes at, in parts per million
+    pub drift_ppm: f64,
+    /// RMS distance of the window minima from the fitted line, in seconds
+    pub jitter: f64,
+}
+
+impl fmt::Display for SyncQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "offset {:+.6}s, jitter {:.3} ms, drift {:+.1} ppm over {} samples",
+            self.offset,
+            self.jitter * 1e3,
+            self.drift_ppm,
+            self.samples
+        )
+    }
+}
+
+/// Estimates the offset and drift of one remote clock
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    /// Remote time of the first sample, the fit is made relative to it for precision
+    origin: Option<f64>,
+    /// Start of each window relative to the origin and the smallest offset within it
+    minima: VecDeque<(f64, f64)>,
+    samples: u64,
+    /// Intercept and slope of the fitted offset
+    fit: Option<(f64, f64)>,
+    residual: f64,
+    shared: SyncHandle,
+}
+
+/// Latest estimate of a [`ClockSync`], read while the driver receives in another task
+#[derive(Debug, Clone, Default)]
+pub struct SyncHandle(Arc<Mutex<Option<SyncQuality>>>);
+
+impl SyncHandle {
+    /// Estimate so far, `None` before the first sample
+    pub fn quality(&self) -> Option<SyncQuality> {
+        *self.0.lock().unwrap()
+    }
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a frame sent at `remote` by the sender's clock and received at `local`
+    pub fn update(&mut self, remote: SystemTime, local: SystemTime) {
+        let (remote, local) = (secs(remote), secs(local));
+        let origin = *self.origin.get_or_insert(remote);
+        let time = remote - origin;
+        let offset = local - remote;
+        self.samples += 1;
+
+        let window = (time / WINDOW).floor() * WINDOW;
+        match self.minima.back_mut() {
+            Some((start, min)) if *start == window => *min = min.min(offset),
+            _ => {
+                if self.minima.len() == WINDOWS {
+                    self.minima.pop_front();
+                }
+                self.minima.push_back((window, offset));
+            }
+        }
+
+        self.fit();
+        *self.shared.0.lock().unwrap() = self.quality();
+    }
+
+    /// Handle to the estimate, updated with every sample
+    pub fn handle(&self) -> SyncHandle {
+        self.shared.clone()
+    }
+
+    /// Map a sender timestamp onto the local clock, `None` before the first sample
+    pub fn correct(&self, remote: SystemTime) -> Option<SystemTime> {
+        let offset = self.offset_at(secs(remote))?;
+        let local = secs(remote) + offset;
+        Some(UNIX_EPOCH + Duration::from_secs_f64(local.max(0.0)))
+    }
+
+    /// Estimate so far, `None` before the first sample
+    pub fn quality(&self) -> Option<SyncQuality> {
+        let (_, slope) = self.fit?;
+        let &(last, _) = self.minima.back()?;
+        let origin = self.origin?;
+        Some(SyncQuality {
+            samples: self.samples,
+            offset: self.offset_at(origin + last + WINDOW)?,
+            drift_ppm: slope * 1e6,
+            jitter: self.residual,
+        })
+    }
+
+    fn offset_at(&self, remote: f64) -> Option<f64> {
+        let (intercept, slope) = self.fit?;
+        Some(intercept + slope * (remote - self.origin?))
+    }
+
+    /// Least squares line through the window minima, flat until there are two
+    fn fit(&mut self) {
+        let n = self.minima.len() as f64;
+        let (sum_t, sum_o) = self
+            .minima
+            .iter()
+            .fold((0.0, 0.0), |(st, so), &(t, o)| (st + t, so + o));
+        let (mean_t, mean_o) = (sum_t / n, sum_o / n);
+
+        let (cov, var) = self.minima.iter().fold((0.0, 0.0), |(cov, var), &(t, o)| {
+            (
+                cov + (t - mean_t) * (o - mean_o),
+                var + (t - mean_t).powi(2),
+            )
+        });
+        let slope = if var > 0.0 { cov / var } else { 0.0 };
+        let intercept = mean_o - slope * mean_t;
+
+        let squares = self
+            .minima
+            .iter()
+            .map(|&(t, o)| (o - intercept - slope * t).powi(2))
+            .sum::<f64>();
+        self.residual = (squares / n).sqrt();
+        self.fit = Some((intercept, slope));
+    }
+}
+
+fn secs(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_and_drift() {
+        // The local clock runs 2.5s ahead and gains 50 ppm, frames take 1 to 5 ms to arrive
+        let start = 1_800_000_000.0;
+        let mut sync = ClockSync::new();
+        assert!(sync.quality().is_none());
+
+        for i in 0..3000u64 {
+            let remote = start + i as f64 * 0.01;
+            let delay = 0.001 + (i * 7919 % 97) as f64 / 97.0 * 0.004;
+            let local = remote + 2.5 + 50e-6 * (remote - start) + delay;
+            sync.update(
+                UNIX_EPOCH + Duration::from_secs_f64(remote),
+                UNIX_EPOCH + Duration::from_secs_f64(local),
+            );
+        }
+
+        let quality = sync.quality().unwrap();
+        assert_eq!(quality.samples, 3000);
+        assert!((quality.drift_ppm - 50.0).abs() < 5.0, "{}", quality);
+        assert!((quality.offset - 2.5025).abs() < 0.0005, "{}", quality);
+        assert!(quality.jitter < 0.0002, "{}", quality);
+        assert_eq!(sync.handle().quality(), Some(quality));
+
+        let remote = UNIX_EPOCH + Duration::from_secs_f64(start + 60.0);
+        let local = secs(sync.correct(remote).unwrap()) - start;
+        assert!((local - (62.5 + 0.003 + 0.001)).abs() < 0.001, "{}", local);
+    }
+}
@@ -0,0 +1,302 @@
+//
+// client.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::{
+    service_name, DiagnosticSession, Nrc, ResetType, SessionTiming, UdsError,
+    DIAGNOSTIC_SESSION_CONTROL, ECU_RESET, NEGATIVE_RESPONSE, POSITIVE_RESPONSE_OFFSET,
+    REQUEST_DOWNLOAD, REQUEST_TRANSFER_EXIT, SECURITY_ACCESS, SUPPRESS_POSITIVE_RESPONSE,
+    TESTER_PRESENT, TRANSFER_DATA, WRITE_DATA_BY_IDENTIFIER,
+};
+
+use crate::isotp::IsoTpChannel;
+
+use std::sync::Arc;
+
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::{self, Duration, Instant},
+};
+
+/// Extra time allowed on top of the server's P2 for bus and scheduling latency
+const P2_MARGIN: Duration = Duration::from_millis(100);
+
+/// Computes the key for a SecurityAccess seed
+pub trait SeedKey {
+    fn key(&self, level: u8, seed: &[u8]) -> Vec<u8>;
+}
+
+impl<F> SeedKey for F
+where
+    F: Fn(u8, &[u8]) -> Vec<u8>,
+{
+    fn key(&self, level: u8, seed: &[u8]) -> Vec<u8> {
+        self(level, seed)
+    }
+}
+
+/// Diagnostic client (tester) for a single server
+pub struct UdsClient {
+    channel: IsoTpChannel,
+    timing: SessionTiming,
+    last_request: Instant,
+}
+
+impl UdsClient {
+    pub fn new(channel: IsoTpChannel) -> Self {
+        UdsClient {
+            channel,
+            timing: SessionTiming::default(),
+            last_request: Instant::now(),
+        }
+    }
+
+    /// Timing of the active session
+    pub fn timing(&self) -> SessionTiming {
+        self.timing
+    }
+
+    pub fn into_inner(self) -> IsoTpChannel {
+        self.channel
+    }
+
+    /// Send a request and wait for its positive response
+    ///
+    /// Returns the response without the service identifier. Response pending replies extend the
+    /// wait to P2*.
+    pub async fn request(&mut self, data: &[u8]) -> Result<Vec<u8>, UdsError> {
+        self.channel.send(data).await?;
+        self.last_request = Instant::now();
+
+        let sid = data[0];
+        let service = service_name(sid).unwrap_or("request");
+        let mut deadline = Instant::now() + self.timing.p2 + P2_MARGIN;
+
+        loop {
+            let response = time::timeout_at(deadline, self.channel.recv())
+                .await
+                .map_err(|_| UdsError::Timeout(service))??;
+
+            match response.as_slice() {
+                [NEGATIVE_RESPONSE, rsid, code, ..] if *rsid == sid => match Nrc::from(*code) {
+                    Nrc::ResponsePending => {
+                        deadline = Instant::now() + self.timing.p2_extended + P2_MARGIN;
+                    }
+                    nrc => return Err(UdsError::Negative { service, nrc }),
+                },
+                [rsid, rest @ ..] if *rsid == sid.wrapping_add(POSITIVE_RESPONSE_OFFSET) => {
+                    return Ok(rest.to_vec())
+                }
+                // Late responses to earlier requests
+                _ => {}
+            }
+        }
+    }
+
+    /// Send a request without waiting for a response
+    pub async fn send(&mut self, data: &[u8]) -> Result<(), UdsError> {
+        self.channel.send(data).await?;
+        self.last_request = Instant::now();
+        Ok(())
+    }
+
+    /// Switch diagnostic session, updating the timing used for later requests
+    pub async fn diagnostic_session_control(
+        &mut self,
+        session: DiagnosticSession,
+    ) -> Result<SessionTiming, UdsError> {
+        let session = u8::from(session);
+        let response = self.request(&[DIAGNOSTIC_SESSION_CONTROL, session]).await?;
+
+        if response.first() != Some(&session) {
+            return Err(UdsError::UnexpectedResponse("DiagnosticSessionControl"));
+        }
+
+        self.timing = SessionTiming::parse(&response[1..]).unwrap_or_default();
+        Ok(self.timing)
+    }
+
+    /// Unlock a security level
+    ///
+    /// `level` is the odd request seed sub-function, the key is sent with `level + 1`.
+    pub async fn security_access(
+        &mut self,
+        level: u8,
+        seed_key: &impl SeedKey,
+    ) -> Result<(), UdsError> {
+        let response = self.request(&[SECURITY_ACCESS, level]).await?;
+
+        let seed = match response.split_first() {
+            Some((&echo, seed)) if echo == level => seed,
+            _ => return Err(UdsError::UnexpectedResponse("SecurityAccess")),
+        };
+
+        // A zero seed means the level is already unlocked
+        if seed.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+
+        let mut request = vec![SECURITY_ACCESS, level.wrapping_add(1)];
+        request.extend(seed_key.key(level, seed));
+        self.request(&request).await?;
+
+        Ok(())
+    }
+
+    pub async fn ecu_reset(&mut self, reset: ResetType) -> Result<(), UdsError> {
+        self.request(&[ECU_RESET, reset.into()]).await.map(|_| ())
+    }
+
+    pub async fn write_data_by_identifier(
+        &mut self,
+        did: u16,
+        data: &[u8],
+    ) -> Result<(), UdsError> {
+        let mut request = vec![WRITE_DATA_BY_IDENTIFIER];
+        request.extend(did.to_be_bytes());
+        request.extend_from_slice(data);
+
+        let response = self.request(&request).await?;
+        if !response.starts_with(&did.to_be_bytes()) {
+            return Err(UdsError::UnexpectedResponse("WriteDataByIdentifier"));
+        }
+
+        Ok(())
+    }
+
+    /// Start a download to the server, returning the maximum length of TransferData requests
+    pub async fn request_download(&mut self, address: u32, size: u32) -> Result<usize, UdsError> {
+        // No compression or encryption, 4 byte address and size
+        let mut request = vec![REQUEST_DOWNLOAD, 0x00, 0x44];
+        request.extend(address.to_be_bytes());
+        request.extend(size.to_be_bytes());
+
+        let response = self.request(&request).await?;
+
+        let (&format, length) = response
+            .split_first()
+            .ok_or(UdsError::UnexpectedResponse("RequestDownload"))?;
+        let length = length
+            .get(..(format >> 4) as usize)
+            .filter(|length| !length.is_empty() && length.len() <= 8)
+            .ok_or(UdsError::UnexpectedResponse("RequestDownload"))?;
+
+        Ok(length
+            .iter()
+            .fold(0usize, |len, &b| (len << 8) | b as usize))
+    }
+
+    /// Send one block of a download
+    pub async fn transfer_data(&mut self, sequence: u8, data: &[u8]) -> Result<(), UdsError> {
+        let mut request = vec![TRANSFER_DATA, sequence];
+        request.extend_from_slice(data);
+
+        let response = self.request(&request).await?;
+        if response.first() != Some(&sequence) {
+            return Err(UdsError::UnexpectedResponse("TransferData"));
+        }
+
+        Ok(())
+    }
+
+    pub async fn request_transfer_exit(&mut self) -> Result<(), UdsError> {
+        self.request(&[REQUEST_TRANSFER_EXIT]).await.map(|_| ())
+    }
+
+    /// Keep the current session alive
+    pub async fn tester_present(&mut self, suppress_response: bool) -> Result<(), UdsError> {
+        if suppress_response {
+            self.send(&[TESTER_PRESENT, SUPPRESS_POSITIVE_RESPONSE])
+                .await
+        } else {
+            self.request(&[TESTER_PRESENT, 0x00]).await.map(|_| ())
+        }
+    }
+
+    /// Send TesterPresent whenever the client has been idle for `period`
+    ///
+    /// The keep-alive stops when the returned handle is dropped.
+    pub fn keep_alive(client: Arc<Mutex<UdsClient>>, period: Duration) -> KeepAlive {
+        KeepAlive(tokio::spawn(async move {
+            loop {
+                let next = client.lock().await.last_request + period;
+                time::sleep_until(next).await;
+
+                let mut client = client.lock().await;
+                // Other requests also keep the session alive
+                if client.last_request.elapsed() < period {
+                    continue;
+                }
+                if client.tester_present(true).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+}
+
+/// Handle to a TesterPresent keep-alive task
+pub struct KeepAlive(JoinHandle<()>);
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{isotp::IsoTpError, uds::tests::server};
+
+    #[tokio::test]
+    async fn session_control() {
+        let mut client = server(vec![(
+            vec![0x10, 0x03],
+            vec![
+                vec![0x7F, 0x10, 0x78],
+                vec![0x50, 0x03, 0x00, 0x19, 0x01, 0xF4],
+            ],
+        )]);
+
+        let timing = client
+            .diagnostic_session_control(DiagnosticSession::Extended)
+            .await
+            .unwrap();
+        assert_eq!(timing.p2, Duration::from_millis(25));
+        assert_eq!(timing.p2_extended, Duration::from_millis(5000));
+    }
+
+    #[tokio::test]
+    async fn security_access() {
+        let mut client = server(vec![
+            (vec![0x27, 0x01], vec![vec![0x67, 0x01, 0x12, 0x34]]),
+            (vec![0x27, 0x02, 0xED, 0xCB], vec![vec![0x67, 0x02]]),
+            (vec![0x27, 0x01], vec![vec![0x67, 0x01, 0x12, 0x34]]),
+            (vec![0x27, 0x02, 0x00, 0x00], vec![vec![0x7F, 0x27, 0x35]]),
+        ]);
+
+        let invert = |_: u8, seed: &[u8]| seed.iter().map(|b| !b).collect::<Vec<u8>>();
+        client.security_access(0x01, &invert).await.unwrap();
+
+        let zeros = |_: u8, _: &[u8]| vec![0, 0];
+        let err = client.security_access(0x01, &zeros).await.unwrap_err();
+        assert_eq!(err.to_string(), "SecurityAccess rejected: invalidKey");
+    }
+
+    #[tokio::test]
+    async fn no_response() {
+        let mut client = server(vec![]);
+        let err = client.tester_present(false).await.unwrap_err();
+        // The server is gone once its script is done
+        assert!(matches!(
+            err,
+            UdsError::Timeout("TesterPresent") | UdsError::Transport(IsoTpError::Closed)
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+//
+// flash.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::{DiagnosticSession, ResetType, SeedKey, UdsClient, UdsError};
+
+use crate::isotp::IsoTpError;
+
+use std::sync::Arc;
+
+use tokio::{sync::Mutex, time::Duration};
+
+/// Idle time before a TesterPresent is sent, well within the usual S3 server timeout of 5s
+const KEEP_ALIVE_PERIOD: Duration = Duration::from_millis(2000);
+
+/// Parameters of the programming sequence
+#[derive(Debug, Clone, Default)]
+pub struct FlashOptions {
+    /// Memory address the image is downloaded to
+    pub address: u32,
+    /// Security level to unlock before programming
+    pub security_level: Option<u8>,
+    /// Data identifiers written before the download, e.g. a programming fingerprint
+    pub data_identifiers: Vec<(u16, Vec<u8>)>,
+    /// Hard reset the server once the download is complete
+    pub reset: bool,
+}
+
+/// Download an image to the server
+///
+/// Runs DiagnosticSessionControl (programming), SecurityAccess, WriteDataByIdentifier,
+/// RequestDownload, TransferData, RequestTransferExit and ECUReset. `progress` is called with
+/// the number of bytes transferred and the image size after every block.
+pub async fn flash(
+    client: &Arc<Mutex<UdsClient>>,
+    image: &[u8],
+    options: &FlashOptions,
+    seed_key: &impl SeedKey,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<(), UdsError> {
+    let size = u32::try_from(image.len()).map_err(|_| IsoTpError::TooLong(image.len()))?;
+
+    let _keep_alive = UdsClient::keep_alive(client.clone(), KEEP_ALIVE_PERIOD);
+
+    client
+        .lock()
+        .await
+        .diagnostic_session_control(DiagnosticSession::Programming)
+        .await?;
+
+    if let Some(level) = options.security_level {
+        client.lock().await.security_access(level, seed_key).await?;
+    }
+
+    for (did, data) in &options.data_identifiers {
+        client
+            .lock()
+            .await
+            .write_data_by_identifier(*did, data)
+            .await?;
+    }
+
+    let max_length = client
+        .lock()
+        .await
+        .request_download(options.address, size)
+        .await?;
+
+    // The maximum length includes the service identifier and block sequence counter
+    let block_size = max_length
+        .checked_sub(2)
+        .filter(|&size| size > 0)
+        .ok_or(UdsError::UnexpectedResponse("RequestDownload"))?;
+
+    let mut transferred = 0;
+    progress(transferred, image.len());
+
+    for (i, block) in image.chunks(block_size).enumerate() {
+        // The counter starts at 1 and wraps to 0
+        let sequence = ((i + 1) & 0xFF) as u8;
+        client.lock().await.transfer_data(sequence, block).await?;
+
+        transferred += block.len();
+        progress(transferred, image.len());
+    }
+
+    client.lock().await.request_transfer_exit().await?;
+
+    if options.reset {
+        client.lock().await.ecu_reset(ResetType::Hard).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::uds::tests::server;
+
+    #[tokio::test]
+    async fn programming_sequence() {
+        let client = server(vec![
+            (
+                vec![0x10, 0x02],
+                vec![vec![0x50, 0x02, 0x00, 0x32, 0x01, 0xF4]],
+            ),
+            (vec![0x27, 0x11], vec![vec![0x67, 0x11, 0xAA]]),
+            (vec![0x27, 0x12, 0x55], vec![vec![0x67, 0x12]]),
+            (vec![0x2E, 0xF1, 0x5A, 0x01], vec![vec![0x6E, 0xF1, 0x5A]]),
+            (
+                vec![0x34, 0x00, 0x44, 0, 0, 0x80, 0, 0, 0, 0, 10],
+                vec![vec![0x74, 0x20, 0x00, 0x06]],
+            ),
+            (vec![0x36, 0x01, 0, 1, 2, 3], vec![vec![0x76, 0x01]]),
+            (
+                vec![0x36, 0x02, 4, 5, 6, 7],
+                vec![vec![0x7F, 0x36, 0x78], vec![0x76, 0x02]],
+            ),
+            (vec![0x36, 0x03, 8, 9], vec![vec![0x76, 0x03]]),
+            (vec![0x37], vec![vec![0x77]]),
+            (vec![0x11, 0x01], vec![vec![0x51, 0x01]]),
+        ]);
+        let client = Arc::new(Mutex::new(client));
+
+        let options = FlashOptions {
+            address: 0x8000,
+            security_level: Some(0x11),
+            data_identifiers: vec![(0xF15A, vec![0x01])],
+            reset: true,
+        };
+        let image: Vec<u8> = (0..10).collect();
+        let key = |_: u8, seed: &[u8]| seed.iter().map(|b| !b).collect::<Vec<u8>>();
+
+        let mut updates = vec![];
+        flash(&client, &image, &options, &key, |done, _| {
+            updates.push(done)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(updates, vec![0, 4, 8, 10]);
+    }
+}
@@ -0,0 +1,142 @@
+//
+// functional.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+use super::{Nrc, SessionTiming, UdsError, NEGATIVE_RESPONSE, POSITIVE_RESPONSE_OFFSET};
+
+use crate::isotp::FunctionalChannel;
+
+use embedded_can::Id;
+use tokio::time::{Duration, Instant};
+
+/// Response of one ECU to a functional request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// Physical response ID of the ECU
+    pub id: Id,
+    /// Response without the service identifier, or the negative response code
+    pub result: Result<Vec<u8>, Nrc>,
+}
+
+/// Diagnostic client sending functional requests to all ECUs
+pub struct FunctionalClient {
+    channel: FunctionalChannel,
+    window: Duration,
+    timing: SessionTiming,
+}
+
+impl FunctionalClient {
+    /// Create a client collecting responses for `window` after each request
+    pub fn new(channel: FunctionalChannel, window: Duration) -> Self {
+        FunctionalClient {
+            channel,
+            window,
+            timing: SessionTiming::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> FunctionalChannel {
+        self.channel
+    }
+
+    /// Send a request and collect the final response of every ECU answering within the window
+    ///
+    /// ECUs replying with response pending are waited for up to P2* after their last reply.
+    pub async fn request(&mut self, data: &[u8]) -> Result<Vec<Response>, UdsError> {
+        self.channel.send(data).await?;
+
+        let sid = data[0];
+        let window = Instant::now() + self.window;
+        let mut pending: Vec<(Id, Instant)> = vec![];
+        let mut responses: Vec<Response> = vec![];
+
+        loop {
+            let deadline = pending
+                .iter()
+                .map(|(_, deadline)| *deadline)
+                .fold(window, Instant::max);
+
+            let Some((id, response)) = self.channel.recv_until(deadline).await? else {
+                break;
+            };
+
+            let result = match response.as_slice() {
+                [NEGATIVE_RESPONSE, rsid, code, ..] if *rsid == sid => match Nrc::from(*code) {
+                    Nrc::ResponsePending => {
+                        let deadline = Instant::now() + self.timing.p2_extended;
+                        pending.retain(|(ecu, _)| *ecu != id);
+                        pending.push((id, deadline));
+                        continue;
+                    }
+                    nrc => Err(nrc),
+                },
+                [rsid, rest @ ..] if *rsid == sid.wrapping_add(POSITIVE_RESPONSE_OFFSET) => {
+                    Ok(rest.to_vec())
+                }
+                // Late responses to earlier requests
+                _ => continue,
+            };
+
+            pending.retain(|(ecu, _)| *ecu != id);
+            responses.push(Response { id, result });
+        }
+
+        Ok(responses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        drivers::{loopback::LoopbackDriver, AsyncCanDriverPtr},
+        frame::CanFrame,
+        isotp::IsoTpConfig,
+    };
+
+    use embedded_can::{Frame, StandardId};
+
+    fn id(raw: u16) -> Id {
+        Id::Standard(StandardId::new(raw).unwrap())
+    }
+
+    #[tokio::test]
+    async fn collect_responses() {
+        let (tester, ecus) = LoopbackDriver::pair();
+        let channel =
+            FunctionalChannel::new(Box::new(tester), IsoTpConfig::new(id(0x7DF), id(0x7E8)));
+        let mut client = FunctionalClient::new(channel, Duration::from_millis(50));
+
+        let mut ecus: AsyncCanDriverPtr = Box::new(ecus);
+        tokio::spawn(async move {
+            ecus.recv().await.unwrap();
+            for (raw, data) in [
+                (0x7E8, [0x03, 0x7F, 0x01, 0x78]),
+                (0x7E9, [0x03, 0x7F, 0x01, 0x12]),
+                (0x7E8, [0x03, 0x41, 0x0D, 0x32]),
+            ] {
+                ecus.send(CanFrame::new(id(raw), &data).unwrap()).await;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        });
+
+        let responses = client.request(&[0x01, 0x0D]).await.unwrap();
+        assert_eq!(
+            responses,
+            vec![
+                Response {
+                    id: id(0x7E9),
+                    result: Err(Nrc::SubFunctionNotSupported)
+                },
+                Response {
+                    id: id(0x7E8),
+                    result: Ok(vec![0x0D, 0x32])
+                },
+            ]
+        );
+    }
+}
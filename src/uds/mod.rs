@@ -0,0 +1,378 @@
+//
+// mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 16 2026
+//
+
+//! UDS (ISO 14229) diagnostic services over ISO-TP
+
+pub mod client;
+pub mod flash;
+pub mod functional;
+
+pub use client::{KeepAlive, SeedKey, UdsClient};
+pub use functional::FunctionalClient;
+
+use crate::isotp::IsoTpError;
+
+use thiserror::Error;
+
+use std::{fmt, time::Duration};
+
+// Service identifiers
+pub const DIAGNOSTIC_SESSION_CONTROL: u8 = 0x10;
+pub const ECU_RESET: u8 = 0x11;
+pub const CLEAR_DIAGNOSTIC_INFORMATION: u8 = 0x14;
+pub const READ_DTC_INFORMATION: u8 = 0x19;
+pub const READ_DATA_BY_IDENTIFIER: u8 = 0x22;
+pub const SECURITY_ACCESS: u8 = 0x27;
+pub const COMMUNICATION_CONTROL: u8 = 0x28;
+pub const WRITE_DATA_BY_IDENTIFIER: u8 = 0x2E;
+pub const ROUTINE_CONTROL: u8 = 0x31;
+pub const REQUEST_DOWNLOAD: u8 = 0x34;
+pub const TRANSFER_DATA: u8 = 0x36;
+pub const REQUEST_TRANSFER_EXIT: u8 = 0x37;
+pub const TESTER_PRESENT: u8 = 0x3E;
+pub const CONTROL_DTC_SETTING: u8 = 0x85;
+
+/// Service identifier of negative responses
+pub const NEGATIVE_RESPONSE: u8 = 0x7F;
+/// Offset added to the service identifier of positive responses
+pub const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+/// Sub-function bit requesting the server to not send a positive response
+pub const SUPPRESS_POSITIVE_RESPONSE: u8 = 0x80;
+
+/// Name of a request service identifier
+pub fn service_name(sid: u8) -> Option<&'static str> {
+    match sid {
+        DIAGNOSTIC_SESSION_CONTROL => Some("DiagnosticSessionControl"),
+        ECU_RESET => Some("ECUReset"),
+        CLEAR_DIAGNOSTIC_INFORMATION => Some("ClearDiagnosticInformation"),
+        READ_DTC_INFORMATION => Some("ReadDTCInformation"),
+        READ_DATA_BY_IDENTIFIER => Some("ReadDataByIdentifier"),
+        SECURITY_ACCESS => Some("SecurityAccess"),
+        COMMUNICATION_CONTROL => Some("CommunicationControl"),
+        WRITE_DATA_BY_IDENTIFIER => Some("WriteDataByIdentifier"),
+        ROUTINE_CONTROL => Some("RoutineControl"),
+        REQUEST_DOWNLOAD => Some("RequestDownload"),
+        TRANSFER_DATA => Some("TransferData"),
+        REQUEST_TRANSFER_EXIT => Some("RequestTransferExit"),
+        TESTER_PRESENT => Some("TesterPresent"),
+        CONTROL_DTC_SETTING => Some("ControlDTCSetting"),
+        _ => None,
+    }
+}
+
+/// Describe a UDS request or response, e.g. "ReadDataByIdentifier DID 0xF190"
+pub fn describe(message: &[u8]) -> String {
+    let Some((&sid, params)) = message.split_first() else {
+        return "empty message".into();
+    };
+
+    if let [NEGATIVE_RESPONSE, rsid, code, ..] = message {
+        return format!("{} negative response: {}", name(*rsid), Nrc::from(*code));
+    }
+
+    match service_name(sid) {
+        Some(service) => format!("{}{}", service, parameters(sid, params, false)),
+        None => match sid
+            .checked_sub(POSITIVE_RESPONSE_OFFSET)
+            .and_then(|request| Some(request).zip(service_name(request)))
+        {
+            Some((request, service)) => format!(
+                "{} positive response{}",
+                service,
+                parameters(request, params, true)
+            ),
+            None => name(sid),
+        },
+    }
+}
+
+fn name(sid: u8) -> String {
+    service_name(sid)
+        .map(String::from)
+        .unwrap_or_else(|| format!("service 0x{:02X}", sid))
+}
+
+/// Describe the leading parameters of a service, such as the sub-function or data identifier
+fn parameters(sid: u8, params: &[u8], response: bool) -> String {
+    let byte = |label: &str| {
+        params
+            .first()
+            .map(|b| format!(" {} 0x{:02X}", label, b & !SUPPRESS_POSITIVE_RESPONSE))
+            .unwrap_or_default()
+    };
+    let did = |data: &[u8]| format!("0x{:04X}", u16::from_be_bytes([data[0], data[1]]));
+
+    match sid {
+        DIAGNOSTIC_SESSION_CONTROL => byte("session"),
+        ECU_RESET => byte("type"),
+        SECURITY_ACCESS => byte("level"),
+        TRANSFER_DATA => byte("block"),
+        READ_DTC_INFORMATION | COMMUNICATION_CONTROL | TESTER_PRESENT | CONTROL_DTC_SETTING => {
+            byte("sub-function")
+        }
+        // Requests can read several identifiers, responses are followed by data of unknown length
+        READ_DATA_BY_IDENTIFIER if !response && params.len() >= 2 => format!(
+            " DID {}",
+            params
+                .chunks_exact(2)
+                .map(did)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        READ_DATA_BY_IDENTIFIER | WRITE_DATA_BY_IDENTIFIER if params.len() >= 2 => {
+            format!(" DID {}", did(params))
+        }
+        ROUTINE_CONTROL if params.len() >= 3 => {
+            format!("{} routine {}", byte("sub-function"), did(&params[1..]))
+        }
+        _ => String::new(),
+    }
+}
+
+/// Negative response code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nrc {
+    GeneralReject,
+    ServiceNotSupported,
+    SubFunctionNotSupported,
+    IncorrectMessageLength,
+    ConditionsNotCorrect,
+    RequestSequenceError,
+    RequestOutOfRange,
+    SecurityAccessDenied,
+    InvalidKey,
+    ExceededNumberOfAttempts,
+    RequiredTimeDelayNotExpired,
+    UploadDownloadNotAccepted,
+    TransferDataSuspended,
+    GeneralProgrammingFailure,
+    WrongBlockSequenceCounter,
+    ResponsePending,
+    SubFunctionNotSupportedInActiveSession,
+    ServiceNotSupportedInActiveSession,
+    Other(u8),
+}
+
+impl From<u8> for Nrc {
+    fn from(value: u8) -> Self {
+        match value {
+            0x10 => Nrc::GeneralReject,
+            0x11 => Nrc::ServiceNotSupported,
+            0x12 => Nrc::SubFunctionNotSupported,
+            0x13 => Nrc::IncorrectMessageLength,
+            0x22 => Nrc::ConditionsNotCorrect,
+            0x24 => Nrc::RequestSequenceError,
+            0x31 => Nrc::RequestOutOfRange,
+            0x33 => Nrc::SecurityAccessDenied,
+            0x35 => Nrc::InvalidKey,
+            0x36 => Nrc::ExceededNumberOfAttempts,
+            0x37 => Nrc::RequiredTimeDelayNotExpired,
+            0x70 => Nrc::UploadDownloadNotAccepted,
+            0x71 => Nrc::TransferDataSuspended,
+            0x72 => Nrc::GeneralProgrammingFailure,
+            0x73 => Nrc::WrongBlockSequenceCounter,
+            0x78 => Nrc::ResponsePending,
+            0x7E => Nrc::SubFunctionNotSupportedInActiveSession,
+            0x7F => Nrc::ServiceNotSupportedInActiveSession,
+            other => Nrc::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for Nrc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nrc::GeneralReject => write!(f, "generalReject"),
+            Nrc::ServiceNotSupported => write!(f, "serviceNotSupported"),
+            Nrc::SubFunctionNotSupported => write!(f, "subFunctionNotSupported"),
+            Nrc::IncorrectMessageLength => write!(f, "incorrectMessageLengthOrInvalidFormat"),
+            Nrc::ConditionsNotCorrect => write!(f, "conditionsNotCorrect"),
+            Nrc::RequestSequenceError => write!(f, "requestSequenceError"),
+            Nrc::RequestOutOfRange => write!(f, "requestOutOfRange"),
+            Nrc::SecurityAccessDenied => write!(f, "securityAccessDenied"),
+            Nrc::InvalidKey => write!(f, "invalidKey"),
+            Nrc::ExceededNumberOfAttempts => write!(f, "exceededNumberOfAttempts"),
+            Nrc::RequiredTimeDelayNotExpired => write!(f, "requiredTimeDelayNotExpired"),
+            Nrc::UploadDownloadNotAccepted => write!(f, "uploadDownloadNotAccepted"),
+            Nrc::TransferDataSuspended => write!(f, "transferDataSuspended"),
+            Nrc::GeneralProgrammingFailure => write!(f, "generalProgrammingFailure"),
+            Nrc::WrongBlockSequenceCounter => write!(f, "wrongBlockSequenceCounter"),
+            Nrc::ResponsePending => write!(f, "requestCorrectlyReceivedResponsePending"),
+            Nrc::SubFunctionNotSupportedInActiveSession => {
+                write!(f, "subFunctionNotSupportedInActiveSession")
+            }
+            Nrc::ServiceNotSupportedInActiveSession => {
+                write!(f, "serviceNotSupportedInActiveSession")
+            }
+            Nrc::Other(code) => write!(f, "0x{:02X}", code),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UdsError {
+    #[error("{0}")]
+    Transport(#[from] IsoTpError),
+    #[error("{service} rejected: {nrc}")]
+    Negative { service: &'static str, nrc: Nrc },
+    #[error("No response to {0}")]
+    Timeout(&'static str),
+    #[error("Unexpected response to {0}")]
+    UnexpectedResponse(&'static str),
+}
+
+/// Diagnostic session type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSession {
+    Default,
+    Programming,
+    Extended,
+    SafetySystem,
+    Other(u8),
+}
+
+impl From<DiagnosticSession> for u8 {
+    fn from(value: DiagnosticSession) -> Self {
+        match value {
+            DiagnosticSession::Default => 0x01,
+            DiagnosticSession::Programming => 0x02,
+            DiagnosticSession::Extended => 0x03,
+            DiagnosticSession::SafetySystem => 0x04,
+            DiagnosticSession::Other(session) => session,
+        }
+    }
+}
+
+/// ECUReset type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetType {
+    Hard,
+    KeyOffOn,
+    Soft,
+    Other(u8),
+}
+
+impl From<ResetType> for u8 {
+    fn from(value: ResetType) -> Self {
+        match value {
+            ResetType::Hard => 0x01,
+            ResetType::KeyOffOn => 0x02,
+            ResetType::Soft => 0x03,
+            ResetType::Other(reset) => reset,
+        }
+    }
+}
+
+/// Server timing reported when changing sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionTiming {
+    /// Time until the server responds to a request
+    pub p2: Duration,
+    /// Time until the server responds after a response pending
+    pub p2_extended: Duration,
+}
+
+impl Default for SessionTiming {
+    fn default() -> Self {
+        SessionTiming {
+            p2: Duration::from_millis(50),
+            p2_extended: Duration::from_millis(5000),
+        }
+    }
+}
+
+impl SessionTiming {
+    /// Parse the session parameter record of a DiagnosticSessionControl response
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let p2 = u16::from_be_bytes([*data.first()?, *data.get(1)?]);
+        let p2_extended = u16::from_be_bytes([*data.get(2)?, *data.get(3)?]);
+
+        Some(SessionTiming {
+            p2: Duration::from_millis(p2 as u64),
+            // P2* is reported in units of 10ms
+            p2_extended: Duration::from_millis(p2_extended as u64 * 10),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use crate::{
+        drivers::loopback::LoopbackDriver,
+        isotp::{IsoTpChannel, IsoTpConfig},
+    };
+
+    use embedded_can::StandardId;
+
+    /// Connect a client to a server answering each request with the next scripted responses
+    pub fn server(script: Vec<(Vec<u8>, Vec<Vec<u8>>)>) -> UdsClient {
+        let tester = StandardId::new(0x7E0).unwrap();
+        let ecu = StandardId::new(0x7E8).unwrap();
+        let (a, b) = LoopbackDriver::pair();
+
+        let mut server = IsoTpChannel::new(Box::new(b), IsoTpConfig::new(ecu, tester));
+        tokio::spawn(async move {
+            for (request, responses) in script {
+                assert_eq!(server.recv().await.unwrap(), request);
+                for response in responses {
+                    server.send(&response).await.unwrap();
+                }
+            }
+        });
+
+        UdsClient::new(IsoTpChannel::new(
+            Box::new(a),
+            IsoTpConfig::new(tester, ecu),
+        ))
+    }
+
+    #[test]
+    fn negative_response_codes() {
+        assert_eq!(Nrc::from(0x35), Nrc::InvalidKey);
+        assert_eq!(
+            Nrc::from(0x78).to_string(),
+            "requestCorrectlyReceivedResponsePending"
+        );
+        assert_eq!(Nrc::from(0x99).to_string(), "0x99");
+    }
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(
+            describe(&[0x22, 0xF1, 0x90, 0xF1, 0x8C]),
+            "ReadDataByIdentifier DID 0xF190, 0xF18C"
+        );
+        assert_eq!(
+            describe(&[0x62, 0xF1, 0x90, 0x31, 0x47]),
+            "ReadDataByIdentifier positive response DID 0xF190"
+        );
+        assert_eq!(
+            describe(&[0x7F, 0x27, 0x35]),
+            "SecurityAccess negative response: invalidKey"
+        );
+        assert_eq!(describe(&[0x3E, 0x80]), "TesterPresent sub-function 0x00");
+        assert_eq!(
+            describe(&[0x71, 0x01, 0xFF, 0x00, 0x00]),
+            "RoutineControl positive response sub-function 0x01 routine 0xFF00"
+        );
+        assert_eq!(describe(&[0xBA, 0x01]), "service 0xBA");
+    }
+
+    #[test]
+    fn session_timing() {
+        assert_eq!(
+            SessionTiming::parse(&[0x00, 0x32, 0x01, 0xF4]),
+            Some(SessionTiming {
+                p2: Duration::from_millis(50),
+                p2_extended: Duration::from_millis(5000),
+            })
+        );
+        assert_eq!(SessionTiming::parse(&[0x00]), None);
+    }
+}
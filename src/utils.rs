@@ -8,6 +8,8 @@
 // use embedded_hal::can::Id;
 use embedded_can::Id;
 
+use std::time::Duration;
+
 pub fn id_to_raw(id: &Id) -> u32 {
     match id {
         Id::Standard(id) => id.as_raw() as u32,
@@ -15,6 +17,34 @@ pub fn id_to_raw(id: &Id) -> u32 {
     }
 }
 
+/// Format an ID as hex, padded to 3 or 8 digits by ID type
+pub fn format_id(id: &Id) -> String {
+    match id {
+        Id::Standard(id) => format!("{:03X}", id.as_raw()),
+        Id::Extended(id) => format!("{:08X}", id.as_raw()),
+    }
+}
+
+/// Parse a duration in ms, s or min, seconds if no unit is given
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .ok_or_else(|| format!("invalid duration '{}'", s))?;
+
+    let secs = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" | "min" => value * 60.0,
+        _ => return Err(format!("unknown unit '{}', expected ms, s or min", unit)),
+    };
+    Ok(Duration::from_secs_f64(secs))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,4 +61,22 @@ mod tests {
         let id: Id = ExtendedId::new(0x1F1).expect("Failed to created ID").into();
         assert_eq!(id_to_raw(&id), 0x1F1u32)
     }
+
+    #[test]
+    fn format_ids() {
+        let id: Id = StandardId::new(0x7E8).unwrap().into();
+        assert_eq!(format_id(&id), "7E8");
+        let id: Id = ExtendedId::new(0x1F1).unwrap().into();
+        assert_eq!(format_id(&id), "000001F1");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("10s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5min"), Ok(Duration::from_secs(90)));
+        assert!(parse_duration("10h").is_err());
+        assert!(parse_duration("s").is_err());
+    }
 }